@@ -1,7 +1,8 @@
 use crate::{
     api::{
         function::{
-            Function
+            Function,
+            Callback
         }
     },
     codegen::{
@@ -11,7 +12,9 @@ use crate::{
     },
     vm::{
         core::{
-            Core
+            Core,
+            CoreError,
+            CoreResult
         },
         register::{
             Register as RegisterUnion,
@@ -28,9 +31,14 @@ use std::{
 };
 
 use serde::{
+    Serialize,
     de::DeserializeOwned
 };
 
+use bincode::{
+    serialize
+};
+
 pub struct Adapter<'c> {
     pub function: Function,
     pub core: &'c mut Core
@@ -45,15 +53,73 @@ impl<'c> Adapter<'c> {
     }
 
     pub fn get_arg<T>(&mut self, arg_index: usize) -> T
+    where T: FromArg {
+        self.try_get_arg(arg_index).unwrap()
+    }
+
+    /// Retrieves an argument, failing instead of panicking if it cannot be read
+    pub fn try_get_arg<T>(&mut self, arg_index: usize) -> CoreResult<T>
     where T: FromArg {
         T::get(self, arg_index)
     }
 
+    /// Returns the stack pointer and the offset of an argument relative to it
+    fn get_arg_addr(&mut self, arg_index: usize) -> CoreResult<(u64, i16)> {
+        let arg_offset = self.function.try_get_arg_offset(arg_index)
+            .ok_or(CoreError::InvalidArgument(arg_index))?;
+        let addr = self.core.reg(Register::SP.into())?.get::<u64>();
+        Ok((addr, arg_offset as i16))
+    }
+
     pub fn return_value<T>(&mut self, value: T)
     where RegisterUnion: RegisterAccess<T> {
         self.core.reg(Register::R0.into()).unwrap().set::<T>(value);
     }
 
+    /// Returns the "Ok" variant of the result return type, holding the given payload
+    pub fn return_ok<T: ToPayload>(&mut self, value: T) -> CoreResult<()> {
+        let (ok_tag, _) = self.function.get_result_tags()
+            .ok_or(CoreError::NotAResult)?;
+        self.return_variant(ok_tag, value)
+    }
+
+    /// Returns the "Err" variant of the result return type, holding the given payload
+    pub fn return_err<T: ToPayload>(&mut self, value: T) -> CoreResult<()> {
+        let (_, err_tag) = self.function.get_result_tags()
+            .ok_or(CoreError::NotAResult)?;
+        self.return_variant(err_tag, value)
+    }
+
+    /// Returns a string, which is written to the return value on the stack
+    pub fn return_string(&mut self, value: &str) -> CoreResult<()> {
+        let addr = self.core.reg(Register::SP.into())?.get::<u64>();
+        value.set(self, addr)
+    }
+
+    /// Makes the script panic with the given message once the function returns,
+    /// unwinding to the innermost try block
+    pub fn panic<T>(&mut self, msg: T)
+    where String: From<T> {
+        self.core.set_foreign_panic(String::from(msg));
+    }
+
+    /// Writes the tag and the payload of an enum value to the return value on the stack
+    fn return_variant<T: ToPayload>(&mut self, tag: usize, value: T) -> CoreResult<()> {
+        let addr = self.core.reg(Register::SP.into())?.get::<u64>();
+        self.core.mem_set((addr, 0), tag as i64)?;
+        value.set(self, addr + 8)
+    }
+
+    /// Calls a script callback with the given arguments, e.g. "(1i64, 2.0f32)",
+    /// and returns its primitive return value
+    pub fn call_callback<A: Serialize, T>(&mut self, callback: &Callback, args: A) -> T
+    where RegisterUnion: RegisterAccess<T> {
+        let args = serialize(&args).unwrap();
+        self.core.call_fn_value(callback.uid, callback.env, &args)
+            .unwrap()
+            .get::<T>()
+    }
+
     // Retrieves a foreign pointer and returns the correct
     /// Arc<Mutex<T>> if found.
     pub fn get_foreign_ptr<T>(&self, ptr: u64) -> Arc<Mutex<T>> {
@@ -69,44 +135,105 @@ impl<'c> Adapter<'c> {
     pub fn remove_foreign_ptr<T>(&mut self, ptr: u64) -> Arc<Mutex<T>> {
         self.core.remove_foreign_ptr(ptr).unwrap()
     }
+
+    /// Keeps a heap allocation alive while the foreign code holds its address
+    pub fn pin_heap_ptr(&mut self, ptr: u64) {
+        self.core.pin_heap_ptr(ptr).unwrap()
+    }
+
+    /// Releases a heap allocation pinned by pin_heap_ptr
+    pub fn unpin_heap_ptr(&mut self, ptr: u64) {
+        self.core.unpin_heap_ptr(ptr).unwrap()
+    }
 }
 
 pub trait FromArg: DeserializeOwned {
-    fn get(adapter: &mut Adapter, arg_index: usize) -> Self;
+    fn get(adapter: &mut Adapter, arg_index: usize) -> CoreResult<Self>;
 }
 
 impl FromArg for String {
-    fn get(adapter: &mut Adapter, arg_index: usize) -> String {
-        let arg_offset = adapter.function.get_arg_offset(arg_index).abs() as u64;
-        //println!("Arg offset of Arg #{}: -{}B", arg_index, arg_offset);
-        let mut stack_addr = adapter.core.reg(16).unwrap().get::<u64>();
-        stack_addr -= arg_offset;
-        let string_res = adapter.core.mem_get_string(stack_addr);
-        //println!("{:?}", string_res);
-        string_res.unwrap()
+    fn get(adapter: &mut Adapter, arg_index: usize) -> CoreResult<String> {
+        let (stack_addr, arg_offset) = adapter.get_arg_addr(arg_index)?;
+        //println!("Arg offset of Arg #{}: {}B", arg_index, arg_offset);
+        adapter.core.mem_get_string(stack_addr - arg_offset.unsigned_abs() as u64)
     }
 }
 
 impl FromArg for i64 {
-    fn get(adapter: &mut Adapter, arg_index: usize) -> i64 {
-        let arg_offset = adapter.function.get_arg_offset(arg_index) as i16;
-        let addr = adapter.core.reg(16).unwrap().get::<u64>();
-        adapter.core.mem_get((addr, arg_offset)).unwrap()
+    fn get(adapter: &mut Adapter, arg_index: usize) -> CoreResult<i64> {
+        let addr = adapter.get_arg_addr(arg_index)?;
+        adapter.core.mem_get(addr)
     }
 }
 
 impl FromArg for f32 {
-    fn get(adapter: &mut Adapter, arg_index: usize) -> f32 {
-        let arg_offset = adapter.function.get_arg_offset(arg_index) as i16;
-        let addr = adapter.core.reg(16).unwrap().get::<u64>();
-        adapter.core.mem_get((addr, arg_offset)).unwrap()
+    fn get(adapter: &mut Adapter, arg_index: usize) -> CoreResult<f32> {
+        let addr = adapter.get_arg_addr(arg_index)?;
+        adapter.core.mem_get(addr)
+    }
+}
+
+impl FromArg for bool {
+    fn get(adapter: &mut Adapter, arg_index: usize) -> CoreResult<bool> {
+        let addr = adapter.get_arg_addr(arg_index)?;
+        adapter.core.mem_get(addr)
     }
 }
 
 impl FromArg for u64 {
-    fn get(adapter: &mut Adapter, arg_index: usize) -> u64 {
-        let arg_offset = adapter.function.get_arg_offset(arg_index) as i16;
-        let addr = adapter.core.reg(16).unwrap().get::<u64>();
-        adapter.core.mem_get((addr, arg_offset)).unwrap()
+    fn get(adapter: &mut Adapter, arg_index: usize) -> CoreResult<u64> {
+        let addr = adapter.get_arg_addr(arg_index)?;
+        adapter.core.mem_get(addr)
+    }
+}
+
+impl FromArg for Callback {
+    fn get(adapter: &mut Adapter, arg_index: usize) -> CoreResult<Callback> {
+        let (addr, arg_offset) = adapter.get_arg_addr(arg_index)?;
+        Ok(Callback {
+            uid: adapter.core.mem_get((addr, arg_offset))?,
+            env: adapter.core.mem_get((addr, arg_offset + 8))?
+        })
     }
-}
\ No newline at end of file
+}
+
+/// A payload of a result variant returned by a foreign function
+pub trait ToPayload {
+    fn set(self, adapter: &mut Adapter, addr: u64) -> CoreResult<()>;
+}
+
+impl ToPayload for () {
+    fn set(self, _adapter: &mut Adapter, _addr: u64) -> CoreResult<()> {
+        Ok(())
+    }
+}
+
+impl ToPayload for String {
+    fn set(self, adapter: &mut Adapter, addr: u64) -> CoreResult<()> {
+        adapter.core.string_store(self.as_bytes(), addr)
+    }
+}
+
+impl ToPayload for &str {
+    fn set(self, adapter: &mut Adapter, addr: u64) -> CoreResult<()> {
+        adapter.core.string_store(self.as_bytes(), addr)
+    }
+}
+
+impl ToPayload for i64 {
+    fn set(self, adapter: &mut Adapter, addr: u64) -> CoreResult<()> {
+        adapter.core.mem_set((addr, 0), self)
+    }
+}
+
+impl ToPayload for f32 {
+    fn set(self, adapter: &mut Adapter, addr: u64) -> CoreResult<()> {
+        adapter.core.mem_set((addr, 0), self)
+    }
+}
+
+impl ToPayload for u64 {
+    fn set(self, adapter: &mut Adapter, addr: u64) -> CoreResult<()> {
+        adapter.core.mem_set((addr, 0), self)
+    }
+}
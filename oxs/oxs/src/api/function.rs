@@ -39,6 +39,11 @@ use std::{
     }
 };
 
+use serde::{
+    Deserialize,
+    Deserializer
+};
+
 /// Represents a foreign function
 #[derive(Clone)]
 pub struct Function {
@@ -50,6 +55,8 @@ pub struct Function {
     arg_sizes: HashMap<usize, usize>,
     /// Return type
     pub return_type: Type,
+    ret_size: usize,
+    result_tags: Option<(usize, usize)>,
     closure: Option<Arc<Mutex<FunctionClosureType>>>
 }
 
@@ -78,6 +85,25 @@ impl Clone for Function {
     }
 }*/
 
+/// A function value passed from a script to a foreign function
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Callback {
+    /// Uid of the function to call
+    pub uid: u64,
+    /// Address of the captured environment, passed as the first argument
+    pub env: u64
+}
+
+impl<'de> Deserialize<'de> for Callback {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Callback, D::Error> {
+        let (uid, env) = <(u64, u64)>::deserialize(deserializer)?;
+        Ok(Callback {
+            uid,
+            env
+        })
+    }
+}
+
 pub type FunctionClosureType = dyn FnMut(&mut Adapter) -> ();
 
 impl Function {
@@ -91,6 +117,8 @@ impl Function {
             arg_offsets: HashMap::new(),
             arg_sizes: HashMap::new(),
             return_type: Type::Void,
+            ret_size: 0,
+            result_tags: None,
             closure: None
         }
     }
@@ -121,11 +149,36 @@ impl Function {
         }
     }
 
+    /// INTERNAL: Sets the size of a return value passed on the stack
+    pub fn set_ret_size(&mut self, ret_size: usize) {
+        self.ret_size = ret_size;
+    }
+
+    /// INTERNAL: Sets the tags of the "Ok" and "Err" variants of a result return type
+    pub fn set_result_tags(&mut self, ok_tag: usize, err_tag: usize) {
+        self.result_tags = Some((ok_tag, err_tag));
+    }
+
     /// Gets the byte offset of an argument
     pub fn get_arg_offset(&self, arg_index: usize) -> i64 {
         *self.arg_offsets.get(&arg_index).unwrap()
     }
 
+    /// Gets the byte offset of an argument, if the argument exists
+    pub fn try_get_arg_offset(&self, arg_index: usize) -> Option<i64> {
+        self.arg_offsets.get(&arg_index).copied()
+    }
+
+    /// Gets the size of a return value passed on the stack, 0 for primitive return types
+    pub fn get_ret_size(&self) -> usize {
+        self.ret_size
+    }
+
+    /// Gets the tags of the "Ok" and "Err" variants, if the return type is a result
+    pub fn get_result_tags(&self) -> Option<(usize, usize)> {
+        self.result_tags
+    }
+
     /// Runs the internal closure
     pub fn run(&self, adapter: &mut Adapter) {
         let closure_arc = self.closure.as_ref().unwrap();
@@ -9,12 +9,18 @@ use crate::{
         function::{
             Function
         }
+    },
+    parser::{
+        ast::{
+            Type
+        }
     }
 };
 
 pub struct Module {
     pub name: String,
     pub functions: HashMap<String, Function>,
+    pub enums: HashMap<String, Vec<(String, Vec<Type>)>>,
     pub modules: HashMap<String, Module>
 }
 
@@ -25,6 +31,7 @@ impl Module {
         Module {
             name: name,
             functions: HashMap::new(),
+            enums: HashMap::new(),
             modules: HashMap::new()
         }
     }
@@ -34,6 +41,14 @@ impl Module {
         self
     }
 
+    /// Declares an enum with the given variants and their payload types.
+    /// An enum with an "Ok" and an "Err" variant is a result type.
+    pub fn with_enum<T>(mut self, name: T, variants: Vec<(String, Vec<Type>)>) -> Module
+    where String: From<T> {
+        self.enums.insert(String::from(name), variants);
+        self
+    }
+
     pub fn with_module(mut self, module: Module) -> Module {
         self.modules.insert(module.name.clone(), module);
         self
@@ -72,7 +72,8 @@ impl Builder {
     pub fn push_instr(&mut self, instruction: Instruction) {
         if instruction.opcode == Opcode::JMP ||
             instruction.opcode == Opcode::JMPT ||
-            instruction.opcode == Opcode::JMPF {
+            instruction.opcode == Opcode::JMPF ||
+            instruction.opcode == Opcode::TRY {
             self.jmp_instructions.push(self.instructions.len());
         }
         self.instructions.push(instruction);
@@ -13,8 +13,11 @@ use crate::{
         uid_generator::UIDGenerator,
         def::{
             ContainerDef,
+            EnumDef,
             FunctionDef,
-            InterfaceDef
+            InterfaceDef,
+            GenericDef,
+            StaticDef
         },
         builder::{
             Builder
@@ -37,13 +40,23 @@ use crate::{
             Declaration,
             Statement,
             Type,
+            TypeParam,
             Expression,
-            IfStatementArgs
+            FunctionDeclArgs,
+            IfStatementArgs,
+            ForStatementArgs,
+            VariableDeclArgs,
+            MatchStatementArgs,
+            TryStatementArgs,
+            MatchPattern
         }
     },
     vm::{
         is::{
             Opcode
+        },
+        core::{
+            ArithmeticMode
         }
     }
 };
@@ -55,6 +68,7 @@ use std::{
         Formatter
     },
     error::Error,
+    convert::TryFrom,
     collections::{
         VecDeque,
         HashMap,
@@ -102,6 +116,36 @@ pub enum CompilerError {
     TypeMismatch(Type, Type),
     CannotDerefNonPointer,
     CannotDerefSlice,
+    CannotIndexNonArray(Type),
+    IndexOutOfBounds(i64, usize),
+    ArraySizeMismatch(usize, usize),
+    TupleAccessOnNonTuple(Type),
+    TupleSizeMismatch(usize, usize),
+    CannotFormat(Type),
+    UnknownVariant(String),
+    NonExhaustiveMatch(String),
+    /// A match arm can never be reached, because an earlier arm already matches its pattern
+    UnreachableMatchArm(String),
+    CannotMatch(Type),
+    TypeArgCountMismatch(usize, usize),
+    UnsatisfiedBound(Type, String),
+    CannotInferTypeArgument(String),
+    CannotInferType(String),
+    InvalidCast(Type, Type),
+    ImplicitConversion(Type, Type),
+    NotAResult(Type),
+    InvalidOptional(Type),
+    NotAnOptional(Type),
+    UncheckedOptional(Type),
+    NonConstantExpression(Expression),
+    /// A constant expression divides by zero
+    DivisionByZero(Expression),
+    /// A constant expression overflows
+    ArithmeticOverflow(Expression),
+    CannotAssignConst(String),
+    ZeroForStep,
+    /// A fixed size array of the contained byte size cannot be addressed with 16 bit stack offsets
+    ArrayTooLarge(usize),
     RegisterMapping
 }
 
@@ -116,6 +160,35 @@ impl Error for CompilerError {}
 /// Convenience type for Results returned by a compilation process
 pub type CompilerResult<T> = Result<T, CompilerError>;
 
+/// Name of the built-in module holding the core interfaces and library
+pub const CORE_MODULE: &str = "core";
+
+/// Canonical name of the built-in destructor interface
+pub const DESTROY_INTERFACE: &str = "root::core::Destroy";
+
+/// Canonical name of the built-in interface for formatting containers in interpolated strings
+pub const DISPLAY_INTERFACE: &str = "root::core::Display";
+
+/// Canonical names of the built-in interfaces for overloading operators on containers
+pub const ADD_INTERFACE: &str = "root::core::Add";
+pub const SUB_INTERFACE: &str = "root::core::Sub";
+pub const MUL_INTERFACE: &str = "root::core::Mul";
+pub const EQ_INTERFACE: &str = "root::core::Eq";
+pub const ORD_INTERFACE: &str = "root::core::Ord";
+
+/// Prefix of the canonical names of closure environments, which cannot clash with declared names
+pub const CLOSURE_PREFIX: &str = "{closure#";
+
+/// A function of a generic instance, closure or function value thunk,
+/// whose body is compiled after the root declarations
+#[derive(Debug, Clone)]
+struct PendingInstance {
+    label: String,
+    module_path: String,
+    fn_def: FunctionDef,
+    fn_decl_args: FunctionDeclArgs
+}
+
 /// The compiler
 pub struct Compiler {
     fn_context_stack: VecDeque<FunctionContext>,
@@ -128,13 +201,23 @@ pub struct Compiler {
     builder: Builder,
     current_cont: Option<String>,
     current_intf: Option<String>,
+    cont_instances: HashMap<String, ContainerDef>,
+    fn_instances: HashMap<String, FunctionDef>,
+    intf_instances: HashMap<String, InterfaceDef>,
+    pending_instances: VecDeque<PendingInstance>,
+    fn_value_thunks: HashMap<u64, u64>,
+    closure_count: usize,
+    statics: HashMap<String, StaticDef>,
+    static_inits: Vec<u64>,
     data: Data
 }
 
 impl Compiler {
     /// Creates a new compiler instance and pushes the "root" module on the context stack
     pub fn new() -> Compiler {
-        let root_mod_ctx = ModuleContext::new(String::from("root"));
+        let mut root_mod_ctx = ModuleContext::new(String::from("root"));
+        root_mod_ctx.add_module(Compiler::core_module_context())
+            .expect("Failed to declare core module");
         let mut mod_context_stack = VecDeque::new();
         mod_context_stack.push_front(root_mod_ctx);
         Compiler {
@@ -148,10 +231,58 @@ impl Compiler {
             builder: Builder::new(),
             current_cont: None,
             current_intf: None,
+            cont_instances: HashMap::new(),
+            fn_instances: HashMap::new(),
+            intf_instances: HashMap::new(),
+            pending_instances: VecDeque::new(),
+            fn_value_thunks: HashMap::new(),
+            closure_count: 0,
+            statics: HashMap::new(),
+            static_inits: Vec::new(),
             data: Data::new()
         }
     }
 
+    /// Creates the built-in "core" module, which declares the core interfaces
+    fn core_module_context() -> ModuleContext {
+        let mut core_mod_ctx = ModuleContext::new(String::from(CORE_MODULE));
+
+        let this_type = Type::Reference(Box::new(Type::Other(String::from(DESTROY_INTERFACE))));
+        let destroy_fn_def = FunctionDef::new(String::from("destroy"))
+            .with_arguments(&[(String::from("this"), this_type)]);
+        let mut destroy_intf_def = InterfaceDef::new(String::from("Destroy"), String::from(DESTROY_INTERFACE));
+        destroy_intf_def.add_function(destroy_fn_def);
+        core_mod_ctx.add_interface(destroy_intf_def);
+
+        let this_type = Type::Reference(Box::new(Type::Other(String::from(DISPLAY_INTERFACE))));
+        let to_string_fn_def = FunctionDef::new(String::from("to_string"))
+            .with_arguments(&[(String::from("this"), this_type)])
+            .with_ret_type(Type::String);
+        let mut display_intf_def = InterfaceDef::new(String::from("Display"), String::from(DISPLAY_INTERFACE));
+        display_intf_def.add_function(to_string_fn_def);
+        core_mod_ctx.add_interface(display_intf_def);
+
+        // Operand and result types are up to the implementing container,
+        // so operator interfaces only declare the name of their function
+        let operator_intfs = [
+            ("Add", ADD_INTERFACE, "add"),
+            ("Sub", SUB_INTERFACE, "sub"),
+            ("Mul", MUL_INTERFACE, "mul"),
+            ("Eq", EQ_INTERFACE, "eq"),
+            ("Ord", ORD_INTERFACE, "cmp")
+        ];
+        for (intf_name, canonical_name, fn_name) in operator_intfs.iter() {
+            let this_type = Type::Reference(Box::new(Type::Other(String::from(*canonical_name))));
+            let fn_def = FunctionDef::new(String::from(*fn_name))
+                .with_arguments(&[(String::from("this"), this_type)]);
+            let mut intf_def = InterfaceDef::new(String::from(*intf_name), String::from(*canonical_name));
+            intf_def.add_function(fn_def);
+            core_mod_ctx.add_interface(intf_def);
+        }
+
+        core_mod_ctx
+    }
+
     /// Retrieves a reference to the underlying builder
     pub fn get_builder(&self) -> &Builder {
         &self.builder
@@ -163,7 +294,7 @@ impl Compiler {
         let data = self.data.clone();
         let data_len = data.bytes.len();
 
-        // Modify target jump addresses of JMP instructions accordingly 
+        // Modify target jump addresses of JMP instructions (and TRY handlers) accordingly 
         for offset in builder.jmp_instructions.clone().iter() {
             let instr = builder.get_instr(offset)
                 .ok_or(CompilerError::Unknown)?;
@@ -171,6 +302,7 @@ impl Compiler {
                 Opcode::JMP => instr.get_operand(0, 8),
                 Opcode::JMPF => instr.get_operand(1, 8),
                 Opcode::JMPT => instr.get_operand(1, 8),
+                Opcode::TRY => instr.get_operand(0, 8),
                 _ => return Err(CompilerError::Unknown)
             };
             instr.remove_operand_bytes(8);
@@ -201,7 +333,8 @@ impl Compiler {
         let program = Program::new()
             .with_code(code)
             .with_functions(functions)
-            .with_foreign_functions(foreign_functions);
+            .with_foreign_functions(foreign_functions)
+            .with_static_pointers(self.data.get_static_ranges());
         
         Ok(program)
     }
@@ -337,9 +470,27 @@ impl Compiler {
             .ok_or(CompilerError::UnknownFunction(name.clone()))
     }
 
+    /// Returns the uids of the functions initialising the static variables, in declaration order
+    pub fn get_static_init_uids(&self) -> &[u64] {
+        &self.static_inits
+    }
+
     /// Resolves a function by name to a FunctionDef
     pub fn resolve_function(&self, name: &String) -> CompilerResult<FunctionDef> {
         //println!("Resolving function: {}", name);
+        if name.contains('<') {
+            if let Some(fn_def) = self.fn_instances.get(name) {
+                return Ok(fn_def.clone());
+            }
+            // Associated functions of generic container instances, e.g. "root::Stack<int>::new"
+            let (cont_name, fn_name) = name.rsplit_once("::")
+                .filter(|(_, fn_name)| !fn_name.contains('>'))
+                .ok_or(CompilerError::UnknownFunction(name.clone()))?;
+            return self.cont_instances.get(cont_name)
+                .ok_or(CompilerError::UnknownFunction(name.clone()))?
+                .get_member_function(&String::from(fn_name))
+                .cloned();
+        }
         if name.contains("::") {
             //println!("is a module path");
             let path_fragments: Vec<String> = name.split("::").map(|s| String::from(s)).collect();
@@ -364,8 +515,20 @@ impl Compiler {
                 }
             }
 
+            // Modules of the core library can be used without the "core::" prefix, e.g. "float::sqrt"
+            if let Some(mod_ctx) = mod_ctx_opt.filter(|_| start_i == 0) {
+                if !mod_ctx.modules.contains_key(&path_fragments[0]) && !mod_ctx.containers.contains_key(&path_fragments[0]) {
+                    if let Some(core_mod_ctx) = self.get_root_module()?.modules.get(CORE_MODULE) {
+                        if core_mod_ctx.modules.contains_key(&path_fragments[0]) {
+                            mod_ctx_opt = Some(core_mod_ctx);
+                        }
+                    }
+                }
+            }
+
             for i in start_i..path_fragments.len() - 1 {
-                let mod_ctx = mod_ctx_opt.unwrap();
+                let mod_ctx = mod_ctx_opt
+                    .ok_or(CompilerError::UnknownFunction(name.clone()))?;
                 if mod_ctx.containers.contains_key(&path_fragments[i]) {
                     //println!("Function is in container {}", &path_fragments[i]);
                     if i != path_fragments.len() - 2 {
@@ -391,7 +554,8 @@ impl Compiler {
                 )
             } else {
                 //println!("Resolved {}. Was in module!", name);
-                let mod_ctx = mod_ctx_opt.unwrap();
+                let mod_ctx = mod_ctx_opt
+                    .ok_or(CompilerError::UnknownFunction(name.clone()))?;
                 //println!("Trying to resolve function {} in module {:?}.", last_path, mod_ctx);
                 //println!("Blub");
                 return mod_ctx.functions.get(last_path)
@@ -418,6 +582,11 @@ impl Compiler {
     /// Resolves an interface by name to an InterfaceDef
     pub fn resolve_interface(&self, name: &String) -> CompilerResult<InterfaceDef> {
         //println!("Resolving container by name {}", name);
+        if name.contains('<') {
+            return self.intf_instances.get(name)
+                .cloned()
+                .ok_or(CompilerError::UnknownInterface(name.clone()));
+        }
         if name.contains("::") {
             let path_fragments: Vec<String> = name.split("::").map(|s| String::from(s)).collect();
             let mut mod_ctx_opt = None;
@@ -432,8 +601,15 @@ impl Compiler {
                 mod_ctx_opt = Some(self.get_current_module()?);
             }
 
+            if let Some(mod_ctx) = mod_ctx_opt {
+                if !mod_ctx.modules.contains_key(&path_fragments[start_i]) {
+                    mod_ctx_opt = Some(self.get_root_module()?);
+                }
+            }
+
             for i in start_i..path_fragments.len() - 1 {
-                let mod_ctx = mod_ctx_opt.unwrap();
+                let mod_ctx = mod_ctx_opt
+                    .ok_or(CompilerError::InvalidModulePath(name.clone()))?;
                 //println!("Blub");
                 mod_ctx_opt = mod_ctx.modules.get(&path_fragments[i]);
             }
@@ -442,7 +618,8 @@ impl Compiler {
 
             //println!("Resolving function {} for mod_ctx {}", last_path, mod_ctx_opt.as_ref().unwrap().name);
 
-            let mod_ctx = mod_ctx_opt.unwrap();
+            let mod_ctx = mod_ctx_opt
+                .ok_or(CompilerError::InvalidModulePath(name.clone()))?;
             return mod_ctx.get_interface(last_path).map(|i| i.clone());
         } else {
             let mod_ctx = self.get_current_module()?;
@@ -454,6 +631,12 @@ impl Compiler {
                     .ok_or(CompilerError::Unknown)?;
                 return self.resolve_interface(import_path);
             }
+            // The core interfaces are available without an import
+            if let Some(core_mod_ctx) = self.get_root_module()?.modules.get(CORE_MODULE) {
+                if core_mod_ctx.interfaces.contains_key(name) {
+                    return core_mod_ctx.get_interface(name).cloned();
+                }
+            }
 
             return Err(CompilerError::UnknownInterface(name.clone()));
         }
@@ -462,6 +645,13 @@ impl Compiler {
     /// Resolves a container by name to a ContainerDef
     pub fn resolve_container(&self, name: &String) -> CompilerResult<ContainerDef> {
         //println!("Resolving container by name {}", name);
+        // Generic instances and closure environments are keyed on their canonical name,
+        // e.g. "root::Pair<int, string>" or "{closure#1}"
+        if name.contains('<') || name.starts_with(CLOSURE_PREFIX) {
+            return self.cont_instances.get(name)
+                .cloned()
+                .ok_or(CompilerError::UnknownContainer(name.clone()));
+        }
         if name.contains("::") {
             let path_fragments: Vec<String> = name.split("::").map(|s| String::from(s)).collect();
             let mut mod_ctx_opt = None;
@@ -507,6 +697,99 @@ impl Compiler {
         }
     }
 
+    /// Resolves an enum by name to an EnumDef
+    pub fn resolve_enum(&self, name: &String) -> CompilerResult<EnumDef> {
+        if name.contains("::") {
+            let path_fragments: Vec<String> = name.split("::").map(String::from).collect();
+            let (mut mod_ctx_opt, start_i) = if path_fragments[0] == "root" {
+                (Some(self.get_root_module()?), 1)
+            } else if path_fragments[0] == "super" {
+                return Err(CompilerError::Unimplemented(String::from("super paths")));
+            } else {
+                (Some(self.get_current_module()?), 0)
+            };
+
+            for path_fragment in path_fragments[start_i..path_fragments.len() - 1].iter() {
+                let mod_ctx = mod_ctx_opt
+                    .ok_or(CompilerError::InvalidModulePath(name.clone()))?;
+                mod_ctx_opt = mod_ctx.modules.get(path_fragment);
+            }
+
+            let last_path = path_fragments.last().unwrap();
+
+            let mod_ctx = mod_ctx_opt
+                .ok_or(CompilerError::InvalidModulePath(name.clone()))?;
+            mod_ctx.enums.get(last_path)
+                .cloned()
+                .ok_or(CompilerError::UnknownContainer(name.clone()))
+        } else {
+            let mod_ctx = self.get_current_module()?;
+            if let Some(enum_def) = mod_ctx.enums.get(name) {
+                return Ok(enum_def.clone());
+            }
+            if let Some(import_path) = mod_ctx.imports.get(name) {
+                return self.resolve_enum(import_path);
+            }
+
+            Err(CompilerError::UnknownContainer(name.clone()))
+        }
+    }
+
+    /// Resolves a generic declaration by name to a GenericDef
+    pub fn resolve_generic(&self, name: &String) -> CompilerResult<GenericDef> {
+        if name.contains("::") {
+            let path_fragments: Vec<String> = name.split("::").map(String::from).collect();
+            let (mut mod_ctx_opt, start_i) = if path_fragments[0] == "root" {
+                (Some(self.get_root_module()?), 1)
+            } else if path_fragments[0] == "super" {
+                return Err(CompilerError::Unimplemented(String::from("super paths")));
+            } else {
+                (Some(self.get_current_module()?), 0)
+            };
+
+            for path_fragment in path_fragments[start_i..path_fragments.len() - 1].iter() {
+                let mod_ctx = mod_ctx_opt
+                    .ok_or(CompilerError::InvalidModulePath(name.clone()))?;
+                mod_ctx_opt = mod_ctx.modules.get(path_fragment);
+            }
+
+            let last_path = path_fragments.last().unwrap();
+
+            let mod_ctx = mod_ctx_opt
+                .ok_or(CompilerError::InvalidModulePath(name.clone()))?;
+            mod_ctx.generics.get(last_path)
+                .cloned()
+                .ok_or(CompilerError::UnknownContainer(name.clone()))
+        } else {
+            let mod_ctx = self.get_current_module()?;
+            if let Some(generic_def) = mod_ctx.generics.get(name) {
+                return Ok(generic_def.clone());
+            }
+            if let Some(import_path) = mod_ctx.imports.get(name) {
+                return self.resolve_generic(import_path);
+            }
+
+            Err(CompilerError::UnknownContainer(name.clone()))
+        }
+    }
+
+    /// Checks if a path names an enum variant, e.g. "Shape::Circle"
+    pub fn is_enum_variant(&self, path: &str) -> bool {
+        match path.rsplit_once("::") {
+            Some((enum_path, _)) => self.resolve_enum(&String::from(enum_path)).is_ok(),
+            None => false
+        }
+    }
+
+    /// Resolves the enum definition and the tag of an enum variant path
+    pub fn resolve_enum_variant(&self, path: &str) -> CompilerResult<(EnumDef, usize)> {
+        let (enum_path, variant_name) = path.rsplit_once("::")
+            .ok_or(CompilerError::UnknownVariant(String::from(path)))?;
+        let enum_def = self.resolve_enum(&String::from(enum_path))?;
+        let (tag, _) = enum_def.get_variant(variant_name)?;
+        Ok((enum_def, tag))
+    }
+
     /// Returns the byte size of a given Type
     pub fn get_size_of_type(&self, var_type: &Type) -> CompilerResult<usize> {
         //println!("Getting size of type");
@@ -516,20 +799,35 @@ impl Compiler {
             Type::Int => 8,
             Type::Reference(inner) => {
                 match inner.deref() {
-                    Type::AutoArray(_) => 16,
+                    Type::AutoArray(_) | Type::Interface(_) => 16,
                     _ => 8
                 }
             },
             Type::Float => 4,
             Type::Bool => 4,
+            Type::AutoArray(_) | Type::Optional(_) => 8,
+            Type::Function(_, _) => 16,
             Type::Other(cont_name) => {
-                let cont_def = self.resolve_container(&cont_name)?;
-                cont_def.get_size(self)?
+                match self.resolve_container(cont_name) {
+                    Ok(cont_def) => cont_def.get_size(self)?,
+                    Err(err) => {
+                        self.resolve_enum(cont_name)
+                            .map_err(|_| err)?
+                            .get_size(self)?
+                    }
+                }
             },
             Type::Array(inner_type, size) => {
                 let inner_type_size = self.get_size_of_type(&inner_type)?;
                 inner_type_size * size
             },
+            Type::Tuple(elem_types) => {
+                let mut size = 0;
+                for elem_type in elem_types.iter() {
+                    size += self.get_size_of_type(elem_type)?;
+                }
+                size
+            },
             _ => {
                 //println!("Error in get_size_of_type()!");
                 return Err(CompilerError::UnknownType(var_type.clone()));
@@ -538,6 +836,18 @@ impl Compiler {
         Ok(size)
     }
 
+    /// Returns the byte offset of a tuple element, laid out like container members
+    pub fn get_tuple_elem_offset(&self, elem_types: &[Type], index: usize) -> CompilerResult<usize> {
+        if index >= elem_types.len() {
+            return Err(CompilerError::IndexOutOfBounds(index as i64, elem_types.len()));
+        }
+        let mut offset = 0;
+        for elem_type in elem_types[..index].iter() {
+            offset += self.get_size_of_type(elem_type)?;
+        }
+        Ok(offset)
+    }
+
     /// Returns the type of a given variable
     pub fn get_type_of_var(&self, var_name: &String) -> CompilerResult<Type> {
         let mut type_opt = None;
@@ -551,7 +861,20 @@ impl Compiler {
             }
         }
 
-        type_opt.ok_or(CompilerError::UnknownVariable(var_name.clone()))
+        match type_opt {
+            Some(var_type) => Ok(var_type),
+            None => self.resolve_static(var_name).map(|static_def| static_def.var_type)
+        }
+    }
+
+    /// Returns the declared type of a given variable, ignoring any narrowing
+    pub fn get_declared_type_of_var(&self, var_name: &String) -> CompilerResult<Type> {
+        let declared_type = self.fn_context_stack.iter()
+            .find_map(|fn_ctx| fn_ctx.get_declared_var_type(var_name).ok());
+        match declared_type {
+            Some(var_type) => Ok(var_type),
+            None => self.resolve_static(var_name).map(|static_def| static_def.var_type)
+        }
     }
 
     /// Returns the offset to SP for a given variable
@@ -604,10 +927,24 @@ impl Compiler {
     /// Registers a foreign module
     fn register_foreign_module(&mut self, module: Module, path: &String) -> CompilerResult<()> {
         let path = format!("{}{}::", path, module.name.clone());
-        let mut mod_ctx = ModuleContext::new(module.name.clone());
+        // Foreign modules extend an existing module of the same name, e.g. the built-in "core"
+        let mut mod_ctx = self.get_current_module_mut()?
+            .modules
+            .remove(&module.name)
+            .unwrap_or_else(|| ModuleContext::new(module.name.clone()));
 
         self.push_module_context(mod_ctx);
 
+        // Enums go first, so functions can return them
+        for (enum_name, variants) in module.enums {
+            let enum_def = EnumDef {
+                canonical_name: self.get_module_path() + &enum_name,
+                name: enum_name,
+                variants
+            };
+            self.get_current_module_mut()?.add_enum(enum_def)?;
+        }
+
         for (_, function) in module.functions {
             self.register_foreign_function(function, &path)?;
         }
@@ -631,6 +968,17 @@ impl Compiler {
 
         let full_fn_name = path.clone() + &function.name;
         let fn_uid = self.uid_generator.get_function_uid(&full_fn_name);
+
+        // Non-primitive return values are written to the stack by the adapter.
+        // The module is not part of the tree yet, so this resolves relative to it.
+        if !function.return_type.is_primitive() {
+            let ret_size = self.get_size_of_type(&function.return_type)?;
+            function.set_ret_size(ret_size);
+        }
+        if let Ok((_, ok_tag, err_tag)) = self.resolve_result_enum(&function.return_type) {
+            function.set_result_tags(ok_tag, err_tag);
+        }
+        self.canonize_type(&mut function.return_type)?;
         let function_clone = function.clone();
 
         let mut arg_offset_sum: i64 = 0;
@@ -681,12 +1029,56 @@ impl Compiler {
                     Type::Reference(Box::new(inner_type.clone()))
                 )
             },
-            Type::Other(cont_name) => {
-                let cont_def = self.resolve_container(cont_name)?;
+            Type::Optional(inner_type) => {
+                let inner_type = inner_type.deref_mut();
+                self.canonize_type(inner_type)?;
+                // Only references which fit into a register can be optional,
+                // the type of "none" has an unknown inner type
+                if *inner_type != Type::Auto && !(inner_type.is_primitive() && matches!(inner_type, Type::Reference(_))) {
+                    return Err(CompilerError::InvalidOptional(inner_type.clone()));
+                }
                 Some(
-                    Type::Other(cont_def.canonical_name.clone())
+                    Type::Optional(Box::new(inner_type.clone()))
                 )
             },
+            Type::Other(cont_name) => {
+                // References to interfaces (e.g. "&this" in an interface) are valid too
+                let canon_type = match self.resolve_container(cont_name) {
+                    Ok(cont_def) => Type::Other(cont_def.canonical_name),
+                    Err(err) => {
+                        if let Ok(enum_def) = self.resolve_enum(cont_name) {
+                            Type::Other(enum_def.canonical_name)
+                        } else {
+                            let intf_def = self.resolve_interface(cont_name)
+                                .map_err(|_| err)?;
+                            Type::Interface(intf_def.canonical_name)
+                        }
+                    }
+                };
+                Some(canon_type)
+            },
+            Type::Generic(name, type_args) => {
+                // Generic types are canonized to the name of their instance
+                let generic_def = self.resolve_generic(name)?;
+                let mut canon_type_args = type_args.clone();
+                for type_arg in canon_type_args.iter_mut() {
+                    self.canonize_type(type_arg)?;
+                }
+                let instance_name = generic_def.get_instance_name(&canon_type_args);
+                match generic_def.decl {
+                    Declaration::Interface(_, _, _) => Some(Type::Interface(instance_name)),
+                    _ => Some(Type::Other(instance_name))
+                }
+            },
+            Type::Function(arg_types, ret_type) => {
+                let mut canon_arg_types = arg_types.clone();
+                for arg_type in canon_arg_types.iter_mut() {
+                    self.canonize_type(arg_type)?;
+                }
+                let mut canon_ret_type = (**ret_type).clone();
+                self.canonize_type(&mut canon_ret_type)?;
+                Some(Type::Function(canon_arg_types, Box::new(canon_ret_type)))
+            },
             _ => None
         };
         if new_type_opt.is_some() {
@@ -713,24 +1105,30 @@ impl Compiler {
             Declaration::Module(_, _) => self.declare_mod_decl(decl)?,
             Declaration::Function(_) => self.declare_fn_decl(decl)?,
             Declaration::Container(_) => self.declare_cont_decl(decl)?,
+            Declaration::Enum(_) => self.declare_enum_decl(decl)?,
             Declaration::Import(_, _) => self.declare_import_decl(decl)?,
             Declaration::Impl(_, _, _) => self.declare_impl_decl(decl)?,
-            Declaration::StaticVar(_) => self.declare_static_var(decl)?,
-            Declaration::Interface(_, _) => self.declare_intf_decl(decl)?,
+            Declaration::StaticVar(_) |
+            Declaration::Constant(_) => self.declare_static_var(decl)?,
+            Declaration::Interface(_, _, _) => self.declare_intf_decl(decl)?,
         };
         Ok(())
     }
 
     /// (Pre-)declares an interface declaratio
     pub fn declare_intf_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
-        let (intf_name, intf_decl_list) = match decl {
-            Declaration::Interface(name, list) => (name, list),
+        let (intf_name, type_params, intf_decl_list) = match decl {
+            Declaration::Interface(name, type_params, list) => (name, type_params, list),
             _ => return Err(CompilerError::Unknown)
         };
 
         let mut canon_name = self.get_module_path();
         canon_name += intf_name;
 
+        if !type_params.is_empty() {
+            return self.declare_generic_decl(intf_name, canon_name, type_params, decl);
+        }
+
         let intf_def = InterfaceDef::new(intf_name.clone(), canon_name);
 
         let mod_ctx = self.get_current_module_mut()?;
@@ -745,8 +1143,22 @@ impl Compiler {
         Ok(())
     }
 
-    /// (Pre-)declares a given static var declaration
+    /// (Pre-)declares a given static var or constant declaration
     pub fn declare_static_var(&mut self, decl: &Declaration) -> CompilerResult<()> {
+        let var_decl_args = match decl {
+            Declaration::StaticVar(var_decl_args) |
+            Declaration::Constant(var_decl_args) => var_decl_args,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let canon_name = self.get_module_path() + &var_decl_args.name;
+        if self.statics.contains_key(&canon_name) {
+            return Err(CompilerError::DuplicateVariable(canon_name));
+        }
+
+        let static_def = StaticDef::from_decl(var_decl_args, canon_name.clone());
+        self.statics.insert(canon_name, static_def);
+
         Ok(())
     }
 
@@ -767,6 +1179,13 @@ impl Compiler {
         }
         full_fn_name += &fn_decl_args.name;
 
+        if !fn_decl_args.type_params.is_empty() {
+            if self.current_cont.is_some() || self.current_intf.is_some() {
+                return Err(CompilerError::Unimplemented(format!("Generic member function {}", full_fn_name)));
+            }
+            return self.declare_generic_decl(&fn_decl_args.name, full_fn_name, &fn_decl_args.type_params, decl);
+        }
+
         let uid = self.uid_generator.get_function_uid(&full_fn_name);
         self.fn_uid_map.insert(full_fn_name.clone(), uid.clone());
 
@@ -776,6 +1195,9 @@ impl Compiler {
         for (arg_name, arg_type) in fn_def.arguments.iter_mut() {
             self.canonize_type(arg_type)?;
         }
+        if fn_def.ret_type.is_generic() {
+            self.canonize_type(&mut fn_def.ret_type)?;
+        }
 
         if let Some(cont_name) = self.current_cont.as_ref().cloned() {
             //println!("Adding function def to cont {}", cont_name);
@@ -830,6 +1252,20 @@ impl Compiler {
         //println!("Declaring cont: {:?}", cont_decl_args);
         let mut canon_name = self.get_module_path();
         canon_name += &cont_decl_args.name;
+
+        if !cont_decl_args.type_params.is_empty() {
+            return self.declare_generic_decl(&cont_decl_args.name, canon_name, &cont_decl_args.type_params, decl);
+        }
+
+        // Generic member types are stored as the instance they refer to
+        let mut cont_decl_args = cont_decl_args.clone();
+        for (_, member_type) in cont_decl_args.members.iter_mut() {
+            if member_type.is_generic() {
+                self.canonize_type(member_type)?;
+            }
+        }
+        let cont_decl_args = &cont_decl_args;
+
         let mod_ctx = self.get_current_module_mut()?;
         if mod_ctx.containers.contains_key(&cont_decl_args.name) {
             let cont_def = mod_ctx.containers.get_mut(&cont_decl_args.name)
@@ -849,6 +1285,25 @@ impl Compiler {
         Ok(())
     }
 
+    /// (Pre-)declares a given enum declaration
+    pub fn declare_enum_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
+        let enum_decl_args = match decl {
+            Declaration::Enum(args) => args,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let mut canon_name = self.get_module_path();
+        canon_name += &enum_decl_args.name;
+        let enum_def = EnumDef::from_decl(enum_decl_args, canon_name);
+        let mod_ctx = self.get_current_module_mut()?;
+        if mod_ctx.modules.contains_key(&enum_decl_args.name) {
+            return Err(CompilerError::AlreadyContainsModule(enum_decl_args.name.clone()));
+        }
+        mod_ctx.add_enum(enum_def)?;
+
+        Ok(())
+    }
+
     /// (Pre-)declares a given import declaration
     pub fn declare_import_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
         let (import_path, import_as) = match decl {
@@ -865,28 +1320,34 @@ impl Compiler {
 
     /// (Pre-)declares a given impl declaration
     pub fn declare_impl_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
-        let (impl_type, impl_for, decl_list) = match decl {
+        let (impl_type, impl_for_type, decl_list) = match decl {
             Declaration::Impl(impl_type, impl_for, decl_list) => (impl_type, impl_for, decl_list), 
             _ => return Err(CompilerError::Unknown)
         };
 
-        let mut canonical_name = self.get_module_path();
-        canonical_name += &impl_type;
+        let impl_for = match impl_for_type {
+            Type::Other(cont_name) => cont_name,
+            Type::Generic(_, _) => return self.declare_generic_impl_decl(decl),
+            _ => return Err(CompilerError::UnknownType(impl_for_type.clone()))
+        };
+
+        if impl_type == impl_for_type {
+            let mut canonical_name = self.get_module_path();
+            canonical_name += impl_for;
 
-        //println!("Declaring impl for {}", canonical_name);
+            //println!("Declaring impl for {}", canonical_name);
 
-        if impl_type == impl_for {
             let mod_ctx = self.get_current_module_mut()?;
-            let cont_res = mod_ctx.get_container(impl_type);
+            let cont_res = mod_ctx.get_container(impl_for);
             if cont_res.is_err() {
-                let cont_def = ContainerDef::new(impl_type.clone(), canonical_name);
+                let cont_def = ContainerDef::new(impl_for.clone(), canonical_name);
                 mod_ctx.add_container(cont_def)?;
             }
-            self.current_cont = Some(impl_type.clone());
+            self.current_cont = Some(impl_for.clone());
             self.declare_decl_list(decl_list)?;
             self.current_cont = None;
         } else {
-            let intf_def = self.resolve_interface(impl_type)?;
+            let intf_def = self.resolve_interface_type(impl_type)?;
             let cont_def = self.resolve_container(impl_for)?;
             let mut interface_fn_set: HashSet<String> = intf_def.functions.keys().cloned().collect();
             let interface_fn_set_clone = interface_fn_set.clone();
@@ -904,10 +1365,14 @@ impl Compiler {
                 }
             }
             if interface_fn_set.len() > 0 {
-                return Err(CompilerError::NotAllInterfaceFunctionsImplemented(impl_type.clone(), impl_for.clone()));
+                return Err(CompilerError::NotAllInterfaceFunctionsImplemented(impl_type.to_string(), impl_for.clone()));
             }
 
-            self.current_cont = Some(impl_type.clone());
+            let mod_ctx = self.get_current_module_mut()?;
+            let cont_def = mod_ctx.get_container_mut(impl_for)?;
+            cont_def.implements(intf_def.canonical_name.clone());
+
+            self.current_cont = Some(impl_for.clone());
             self.declare_decl_list(decl_list)?;
             self.current_cont = None;
         }
@@ -915,1071 +1380,3379 @@ impl Compiler {
         Ok(())
     }
 
-    // #endregion
-    
-    // #region compile functions
+    /// (Pre-)declares a generic container, function or interface declaration
+    pub fn declare_generic_decl(&mut self, name: &String, canon_name: String, type_params: &[TypeParam], decl: &Declaration) -> CompilerResult<()> {
+        let mut canon_type_params = Vec::new();
+        for (param_name, bounds) in type_params.iter() {
+            let mut canon_bounds = Vec::new();
+            for bound in bounds.iter() {
+                let canon_bound = match bound {
+                    Type::Other(intf_name) => Type::Other(self.resolve_interface(intf_name)?.canonical_name),
+                    // Generic bounds may refer to type parameters, e.g. "T: Into<U>"
+                    Type::Generic(_, _) => bound.clone(),
+                    _ => return Err(CompilerError::UnknownInterface(bound.to_string()))
+                };
+                canon_bounds.push(canon_bound);
+            }
+            canon_type_params.push((param_name.clone(), canon_bounds));
+        }
+
+        let generic_def = GenericDef::from_decl(name.clone(), canon_name, canon_type_params, decl);
+        let mod_ctx = self.get_current_module_mut()?;
+        if mod_ctx.modules.contains_key(name) {
+            return Err(CompilerError::AlreadyContainsModule(name.clone()));
+        }
+        mod_ctx.add_generic(generic_def)?;
 
-    /// Compiles the decl list for the root module
-    pub fn compile_root(&mut self, decl_list: &[Declaration]) -> CompilerResult<()> {
-        self.declare_decl_list(decl_list)?;
-        self.compile_decl_list(decl_list)?;
         Ok(())
     }
 
-    /// Compiles a declaration list
-    pub fn compile_decl_list(&mut self, decl_list: &[Declaration]) -> CompilerResult<()> {
+    /// (Pre-)declares an impl of a generic container, which is declared for every instance
+    pub fn declare_generic_impl_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
+        let (impl_type, impl_for, decl_list) = match decl {
+            Declaration::Impl(impl_type, impl_for, decl_list) => (impl_type, impl_for, decl_list),
+            _ => return Err(CompilerError::Unknown)
+        };
+        let (cont_name, type_args) = match impl_for {
+            Type::Generic(cont_name, type_args) => (cont_name, type_args),
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        // The type arguments of the impl name the type parameters, e.g. "impl: Pair<A, B>"
+        let mut param_names = Vec::new();
+        for type_arg in type_args.iter() {
+            match type_arg {
+                Type::Other(param_name) => param_names.push(param_name.clone()),
+                _ => return Err(CompilerError::UnknownType(type_arg.clone()))
+            };
+        }
+        let impl_intf = if impl_type == impl_for {
+            None
+        } else {
+            Some(impl_type.clone())
+        };
+
+        let mod_ctx = self.get_current_module_mut()?;
+        let generic_def = mod_ctx.generics.get_mut(cont_name)
+            .ok_or(CompilerError::UnknownContainer(cont_name.clone()))?;
+        if generic_def.type_params.len() != param_names.len() {
+            return Err(CompilerError::TypeArgCountMismatch(generic_def.type_params.len(), param_names.len()));
+        }
+        generic_def.impls.push((param_names, impl_intf, decl_list.clone()));
+
+        Ok(())
+    }
+
+    /// Resolves an interface type, instantiating it if it is generic, e.g. "Into<int>"
+    pub fn resolve_interface_type(&mut self, intf_type: &Type) -> CompilerResult<InterfaceDef> {
+        match intf_type {
+            Type::Other(intf_name) => self.resolve_interface(intf_name),
+            Type::Generic(_, _) => {
+                self.instantiate_type(intf_type)?;
+                let mut canon_type = intf_type.clone();
+                self.canonize_type(&mut canon_type)?;
+                self.resolve_interface(&canon_type.to_string())
+            },
+            _ => Err(CompilerError::UnknownType(intf_type.clone()))
+        }
+    }
+
+    // #endregion
+
+    // #region generics
+
+    /// Instantiates the generic types used in the signatures of a declaration list
+    pub fn instantiate_decl_list(&mut self, decl_list: &[Declaration]) -> CompilerResult<()> {
         for decl in decl_list.iter() {
-            self.compile_decl(decl)?;
+            match decl {
+                Declaration::Function(fn_decl_args) if fn_decl_args.type_params.is_empty() => {
+                    for (_, arg_type) in fn_decl_args.arguments.iter() {
+                        self.instantiate_type(arg_type)?;
+                    }
+                    self.instantiate_type(&fn_decl_args.returns)?;
+                },
+                Declaration::Container(cont_decl_args) if cont_decl_args.type_params.is_empty() => {
+                    for (_, member_type) in cont_decl_args.members.iter() {
+                        self.instantiate_type(member_type)?;
+                    }
+                },
+                Declaration::Interface(_, type_params, decl_list) if type_params.is_empty() => {
+                    self.instantiate_decl_list(decl_list)?;
+                },
+                Declaration::Impl(_, Type::Other(_), decl_list) => {
+                    self.instantiate_decl_list(decl_list)?;
+                },
+                Declaration::Module(mod_name, decl_list) => {
+                    let mod_ctx = self.get_current_module()?
+                        .modules.get(mod_name)
+                        .cloned()
+                        .ok_or(CompilerError::UnknownModule(mod_name.clone()))?;
+                    self.push_module_context(mod_ctx);
+                    self.instantiate_decl_list(decl_list)?;
+                    self.pop_module_context()?;
+                },
+                Declaration::StaticVar(_) |
+                Declaration::Constant(_) => {
+                    self.instantiate_static_var(decl)?;
+                },
+                _ => {}
+            };
         }
         Ok(())
     }
 
-    /// Compiles a declaration
-    pub fn compile_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
-        match decl {
-            Declaration::Function(_) => self.compile_fn_decl(decl)?,
-            Declaration::Impl(_, _, _) => self.compile_impl_decl(decl)?,
-            Declaration::Module(_, _) => self.compile_mod_decl(decl)?,
-            Declaration::Interface(_, _ ) => self.compile_intf_decl(decl)?,
+    /// Instantiates the generic types, functions and containers used by a statement.
+    /// Nested statement blocks are instantiated when they are compiled.
+    pub fn instantiate_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        match stmt {
+            Statement::VariableDecl(var_decl_args) => {
+                self.instantiate_type(&var_decl_args.var_type)?;
+                for child_expr in var_decl_args.assignment.get_child_exprs() {
+                    self.instantiate_expr(child_expr)?;
+                }
+                match self.infer_typed_generic_call(&var_decl_args.assignment, &var_decl_args.var_type) {
+                    Some((generic_def, type_args, _)) => {
+                        self.instantiate_generic(&generic_def, type_args)?;
+                    },
+                    None => self.instantiate_expr(&var_decl_args.assignment)?
+                };
+            },
+            Statement::TupleDecl(tuple_decl_args) => {
+                self.instantiate_expr(&tuple_decl_args.assignment)?;
+            },
+            Statement::Expression(expr) |
+            Statement::Return(Some(expr)) => {
+                self.instantiate_expr(expr)?;
+            },
+            Statement::While(expr, _) |
+            Statement::Panic(expr) => {
+                self.instantiate_expr(expr)?;
+            },
+            Statement::If(if_stmt_args) => {
+                self.instantiate_expr(&if_stmt_args.if_expr)?;
+                for (else_if_expr, _) in if_stmt_args.else_if_list.iter().flatten() {
+                    self.instantiate_expr(else_if_expr)?;
+                }
+            },
+            Statement::For(for_stmt_args) => {
+                self.instantiate_expr(&for_stmt_args.range_start)?;
+                self.instantiate_expr(&for_stmt_args.range_end)?;
+                if let Some(step_expr) = for_stmt_args.step.as_ref() {
+                    self.instantiate_expr(step_expr)?;
+                }
+            },
+            Statement::Match(match_stmt_args) => {
+                self.instantiate_expr(&match_stmt_args.match_expr)?;
+            },
             _ => {}
         };
         Ok(())
     }
 
-    /// Compiles an interface declaration
-    pub fn compile_intf_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
-        let (intf_name, intf_decl_list) = match decl {
-            Declaration::Interface(name, list) => (name, list),
-            _ => return Err(CompilerError::Unknown)
+    /// Instantiates the generic functions and containers used by an expression
+    pub fn instantiate_expr(&mut self, expr: &Expression) -> CompilerResult<()> {
+        // Sub-expressions first, as their types are needed for inferring type arguments
+        for child_expr in expr.get_child_exprs() {
+            self.instantiate_expr(child_expr)?;
+        }
+        match expr {
+            Expression::Call(fn_name, arg_exprs) if self.resolve_function(fn_name).is_err() => {
+                match self.infer_generic_call(fn_name, arg_exprs, None) {
+                    Ok((generic_def, type_args)) => {
+                        self.instantiate_generic(&generic_def, type_args)?;
+                    },
+                    Err(CompilerError::UnknownFunction(_)) => {},
+                    Err(err) => return Err(err)
+                };
+            },
+            Expression::ContainerInstance(cont_name, member_map) if self.resolve_container(cont_name).is_err() && self.resolve_generic(cont_name).is_ok() => {
+                let (generic_def, type_args) = self.infer_generic_cont_instance(cont_name, member_map)?;
+                self.instantiate_generic(&generic_def, type_args)?;
+            },
+            Expression::TypeCall(cont_type, _, _) => {
+                self.instantiate_type(cont_type)?;
+            },
+            _ => {}
         };
+        Ok(())
+    }
 
-        let intf_def = self.resolve_interface(intf_name)?;
+    /// Instantiates the generic types mentioned in a type, e.g. "&Pair<int, Stack<int>>"
+    pub fn instantiate_type(&mut self, var_type: &Type) -> CompilerResult<()> {
+        match var_type {
+            Type::Generic(name, type_args) => {
+                let mut canon_type_args = Vec::new();
+                for type_arg in type_args.iter() {
+                    self.instantiate_type(type_arg)?;
+                    let mut canon_type_arg = type_arg.clone();
+                    self.canonize_type(&mut canon_type_arg)?;
+                    canon_type_args.push(canon_type_arg);
+                }
+                let generic_def = self.resolve_generic(name)?;
+                self.instantiate_generic(&generic_def, canon_type_args)?;
+            },
+            Type::Array(inner_type, _) |
+            Type::AutoArray(inner_type) |
+            Type::Reference(inner_type) |
+            Type::Optional(inner_type) => self.instantiate_type(inner_type)?,
+            Type::Tuple(elem_types) => {
+                for elem_type in elem_types.iter() {
+                    self.instantiate_type(elem_type)?;
+                }
+            },
+            Type::Function(arg_types, ret_type) => {
+                for arg_type in arg_types.iter() {
+                    self.instantiate_type(arg_type)?;
+                }
+                self.instantiate_type(ret_type)?;
+            },
+            _ => {}
+        };
+        Ok(())
+    }
 
-        self.current_intf = Some(intf_def.canonical_name.clone());
+    /// Monomorphises a generic declaration for the given (canonical) type arguments.
+    /// Returns the canonical name of the instance.
+    pub fn instantiate_generic(&mut self, generic_def: &GenericDef, type_args: Vec<Type>) -> CompilerResult<String> {
+        if type_args.len() != generic_def.type_params.len() {
+            return Err(CompilerError::TypeArgCountMismatch(generic_def.type_params.len(), type_args.len()));
+        }
 
-        self.compile_decl_list(intf_decl_list)?;
+        let instance_name = generic_def.get_instance_name(&type_args);
+        if self.cont_instances.contains_key(&instance_name) ||
+            self.fn_instances.contains_key(&instance_name) ||
+            self.intf_instances.contains_key(&instance_name) {
+            return Ok(instance_name);
+        }
 
-        self.current_intf = None;
+        // Names in a generic declaration are resolved in the module it was declared in
+        let pushed_modules = self.push_module_path(generic_def.get_module_path())?;
+        let instantiate_res = self.check_type_bounds(&generic_def.type_params, &type_args)
+            .and_then(|_| self.instantiate_generic_decl(generic_def, &type_args, &instance_name));
+        for _ in 0..pushed_modules {
+            self.pop_module_context()?;
+        }
+        instantiate_res?;
 
-        Ok(())
+        Ok(instance_name)
     }
 
-    /// Compiles a function declaration
-    pub fn compile_fn_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
-        let fn_decl_args = match decl {
-            Declaration::Function(fn_decl_args) => fn_decl_args,
-            _ => return Err(CompilerError::Unknown)
-        };
+    /// Declares the instance of a generic declaration. Instances are registered
+    /// before their members, as members may refer to the instance itself.
+    fn instantiate_generic_decl(&mut self, generic_def: &GenericDef, type_args: &[Type], instance_name: &String) -> CompilerResult<()> {
+        let type_map = GenericDef::get_type_map(&generic_def.get_type_param_names(), type_args);
+        match &generic_def.decl {
+            Declaration::Container(cont_decl_args) => {
+                let mut cont_def = ContainerDef::new(generic_def.name.clone(), instance_name.clone());
+                cont_def.generic = Some((generic_def.canonical_name.clone(), type_args.to_vec()));
+                self.cont_instances.insert(instance_name.clone(), cont_def);
+
+                for (member_name, member_type) in cont_decl_args.members.iter() {
+                    let member_type = self.instantiate_sig_type(&member_type.substitute(&type_map))?;
+                    self.get_cont_instance_mut(instance_name)?
+                        .add_member_variable((member_name.clone(), member_type))?;
+                }
 
-        //println!("Compiling fn_decl");
-        let fn_def;
-        if self.current_cont.is_some() {
-            let current_cont_name = self.current_cont.as_ref().ok_or(CompilerError::Unknown)?;
-            let cont_def = self.resolve_container(current_cont_name)?;
-            fn_def = cont_def.get_member_function(&fn_decl_args.name)?
-                .clone();
-        } else if self.current_intf.is_some() {
-            let current_intf_name = self.current_intf.as_ref().ok_or(CompilerError::Unknown)?;
-            let intf_def = self.resolve_interface(&current_intf_name)?;
-            fn_def = intf_def.get_function(&fn_decl_args.name)?
-                .clone();
-        } else {
-            fn_def = self.resolve_function(&fn_decl_args.name)?;
-        }
-
-        //println!("Fn def: {:?}", fn_def);
+                for (impl_params, impl_intf, decl_list) in generic_def.impls.iter() {
+                    let impl_type_map = GenericDef::get_type_map(impl_params, type_args);
+                    if let Some(intf_type) = impl_intf {
+                        let intf_def = self.resolve_interface_type(&intf_type.substitute(&impl_type_map))?;
+                        for intf_fn_name in intf_def.functions.keys() {
+                            let implemented = decl_list.iter().any(|decl| match decl {
+                                Declaration::Function(fn_decl_args) => fn_decl_args.name == *intf_fn_name,
+                                _ => false
+                            });
+                            if !implemented {
+                                return Err(CompilerError::NotAllInterfaceFunctionsImplemented(intf_def.canonical_name.clone(), instance_name.clone()));
+                            }
+                        }
+                        self.get_cont_instance_mut(instance_name)?
+                            .implements(intf_def.canonical_name);
+                    }
+                    for decl in decl_list.iter() {
+                        if let Declaration::Function(fn_decl_args) = decl {
+                            let label = format!("{}::{}", instance_name, fn_decl_args.name);
+                            let fn_def = self.declare_instance_fn(generic_def, label, fn_decl_args.substitute_types(&impl_type_map))?;
+                            self.get_cont_instance_mut(instance_name)?
+                                .add_member_function(fn_def)?;
+                        }
+                    }
+                }
+            },
+            Declaration::Function(fn_decl_args) => {
+                let fn_decl_args = fn_decl_args.substitute_types(&type_map);
+                let fn_def = self.declare_instance_fn(generic_def, instance_name.clone(), fn_decl_args)?;
+                self.fn_instances.insert(instance_name.clone(), fn_def);
+            },
+            Declaration::Interface(_, _, decl_list) => {
+                let intf_def = InterfaceDef::new(generic_def.name.clone(), instance_name.clone());
+                self.intf_instances.insert(instance_name.clone(), intf_def);
+
+                for decl in decl_list.iter() {
+                    if let Declaration::Function(fn_decl_args) = decl {
+                        let fn_def = self.instantiate_fn_def(&fn_decl_args.substitute_types(&type_map))?;
+                        self.intf_instances.get_mut(instance_name)
+                            .ok_or(CompilerError::UnknownInterface(instance_name.clone()))?
+                            .add_function(fn_def);
+                    }
+                }
+            },
+            _ => return Err(CompilerError::Unknown)
+        };
+        Ok(())
+    }
 
-        let fn_ret_type = fn_def.ret_type.clone();
+    /// Returns the container definition of a generic instance, mutably
+    fn get_cont_instance_mut(&mut self, instance_name: &String) -> CompilerResult<&mut ContainerDef> {
+        self.cont_instances.get_mut(instance_name)
+            .ok_or(CompilerError::UnknownContainer(instance_name.clone()))
+    }
 
-        let mut fn_ctx = FunctionContext::new(self, fn_def)?;
+    /// Instantiates and canonizes a type in the signature of a generic instance
+    fn instantiate_sig_type(&mut self, sig_type: &Type) -> CompilerResult<Type> {
+        self.instantiate_type(sig_type)?;
+        let mut sig_type = sig_type.clone();
+        if sig_type.is_generic() {
+            self.canonize_type(&mut sig_type)?;
+        }
+        Ok(sig_type)
+    }
 
-        let mut full_fn_name = self.get_module_path();
-        if self.current_cont.is_some() {
-            full_fn_name += self.current_cont.as_ref().unwrap();
-            full_fn_name += "::";
-        } else if self.current_intf.is_some() {
-            full_fn_name += self.current_intf.as_ref().unwrap();
-            full_fn_name += "::";
+    /// Creates the function definition of a monomorphic function declaration
+    fn instantiate_fn_def(&mut self, fn_decl_args: &FunctionDeclArgs) -> CompilerResult<FunctionDef> {
+        let mut fn_def = FunctionDef::from(fn_decl_args);
+        for (_, arg_type) in fn_def.arguments.iter_mut() {
+            self.instantiate_type(arg_type)?;
+            self.canonize_type(arg_type)?;
         }
-        full_fn_name += &fn_decl_args.name;
+        fn_def.ret_type = self.instantiate_sig_type(&fn_def.ret_type)?;
+        Ok(fn_def)
+    }
 
-        //println!("Compiling fn decl with label {}", full_fn_name);
+    /// Declares a function of a generic instance and queues its body for compilation
+    fn declare_instance_fn(&mut self, generic_def: &GenericDef, label: String, fn_decl_args: FunctionDeclArgs) -> CompilerResult<FunctionDef> {
+        let uid = self.uid_generator.get_function_uid(&label);
+        self.fn_uid_map.insert(label.clone(), uid);
 
-        
-        self.builder.push_label(full_fn_name);
+        let fn_def = self.instantiate_fn_def(&fn_decl_args)?
+            .with_uid(uid);
 
-        self.push_function_context(fn_ctx);
+        self.pending_instances.push_back(PendingInstance {
+            label,
+            module_path: String::from(generic_def.get_module_path()),
+            fn_def: fn_def.clone(),
+            fn_decl_args
+        });
 
-        if let Some(stmt_list) = &fn_decl_args.code_block {
-            self.compile_stmt_list(stmt_list)?;
+        Ok(fn_def)
+    }
+
+    /// Checks that the type arguments implement the interfaces bounding their type parameters
+    fn check_type_bounds(&mut self, type_params: &[TypeParam], type_args: &[Type]) -> CompilerResult<()> {
+        let param_names: Vec<String> = type_params.iter()
+            .map(|(param_name, _)| param_name.clone())
+            .collect();
+        let type_map = GenericDef::get_type_map(&param_names, type_args);
+        for ((_, bounds), type_arg) in type_params.iter().zip(type_args.iter()) {
+            for bound in bounds.iter() {
+                let intf_name = match bound {
+                    Type::Other(intf_name) => intf_name.clone(),
+                    _ => self.resolve_interface_type(&bound.substitute(&type_map))?.canonical_name
+                };
+                let satisfied = match type_arg {
+                    Type::Other(cont_name) => self.resolve_container(cont_name)
+                        .map(|cont_def| cont_def.does_implement(&intf_name))
+                        .unwrap_or(false),
+                    _ => Compiler::does_primitive_implement(type_arg, &intf_name)
+                };
+                if !satisfied {
+                    return Err(CompilerError::UnsatisfiedBound(type_arg.clone(), intf_name));
+                }
+            }
         }
+        Ok(())
+    }
 
-        // If the type is void, automatically add a return Statement
-        if fn_ret_type == Type::Void {
-            let ret_stmt = Statement::Return(None);
-            self.compile_return_stmt(&ret_stmt)?;
+    /// Checks whether a primitive type supports the operators of a core interface natively,
+    /// e.g. int satisfies "T: Ord" as it can be compared with "<"
+    fn does_primitive_implement(prim_type: &Type, intf_name: &str) -> bool {
+        match prim_type {
+            Type::Int | Type::Float => {
+                [ADD_INTERFACE, SUB_INTERFACE, MUL_INTERFACE, EQ_INTERFACE, ORD_INTERFACE].contains(&intf_name)
+            },
+            _ => false
         }
+    }
 
-        // Instruction in case the function didnt return a value
-        let halt_instr = Instruction::new(Opcode::HALT)
-            .with_operand::<u8>(1);
-        self.builder.push_instr(halt_instr);
+    /// Infers type arguments by matching the types of parameters against the types of arguments
+    fn infer_type_args(&self, param_names: &[String], param_types: &[Type], arg_types: &[Type]) -> CompilerResult<Vec<Type>> {
+        let mut type_map = HashMap::new();
+        for (param_type, arg_type) in param_types.iter().zip(arg_types.iter()) {
+            self.unify_type(param_names, param_type, arg_type, &mut type_map)?;
+        }
+        param_names.iter()
+            .map(|param_name| {
+                type_map.get(param_name)
+                    .cloned()
+                    .ok_or(CompilerError::CannotInferTypeArgument(param_name.clone()))
+            })
+            .collect()
+    }
 
+    /// Binds the type parameters in a parameter type to the matching parts of an argument type
+    fn unify_type(&self, param_names: &[String], param_type: &Type, arg_type: &Type, type_map: &mut HashMap<String, Type>) -> CompilerResult<()> {
+        match (param_type, arg_type) {
+            (Type::Other(param_name), _) if param_names.contains(param_name) => {
+                if let Some(bound_type) = type_map.get(param_name) {
+                    if bound_type != arg_type {
+                        return Err(CompilerError::TypeMismatch(bound_type.clone(), arg_type.clone()));
+                    }
+                }
+                type_map.insert(param_name.clone(), arg_type.clone());
+            },
+            (Type::Reference(param_type), Type::Reference(arg_type)) |
+            (Type::AutoArray(param_type), Type::AutoArray(arg_type)) |
+            (Type::Array(param_type, _), Type::Array(arg_type, _)) => {
+                self.unify_type(param_names, param_type, arg_type, type_map)?;
+            },
+            (Type::Tuple(param_types), Type::Tuple(arg_types)) => {
+                for (param_type, arg_type) in param_types.iter().zip(arg_types.iter()) {
+                    self.unify_type(param_names, param_type, arg_type, type_map)?;
+                }
+            },
+            (Type::Function(param_types, param_ret_type), Type::Function(arg_types, arg_ret_type)) => {
+                for (param_type, arg_type) in param_types.iter().zip(arg_types.iter()) {
+                    self.unify_type(param_names, param_type, arg_type, type_map)?;
+                }
+                self.unify_type(param_names, param_ret_type, arg_ret_type, type_map)?;
+            },
+            // Instances of generic containers are matched by their type arguments
+            (Type::Generic(_, param_types), Type::Other(cont_name)) => {
+                let generic_opt = self.resolve_container(cont_name)
+                    .ok()
+                    .and_then(|cont_def| cont_def.generic);
+                if let Some((_, arg_types)) = generic_opt {
+                    for (param_type, arg_type) in param_types.iter().zip(arg_types.iter()) {
+                        self.unify_type(param_names, param_type, arg_type, type_map)?;
+                    }
+                }
+            },
+            _ => {}
+        };
         Ok(())
     }
 
-    /// Compiles the proper SUBU_I instruction for a break statement
-    pub fn compile_stack_loop(&mut self) -> CompilerResult<()> {
-        let mut pop_size = 0;
+    /// Returns the canonical types of a list of expressions
+    fn check_canon_expr_types<'a>(&self, exprs: impl Iterator<Item = &'a Expression>) -> CompilerResult<Vec<Type>> {
+        let mut ret = Vec::new();
+        for expr in exprs {
+            let mut expr_type = self.check_expr_type(expr)?;
+            self.canonize_type(&mut expr_type)?;
+            ret.push(expr_type);
+        }
+        Ok(ret)
+    }
 
-        // Pop all values until the first loop context is hit
-        for i in 0..self.fn_context_stack.len() {
-            let fn_ctx = self.fn_context_stack.get(i)
-                .ok_or(CompilerError::Unknown)?;
-            pop_size += fn_ctx.stack_size;
-            if fn_ctx.is_loop {
-                break;
+    /// Infers the generic declaration and type arguments of a call to a generic function,
+    /// e.g. "max(1, 2)", or to an associated function of a generic container, e.g. "Pair::new(1, 2)"
+    /// Type parameters only used by the return type are inferred from the expected type, if given.
+    fn infer_generic_call(&self, fn_name: &String, arg_exprs: &[Expression], expected_type: Option<&Type>) -> CompilerResult<(GenericDef, Vec<Type>)> {
+        let not_generic = CompilerError::UnknownFunction(fn_name.clone());
+        let (generic_def, param_names, fn_decl_args) = match self.resolve_generic(fn_name) {
+            Ok(generic_def) => {
+                let fn_decl_args = match &generic_def.decl {
+                    Declaration::Function(fn_decl_args) => fn_decl_args.clone(),
+                    _ => return Err(not_generic)
+                };
+                let param_names = generic_def.get_type_param_names();
+                (generic_def, param_names, fn_decl_args)
+            },
+            Err(_) => {
+                let (cont_path, member_name) = fn_name.rsplit_once("::")
+                    .ok_or(not_generic.clone())?;
+                let generic_def = self.resolve_generic(&String::from(cont_path))
+                    .map_err(|_| not_generic.clone())?;
+                let (param_names, fn_decl_args) = generic_def.impls.iter()
+                    .flat_map(|(impl_params, _, decl_list)| {
+                        decl_list.iter().filter_map(move |decl| match decl {
+                            Declaration::Function(fn_decl_args) if fn_decl_args.name == member_name => {
+                                Some((impl_params.clone(), fn_decl_args.clone()))
+                            },
+                            _ => None
+                        })
+                    })
+                    .next()
+                    .ok_or(not_generic)?;
+                (generic_def, param_names, fn_decl_args)
             }
+        };
+
+        let mut param_types: Vec<Type> = fn_decl_args.arguments.iter()
+            .map(|(_, arg_type)| arg_type.clone())
+            .collect();
+        let mut arg_types = self.check_canon_expr_types(arg_exprs.iter())?;
+        if let Some(expected_type) = expected_type {
+            param_types.push(fn_decl_args.returns.clone());
+            arg_types.push(expected_type.clone());
         }
+        let type_args = self.infer_type_args(&param_names, &param_types, &arg_types)?;
 
-        //println!("Compiling loop stack cleanup with pop size {}", pop_size);
+        Ok((generic_def, type_args))
+    }
 
-        let stack_instr = Instruction::new_dec_stack(pop_size);
-        self.builder.push_instr(stack_instr);
+    /// Infers the generic container and type arguments of a container instance, e.g. "Pair { first: 1, second: 2 }"
+    fn infer_generic_cont_instance(&self, cont_name: &String, member_map: &HashMap<String, Expression>) -> CompilerResult<(GenericDef, Vec<Type>)> {
+        let generic_def = self.resolve_generic(cont_name)?;
+        let cont_decl_args = match &generic_def.decl {
+            Declaration::Container(cont_decl_args) => cont_decl_args,
+            _ => return Err(CompilerError::UnknownContainer(cont_name.clone()))
+        };
 
-        Ok(())
+        let mut param_types = Vec::new();
+        let mut member_exprs = Vec::new();
+        for (member_name, member_type) in cont_decl_args.members.iter() {
+            if let Some(member_expr) = member_map.get(member_name) {
+                param_types.push(member_type.clone());
+                member_exprs.push(member_expr);
+            }
+        }
+        let arg_types = self.check_canon_expr_types(member_exprs.into_iter())?;
+        let type_args = self.infer_type_args(&generic_def.get_type_param_names(), &param_types, &arg_types)?;
+
+        Ok((generic_def, type_args))
     }
-    
 
-    /// Compiles a stack cleanup for a given function context
-    pub fn compile_stack_cleanup_block(&mut self, fn_ctx: &FunctionContext) -> CompilerResult<()> {
-        let pop_size = fn_ctx.stack_size;
+    /// Infers the generic declaration and type arguments of a call whose type arguments
+    /// can only be inferred from the declared type of its target, e.g. "var s: Stack<int> = Stack::new();".
+    /// Returns the call rewritten to the instance.
+    fn infer_typed_generic_call(&self, expr: &Expression, expected_type: &Type) -> Option<(GenericDef, Vec<Type>, Expression)> {
+        let (fn_name, arg_exprs) = match expr {
+            Expression::Call(fn_name, arg_exprs) if self.resolve_function(fn_name).is_err() => (fn_name, arg_exprs),
+            _ => return None
+        };
+        match self.infer_generic_call(fn_name, arg_exprs, None) {
+            Err(CompilerError::CannotInferTypeArgument(_)) => {},
+            _ => return None
+        };
+        let mut expected_type = expected_type.clone();
+        self.canonize_type(&mut expected_type).ok()?;
+        let (generic_def, type_args) = self.infer_generic_call(fn_name, arg_exprs, Some(&expected_type)).ok()?;
+
+        let instance_name = generic_def.get_instance_name(&type_args);
+        let call_name = match generic_def.decl {
+            Declaration::Function(_) => instance_name,
+            _ => format!("{}::{}", instance_name, fn_name.rsplit("::").next()?)
+        };
+        Some((generic_def, type_args, Expression::Call(call_name, arg_exprs.clone())))
+    }
 
-        //println!("Compiling stack cleanup with stack size {}", pop_size);
+    /// Turns a call with explicit type arguments into a call on the instance,
+    /// e.g. "Stack<int>::new()" into "root::Stack<int>::new()"
+    fn get_instance_call_expr(&self, expr: &Expression) -> CompilerResult<Expression> {
+        let (cont_type, fn_name, arg_exprs) = match expr {
+            Expression::TypeCall(cont_type, fn_name, arg_exprs) => (cont_type, fn_name, arg_exprs),
+            _ => return Err(CompilerError::UnsupportedExpression(expr.clone()))
+        };
+        let mut cont_type = cont_type.clone();
+        self.canonize_type(&mut cont_type)?;
+        Ok(Expression::Call(format!("{}::{}", cont_type, fn_name), arg_exprs.clone()))
+    }
 
-        // Instruction for popping values off the stack
-        if pop_size > 0 {
-            let pop_stack_instr = Instruction::new_dec_stack(pop_size);
-            self.builder.push_instr(pop_stack_instr);
+    /// Resolves the function called by a call expression, including instances of generic functions
+    pub fn resolve_call(&self, fn_name: &String, arg_exprs: &[Expression]) -> CompilerResult<FunctionDef> {
+        // Variables holding function values shadow functions of the same name
+        if let Some(Type::Function(arg_types, ret_type)) = self.get_fn_value_type(fn_name) {
+            let arguments: Vec<(String, Type)> = arg_types.into_iter()
+                .map(|arg_type| (String::new(), arg_type))
+                .collect();
+            return Ok(
+                FunctionDef::new(fn_name.clone())
+                    .with_arguments(&arguments)
+                    .with_ret_type(*ret_type)
+            );
+        }
+        let err = match self.resolve_function(fn_name) {
+            Ok(fn_def) => return Ok(fn_def),
+            Err(err) => err
+        };
+        let (generic_def, type_args) = match self.infer_generic_call(fn_name, arg_exprs, None) {
+            Ok(generic) => generic,
+            Err(CompilerError::UnknownFunction(_)) => return Err(err),
+            Err(infer_err) => return Err(infer_err)
+        };
+
+        let instance_name = generic_def.get_instance_name(&type_args);
+        match generic_def.decl {
+            Declaration::Function(_) => self.resolve_function(&instance_name),
+            _ => {
+                let member_name = fn_name.rsplit("::")
+                    .next()
+                    .ok_or(CompilerError::UnknownFunction(fn_name.clone()))?;
+                self.resolve_container(&instance_name)?
+                    .get_member_function(&String::from(member_name))
+                    .cloned()
+            }
         }
+    }
 
-        Ok(())
+    /// Resolves the container of a container instance expression, including instances of generic containers
+    pub fn resolve_cont_instance(&self, cont_name: &String, member_map: &HashMap<String, Expression>) -> CompilerResult<ContainerDef> {
+        let err = match self.resolve_container(cont_name) {
+            Ok(cont_def) => return Ok(cont_def),
+            Err(err) => err
+        };
+        if self.resolve_generic(cont_name).is_err() {
+            return Err(err);
+        }
+        let (generic_def, type_args) = self.infer_generic_cont_instance(cont_name, member_map)?;
+        self.resolve_container(&generic_def.get_instance_name(&type_args))
     }
 
-    /// Compiles a full stack unwind until the parent function is hit 
-    pub fn compile_stack_cleanup_return(&mut self) -> CompilerResult<()> {
-        let mut parent_fn_ctx_opt = None;
-        let mut stack_size = 0;
+    /// Pushes the modules of a canonical module path, e.g. "root::math::", on the context
+    /// stack, unless it already is the current module. Returns the number of pushed modules.
+    fn push_module_path(&mut self, module_path: &str) -> CompilerResult<usize> {
+        if module_path == self.get_module_path() {
+            return Ok(0);
+        }
+        let mut mod_ctx = self.get_root_module()?;
+        let mut mod_ctxs = vec![mod_ctx.clone()];
+        for mod_name in module_path.split("::").filter(|s| !s.is_empty()).skip(1) {
+            mod_ctx = mod_ctx.modules.get(mod_name)
+                .ok_or(CompilerError::UnknownModule(String::from(mod_name)))?;
+            mod_ctxs.push(mod_ctx.clone());
+        }
+        let pushed_modules = mod_ctxs.len();
+        for mod_ctx in mod_ctxs {
+            self.push_module_context(mod_ctx);
+        }
+        Ok(pushed_modules)
+    }
 
-        for ctx in self.fn_context_stack.iter() {
-            stack_size += ctx.stack_size;
-            if !ctx.weak {
-                parent_fn_ctx_opt = Some(ctx);
-                break;
+    /// Compiles the functions of generic instances, which may instantiate further generics
+    fn compile_pending_instances(&mut self) -> CompilerResult<()> {
+        while let Some(pending) = self.pending_instances.pop_front() {
+            let pushed_modules = self.push_module_path(&pending.module_path)?;
+            self.compile_fn_body(pending.label, pending.fn_def, &pending.fn_decl_args)?;
+            for _ in 0..pushed_modules {
+                self.pop_module_context()?;
             }
         }
+        Ok(())
+    }
 
-        let parent_fn_ctx = parent_fn_ctx_opt.ok_or(CompilerError::Unknown)?;
-        let ret_type = parent_fn_ctx.get_ret_type()?;
-        let ret_size = self.get_size_of_type(&ret_type)?;
-        let mut pop_size = stack_size;
-        let stack_begin_offset = -(stack_size as i16);
-        
-        if !ret_type.is_primitive() {
-            //println!("fn return type is non-primitive.");
-            pop_size -= ret_size;
-            if pop_size > 0 {
-                let mov_stack_instr = Instruction::new(Opcode::MOVN_A)
-                    .with_operand::<u8>(Register::SP.into())
-                    .with_operand::<i16>(-(ret_size as i16))
-                    .with_operand::<u8>(Register::SP.into())
-                    .with_operand::<i16>(stack_begin_offset)
-                    .with_operand::<u32>(ret_size as u32);
-                self.builder.push_instr(mov_stack_instr);
+    // #endregion
+
+    // #region statics
+
+    /// Resolves a static variable or constant by name to a StaticDef
+    pub fn resolve_static(&self, name: &String) -> CompilerResult<StaticDef> {
+        let candidates = if name.starts_with("root::") {
+            vec![name.clone()]
+        } else {
+            vec![self.get_module_path() + name, format!("root::{}", name)]
+        };
+        for candidate in candidates.iter() {
+            if let Some(static_def) = self.statics.get(candidate) {
+                return Ok(static_def.clone());
             }
         }
-
-        if pop_size > 0 {
-            //println!("Popping {} off the stack at return.", pop_size);
-            let pop_stack_instr = Instruction::new_dec_stack(pop_size);
-            self.dec_stack(pop_size)?;
-            self.builder.push_instr(pop_stack_instr);
+        if !name.contains("::") {
+            let mod_ctx = self.get_current_module()?;
+            if let Some(import_path) = mod_ctx.imports.get(name) {
+                return self.resolve_static(import_path);
+            }
         }
+        Err(CompilerError::UnknownVariable(name.clone()))
+    }
 
-        Ok(())
+    /// Returns true if a variable is declared in a function context
+    pub fn is_local_var(&self, var_name: &String) -> bool {
+        self.fn_context_stack.iter()
+            .any(|fn_ctx| fn_ctx.get_var_type(var_name).is_ok())
     }
 
-    /// Compiles a module declaration
-    pub fn compile_mod_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
-        let (mod_name, decl_list) = match decl {
-            Declaration::Module(mod_name, decl_list) => (mod_name, decl_list),
+    /// Returns the folded value of a constant referred to by an expression
+    pub fn get_const_expr(&self, expr: &Expression) -> Option<Expression> {
+        match expr {
+            Expression::Variable(name) if !self.is_local_var(name) => {
+                self.resolve_static(name).ok()?.const_value
+            },
+            _ => None
+        }
+    }
+
+    /// Resolves the type of a static variable or constant, allocates the storage of static
+    /// variables and queues their initialisers. Constants are folded instead.
+    fn instantiate_static_var(&mut self, decl: &Declaration) -> CompilerResult<()> {
+        let (var_decl_args, is_const) = match decl {
+            Declaration::StaticVar(var_decl_args) => (var_decl_args, false),
+            Declaration::Constant(var_decl_args) => (var_decl_args, true),
             _ => return Err(CompilerError::Unknown)
         };
+        let canon_name = self.get_module_path() + &var_decl_args.name;
+        let mut static_def = self.statics.get(&canon_name)
+            .cloned()
+            .ok_or(CompilerError::UnknownVariable(canon_name.clone()))?;
 
-        let module_declared = {
-            let front_mod_ctx = self.get_current_module()?;
-            front_mod_ctx.modules.contains_key(mod_name)
+        let const_value = match is_const {
+            true => Some(self.fold_const_expr(&var_decl_args.assignment)?),
+            false => None
         };
+        let value_expr = const_value.as_ref().unwrap_or(&var_decl_args.assignment);
 
-        if !module_declared {
-            return Err(CompilerError::UnknownModule(mod_name.clone()));
+        let mut var_type = static_def.var_type.clone();
+        if var_type == Type::Auto {
+            var_type = self.check_expr_type(value_expr)?;
+        } else {
+            self.instantiate_type(&var_type)?;
         }
+        self.canonize_type(&mut var_type)?;
 
-        let mod_ctx = {
-            let front_mod_ctx = self.get_current_module()?;
-            front_mod_ctx.modules.get(mod_name)
-                .cloned()
-                .ok_or(CompilerError::Unknown)?
-        };
-
-        self.push_module_context(mod_ctx);
-
-        self.compile_decl_list(decl_list)?;
+        if is_const {
+            let value_type = self.check_expr_type(value_expr)?;
+            if var_type != value_type {
+                return Err(CompilerError::TypeMismatch(var_type, value_type));
+            }
+            static_def.var_type = var_type;
+            static_def.const_value = const_value;
+            self.statics.insert(canon_name, static_def);
+            return Ok(());
+        }
 
-        self.pop_module_context()?;
+        let size = self.get_size_of_type(&var_type)?;
+        static_def.addr = self.data.get_static(&canon_name, size);
+        static_def.var_type = var_type;
+        self.statics.insert(canon_name.clone(), static_def);
+
+        // The initialiser assigns the static variable once the program is loaded
+        let label = format!("{{static#{}}}", canon_name);
+        let assign_expr = Expression::Assign(
+            Box::new(Expression::Variable(canon_name)),
+            var_decl_args.assignment.clone()
+        );
+        let fn_decl_args = FunctionDeclArgs {
+            name: label.clone(),
+            type_params: Vec::new(),
+            arguments: Vec::new(),
+            returns: Type::Void,
+            code_block: Some(vec![Statement::Expression(assign_expr)])
+        };
+        let init_uid = self.declare_generated_fn(label, fn_decl_args)?;
+        self.static_inits.push(init_uid);
 
         Ok(())
     }
 
-    /// Compiles an impl declaration
-    pub fn compile_impl_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
-        let (impl_type, impl_for, decl_list) = match decl {
-            Declaration::Impl(impl_type, impl_for, decl_list) => (impl_type, impl_for, decl_list), 
-            _ => return Err(CompilerError::Unknown)
+    /// Folds a constant expression into a literal
+    pub fn fold_const_expr(&self, expr: &Expression) -> CompilerResult<Expression> {
+        let non_const_err = || CompilerError::NonConstantExpression(expr.clone());
+        let folded_opt = match expr {
+            Expression::IntLiteral(_) |
+            Expression::FloatLiteral(_) |
+            Expression::BoolLiteral(_) |
+            Expression::StringLiteral(_) => Some(expr.clone()),
+            Expression::Variable(_) => self.get_const_expr(expr),
+            Expression::Not(op_expr) => match self.fold_const_expr(op_expr)? {
+                Expression::BoolLiteral(value) => Some(Expression::BoolLiteral(!value)),
+                Expression::IntLiteral(value) => Some(Expression::IntLiteral(!value)),
+                _ => None
+            },
+            Expression::Negate(op_expr) => match self.fold_const_expr(op_expr)? {
+                Expression::IntLiteral(value) => Some(
+                    value.checked_neg()
+                        .map(Expression::IntLiteral)
+                        .ok_or_else(|| CompilerError::ArithmeticOverflow(expr.clone()))?
+                ),
+                Expression::FloatLiteral(value) => Some(Expression::FloatLiteral(-value)),
+                _ => None
+            },
+            Expression::Cast(op_expr, cast_type) => match (self.fold_const_expr(op_expr)?, cast_type) {
+                (Expression::IntLiteral(value), Type::Int) => Some(Expression::IntLiteral(value)),
+                (Expression::IntLiteral(value), Type::Float) => Some(Expression::FloatLiteral(value as f32)),
+                (Expression::IntLiteral(value), Type::Bool) => Some(Expression::BoolLiteral(value != 0)),
+                (Expression::FloatLiteral(value), Type::Float) => Some(Expression::FloatLiteral(value)),
+                (Expression::FloatLiteral(value), Type::Int) => Some(Expression::IntLiteral(value as i64)),
+                (Expression::BoolLiteral(value), Type::Bool) => Some(Expression::BoolLiteral(value)),
+                (Expression::BoolLiteral(value), Type::Int) => Some(Expression::IntLiteral(value as i64)),
+                _ => None
+            },
+            Expression::Addition(lhs, rhs) |
+            Expression::Subtraction(lhs, rhs) |
+            Expression::Multiplication(lhs, rhs) |
+            Expression::Division(lhs, rhs) |
+            Expression::Modulo(lhs, rhs) |
+            Expression::BitAnd(lhs, rhs) |
+            Expression::BitOr(lhs, rhs) |
+            Expression::BitXor(lhs, rhs) |
+            Expression::ShiftLeft(lhs, rhs) |
+            Expression::ShiftRight(lhs, rhs) |
+            Expression::And(lhs, rhs) |
+            Expression::Or(lhs, rhs) |
+            Expression::Equals(lhs, rhs) |
+            Expression::NotEquals(lhs, rhs) |
+            Expression::GreaterThan(lhs, rhs) |
+            Expression::LessThan(lhs, rhs) |
+            Expression::GreaterThanEquals(lhs, rhs) |
+            Expression::LessThanEquals(lhs, rhs) => {
+                let lhs = self.fold_const_expr(lhs)?;
+                let rhs = self.fold_const_expr(rhs)?;
+                Compiler::fold_binary_expr(expr, &lhs, &rhs)?
+            },
+            _ => None
         };
+        folded_opt.ok_or_else(non_const_err)
+    }
 
-        //println!("Compiling impl: {:?}", decl);
-
-        if impl_type == impl_for {
-            self.current_cont = Some(impl_type.clone());
-            self.compile_decl_list(decl_list)?;
-            self.current_cont = None;
-        } else {
-            return Err(CompilerError::Unimplemented(format!("impl of interfaces not supported yet!")));
+    /// Folds a binary expression of two literals, e.g. "2 * 3". Fails on overflows, out of range shifts and divisions by zero.
+    fn fold_binary_expr(expr: &Expression, lhs: &Expression, rhs: &Expression) -> CompilerResult<Option<Expression>> {
+        if let (Expression::IntLiteral(lhs), Expression::IntLiteral(rhs)) = (lhs, rhs) {
+            let checked_opt = match expr {
+                Expression::Addition(_, _) => Some(lhs.checked_add(*rhs)),
+                Expression::Subtraction(_, _) => Some(lhs.checked_sub(*rhs)),
+                Expression::Multiplication(_, _) => Some(lhs.checked_mul(*rhs)),
+                Expression::Division(_, _) => Some(lhs.checked_div(*rhs)),
+                Expression::Modulo(_, _) => Some(lhs.checked_rem(*rhs)),
+                Expression::ShiftLeft(_, _) => Some(ArithmeticMode::Checked.shl(*lhs, *rhs)),
+                Expression::ShiftRight(_, _) => Some(ArithmeticMode::Checked.shr(*lhs, *rhs)),
+                _ => None
+            };
+            match checked_opt {
+                Some(Some(value)) => return Ok(Some(Expression::IntLiteral(value))),
+                Some(None) if *rhs == 0 => return Err(CompilerError::DivisionByZero(expr.clone())),
+                Some(None) => return Err(CompilerError::ArithmeticOverflow(expr.clone())),
+                None => {}
+            }
         }
+        let folded_opt = match (lhs, rhs) {
+            (Expression::IntLiteral(lhs), Expression::IntLiteral(rhs)) => match expr {
+                Expression::BitAnd(_, _) => Some(Expression::IntLiteral(lhs & rhs)),
+                Expression::BitOr(_, _) => Some(Expression::IntLiteral(lhs | rhs)),
+                Expression::BitXor(_, _) => Some(Expression::IntLiteral(lhs ^ rhs)),
+                Expression::Equals(_, _) => Some(Expression::BoolLiteral(lhs == rhs)),
+                Expression::NotEquals(_, _) => Some(Expression::BoolLiteral(lhs != rhs)),
+                Expression::GreaterThan(_, _) => Some(Expression::BoolLiteral(lhs > rhs)),
+                Expression::LessThan(_, _) => Some(Expression::BoolLiteral(lhs < rhs)),
+                Expression::GreaterThanEquals(_, _) => Some(Expression::BoolLiteral(lhs >= rhs)),
+                Expression::LessThanEquals(_, _) => Some(Expression::BoolLiteral(lhs <= rhs)),
+                _ => None
+            },
+            (Expression::FloatLiteral(lhs), Expression::FloatLiteral(rhs)) => match expr {
+                Expression::Addition(_, _) => Some(Expression::FloatLiteral(lhs + rhs)),
+                Expression::Subtraction(_, _) => Some(Expression::FloatLiteral(lhs - rhs)),
+                Expression::Multiplication(_, _) => Some(Expression::FloatLiteral(lhs * rhs)),
+                Expression::Division(_, _) => Some(Expression::FloatLiteral(lhs / rhs)),
+                Expression::Equals(_, _) => Some(Expression::BoolLiteral(lhs == rhs)),
+                Expression::NotEquals(_, _) => Some(Expression::BoolLiteral(lhs != rhs)),
+                Expression::GreaterThan(_, _) => Some(Expression::BoolLiteral(lhs > rhs)),
+                Expression::LessThan(_, _) => Some(Expression::BoolLiteral(lhs < rhs)),
+                Expression::GreaterThanEquals(_, _) => Some(Expression::BoolLiteral(lhs >= rhs)),
+                Expression::LessThanEquals(_, _) => Some(Expression::BoolLiteral(lhs <= rhs)),
+                _ => None
+            },
+            (Expression::BoolLiteral(lhs), Expression::BoolLiteral(rhs)) => match expr {
+                Expression::And(_, _) => Some(Expression::BoolLiteral(*lhs && *rhs)),
+                Expression::Or(_, _) => Some(Expression::BoolLiteral(*lhs || *rhs)),
+                Expression::Equals(_, _) => Some(Expression::BoolLiteral(lhs == rhs)),
+                Expression::NotEquals(_, _) => Some(Expression::BoolLiteral(lhs != rhs)),
+                _ => None
+            },
+            _ => None
+        };
+        Ok(folded_opt)
+    }
 
-        Ok(())
+    /// Compiles the location of a variable, which is either an offset to SP for local
+    /// variables or a register holding the address of a static variable with offset 0
+    fn compile_var_location(&mut self, var_name: &String) -> CompilerResult<(Register, i64)> {
+        if self.is_local_var(var_name) {
+            return Ok((Register::SP, self.get_sp_offset_of_var(var_name)?));
+        }
+        let static_def = self.resolve_static(var_name)?;
+        if static_def.const_value.is_some() {
+            return Err(CompilerError::CannotAssignConst(static_def.canonical_name));
+        }
+        let addr_reg = self.get_next_register()?;
+        let lda_instr = Instruction::new(Opcode::LDA)
+            .with_operand::<u64>(static_def.addr)
+            .with_operand::<u8>(addr_reg.clone().into());
+        self.builder.push_instr(lda_instr);
+        Ok((addr_reg, 0))
     }
 
-    /// Compiles a statement list
-    pub fn compile_stmt_list(&mut self, stmt_list: &[Statement]) -> CompilerResult<()> {
-        for stmt in stmt_list.iter() {
-            //println!("Compiling statement... Stack size: {}", self.get_stack_size()?);
-            self.compile_stmt(stmt)?;
-            //println!("Compiled statement... Stack size: {}", self.get_stack_size()?);
+    /// Compiles the address of a variable into a register
+    fn compile_var_addr(&mut self, var_name: &String) -> CompilerResult<Register> {
+        let (base_reg, offset) = self.compile_var_location(var_name)?;
+        if base_reg != Register::SP {
+            return Ok(base_reg);
         }
-        Ok(())
+        let addr_reg = self.get_next_register()?;
+        let subui_instr = Instruction::new(Opcode::SUBU_I)
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<u64>(offset.unsigned_abs())
+            .with_operand::<u8>(addr_reg.clone().into());
+        self.builder.push_instr(subui_instr);
+        Ok(addr_reg)
     }
 
-    /// Compiles a statement
-    pub fn compile_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
-        match stmt {
-            Statement::VariableDecl(_) => self.compile_var_decl_stmt(stmt)?,
-            Statement::Expression(_) => self.compile_expr_stmt(stmt)?,
-            Statement::Return(_) => self.compile_return_stmt(stmt)?,
-            Statement::If(_) => self.compile_if_stmt(stmt)?,
-            Statement::While(_, _) => self.compile_while_stmt(stmt)?, 
-            Statement::Continue => self.compile_continue_stmt(stmt)?,
-            Statement::Break => self.compile_break_stmt(stmt)?,
-            _ => return Err(CompilerError::Unimplemented(format!("Compilation of {:?} not implemented!", stmt)))
+    // #endregion
+
+    // #region casts
+
+    /// Returns the opcode converting a value of one primitive type into another.
+    /// Casts to the same type need no conversion and return None.
+    pub fn get_cast_opcode(from_type: &Type, to_type: &Type) -> CompilerResult<Option<Opcode>> {
+        let opcode = match (from_type, to_type) {
+            (Type::Int, Type::Int) |
+            (Type::Float, Type::Float) |
+            (Type::Bool, Type::Bool) => None,
+            (Type::Int, Type::Float) => Some(Opcode::I2F),
+            (Type::Float, Type::Int) => Some(Opcode::F2I),
+            (Type::Int, Type::Bool) => Some(Opcode::I2B),
+            (Type::Bool, Type::Int) => Some(Opcode::B2I),
+            _ => return Err(CompilerError::InvalidCast(from_type.clone(), to_type.clone()))
         };
+        Ok(opcode)
+    }
+
+    /// Compiles a cast expression, e.g. "x as float"
+    pub fn compile_cast_expr(&mut self, op_expr: &Expression, cast_type: &Type) -> CompilerResult<()> {
+        let op_type = self.check_expr_type(op_expr)?;
+        let opcode_opt = Compiler::get_cast_opcode(&op_type, cast_type)?;
+        self.compile_expr(op_expr)?;
+        if let Some(opcode) = opcode_opt {
+            let value_reg = self.get_last_register()?;
+            let target_reg = self.get_next_register()?;
+            let cast_instr = Instruction::new(opcode)
+                .with_operand::<u8>(value_reg.into())
+                .with_operand::<u8>(target_reg.into());
+            self.builder.push_instr(cast_instr);
+        }
         Ok(())
     }
 
-    /// Compiles a variable declaration statement
-    pub fn compile_var_decl_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
-        let var_decl_args = match stmt {
-            Statement::VariableDecl(var_decl_args) => var_decl_args,
-            _ => return Err(CompilerError::Unknown)
-        };
-        //println!("Compiling var decl stmt. Args: {:?}", var_decl_args);
-        //println!("Compiling var decl stmt");
-        // The variable name
-        let var_name = var_decl_args.name.clone();
-        // The variable type
-        let mut var_type = var_decl_args.var_type.clone();
-        // The assignment expression
-        let assignment_expr = &var_decl_args.assignment;
-        let assignment_expr_type = self.check_expr_type(&assignment_expr)?;
-        //println!("var decl assign expr: {:?}", assignment_expr);
-        //println!("var decl assign expr type: {:?}", assignment_expr_type);
-        // Special handling for auto typed vars
-        if var_type == Type::Auto {
-            var_type = assignment_expr_type;
+    /// Returns the error for the operands of a binary expression having different types.
+    /// Mixing int and float requires an explicit cast, as there are no implicit conversions.
+    pub fn operand_type_error(lhs_type: Type, rhs_type: Type) -> CompilerError {
+        match (&lhs_type, &rhs_type) {
+            (Type::Int, Type::Float) |
+            (Type::Float, Type::Int) => CompilerError::ImplicitConversion(lhs_type, rhs_type),
+            _ => CompilerError::TypeMismatch(lhs_type, rhs_type)
         }
+    }
 
-        //println!("Var type: {:?}", var_type);
-        // Byte size of this type
-        let var_size = self.get_size_of_type(&var_type)?;
-        //println!("Size of type: {}", var_size);
-        // Compile said expression
-        //println!("Compiling assignment expr ({:?}). SP: {}", assignment_expr, self.get_stack_size()?);
-        self.compile_expr(assignment_expr)?;
-        //println!("Compiled assignment expr ({:?}). SP: {}", assignment_expr, self.get_stack_size()?);
+    // #endregion
 
-        // If the type can be contained in a register
-        if var_type.is_primitive() {
-            let last_reg = {
-                let fn_ctx = self.get_current_function()?;
-                fn_ctx.register_allocator.get_last_temp_register()?
-            };
-            //println!("Last reg: {:?}", last_reg);
-            let var_sp_offset = -(var_size as i16);
-            let stack_inc_instr = Instruction::new_inc_stack(var_size);
-            self.builder.push_instr(stack_inc_instr);
-            self.inc_stack(var_size)?;
-            let mov_instr = match var_type {
-                Type::Int => {
-                    Instruction::new(Opcode::MOVI_RA)
-                        .with_operand::<u8>(last_reg.into())
-                        .with_operand::<u8>(Register::SP.into())
-                        .with_operand::<i16>(var_sp_offset)
-                },
-                Type::Float => {
-                    Instruction::new(Opcode::MOVF_RA)
-                        .with_operand::<u8>(last_reg.into())
-                        .with_operand::<u8>(Register::SP.into())
-                        .with_operand::<i16>(var_sp_offset)
-                },
-                Type::Reference(_) => {
-                    Instruction::new(Opcode::MOVA_RA)
-                        .with_operand::<u8>(last_reg.into())
-                        .with_operand::<u8>(Register::SP.into())
-                        .with_operand::<i16>(var_sp_offset)
-                },
-                Type::Bool => {
-                    Instruction::new(Opcode::MOVB_RA)
-                        .with_operand::<u8>(last_reg.into())
-                        .with_operand::<u8>(Register::SP.into())
-                        .with_operand::<i16>(var_sp_offset)
-                },
-                _ => {
-                    //println!("Error in compile_var_decl_stmt()!");
-                    return Err(CompilerError::UnknownType(var_type));
-                }
-            };
-            self.builder.push_instr(mov_instr);
-        }
-        // Otherwise, the value is already on the top of the stack.
-        // Set the variable in the context.
-        let fn_ctx = self.get_current_function_mut()?;
-        fn_ctx.set_stack_var((var_name.clone(), var_type.clone()), (fn_ctx.stack_size - var_size) as i64)?;
-        //println!("Setting var {}: {:?} to position {}", var_name, var_type, fn_ctx.stack_size - var_size);
+    // #region integer operators
+
+    /// Returns the opcode of an integer operator and its variant taking an immediate rhs
+    fn get_int_binary_opcodes(expr: &Expression) -> CompilerResult<(Opcode, Opcode)> {
+        let opcodes = match expr {
+            Expression::Modulo(_, _) => (Opcode::MODI, Opcode::MODI_I),
+            Expression::BitAnd(_, _) => (Opcode::ANDI, Opcode::ANDI_I),
+            Expression::BitOr(_, _) => (Opcode::ORI, Opcode::ORI_I),
+            Expression::BitXor(_, _) => (Opcode::XORI, Opcode::XORI_I),
+            Expression::ShiftLeft(_, _) => (Opcode::SHLI, Opcode::SHLI_I),
+            Expression::ShiftRight(_, _) => (Opcode::SHRI, Opcode::SHRI_I),
+            _ => return Err(CompilerError::UnsupportedExpression(expr.clone()))
+        };
+        Ok(opcodes)
+    }
+
+    /// Compiles a remainder, bitwise or shift expression. Integer literals on the rhs
+    /// are encoded as immediates.
+    pub fn compile_int_binary_expr(&mut self, expr: &Expression, lhs: &Expression, rhs: &Expression) -> CompilerResult<()> {
+        let (opcode, imm_opcode) = Compiler::get_int_binary_opcodes(expr)?;
+        self.compile_expr(lhs)?;
+        let lhs_reg = self.get_last_register()?;
+        let instr = match rhs {
+            Expression::IntLiteral(value) => {
+                let target_reg = self.get_next_register()?;
+                Instruction::new(imm_opcode)
+                    .with_operand::<u8>(lhs_reg.into())
+                    .with_operand::<i64>(*value)
+                    .with_operand::<u8>(target_reg.into())
+            },
+            _ => {
+                self.compile_expr(rhs)?;
+                let rhs_reg = self.get_last_register()?;
+                let target_reg = self.get_next_register()?;
+                Instruction::new(opcode)
+                    .with_operand::<u8>(lhs_reg.into())
+                    .with_operand::<u8>(rhs_reg.into())
+                    .with_operand::<u8>(target_reg.into())
+            }
+        };
+        self.builder.push_instr(instr);
         Ok(())
     }
 
-    /// Compiles a statement expression
-    pub fn compile_expr_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
-        let stmt_expr = match stmt {
-            Statement::Expression(expr) => expr,
-            _ => return Err(CompilerError::Unknown)
+    // #endregion
+
+    // #region operator overloading
+
+    /// Returns the core interface and its function overloading the operator of an expression
+    fn get_operator_fn(expr: &Expression) -> Option<(&'static str, &'static str)> {
+        let operator_fn = match expr {
+            Expression::Addition(_, _) => (ADD_INTERFACE, "add"),
+            Expression::Subtraction(_, _) => (SUB_INTERFACE, "sub"),
+            Expression::Multiplication(_, _) => (MUL_INTERFACE, "mul"),
+            Expression::Equals(_, _) |
+            Expression::NotEquals(_, _) => (EQ_INTERFACE, "eq"),
+            Expression::LessThan(_, _) |
+            Expression::GreaterThan(_, _) |
+            Expression::LessThanEquals(_, _) |
+            Expression::GreaterThanEquals(_, _) => (ORD_INTERFACE, "cmp"),
+            _ => return None
         };
+        Some(operator_fn)
+    }
 
-        match stmt_expr {
-            Expression::Call(_, _) => self.compile_expr(stmt_expr)?,
-            Expression::Assign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
-            Expression::AddAssign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
-            Expression::SubAssign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
-            Expression::MulAssign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
-            Expression::DivAssign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
-            _ => return Err(CompilerError::UnsupportedExpression(stmt_expr.clone()))
+    /// Resolves the member function overloading the operator of an expression,
+    /// if the lhs operand is a container implementing the matching core interface
+    fn resolve_operator_fn(&self, expr: &Expression) -> Option<FunctionDef> {
+        let (intf_name, fn_name) = Compiler::get_operator_fn(expr)?;
+        let lhs_expr = match expr {
+            Expression::Addition(lhs, _) |
+            Expression::Subtraction(lhs, _) |
+            Expression::Multiplication(lhs, _) |
+            Expression::Equals(lhs, _) |
+            Expression::NotEquals(lhs, _) |
+            Expression::LessThan(lhs, _) |
+            Expression::GreaterThan(lhs, _) |
+            Expression::LessThanEquals(lhs, _) |
+            Expression::GreaterThanEquals(lhs, _) => lhs,
+            _ => return None
         };
+        let mut lhs_type = self.check_expr_type(lhs_expr).ok()?;
+        self.canonize_type(&mut lhs_type).ok()?;
+        let cont_def = match &lhs_type {
+            Type::Other(cont_name) => self.resolve_container(cont_name).ok()?,
+            _ => return None
+        };
+        if !cont_def.does_implement(&String::from(intf_name)) {
+            return None;
+        }
+        cont_def.get_member_function(&String::from(fn_name)).ok().cloned()
+    }
 
-        Ok(())
-        //Err(CompilerError::Unimplemented(format!("Statement expr compilation not implemented!")))
+    /// Returns the type of an overloaded operator expression.
+    /// "eq" has to return a bool and "cmp" an int ordering, which is compared against 0.
+    fn check_operator_expr_type(&self, expr: &Expression, fn_def: &FunctionDef) -> CompilerResult<Type> {
+        let (intf_name, _) = Compiler::get_operator_fn(expr)
+            .ok_or(CompilerError::Unknown)?;
+        let expected_ret_type = match intf_name {
+            EQ_INTERFACE => Type::Bool,
+            ORD_INTERFACE => Type::Int,
+            _ => return Ok(fn_def.ret_type.clone())
+        };
+        if fn_def.ret_type != expected_ret_type {
+            return Err(CompilerError::TypeMismatch(expected_ret_type, fn_def.ret_type.clone()));
+        }
+        Ok(Type::Bool)
     }
-    
 
-    /// Compiles an if statement
-    pub fn compile_if_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
-        let if_stmt_args: &IfStatementArgs = match stmt {
-            Statement::If(if_stmt_args) => if_stmt_args,
+    /// Compiles an operator on a container into a call of the member function overloading it.
+    /// An lhs which is not a variable is evaluated into a temporary on the stack.
+    pub fn compile_operator_expr(&mut self, expr: &Expression, fn_def: &FunctionDef) -> CompilerResult<()> {
+        let (lhs_expr, rhs_expr) = match expr {
+            Expression::Addition(lhs, rhs) |
+            Expression::Subtraction(lhs, rhs) |
+            Expression::Multiplication(lhs, rhs) |
+            Expression::Equals(lhs, rhs) |
+            Expression::NotEquals(lhs, rhs) |
+            Expression::LessThan(lhs, rhs) |
+            Expression::GreaterThan(lhs, rhs) |
+            Expression::LessThanEquals(lhs, rhs) |
+            Expression::GreaterThanEquals(lhs, rhs) => (lhs.deref(), rhs.deref()),
             _ => return Err(CompilerError::Unknown)
         };
+        self.check_operator_expr_type(expr, fn_def)?;
 
-        // Generate an instruction tag to fill in the end of this if/else chain
-        let tag_end = self.uid_generator.generate();
-        // Generate an instruction tag for the next branch
-        let mut tag_next = self.uid_generator.generate();
+        let mut temp_size = 0;
+        match lhs_expr {
+            Expression::Variable(var_name) if self.get_this_member_expr(lhs_expr).is_none() => {
+                self.compile_var_addr(var_name)?;
+            },
+            _ => {
+                let lhs_type = self.check_expr_type(lhs_expr)?;
+                temp_size = self.get_size_of_type(&lhs_type)?;
+                self.compile_expr(lhs_expr)?;
+                self.compile_stack_addr(temp_size)?;
+            }
+        }
 
-        let expr_type = self.check_expr_type(&if_stmt_args.if_expr)?;
-        // Only boolean expressions are allowed
-        if expr_type != Type::Bool {
-            return Err(CompilerError::TypeMismatch(expr_type, Type::Bool));
+        self.compile_this_call_expr(fn_def, std::slice::from_ref(rhs_expr), None)?;
+
+        // Remove the temporary lhs below the result
+        if temp_size > 0 {
+            if !fn_def.ret_type.is_primitive() {
+                let ret_size = self.get_size_of_type(&fn_def.ret_type)?;
+                let mov_stack_instr = Instruction::new(Opcode::MOVN_A)
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(-(ret_size as i16))
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(-((ret_size + temp_size) as i16))
+                    .with_operand::<u32>(ret_size as u32);
+                self.builder.push_instr(mov_stack_instr);
+            }
+            let stack_dec_instr = Instruction::new_dec_stack(temp_size);
+            self.dec_stack(temp_size)?;
+            self.builder.push_instr(stack_dec_instr);
         }
-        // Compile the if expression
-        self.compile_expr(&if_stmt_args.if_expr)?;
-        // Get the register the result of this boolean expression was saved in
-        let last_reg = {
-            self.get_current_function()?
-                .register_allocator
-                .get_last_temp_register()?
+
+        if fn_def.ret_type.is_primitive() {
+            self.force_temp_register(Register::R0)?;
+        }
+
+        match expr {
+            Expression::NotEquals(_, _) => {
+                let target_reg = self.get_next_register()?;
+                let not_instr = Instruction::new(Opcode::NOT)
+                    .with_operand::<u8>(Register::R0.into())
+                    .with_operand::<u8>(target_reg.into());
+                self.builder.push_instr(not_instr);
+            },
+            Expression::LessThan(_, _) |
+            Expression::GreaterThan(_, _) |
+            Expression::LessThanEquals(_, _) |
+            Expression::GreaterThanEquals(_, _) => {
+                let cmp_opcode = match expr {
+                    Expression::LessThan(_, _) => Opcode::LTI,
+                    Expression::GreaterThan(_, _) => Opcode::GTI,
+                    Expression::LessThanEquals(_, _) => Opcode::LTEQI,
+                    _ => Opcode::GTEQI
+                };
+                // Compare the ordering against 0
+                let zero_reg = self.get_next_register()?;
+                let ldi_instr = Instruction::new(Opcode::LDI)
+                    .with_operand::<i64>(0)
+                    .with_operand::<u8>(zero_reg.clone().into());
+                let res_reg = self.get_next_register()?;
+                let cmp_instr = Instruction::new(cmp_opcode)
+                    .with_operand::<u8>(Register::R0.into())
+                    .with_operand::<u8>(zero_reg.into())
+                    .with_operand::<u8>(res_reg.into());
+                self.builder.push_instr(ldi_instr);
+                self.builder.push_instr(cmp_instr);
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // #endregion
+
+    // #region results
+
+    /// Resolves a result type, i.e. an enum with an "Ok" and an "Err" variant.
+    /// Returns the enum definition and the tags of both variants.
+    pub fn resolve_result_enum(&self, result_type: &Type) -> CompilerResult<(EnumDef, usize, usize)> {
+        let not_a_result = || CompilerError::NotAResult(result_type.clone());
+        let enum_def = match result_type {
+            Type::Other(enum_name) => self.resolve_enum(enum_name).map_err(|_| not_a_result())?,
+            _ => return Err(not_a_result())
+        };
+        let (ok_tag, _) = enum_def.get_variant("Ok").map_err(|_| not_a_result())?;
+        let (err_tag, _) = enum_def.get_variant("Err").map_err(|_| not_a_result())?;
+        Ok((enum_def, ok_tag, err_tag))
+    }
+
+    /// Returns the type of the value an "Ok" or "Err" variant holds.
+    /// Variants without payload hold void, several payloads form a tuple.
+    pub fn get_result_payload_type(&self, enum_def: &EnumDef, tag: usize) -> CompilerResult<Type> {
+        let mut payload_types = enum_def.variants[tag].1.clone();
+        for payload_type in payload_types.iter_mut() {
+            self.canonize_type(payload_type)?;
+        }
+        let payload_type = match payload_types.len() {
+            0 => Type::Void,
+            1 => payload_types.remove(0),
+            _ => Type::Tuple(payload_types)
+        };
+        Ok(payload_type)
+    }
+
+    /// Compiles an error propagation, e.g. "parse(text)?".
+    /// The "Err" variant returns from the function, either as is or rewrapped into
+    /// the "Err" variant of the function's result type. The "Ok" payload is the value.
+    pub fn compile_try_expr(&mut self, op_expr: &Expression) -> CompilerResult<()> {
+        let mut op_type = self.check_expr_type(op_expr)?;
+        self.canonize_type(&mut op_type)?;
+        let (enum_def, ok_tag, err_tag) = self.resolve_result_enum(&op_type)?;
+        let enum_size = enum_def.get_size(self)?;
+
+        let mut fn_ret_type = {
+            let fn_ctx = self.get_parent_function()?;
+            fn_ctx.get_ret_type()?
+        };
+        self.canonize_type(&mut fn_ret_type)?;
+
+        // The tag and size of the "Err" variant to rewrap the error into
+        let rewrap_opt = if fn_ret_type == op_type {
+            None
+        } else {
+            let type_mismatch = || CompilerError::TypeMismatch(fn_ret_type.clone(), op_type.clone());
+            let (ret_enum_def, _, ret_err_tag) = self.resolve_result_enum(&fn_ret_type)
+                .map_err(|_| type_mismatch())?;
+            let err_type = self.get_result_payload_type(&enum_def, err_tag)?;
+            let ret_err_type = self.get_result_payload_type(&ret_enum_def, ret_err_tag)?;
+            if err_type != ret_err_type {
+                return Err(type_mismatch());
+            }
+            let err_size = self.get_size_of_type(&err_type)?;
+            Some((ret_err_tag, ret_enum_def.get_size(self)?, err_size))
         };
 
-        // Instruction for this if expr
+        self.compile_expr(op_expr)?;
+
+        // Compare the tag at the start of the value with the "Err" tag
+        let tag_ok = self.uid_generator.generate();
+        let tag_reg = self.get_next_register()?;
+        let movi_instr = Instruction::new(Opcode::MOVI_AR)
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(-(enum_size as i16))
+            .with_operand::<u8>(tag_reg.clone().into());
+        let err_tag_reg = self.get_next_register()?;
+        let ldi_instr = Instruction::new(Opcode::LDI)
+            .with_operand::<i64>(err_tag as i64)
+            .with_operand::<u8>(err_tag_reg.clone().into());
+        let cmp_reg = self.get_next_register()?;
+        let eqi_instr = Instruction::new(Opcode::EQI)
+            .with_operand::<u8>(tag_reg.into())
+            .with_operand::<u8>(err_tag_reg.into())
+            .with_operand::<u8>(cmp_reg.clone().into());
         let jmpf_instr = Instruction::new(Opcode::JMPF)
-            .with_operand::<u8>(last_reg.into())
-            .with_operand(tag_next);
-        self.builder.tag(tag_next);
+            .with_operand::<u8>(cmp_reg.into())
+            .with_operand(tag_ok);
+        self.builder.push_instr(movi_instr);
+        self.builder.push_instr(ldi_instr);
+        self.builder.push_instr(eqi_instr);
+        self.builder.tag(tag_ok);
         self.builder.push_instr(jmpf_instr);
 
-        // Create new weak function context
-        let mut if_fn_ctx = {
-            let fn_ctx = self.get_current_function()?;
-            FunctionContext::new_weak(fn_ctx)?
+        // Return the error
+        let before_stack_size = self.get_stack_size()?;
+        if let Some((ret_err_tag, ret_size, err_size)) = rewrap_opt {
+            let stack_inc_instr = Instruction::new_inc_stack(ret_size);
+            self.inc_stack(ret_size)?;
+            self.builder.push_instr(stack_inc_instr);
+            let ret_tag_reg = self.get_next_register()?;
+            let ldi_instr = Instruction::new(Opcode::LDI)
+                .with_operand::<i64>(ret_err_tag as i64)
+                .with_operand::<u8>(ret_tag_reg.clone().into());
+            self.builder.push_instr(ldi_instr);
+            self.compile_store_to_addr(&Type::Int, ret_tag_reg, Register::SP, -(ret_size as i16))?;
+            if err_size > 0 {
+                let movn_instr = Instruction::new(Opcode::MOVN_A)
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(8 - (ret_size + enum_size) as i16)
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(8 - ret_size as i16)
+                    .with_operand::<u32>(err_size as u32);
+                self.builder.push_instr(movn_instr);
+            }
+        }
+        self.compile_stack_cleanup_return()?;
+        let ret_instr = Instruction::new(Opcode::RET);
+        self.builder.push_instr(ret_instr);
+        {
+            let fn_ctx = self.get_current_function_mut()?;
+            fn_ctx.stack_size = before_stack_size;
+        }
+
+        let pos_ok = self.builder.get_current_offset();
+        self.patch_jmp_tag(tag_ok, pos_ok as u64)?;
+
+        // The "Ok" payload follows the tag, compile_expr() pops the temporary value
+        let ok_type = self.get_result_payload_type(&enum_def, ok_tag)?;
+        if ok_type != Type::Void {
+            let addr_reg = self.get_next_register()?;
+            let subui_instr = Instruction::new(Opcode::SUBU_I)
+                .with_operand::<u8>(Register::SP.into())
+                .with_operand::<u64>((enum_size - 8) as u64)
+                .with_operand::<u8>(addr_reg.clone().into());
+            self.builder.push_instr(subui_instr);
+            self.compile_load_from_addr(&ok_type, addr_reg)?;
+        }
+
+        Ok(())
+    }
+
+    // #endregion
+
+    // #region closures
+
+    /// Returns the type of a variable or captured variable holding a function value
+    pub fn get_fn_value_type(&self, name: &String) -> Option<Type> {
+        if name.contains("::") {
+            return None;
+        }
+        let var_type = match self.get_type_of_var(name) {
+            Ok(var_type) => var_type,
+            Err(_) => {
+                let member_expr = self.get_this_member_expr(&Expression::Variable(name.clone()))?;
+                self.check_expr_type(&member_expr).ok()?
+            }
         };
-        // And push it on the stack
-        self.push_function_context(if_fn_ctx);
+        match var_type {
+            Type::Function(_, _) => Some(var_type),
+            _ => None
+        }
+    }
 
-        // Compile the if statement list
-        self.compile_stmt_list(&if_stmt_args.if_block)?;
+    /// Returns true if a variable expression refers to a function, which is used as a value
+    pub fn is_fn_ref_expr(&self, name: &String) -> bool {
+        self.get_type_of_var(name).is_err() && self.resolve_function(name).is_ok()
+    }
 
-        // Pop the function context off the stack again
-        if_fn_ctx = self.pop_function_context()?;
+    /// Returns the canonical function type of a function definition
+    pub fn get_fn_type(&self, fn_def: &FunctionDef) -> CompilerResult<Type> {
+        let mut arg_types = Vec::new();
+        for (_, arg_type) in fn_def.arguments.iter() {
+            let mut arg_type = arg_type.clone();
+            self.canonize_type(&mut arg_type)?;
+            arg_types.push(arg_type);
+        }
+        let mut ret_type = fn_def.ret_type.clone();
+        self.canonize_type(&mut ret_type)?;
+        Ok(Type::Function(arg_types, Box::new(ret_type)))
+    }
 
-        self.compile_stack_cleanup_block(&if_fn_ctx)?;
+    /// Returns the variables of the enclosing scope a closure refers to, with their types
+    fn get_closure_captures(&self, fn_decl_args: &FunctionDeclArgs) -> CompilerResult<Vec<(String, Type)>> {
+        let mut names = Vec::new();
+        for stmt in fn_decl_args.code_block.iter().flatten() {
+            stmt.collect_names(&mut names);
+        }
 
-        // Instruction for jumping to the end
-        let jmp_end_instr = Instruction::new(Opcode::JMP)
-            .with_operand(tag_end);
-        self.builder.tag(tag_end);
-        self.builder.push_instr(jmp_end_instr);
+        let mut captures: Vec<(String, Type)> = Vec::new();
+        for name in names {
+            let is_arg = fn_decl_args.arguments.iter().any(|(arg_name, _)| arg_name == name);
+            let is_captured = captures.iter().any(|(capture_name, _)| capture_name == name);
+            if is_arg || is_captured || name == "this" {
+                continue;
+            }
+            // Anything else is a function or a local variable of the closure
+            let var_expr = Expression::Variable(name.clone());
+            if !self.is_local_var(name) && self.get_this_member_expr(&var_expr).is_none() {
+                continue;
+            }
+            let mut var_type = self.check_expr_type(&var_expr)?;
+            self.canonize_type(&mut var_type)?;
+            captures.push((name.clone(), var_type));
+        }
+        Ok(captures)
+    }
 
-        if if_stmt_args.else_if_list.is_some() {
-            let else_if_list = if_stmt_args.else_if_list
-                .as_ref()
-                .ok_or(CompilerError::Unknown)?;
-            for (else_if_expr, else_if_stmt_list) in else_if_list.iter() {
-                // Current instruction position
-                let pos = self.builder.get_current_offset();
-                // Set the last JMPF to jump to this instruction
-                {
-                    // Retrieve the position list
-                    let jmp_next_instr_pos_list = self.builder.get_tag(&tag_next)
-                        .ok_or(CompilerError::Unknown)?;
-                    // Retrieve the position
-                    // (Only one instruction should exist with this tag)
-                    let jmp_next_instr_pos = jmp_next_instr_pos_list.get(0)
-                        .ok_or(CompilerError::Unknown)?;
-                    // Get the mutable reference to this instruction
-                    let jmp_next_instr = self.builder.get_instr(&jmp_next_instr_pos)
-                        .ok_or(CompilerError::Unknown)?;
-                    
-                    // Update the jump destination
-                    jmp_next_instr.remove_operand_bytes(8);
-                    jmp_next_instr.append_operand(pos);
-                }
-                // Only boolean expressions are allowed
-                let expr_type = self.check_expr_type(else_if_expr)?;
-                if expr_type != Type::Bool {
-                    return Err(CompilerError::TypeMismatch(expr_type, Type::Bool));
-                }
-                // Compile the expression
-                self.compile_expr(else_if_expr)?;
-                // Get the result register
-                let last_reg = {
-                    self.get_current_function()?
-                        .register_allocator
-                        .get_last_temp_register()?
-                };
-                // Generate new tag for the next jump
-                tag_next = self.uid_generator.generate();
-                // Instruction for jumping to next or inside statement list
-                let jmpf_instr = Instruction::new(Opcode::JMPF)
-                    .with_operand::<u8>(last_reg.into())
-                    .with_operand(tag_next);
-                self.builder.tag(tag_next);
-                self.builder.push_instr(jmpf_instr);
-
-                // Create a new weak function context
-                let mut else_if_fn_ctx = {
-                    let fn_ctx = self.get_current_function()?;
-                    FunctionContext::new_weak(fn_ctx)?
-                };
-                // and push it on the stack
-                self.push_function_context(else_if_fn_ctx);
+    /// Declares a compiler generated function in the current module and queues its body for compilation
+    fn declare_generated_fn(&mut self, label: String, fn_decl_args: FunctionDeclArgs) -> CompilerResult<u64> {
+        let uid = self.uid_generator.get_function_uid(&label);
+        self.fn_uid_map.insert(label.clone(), uid);
 
-                // Compile this "else if"s statement list
-                self.compile_stmt_list(else_if_stmt_list)?;
+        let fn_def = self.instantiate_fn_def(&fn_decl_args)?
+            .with_uid(uid);
 
-                // Pop the context off the stack again
-                else_if_fn_ctx = self.pop_function_context()?;
+        self.pending_instances.push_back(PendingInstance {
+            label,
+            module_path: self.get_module_path(),
+            fn_def,
+            fn_decl_args
+        });
 
-                self.compile_stack_cleanup_block(&else_if_fn_ctx)?;
+        Ok(uid)
+    }
 
-                // Instruction for jumping to the end
-                let jmp_end_instr = Instruction::new(Opcode::JMP)
-                    .with_operand(tag_end);
-                self.builder.tag(tag_end);
-                self.builder.push_instr(jmp_end_instr);
-            }
+    /// Declares a thunk which calls a function with the arguments of a function value call,
+    /// skipping the environment pointer. Returns the uid of the thunk.
+    fn declare_fn_value_thunk(&mut self, fn_name: &str, fn_def: &FunctionDef) -> CompilerResult<u64> {
+        let label = format!("{{thunk#{:x}}}", fn_def.uid);
+        let mut arguments = vec![(String::from("{env}"), Type::Reference(Box::new(Type::Void)))];
+        let mut arg_exprs = Vec::new();
+        for (i, (_, arg_type)) in fn_def.arguments.iter().enumerate() {
+            let arg_name = format!("{{arg#{}}}", i);
+            arg_exprs.push(Expression::Variable(arg_name.clone()));
+            arguments.push((arg_name, arg_type.clone()));
         }
 
-        // If an "else" block exists
-        if if_stmt_args.else_block.is_some() {
-            let else_stmt_list = if_stmt_args.else_block.as_ref()
-                .ok_or(CompilerError::Unknown)?;
-            // Set the last JMPF to jump to this instruction
-            let pos = self.builder.get_current_offset();
-            {
-                // Retrieve the position list
-                let jmp_next_instr_pos_list = self.builder.get_tag(&tag_next)
-                    .ok_or(CompilerError::Unknown)?;
-                // Retrieve the position
-                // (Only one instruction should exist with this tag)
-                let jmp_next_instr_pos = jmp_next_instr_pos_list.get(0)
-                    .ok_or(CompilerError::Unknown)?;
-                // Get the mutable reference to this instruction
-                let jmp_next_instr = self.builder.get_instr(&jmp_next_instr_pos)
-                    .ok_or(CompilerError::Unknown)?;
-                    
-                // Update the jump destination
-                jmp_next_instr.remove_operand_bytes(8);
-                jmp_next_instr.append_operand(pos);
-            }
+        let call_expr = Expression::Call(fn_name.to_string(), arg_exprs);
+        let stmt = match fn_def.ret_type {
+            Type::Void => Statement::Expression(call_expr),
+            _ => Statement::Return(Some(call_expr))
+        };
 
-            // Create a new weak function context
-            let mut else_fn_ctx = {
-                let fn_ctx = self.get_current_function()?;
-                FunctionContext::new_weak(fn_ctx)?
-            };
-            // And push it on the stack
-            self.push_function_context(else_fn_ctx);
+        let fn_decl_args = FunctionDeclArgs {
+            name: label.clone(),
+            type_params: Vec::new(),
+            arguments,
+            returns: fn_def.ret_type.clone(),
+            code_block: Some(vec![stmt])
+        };
+        self.declare_generated_fn(label, fn_decl_args)
+    }
 
-            // Compile the statement list for this else block
-            self.compile_stmt_list(else_stmt_list)?;
+    /// Compiles a function used as a value, e.g. "apply(double, 2)"
+    pub fn compile_fn_ref_expr(&mut self, fn_name: &String) -> CompilerResult<()> {
+        let fn_def = self.resolve_function(fn_name)?;
+        let thunk_uid = match self.fn_value_thunks.get(&fn_def.uid) {
+            Some(thunk_uid) => *thunk_uid,
+            None => {
+                let thunk_uid = self.declare_fn_value_thunk(fn_name, &fn_def)?;
+                self.fn_value_thunks.insert(fn_def.uid, thunk_uid);
+                thunk_uid
+            }
+        };
+        self.compile_fn_value(thunk_uid, None)
+    }
 
-            // Pop it off the stack again
-            else_fn_ctx = self.pop_function_context()?;
+    /// Compiles a closure. The captured variables are copied into an environment container
+    /// on the heap, which the body of the closure accesses through "this".
+    pub fn compile_closure_expr(&mut self, fn_decl_args: &FunctionDeclArgs) -> CompilerResult<()> {
+        let captures = self.get_closure_captures(fn_decl_args)?;
 
-            self.compile_stack_cleanup_block(&else_fn_ctx)?;
-        } else {
-            // Set the last JMPF to jump to this instruction
-            let pos = self.builder.get_current_offset();
-            {
-                // Retrieve the position list
-                let jmp_next_instr_pos_list = self.builder.get_tag(&tag_next)
-                    .ok_or(CompilerError::Unknown)?;
-                // Retrieve the position
-                // (Only one instruction should exist with this tag)
-                let jmp_next_instr_pos = jmp_next_instr_pos_list.get(0)
-                    .ok_or(CompilerError::Unknown)?;
-                // Get the mutable reference to this instruction
-                let jmp_next_instr = self.builder.get_instr(&jmp_next_instr_pos)
-                    .ok_or(CompilerError::Unknown)?;
-                    
-                // Update the jump destination
-                jmp_next_instr.remove_operand_bytes(8);
-                jmp_next_instr.append_operand(pos);
-            }
+        self.closure_count += 1;
+        let env_name = format!("{}{}}}", CLOSURE_PREFIX, self.closure_count);
+        let mut env_def = ContainerDef::new(env_name.clone(), env_name.clone());
+        for capture in captures.iter() {
+            env_def.add_member_variable(capture.clone())?;
         }
+        self.cont_instances.insert(env_name.clone(), env_def);
 
-        // Current position is at the end of the entire if/else if/else chain
-        let pos_end = self.builder.get_current_offset();
-
-        let jmp_end_pos_list = self.builder.get_tag(&tag_end)
-            .ok_or(CompilerError::Unknown)?;
+        let mut closure_decl_args = fn_decl_args.clone();
+        closure_decl_args.name = env_name.clone();
+        closure_decl_args.arguments.insert(0, (String::from("this"), Type::Reference(Box::new(Type::Other(env_name.clone())))));
+        let uid = self.declare_generated_fn(env_name.clone(), closure_decl_args)?;
 
-        // Make all the jump instructions jump to the end properly
-        for jmp_end_pos in jmp_end_pos_list.iter() {
-            let jmp_instr = self.builder.get_instr(jmp_end_pos)
-                .ok_or(CompilerError::Unknown)?;
-            jmp_instr.remove_operand_bytes(8);
-            jmp_instr.append_operand(pos_end);
+        if captures.is_empty() {
+            return self.compile_fn_value(uid, None);
         }
-
-        Ok(())
+        let member_map = captures.into_iter()
+            .map(|(name, _)| (name.clone(), Expression::Variable(name)))
+            .collect();
+        let env_expr = Expression::Box(Box::new(Expression::ContainerInstance(env_name, member_map)));
+        self.compile_fn_value(uid, Some(&env_expr))
     }
 
-    /// Compiles a while statement
-    pub fn compile_while_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
-        let (while_expr, while_stmt_list) = match stmt {
-            Statement::While(while_expr, while_stmt_list) => (while_expr, while_stmt_list),
-            _ => return Err(CompilerError::Unknown)
+    /// Compiles a function value on top of the stack. Function values consist of the function uid
+    /// followed by the environment pointer, which is passed to the function as its first argument.
+    fn compile_fn_value(&mut self, fn_uid: u64, env_expr: Option<&Expression>) -> CompilerResult<()> {
+        let env_reg = match env_expr {
+            Some(env_expr) => {
+                self.compile_expr(env_expr)?;
+                self.get_last_register()?
+            },
+            None => {
+                let env_reg = self.get_next_register()?;
+                let env_lda_instr = Instruction::new(Opcode::LDA)
+                    .with_operand::<u64>(0)
+                    .with_operand::<u8>(env_reg.clone().into());
+                self.builder.push_instr(env_lda_instr);
+                env_reg
+            }
         };
+        let uid_reg = self.get_next_register()?;
+
+        let stack_inc_instr = Instruction::new_inc_stack(16);
+        self.inc_stack(16)?;
+        let uid_lda_instr = Instruction::new(Opcode::LDA)
+            .with_operand::<u64>(fn_uid)
+            .with_operand::<u8>(uid_reg.clone().into());
+        let mov_uid_instr = Instruction::new(Opcode::MOVA_RA)
+            .with_operand::<u8>(uid_reg.into())
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(-16);
+        let mov_env_instr = Instruction::new(Opcode::MOVA_RA)
+            .with_operand::<u8>(env_reg.into())
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(-8);
 
-        let while_fn_ctx = FunctionContext::new_loop(self.get_current_function()?)?;
-        self.push_function_context(while_fn_ctx);
-        let while_start_pos = self.builder.get_current_offset();
-        let tag_end = self.uid_generator.generate();
-        let mut while_loop_ctx = LoopContext::new(while_start_pos, tag_end);
-        self.push_loop_context(while_loop_ctx);
-
-        // Check type of while expr
-        let expr_type = self.check_expr_type(while_expr)?;
-        // Only boolean expr are allowed
-        if expr_type != Type::Bool {
-            return Err(CompilerError::TypeMismatch(Type::Bool, expr_type.clone()));
-        }
-
-        // Compile the expression
-        self.compile_expr(while_expr)?;
-
-        let last_reg = {
-            self.get_current_function()?
-                .register_allocator
-                .get_last_temp_register()?
-        };
+        self.builder.push_instr(stack_inc_instr);
+        self.builder.push_instr(uid_lda_instr);
+        self.builder.push_instr(mov_uid_instr);
+        self.builder.push_instr(mov_env_instr);
 
-        self.builder.tag(tag_end);
-        let jmpf_instr = Instruction::new(Opcode::JMPF)
-            .with_operand::<u8>(last_reg.into())
-            .with_operand(tag_end);
-        self.builder.push_instr(jmpf_instr);
+        Ok(())
+    }
 
-        // Compile the statement list
-        self.compile_stmt_list(while_stmt_list)?;
+    // #endregion
+    
+    // #region compile functions
 
-        // Compile a continue statement
-        self.compile_continue_stmt(&Statement::Continue)?;
+    /// Compiles the decl list for the root module
+    pub fn compile_root(&mut self, decl_list: &[Declaration]) -> CompilerResult<()> {
+        self.declare_decl_list(decl_list)?;
+        self.instantiate_decl_list(decl_list)?;
+        self.compile_decl_list(decl_list)?;
+        self.compile_pending_instances()?;
+        Ok(())
+    }
 
-        // This is the end of this while loop
-        let while_end_pos = self.builder.get_current_offset();
-        
-        // Pop the while loop off the stack
-        while_loop_ctx = self.pop_loop_context()?;
-        let instr_pos_list = self.builder.get_tag(&while_loop_ctx.tag_end)
-            .ok_or(CompilerError::Unknown)?;
-        
-        // Update with correct end position
-        for instr_pos in instr_pos_list {
-            let jmpf_instr = self.builder.get_instr(&instr_pos)
-                .ok_or(CompilerError::Unknown)?;
-            jmpf_instr.remove_operand_bytes(8);
-            jmpf_instr.append_operand::<u64>(while_end_pos as u64);
+    /// Compiles a declaration list
+    pub fn compile_decl_list(&mut self, decl_list: &[Declaration]) -> CompilerResult<()> {
+        for decl in decl_list.iter() {
+            self.compile_decl(decl)?;
         }
+        Ok(())
+    }
 
-        // Pop this while loops fn context off the stack
-        self.pop_function_context()?;
-
+    /// Compiles a declaration
+    pub fn compile_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
+        match decl {
+            Declaration::Function(_) => self.compile_fn_decl(decl)?,
+            Declaration::Impl(_, _, _) => self.compile_impl_decl(decl)?,
+            Declaration::Module(_, _) => self.compile_mod_decl(decl)?,
+            Declaration::Interface(_, _, _) => self.compile_intf_decl(decl)?,
+            _ => {}
+        };
         Ok(())
     }
 
-    /// Compiles a break statement
-    pub fn compile_break_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
-        if *stmt != Statement::Break {
-            return Err(CompilerError::Unknown);
+    /// Compiles an interface declaration
+    pub fn compile_intf_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
+        let (intf_name, type_params, intf_decl_list) = match decl {
+            Declaration::Interface(name, type_params, list) => (name, type_params, list),
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        // Generic interfaces only declare signatures per instance
+        if !type_params.is_empty() {
+            return Ok(());
         }
 
-        // Compile the stack cleanup
-        self.compile_stack_loop()?;
+        self.current_intf = Some(intf_name.clone());
 
-        let tag_end = {
-            self.get_current_loop()?
-                .tag_end
-        };
+        self.compile_decl_list(intf_decl_list)?;
 
-        // Tag this instruction
-        self.builder.tag(tag_end);
-        // JMP to end instr
-        let jmp_end_instr = Instruction::new(Opcode::JMP)
-            .with_operand::<u64>(tag_end);
-        self.builder.push_instr(jmp_end_instr);
+        self.current_intf = None;
 
         Ok(())
     }
 
-    /// Compiles a continue statement
-    pub fn compile_continue_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
-        if *stmt != Statement::Continue {
-            return Err(CompilerError::Unknown);
+    /// Compiles a function declaration
+    pub fn compile_fn_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
+        let fn_decl_args = match decl {
+            Declaration::Function(fn_decl_args) => fn_decl_args,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        // Generic functions are compiled per instance
+        if !fn_decl_args.type_params.is_empty() {
+            return Ok(());
         }
 
-        // Compile the stack cleanup
-        self.compile_stack_loop()?;
+        //println!("Compiling fn_decl");
+        let fn_def;
+        if self.current_cont.is_some() {
+            let current_cont_name = self.current_cont.as_ref().ok_or(CompilerError::Unknown)?;
+            let cont_def = self.resolve_container(current_cont_name)?;
+            fn_def = cont_def.get_member_function(&fn_decl_args.name)?
+                .clone();
+        } else if self.current_intf.is_some() {
+            let current_intf_name = self.current_intf.as_ref().ok_or(CompilerError::Unknown)?;
+            let intf_def = self.resolve_interface(&current_intf_name)?;
+            fn_def = intf_def.get_function(&fn_decl_args.name)?
+                .clone();
+        } else {
+            fn_def = self.resolve_function(&fn_decl_args.name)?;
+        }
 
-        let loop_start_pos = {
-            self.get_current_loop()?
-                .pos_start
-        };
+        //println!("Fn def: {:?}", fn_def);
 
-        // JMP to begin instr
-        let jmp_begin_instr = Instruction::new(Opcode::JMP)
-            .with_operand::<u64>(loop_start_pos as u64);
-        self.builder.push_instr(jmp_begin_instr);
-        
-        Ok(())
+        let mut full_fn_name = self.get_module_path();
+        if self.current_cont.is_some() {
+            full_fn_name += self.current_cont.as_ref().unwrap();
+            full_fn_name += "::";
+        } else if self.current_intf.is_some() {
+            full_fn_name += self.current_intf.as_ref().unwrap();
+            full_fn_name += "::";
+        }
+        full_fn_name += &fn_decl_args.name;
+
+        //println!("Compiling fn decl with label {}", full_fn_name);
+
+        self.compile_fn_body(full_fn_name, fn_def, fn_decl_args)
     }
 
-    /// Compiles a return statement
-    pub fn compile_return_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
-        let return_expr_opt = match stmt {
-            Statement::Return(ret_expr) => ret_expr,
-            _ => return Err(CompilerError::Unknown)
-        };
+    /// Compiles the body of a function under the given label
+    fn compile_fn_body(&mut self, label: String, fn_def: FunctionDef, fn_decl_args: &FunctionDeclArgs) -> CompilerResult<()> {
+        let fn_ret_type = fn_def.ret_type.clone();
 
-        let mut return_expr_type = Type::Void;
+        let fn_ctx = FunctionContext::new(self, fn_def)?;
 
-        if return_expr_opt.is_some() {
-            let return_expr_ref = return_expr_opt.as_ref().unwrap();
-            return_expr_type = self.check_expr_type(return_expr_ref)?;
-        }
+        self.builder.push_label(label);
 
-        let fn_ret_type = {
-            let fn_ctx = self.get_parent_function()?;
-            fn_ctx.get_ret_type()?
-        };
+        self.push_function_context(fn_ctx);
 
-        if fn_ret_type != return_expr_type {
-            return Err(CompilerError::TypeMismatch(fn_ret_type, return_expr_type));
+        if let Some(stmt_list) = &fn_decl_args.code_block {
+            self.compile_stmt_list(stmt_list)?;
         }
 
-        if return_expr_opt.is_some() {
-            let return_expr = return_expr_opt.as_ref().unwrap();
-            let ret_expr_type = self.check_expr_type(return_expr)?;
-            //println!("Ret expr type: {:?}", ret_expr_type);
-            //println!("Ret expr: {:?}", return_expr);
-            self.compile_expr(return_expr)?;
-
-            // Move to R0 register if type is primitive
-            if ret_expr_type.is_primitive() {
-                match fn_ret_type {
-                    Type::Int => {
-                        let last_reg = {
-                            let fn_ctx = self.get_current_function()?;
-                            fn_ctx.register_allocator.get_last_temp_register()?
-                        };
-                        // Instruction for doing so
-                        let mov_ret_instr = Instruction::new(Opcode::MOVI)
-                            .with_operand::<u8>(last_reg.into())
-                            .with_operand::<u8>(Register::R0.into());
-                        self.builder.push_instr(mov_ret_instr);
-                    },
-                    Type::Float => {
-                        let last_reg = {
-                            let fn_ctx = self.get_current_function()?;
-                            fn_ctx.register_allocator.get_last_temp_register()?
-                        };
-                        // Instruction for doing so
-                        let mov_ret_instr = Instruction::new(Opcode::MOVF)
-                            .with_operand::<u8>(last_reg.into())
-                            .with_operand::<u8>(Register::R0.into());
-                        self.builder.push_instr(mov_ret_instr);
-                    },
-                    Type::Bool => {
-                        let last_reg = {
-                            let fn_ctx = self.get_current_function()?;
-                            fn_ctx.register_allocator.get_last_temp_register()?
-                        };
-                        // Instruction for doing so
-                        let mov_ret_instr = Instruction::new(Opcode::MOVB)
-                            .with_operand::<u8>(last_reg.into())
-                            .with_operand::<u8>(Register::R0.into());
-                        self.builder.push_instr(mov_ret_instr);
-                    },
-                    Type::Reference(_) => {
-                        let last_reg = {
-                            let fn_ctx = self.get_current_function()?;
-                            fn_ctx.register_allocator.get_last_temp_register()?
-                        };
-                        // Instruction for doing so
-                        let mov_ret_instr = Instruction::new(Opcode::MOVA)
-                            .with_operand::<u8>(last_reg.into())
-                            .with_operand::<u8>(Register::R0.into());
-                        self.builder.push_instr(mov_ret_instr);
-                    },
-                    _ => {}
-                };
-            }
+        // If the type is void, automatically add a return Statement
+        if fn_ret_type == Type::Void {
+            let ret_stmt = Statement::Return(None);
+            self.compile_return_stmt(&ret_stmt)?;
         }
 
-        // Clean up the stack.
-        self.compile_stack_cleanup_return()?;
+        // Instruction in case the function didnt return a value
+        let halt_instr = Instruction::new(Opcode::HALT)
+            .with_operand::<u8>(1);
+        self.builder.push_instr(halt_instr);
 
-        // Add the RET function
-        let ret_instr = Instruction::new(Opcode::RET);
-        self.builder.push_instr(ret_instr);
+        // Variables (and "this") must not leak into the next function
+        self.pop_function_context()?;
 
         Ok(())
     }
 
-    /// Compiles a variable assign statement expression
-    pub fn compile_var_assign_stmt_expr(&mut self, assign_expr: &Expression) -> CompilerResult<()> {
-        let (lhs_expr, rhs_expr) = match assign_expr {
-            Expression::Assign(lhs, rhs) => (lhs.deref().clone(), rhs.deref().clone()),
-            Expression::AddAssign(lhs, rhs) => {
-                let rhs_expr = Expression::Addition(lhs.clone(), rhs.clone());
-                (lhs.deref().clone(), rhs_expr)
-            },
-            Expression::SubAssign(lhs, rhs) => {
-                let rhs_expr = Expression::Subtraction(lhs.clone(), rhs.clone());
-                (lhs.deref().clone(), rhs_expr)
-            },
-            Expression::DivAssign(lhs, rhs) => {
-                let rhs_expr = Expression::Division(lhs.clone(), rhs.clone());
-                (lhs.deref().clone(), rhs_expr)
-            },
-            Expression::MulAssign(lhs, rhs) => {
-                let rhs_expr = Expression::Multiplication(lhs.clone(), rhs.clone());
-                (lhs.deref().clone(), rhs_expr)
-            },
-            _ => return Err(CompilerError::Unknown)
-        };
+    /// Compiles the proper SUBU_I instruction for a break statement
+    pub fn compile_stack_loop(&mut self) -> CompilerResult<()> {
+        let mut pop_size = 0;
+        let mut try_count = 0;
 
-        // Compile the left hand side of this expression
-        let lhs_expr_type = self.compile_lhs_assign_expr(&lhs_expr)?;
+        // Pop all values until the first loop context is hit
+        for i in 0..self.fn_context_stack.len() {
+            let fn_ctx = self.fn_context_stack.get(i)
+                .ok_or(CompilerError::Unknown)?;
+            pop_size += fn_ctx.stack_size;
+            if fn_ctx.is_loop {
+                break;
+            }
+            if fn_ctx.is_try {
+                try_count += 1;
+            }
+        }
 
-        //println!("Type to be assigned to: {:?}", lhs_expr_type);
-        // Get the result register
-        let mut lhs_reg = {
-            let fn_ctx = self.get_current_function_mut()?;
-            fn_ctx.register_allocator.get_last_temp_register()?
-        };
+        self.compile_try_end(try_count);
 
-        // Save the result pointer to the stack;
-        let stack_inc_instr = Instruction::new_inc_stack(8);
-        self.inc_stack(8)?;
+        //println!("Compiling loop stack cleanup with pop size {}", pop_size);
 
-        let save_stack_instr = Instruction::new(Opcode::MOVA_RA)
-            .with_operand::<u8>(lhs_reg.into())
-            .with_operand::<u8>(Register::SP.into())
-            .with_operand::<i16>(-8);
-        
-        self.builder.push_instr(stack_inc_instr);
-        self.builder.push_instr(save_stack_instr);
+        let stack_instr = Instruction::new_dec_stack(pop_size);
+        self.builder.push_instr(stack_instr);
 
-        let lhs_ptr_pos = {
-            self.get_current_function()?
-                .stack_size - 8
-        };
+        Ok(())
+    }
+    
 
-        // Check the type of the rhs expression
-        let rhs_expr_type = self.check_expr_type(&rhs_expr)?;
+    /// Compiles a stack cleanup for a given function context
+    pub fn compile_stack_cleanup_block(&mut self, fn_ctx: &FunctionContext) -> CompilerResult<()> {
+        let pop_size = fn_ctx.stack_size;
 
-        // Check for type mismatch
-        if lhs_expr_type != rhs_expr_type {
-            return Err(CompilerError::TypeMismatch(lhs_expr_type, rhs_expr_type));
+        //println!("Compiling stack cleanup with stack size {}", pop_size);
+
+        // Instruction for popping values off the stack
+        if pop_size > 0 {
+            let pop_stack_instr = Instruction::new_dec_stack(pop_size);
+            self.builder.push_instr(pop_stack_instr);
         }
 
-        let mut stack_size = self.get_stack_size()?;
+        Ok(())
+    }
 
-        //println!("Stack size before assign expr: {}", stack_size);
+    /// Compiles a full stack unwind until the parent function is hit 
+    pub fn compile_stack_cleanup_return(&mut self) -> CompilerResult<()> {
+        let mut parent_fn_ctx_opt = None;
+        let mut stack_size = 0;
+        let mut try_count = 0;
 
-        // Compile the right hand of this expression
-        self.compile_expr(&rhs_expr)?;
-        stack_size = self.get_stack_size()?;
-        //println!("Stack size after assign expr: {}", stack_size);
+        for ctx in self.fn_context_stack.iter() {
+            stack_size += ctx.stack_size;
+            if !ctx.weak {
+                parent_fn_ctx_opt = Some(ctx);
+                break;
+            }
+            if ctx.is_try {
+                try_count += 1;
+            }
+        }
 
-        // Last register used may contain the assignment value
-        let rhs_reg = self.get_last_register()?;
+        let parent_fn_ctx = parent_fn_ctx_opt.ok_or(CompilerError::Unknown)?;
+        let ret_type = parent_fn_ctx.get_ret_type()?;
+        let ret_size = self.get_size_of_type(&ret_type)?;
+        let mut pop_size = stack_size;
+        let stack_begin_offset = -(stack_size as i16);
 
-        lhs_reg = self.get_next_register()?;
+        // Returning from within try blocks removes their handlers
+        self.compile_try_end(try_count);
+        
+        if !ret_type.is_primitive() {
+            //println!("fn return type is non-primitive.");
+            pop_size -= ret_size;
+            if pop_size > 0 {
+                let mov_stack_instr = Instruction::new(Opcode::MOVN_A)
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(-(ret_size as i16))
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(stack_begin_offset)
+                    .with_operand::<u32>(ret_size as u32);
+                self.builder.push_instr(mov_stack_instr);
+            }
+        }
 
-        let stack_offset: i16 = {
-            let curr_stack_size = self.get_stack_size()?;
-            -((curr_stack_size - lhs_ptr_pos) as i16)
-        };
+        // The compile time stack size is left untouched, as it may span several
+        // nested contexts. compile_return_stmt() restores it after the return.
+        if pop_size > 0 {
+            //println!("Popping {} off the stack at return.", pop_size);
+            let pop_stack_instr = Instruction::new_dec_stack(pop_size);
+            self.builder.push_instr(pop_stack_instr);
+        }
 
-        // Move the pointer from the stack into the lhs register
-        let mov_stack_instr = Instruction::new(Opcode::MOVA_AR)
-            .with_operand::<u8>(Register::SP.into())
-            .with_operand::<i16>(stack_offset)
-            .with_operand::<u8>(lhs_reg.clone().into());
-        self.builder.push_instr(mov_stack_instr);
-
-        // Move the value to the assignment destination
-        let assign_instr = match rhs_expr_type {
-            Type::Int => {
-                //println!("Moving value from {:?} to the address in {:?}", rhs_reg, lhs_reg);
-                Instruction::new(Opcode::MOVI_RA)
-                    .with_operand::<u8>(rhs_reg.into())
-                    .with_operand::<u8>(lhs_reg.into())
-                    .with_operand::<i16>(0)
-            },
-            Type::Float => {
-                Instruction::new(Opcode::MOVF_RA)
-                    .with_operand::<u8>(rhs_reg.into())
-                    .with_operand::<u8>(lhs_reg.into())
-                    .with_operand::<i16>(0)
-            },
-            Type::Bool => {
-                Instruction::new(Opcode::MOVB_RA)
-                    .with_operand::<u8>(rhs_reg.into())
-                    .with_operand::<u8>(lhs_reg.into())
-                    .with_operand::<i16>(0)
-            },
-            Type::Reference(inner) => {
-                match inner.deref() {
-                    Type::AutoArray(_) => {
-                        Instruction::new(Opcode::MOVN_A)
-                            .with_operand::<u8>(Register::SP.into())
-                            .with_operand::<i16>(-16)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<i16>(0)
-                            .with_operand::<u32>(16)
-                    },
-                    _ => {
-                        Instruction::new(Opcode::MOVA_RA)
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<i16>(0)
-                    }
-                }
-            },
-            _ => {
-                let size = self.get_size_of_type(&rhs_expr_type)?;
-                Instruction::new(Opcode::MOVN_A)
-                    .with_operand::<u8>(Register::SP.into())
-                    .with_operand::<i16>(-(size as i16))
-                    .with_operand::<u8>(lhs_reg.into())
-                    .with_operand::<i16>(0)
-                    .with_operand::<u32>(size as u32)
-            }
-        };
-
-        self.builder.push_instr(assign_instr);
         Ok(())
-        //Err(CompilerError::Unimplemented(format!("Var assign compilation not implemented!")))
     }
 
-    /// Compiles the left hand side of an assignment expression
-    pub fn compile_lhs_assign_expr(&mut self, expr: &Expression) -> CompilerResult<Type> {
-        let expr_type = match expr {
-            Expression::Variable(var_name) => {
-                let stack_offset = self.get_sp_offset_of_var(var_name)?.abs() as u64;
-                let target_reg = {
-                    let fn_ctx = self.get_current_function_mut()?;
-                    fn_ctx.register_allocator.get_temp_register()?
-                };
-                // Instruction for assign
-                let stack_offset_instr = Instruction::new(Opcode::SUBU_I)
-                    .with_operand::<u8>(Register::SP.into())
-                    .with_operand::<u64>(stack_offset)
-                    .with_operand::<u8>(target_reg.into());
-                self.builder.push_instr(stack_offset_instr);
-                self.get_type_of_var(var_name)?
-            },
-            Expression::MemberAccess(lhs_expr, rhs_expr) => {
-                let var_name = match lhs_expr.deref() {
-                    Expression::Variable(var_name) => var_name,
-                    _ => return Err(CompilerError::UnsupportedExpression(lhs_expr.deref().clone()))
-                };
-                let var_offset = self.get_sp_offset_of_var(var_name)?;
-                let var_type = self.get_type_of_var(var_name)?;
-                let lhs_ptr_reg = self.get_next_register()?;
+    /// Compiles a module declaration
+    pub fn compile_mod_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
+        let (mod_name, decl_list) = match decl {
+            Declaration::Module(mod_name, decl_list) => (mod_name, decl_list),
+            _ => return Err(CompilerError::Unknown)
+        };
 
-                let cont_def = match var_type {
-                    Type::Other(cont_name) => {
-                        let subui_instr = Instruction::new(Opcode::SUBU_I)
-                            .with_operand::<u8>(Register::SP.into())
-                            .with_operand::<u64>(var_offset.abs() as u64)
-                            .with_operand::<u8>(lhs_ptr_reg.into());
-                        self.builder.push_instr(subui_instr);
-                        self.resolve_container(&cont_name)?
-                    },
-                    Type::Reference(inner_type) => {
-                        match inner_type.deref() {
-                            Type::Other(cont_name) => {
-                                let mova_instr = Instruction::new(Opcode::MOVA_AR)
-                                    .with_operand::<u8>(Register::SP.into())
-                                    .with_operand::<i16>(var_offset as i16)
-                                    .with_operand::<u8>(lhs_ptr_reg.into());
-                                self.builder.push_instr(mova_instr);
-                                self.resolve_container(cont_name)?
-                            },
-                            _ => return Err(CompilerError::Unknown)
-                        }
-                    },
-                    _ => return Err(CompilerError::Unknown)
-                };
+        let module_declared = {
+            let front_mod_ctx = self.get_current_module()?;
+            front_mod_ctx.modules.contains_key(mod_name)
+        };
 
-                self.compile_lhs_assign_member_expr(rhs_expr, &cont_def)?
-            },
-            _ => return Err(CompilerError::UnsupportedExpression(expr.clone()))
+        if !module_declared {
+            return Err(CompilerError::UnknownModule(mod_name.clone()));
+        }
+
+        let mod_ctx = {
+            let front_mod_ctx = self.get_current_module()?;
+            front_mod_ctx.modules.get(mod_name)
+                .cloned()
+                .ok_or(CompilerError::Unknown)?
         };
-        Ok(expr_type)
-    }
 
-    pub fn compile_lhs_assign_member_expr(&mut self, rhs_expr: &Expression, cont_def: &ContainerDef) -> CompilerResult<Type> {
-        match rhs_expr {
-            Expression::Variable(var_name) => {
-                let last_reg = self.get_last_register()?;
-                let next_reg = self.get_next_register()?;
+        self.push_module_context(mod_ctx);
 
-                let member_offset = cont_def.get_member_offset(self, var_name)?;
+        self.compile_decl_list(decl_list)?;
 
-                let addui_instr = Instruction::new(Opcode::ADDU_I)
-                    .with_operand::<u8>(last_reg.into())
-                    .with_operand::<u64>(member_offset as u64)
-                    .with_operand::<u8>(next_reg.into());
-                
-                self.builder.push_instr(addui_instr);
-                cont_def.get_member_type(var_name)
-            },
-            Expression::MemberAccess(lhs_expr, rhs_expr) => {
-                let var_name;
-                if let Expression::Variable(name) = lhs_expr.deref() {
-                    var_name = name;
-                } else {
-                    return Err(CompilerError::UnsupportedExpression(lhs_expr.deref().clone()));
-                }
+        self.pop_module_context()?;
 
-                let member_offset = cont_def.get_member_offset(self, var_name)?;
-                let member_type = cont_def.get_member_type(var_name)?;
+        Ok(())
+    }
 
-                let last_reg = self.get_last_register()?;
-                let next_reg = self.get_next_register()?;
+    /// Compiles an impl declaration
+    pub fn compile_impl_decl(&mut self, decl: &Declaration) -> CompilerResult<()> {
+        let (impl_type, impl_for_type, decl_list) = match decl {
+            Declaration::Impl(impl_type, impl_for, decl_list) => (impl_type, impl_for, decl_list), 
+            _ => return Err(CompilerError::Unknown)
+        };
 
-                let cont_def = match member_type {
-                    Type::Other(cont_name) => {
-                        let addui_instr = Instruction::new(Opcode::ADDU_I)
-                            .with_operand::<u8>(last_reg.into())
-                            .with_operand::<u64>(member_offset as u64)
-                            .with_operand::<u8>(next_reg.into());
-                        self.builder.push_instr(addui_instr);
-                        self.resolve_container(&cont_name)?
-                    },
-                    Type::Reference(inner_type) => {
-                        match inner_type.deref() {
-                            Type::Other(cont_name) => {
-                                let mova_instr = Instruction::new(Opcode::MOVA_AR)
-                                    .with_operand::<u8>(last_reg.into())
-                                    .with_operand::<i16>(member_offset as i16)
-                                    .with_operand::<u8>(next_reg.into());
-                                self.builder.push_instr(mova_instr);
-                                self.resolve_container(cont_name)?
-                            },
-                            _ => return Err(CompilerError::Unknown)
-                        }
-                    },
-                    _ => return Err(CompilerError::Unknown)
-                };
+        //println!("Compiling impl: {:?}", decl);
 
-                self.compile_lhs_assign_member_expr(rhs_expr, &cont_def)
-            },
-            _ => return Err(CompilerError::UnsupportedExpression(rhs_expr.deref().clone()))
+        let impl_for = match impl_for_type {
+            Type::Other(cont_name) => cont_name,
+            // Impls of generic containers are compiled per instance
+            Type::Generic(_, _) => return Ok(()),
+            _ => return Err(CompilerError::UnknownType(impl_for_type.clone()))
+        };
+
+        if impl_type == impl_for_type {
+            self.current_cont = Some(impl_for.clone());
+            self.compile_decl_list(decl_list)?;
+            self.current_cont = None;
+        } else {
+            // Interface functions are compiled as member functions of the container
+            self.current_cont = Some(impl_for.clone());
+            self.compile_decl_list(decl_list)?;
+            self.current_cont = None;
         }
+
+        Ok(())
     }
 
-    /// Compiles an expression
-    pub fn compile_expr(&mut self, expr: &Expression) -> CompilerResult<()> {
-        let expr_type = self.check_expr_type(expr)?;
-        let expr_size = self.get_size_of_type(&expr_type)?;
-        //println!("Expr size: {}", expr_size);
-        let before_stack_size = self.get_stack_size()?;
-        match expr {
-            Expression::IntLiteral(int) => {
-                let reg = {
-                    let fn_ctx = self.get_current_function_mut()?;
-                    fn_ctx.register_allocator.get_temp_register()?
-                };
+    /// Compiles a statement list
+    pub fn compile_stmt_list(&mut self, stmt_list: &[Statement]) -> CompilerResult<()> {
+        for stmt in stmt_list.iter() {
+            //println!("Compiling statement... Stack size: {}", self.get_stack_size()?);
+            self.compile_stmt(stmt)?;
+            //println!("Compiled statement... Stack size: {}", self.get_stack_size()?);
+        }
+        Ok(())
+    }
 
-                let ldi_instr = Instruction::new(Opcode::LDI)
-                    .with_operand::<i64>(*int)
-                    .with_operand::<u8>(reg.into());
+    /// Compiles a statement
+    pub fn compile_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        self.instantiate_stmt(stmt)?;
+        match stmt {
+            Statement::VariableDecl(_) => self.compile_var_decl_stmt(stmt)?,
+            Statement::TupleDecl(_) => self.compile_tuple_decl_stmt(stmt)?,
+            Statement::Expression(_) => self.compile_expr_stmt(stmt)?,
+            Statement::Return(_) => self.compile_return_stmt(stmt)?,
+            Statement::If(_) => self.compile_if_stmt(stmt)?,
+            Statement::Match(_) => self.compile_match_stmt(stmt)?,
+            Statement::While(_, _) => self.compile_while_stmt(stmt)?, 
+            Statement::For(_) => self.compile_for_stmt(stmt)?,
+            Statement::Loop(_) => self.compile_loop_stmt(stmt)?,
+            Statement::CodeBlock(_) => self.compile_code_block_stmt(stmt)?,
+            Statement::Continue => self.compile_continue_stmt(stmt)?,
+            Statement::Break => self.compile_break_stmt(stmt)?,
+            Statement::Try(_) => self.compile_try_stmt(stmt)?,
+            Statement::Panic(_) => self.compile_panic_stmt(stmt)?,
+            _ => return Err(CompilerError::Unimplemented(format!("Compilation of {:?} not implemented!", stmt)))
+        };
+        Ok(())
+    }
 
-                self.builder.push_instr(ldi_instr);
-            },
-            Expression::FloatLiteral(float) => {
-                let reg = {
-                    let fn_ctx = self.get_current_function_mut()?;
-                    fn_ctx.register_allocator.get_temp_register()?
-                };
+    /// Compiles a variable declaration statement
+    pub fn compile_var_decl_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let var_decl_args = match stmt {
+            Statement::VariableDecl(var_decl_args) => var_decl_args,
+            _ => return Err(CompilerError::Unknown)
+        };
+        //println!("Compiling var decl stmt. Args: {:?}", var_decl_args);
+        //println!("Compiling var decl stmt");
+        // The variable name
+        let var_name = var_decl_args.name.clone();
+        // The variable type
+        let mut var_type = var_decl_args.var_type.clone();
+        // The assignment expression, with the type arguments of generic calls taken from the variable type
+        let typed_call_opt = self.infer_typed_generic_call(&var_decl_args.assignment, &var_type);
+        let assignment_expr = match typed_call_opt.as_ref() {
+            Some((_, _, typed_call_expr)) => typed_call_expr,
+            None => &var_decl_args.assignment
+        };
+        let assignment_expr_type = self.check_expr_type(&assignment_expr)?;
+        //println!("var decl assign expr: {:?}", assignment_expr);
+        //println!("var decl assign expr type: {:?}", assignment_expr_type);
+        // Auto typed vars take the type of their assignment
+        if var_type == Type::Auto {
+            if !assignment_expr_type.is_known() {
+                return Err(CompilerError::CannotInferType(var_name));
+            }
+            var_type = assignment_expr_type;
+        }
+        self.canonize_type(&mut var_type)?;
 
-                let ldf_instr = Instruction::new(Opcode::LDF)
-                    .with_operand::<f32>(*float)
-                    .with_operand::<u8>(reg.into());
-                    
+        //println!("Var type: {:?}", var_type);
+        // Byte size of this type
+        let var_size = self.get_size_of_type(&var_type)?;
+        //println!("Size of type: {}", var_size);
+        // Compile said expression
+        //println!("Compiling assignment expr ({:?}). SP: {}", assignment_expr, self.get_stack_size()?);
+        let mut compiled_type = self.compile_expr_with_type(assignment_expr, &var_type)?;
+        self.canonize_type(&mut compiled_type)?;
+        if compiled_type != var_type {
+            return Err(CompilerError::TypeMismatch(var_type, compiled_type));
+        }
+        //println!("Compiled assignment expr ({:?}). SP: {}", assignment_expr, self.get_stack_size()?);
+
+        // If the type can be contained in a register
+        if var_type.is_primitive() {
+            let last_reg = {
+                let fn_ctx = self.get_current_function()?;
+                fn_ctx.register_allocator.get_last_temp_register()?
+            };
+            //println!("Last reg: {:?}", last_reg);
+            let var_sp_offset = -(var_size as i16);
+            let stack_inc_instr = Instruction::new_inc_stack(var_size);
+            self.builder.push_instr(stack_inc_instr);
+            self.inc_stack(var_size)?;
+            let mov_instr = match var_type {
+                Type::Int => {
+                    Instruction::new(Opcode::MOVI_RA)
+                        .with_operand::<u8>(last_reg.into())
+                        .with_operand::<u8>(Register::SP.into())
+                        .with_operand::<i16>(var_sp_offset)
+                },
+                Type::Float => {
+                    Instruction::new(Opcode::MOVF_RA)
+                        .with_operand::<u8>(last_reg.into())
+                        .with_operand::<u8>(Register::SP.into())
+                        .with_operand::<i16>(var_sp_offset)
+                },
+                Type::Reference(_) | Type::AutoArray(_) | Type::Optional(_) => {
+                    Instruction::new(Opcode::MOVA_RA)
+                        .with_operand::<u8>(last_reg.into())
+                        .with_operand::<u8>(Register::SP.into())
+                        .with_operand::<i16>(var_sp_offset)
+                },
+                Type::Bool => {
+                    Instruction::new(Opcode::MOVB_RA)
+                        .with_operand::<u8>(last_reg.into())
+                        .with_operand::<u8>(Register::SP.into())
+                        .with_operand::<i16>(var_sp_offset)
+                },
+                _ => {
+                    //println!("Error in compile_var_decl_stmt()!");
+                    return Err(CompilerError::UnknownType(var_type));
+                }
+            };
+            self.builder.push_instr(mov_instr);
+        }
+        // Otherwise, the value is already on the top of the stack.
+        // Set the variable in the context.
+        let fn_ctx = self.get_current_function_mut()?;
+        fn_ctx.set_stack_var((var_name.clone(), var_type.clone()), (fn_ctx.stack_size - var_size) as i64)?;
+        //println!("Setting var {}: {:?} to position {}", var_name, var_type, fn_ctx.stack_size - var_size);
+        Ok(())
+    }
+
+    /// Compiles a destructuring declaration, e.g. "var (a, b) = f();"
+    pub fn compile_tuple_decl_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let tuple_decl_args = match stmt {
+            Statement::TupleDecl(tuple_decl_args) => tuple_decl_args,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let assignment_expr = &tuple_decl_args.assignment;
+        let elem_types = match self.check_expr_type(assignment_expr)? {
+            Type::Tuple(elem_types) => elem_types,
+            expr_type => return Err(CompilerError::TupleAccessOnNonTuple(expr_type))
+        };
+        if elem_types.len() != tuple_decl_args.names.len() {
+            return Err(CompilerError::TupleSizeMismatch(elem_types.len(), tuple_decl_args.names.len()));
+        }
+
+        // The tuple stays on the stack, the variables are declared on top of its elements
+        self.compile_expr(assignment_expr)?;
+        let tuple_size = self.get_size_of_type(&Type::Tuple(elem_types.clone()))?;
+        let tuple_pos = self.get_stack_size()? - tuple_size;
+
+        for (i, var_name) in tuple_decl_args.names.iter().enumerate() {
+            let elem_offset = self.get_tuple_elem_offset(&elem_types, i)?;
+            let fn_ctx = self.get_current_function_mut()?;
+            fn_ctx.set_stack_var((var_name.clone(), elem_types[i].clone()), (tuple_pos + elem_offset) as i64)?;
+        }
+        Ok(())
+    }
+
+    /// Compiles a statement expression
+    pub fn compile_expr_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let stmt_expr = match stmt {
+            Statement::Expression(expr) => expr,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        match stmt_expr {
+            Expression::Call(_, _) => self.compile_expr(stmt_expr)?,
+            Expression::Try(_) => self.compile_expr(stmt_expr)?,
+            Expression::Assign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
+            Expression::AddAssign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
+            Expression::SubAssign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
+            Expression::MulAssign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
+            Expression::DivAssign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
+            Expression::ModAssign(_, _) |
+            Expression::BitAndAssign(_, _) |
+            Expression::BitOrAssign(_, _) |
+            Expression::BitXorAssign(_, _) |
+            Expression::ShiftLeftAssign(_, _) |
+            Expression::ShiftRightAssign(_, _) => self.compile_var_assign_stmt_expr(stmt_expr)?,
+            Expression::MemberAccess(_, _) if stmt_expr.ends_in_call() => self.compile_expr(stmt_expr)?,
+            _ => return Err(CompilerError::UnsupportedExpression(stmt_expr.clone()))
+        };
+
+        Ok(())
+        //Err(CompilerError::Unimplemented(format!("Statement expr compilation not implemented!")))
+    }
+    
+
+    /// Compiles an if statement
+    pub fn compile_if_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let if_stmt_args: &IfStatementArgs = match stmt {
+            Statement::If(if_stmt_args) => if_stmt_args,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        // Generate an instruction tag to fill in the end of this if/else chain
+        let tag_end = self.uid_generator.generate();
+        // Generate an instruction tag for the next branch
+        let mut tag_next = self.uid_generator.generate();
+
+        let mut if_fn_ctx = if let Some(var_name) = if_stmt_args.binding.as_ref() {
+            self.compile_if_var_cond(var_name, &if_stmt_args.if_expr, tag_next)?
+        } else {
+            let expr_type = self.check_expr_type(&if_stmt_args.if_expr)?;
+            // Only boolean expressions are allowed
+            if expr_type != Type::Bool {
+                return Err(CompilerError::TypeMismatch(expr_type, Type::Bool));
+            }
+            // Compile the if expression
+            self.compile_expr(&if_stmt_args.if_expr)?;
+            // Get the register the result of this boolean expression was saved in
+            let last_reg = {
+                self.get_current_function()?
+                    .register_allocator
+                    .get_last_temp_register()?
+            };
+
+            // Instruction for this if expr
+            let jmpf_instr = Instruction::new(Opcode::JMPF)
+                .with_operand::<u8>(last_reg.into())
+                .with_operand(tag_next);
+            self.builder.tag(tag_next);
+            self.builder.push_instr(jmpf_instr);
+
+            // Create new weak function context
+            let mut if_fn_ctx = {
+                let fn_ctx = self.get_current_function()?;
+                FunctionContext::new_weak(fn_ctx)?
+            };
+            // An optional checked against "none" can be accessed inside the block
+            if let Some((var_name, inner_type)) = self.get_checked_optional(&if_stmt_args.if_expr) {
+                if_fn_ctx.narrow_var_type(&var_name, inner_type)?;
+            }
+            if_fn_ctx
+        };
+        // Push the weak function context on the stack
+        self.push_function_context(if_fn_ctx);
+
+        // Compile the if statement list
+        self.compile_stmt_list(&if_stmt_args.if_block)?;
+
+        // Pop the function context off the stack again
+        if_fn_ctx = self.pop_function_context()?;
+
+        self.compile_stack_cleanup_block(&if_fn_ctx)?;
+
+        // Instruction for jumping to the end
+        let jmp_end_instr = Instruction::new(Opcode::JMP)
+            .with_operand(tag_end);
+        self.builder.tag(tag_end);
+        self.builder.push_instr(jmp_end_instr);
+
+        if if_stmt_args.else_if_list.is_some() {
+            let else_if_list = if_stmt_args.else_if_list
+                .as_ref()
+                .ok_or(CompilerError::Unknown)?;
+            for (else_if_expr, else_if_stmt_list) in else_if_list.iter() {
+                // Current instruction position
+                let pos = self.builder.get_current_offset();
+                // Set the last JMPF to jump to this instruction
+                {
+                    // Retrieve the position list
+                    let jmp_next_instr_pos_list = self.builder.get_tag(&tag_next)
+                        .ok_or(CompilerError::Unknown)?;
+                    // Retrieve the position
+                    // (Only one instruction should exist with this tag)
+                    let jmp_next_instr_pos = jmp_next_instr_pos_list.get(0)
+                        .ok_or(CompilerError::Unknown)?;
+                    // Get the mutable reference to this instruction
+                    let jmp_next_instr = self.builder.get_instr(&jmp_next_instr_pos)
+                        .ok_or(CompilerError::Unknown)?;
+                    
+                    // Update the jump destination
+                    jmp_next_instr.remove_operand_bytes(8);
+                    jmp_next_instr.append_operand(pos);
+                }
+                // Only boolean expressions are allowed
+                let expr_type = self.check_expr_type(else_if_expr)?;
+                if expr_type != Type::Bool {
+                    return Err(CompilerError::TypeMismatch(expr_type, Type::Bool));
+                }
+                // Compile the expression
+                self.compile_expr(else_if_expr)?;
+                // Get the result register
+                let last_reg = {
+                    self.get_current_function()?
+                        .register_allocator
+                        .get_last_temp_register()?
+                };
+                // Generate new tag for the next jump
+                tag_next = self.uid_generator.generate();
+                // Instruction for jumping to next or inside statement list
+                let jmpf_instr = Instruction::new(Opcode::JMPF)
+                    .with_operand::<u8>(last_reg.into())
+                    .with_operand(tag_next);
+                self.builder.tag(tag_next);
+                self.builder.push_instr(jmpf_instr);
+
+                // Create a new weak function context
+                let mut else_if_fn_ctx = {
+                    let fn_ctx = self.get_current_function()?;
+                    FunctionContext::new_weak(fn_ctx)?
+                };
+                // and push it on the stack
+                self.push_function_context(else_if_fn_ctx);
+
+                // Compile this "else if"s statement list
+                self.compile_stmt_list(else_if_stmt_list)?;
+
+                // Pop the context off the stack again
+                else_if_fn_ctx = self.pop_function_context()?;
+
+                self.compile_stack_cleanup_block(&else_if_fn_ctx)?;
+
+                // Instruction for jumping to the end
+                let jmp_end_instr = Instruction::new(Opcode::JMP)
+                    .with_operand(tag_end);
+                self.builder.tag(tag_end);
+                self.builder.push_instr(jmp_end_instr);
+            }
+        }
+
+        // If an "else" block exists
+        if if_stmt_args.else_block.is_some() {
+            let else_stmt_list = if_stmt_args.else_block.as_ref()
+                .ok_or(CompilerError::Unknown)?;
+            // Set the last JMPF to jump to this instruction
+            let pos = self.builder.get_current_offset();
+            {
+                // Retrieve the position list
+                let jmp_next_instr_pos_list = self.builder.get_tag(&tag_next)
+                    .ok_or(CompilerError::Unknown)?;
+                // Retrieve the position
+                // (Only one instruction should exist with this tag)
+                let jmp_next_instr_pos = jmp_next_instr_pos_list.get(0)
+                    .ok_or(CompilerError::Unknown)?;
+                // Get the mutable reference to this instruction
+                let jmp_next_instr = self.builder.get_instr(&jmp_next_instr_pos)
+                    .ok_or(CompilerError::Unknown)?;
+                    
+                // Update the jump destination
+                jmp_next_instr.remove_operand_bytes(8);
+                jmp_next_instr.append_operand(pos);
+            }
+
+            // Create a new weak function context
+            let mut else_fn_ctx = {
+                let fn_ctx = self.get_current_function()?;
+                FunctionContext::new_weak(fn_ctx)?
+            };
+            // And push it on the stack
+            self.push_function_context(else_fn_ctx);
+
+            // Compile the statement list for this else block
+            self.compile_stmt_list(else_stmt_list)?;
+
+            // Pop it off the stack again
+            else_fn_ctx = self.pop_function_context()?;
+
+            self.compile_stack_cleanup_block(&else_fn_ctx)?;
+        } else {
+            // Set the last JMPF to jump to this instruction
+            let pos = self.builder.get_current_offset();
+            {
+                // Retrieve the position list
+                let jmp_next_instr_pos_list = self.builder.get_tag(&tag_next)
+                    .ok_or(CompilerError::Unknown)?;
+                // Retrieve the position
+                // (Only one instruction should exist with this tag)
+                let jmp_next_instr_pos = jmp_next_instr_pos_list.get(0)
+                    .ok_or(CompilerError::Unknown)?;
+                // Get the mutable reference to this instruction
+                let jmp_next_instr = self.builder.get_instr(&jmp_next_instr_pos)
+                    .ok_or(CompilerError::Unknown)?;
+                    
+                // Update the jump destination
+                jmp_next_instr.remove_operand_bytes(8);
+                jmp_next_instr.append_operand(pos);
+            }
+        }
+
+        // Current position is at the end of the entire if/else if/else chain
+        let pos_end = self.builder.get_current_offset();
+
+        let jmp_end_pos_list = self.builder.get_tag(&tag_end)
+            .ok_or(CompilerError::Unknown)?;
+
+        // Make all the jump instructions jump to the end properly
+        for jmp_end_pos in jmp_end_pos_list.iter() {
+            let jmp_instr = self.builder.get_instr(jmp_end_pos)
+                .ok_or(CompilerError::Unknown)?;
+            jmp_instr.remove_operand_bytes(8);
+            jmp_instr.append_operand(pos_end);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the destination of all jump instructions with the given tag
+    fn patch_jmp_tag(&mut self, tag: u64, pos: u64) -> CompilerResult<()> {
+        let jmp_pos_list = self.builder.get_tag(&tag)
+            .unwrap_or_default();
+        for jmp_pos in jmp_pos_list.iter() {
+            let jmp_instr = self.builder.get_instr(jmp_pos)
+                .ok_or(CompilerError::Unknown)?;
+            jmp_instr.remove_operand_bytes(8);
+            jmp_instr.append_operand::<u64>(pos);
+        }
+        Ok(())
+    }
+
+    /// Checks the arms of a match statement against the matched type.
+    /// Every enum variant needs an arm, other values need a wildcard arm.
+    /// Patterns matched by an earlier arm and arms after the wildcard are rejected.
+    fn check_match_arms(&self, match_type: &Type, match_stmt_args: &MatchStatementArgs) -> CompilerResult<()> {
+        let has_wildcard = match_stmt_args.arms.iter()
+            .any(|(pattern, _)| *pattern == MatchPattern::Wildcard);
+        let wildcard_pos = match_stmt_args.arms.iter()
+            .position(|(pattern, _)| *pattern == MatchPattern::Wildcard);
+        if let Some((pattern, _)) = wildcard_pos.and_then(|pos| match_stmt_args.arms.get(pos + 1)) {
+            return Err(CompilerError::UnreachableMatchArm(Self::get_match_pattern_name(pattern)));
+        }
+
+        let enum_def = match match_type {
+            Type::Int => {
+                let mut covered = HashSet::new();
+                for (pattern, _) in match_stmt_args.arms.iter() {
+                    match pattern {
+                        MatchPattern::Variant(variant_path, _) => {
+                            let (enum_def, _) = self.resolve_enum_variant(variant_path)?;
+                            return Err(CompilerError::TypeMismatch(Type::Int, Type::Other(enum_def.canonical_name)));
+                        },
+                        MatchPattern::Int(value) if !covered.insert(*value) => {
+                            return Err(CompilerError::UnreachableMatchArm(value.to_string()));
+                        },
+                        _ => {}
+                    }
+                }
+                if !has_wildcard {
+                    return Err(CompilerError::NonExhaustiveMatch(String::from("_")));
+                }
+                return Ok(());
+            },
+            Type::Other(type_name) => {
+                self.resolve_enum(type_name)
+                    .map_err(|_| CompilerError::CannotMatch(match_type.clone()))?
+            },
+            _ => return Err(CompilerError::CannotMatch(match_type.clone()))
+        };
+
+        let mut covered = HashSet::new();
+        for (pattern, _) in match_stmt_args.arms.iter() {
+            match pattern {
+                MatchPattern::Variant(variant_path, bindings) => {
+                    let (variant_enum_def, tag) = self.resolve_enum_variant(variant_path)?;
+                    if variant_enum_def.canonical_name != enum_def.canonical_name {
+                        return Err(CompilerError::TypeMismatch(match_type.clone(), Type::Other(variant_enum_def.canonical_name)));
+                    }
+                    if bindings.len() != enum_def.variants[tag].1.len() {
+                        return Err(CompilerError::ArgumentMismatch(variant_path.clone()));
+                    }
+                    if !covered.insert(tag) {
+                        return Err(CompilerError::UnreachableMatchArm(variant_path.clone()));
+                    }
+                },
+                MatchPattern::Int(_) => return Err(CompilerError::TypeMismatch(match_type.clone(), Type::Int)),
+                MatchPattern::Wildcard => {}
+            }
+        }
+
+        if !has_wildcard {
+            for (tag, (variant_name, _)) in enum_def.variants.iter().enumerate() {
+                if !covered.contains(&tag) {
+                    return Err(CompilerError::NonExhaustiveMatch(format!("{}::{}", enum_def.canonical_name, variant_name)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the pattern of a match arm as written, for error messages
+    fn get_match_pattern_name(pattern: &MatchPattern) -> String {
+        match pattern {
+            MatchPattern::Variant(variant_path, _) => variant_path.clone(),
+            MatchPattern::Int(value) => value.to_string(),
+            MatchPattern::Wildcard => String::from("_")
+        }
+    }
+
+    /// Compiles a match statement into a chain of comparisons.
+    /// The matched value is copied onto the stack, arm bindings refer to its payload.
+    pub fn compile_match_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let match_stmt_args: &MatchStatementArgs = match stmt {
+            Statement::Match(match_stmt_args) => match_stmt_args,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let mut match_type = self.check_expr_type(&match_stmt_args.match_expr)?;
+        self.canonize_type(&mut match_type)?;
+        self.check_match_arms(&match_type, match_stmt_args)?;
+
+        let payload_types = match &match_type {
+            Type::Other(enum_name) => self.resolve_enum(enum_name)?.variants,
+            _ => Vec::new()
+        };
+
+        // The matched value lives in its own weak function context
+        let match_fn_ctx = {
+            let fn_ctx = self.get_current_function()?;
+            FunctionContext::new_weak(fn_ctx)?
+        };
+        self.push_function_context(match_fn_ctx);
+
+        // Tags and integers both end up at the start of the value
+        self.compile_expr(&match_stmt_args.match_expr)?;
+        if match_type == Type::Int {
+            let value_reg = self.get_last_register()?;
+            let stack_inc_instr = Instruction::new_inc_stack(8);
+            self.inc_stack(8)?;
+            self.builder.push_instr(stack_inc_instr);
+            self.compile_store_to_addr(&Type::Int, value_reg, Register::SP, -8)?;
+        }
+        let value_size = self.get_stack_size()?;
+
+        let tag_end = self.uid_generator.generate();
+
+        for (pattern, stmt_list) in match_stmt_args.arms.iter() {
+            let tag_next = self.uid_generator.generate();
+
+            let expected = match pattern {
+                MatchPattern::Variant(variant_path, _) => Some(self.resolve_enum_variant(variant_path)?.1 as i64),
+                MatchPattern::Int(int) => Some(*int),
+                MatchPattern::Wildcard => None
+            };
+            if let Some(expected) = expected {
+                let value_reg = self.get_next_register()?;
+                let movi_instr = Instruction::new(Opcode::MOVI_AR)
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(-(value_size as i16))
+                    .with_operand::<u8>(value_reg.clone().into());
+                let expected_reg = self.get_next_register()?;
+                let ldi_instr = Instruction::new(Opcode::LDI)
+                    .with_operand::<i64>(expected)
+                    .with_operand::<u8>(expected_reg.clone().into());
+                let cmp_reg = self.get_next_register()?;
+                let eqi_instr = Instruction::new(Opcode::EQI)
+                    .with_operand::<u8>(value_reg.into())
+                    .with_operand::<u8>(expected_reg.into())
+                    .with_operand::<u8>(cmp_reg.clone().into());
+                let jmpf_instr = Instruction::new(Opcode::JMPF)
+                    .with_operand::<u8>(cmp_reg.into())
+                    .with_operand(tag_next);
+                self.builder.push_instr(movi_instr);
+                self.builder.push_instr(ldi_instr);
+                self.builder.push_instr(eqi_instr);
+                self.builder.tag(tag_next);
+                self.builder.push_instr(jmpf_instr);
+            }
+
+            let mut arm_fn_ctx = {
+                let fn_ctx = self.get_current_function()?;
+                FunctionContext::new_weak(fn_ctx)?
+            };
+            // Bind the payload in place, right behind the tag
+            if let MatchPattern::Variant(variant_path, bindings) = pattern {
+                let (_, tag) = self.resolve_enum_variant(variant_path)?;
+                let variant_types = &payload_types[tag].1;
+                for (i, binding) in bindings.iter().enumerate() {
+                    if binding == "_" {
+                        continue;
+                    }
+                    let mut binding_type = variant_types[i].clone();
+                    self.canonize_type(&mut binding_type)?;
+                    let payload_offset = 8 + self.get_tuple_elem_offset(variant_types, i)?;
+                    let binding_pos = payload_offset as i64 - value_size as i64;
+                    arm_fn_ctx.set_stack_var((binding.clone(), binding_type), binding_pos)?;
+                }
+            }
+            self.push_function_context(arm_fn_ctx);
+
+            self.compile_stmt_list(stmt_list)?;
+
+            arm_fn_ctx = self.pop_function_context()?;
+            self.compile_stack_cleanup_block(&arm_fn_ctx)?;
+
+            let jmp_end_instr = Instruction::new(Opcode::JMP)
+                .with_operand(tag_end);
+            self.builder.tag(tag_end);
+            self.builder.push_instr(jmp_end_instr);
+
+            let pos_next = self.builder.get_current_offset();
+            self.patch_jmp_tag(tag_next, pos_next as u64)?;
+
+            // Nothing after a wildcard can match
+            if expected.is_none() {
+                break;
+            }
+        }
+
+        let pos_end = self.builder.get_current_offset();
+        self.patch_jmp_tag(tag_end, pos_end as u64)?;
+
+        let match_fn_ctx = self.pop_function_context()?;
+        self.compile_stack_cleanup_block(&match_fn_ctx)?;
+
+        Ok(())
+    }
+
+    /// Compiles the condition of "if var x = maybe { }", which tests the optional against "none"
+    /// and returns the context of the if block with the unwrapped reference bound to the variable
+    fn compile_if_var_cond(&mut self, var_name: &str, opt_expr: &Expression, tag_next: u64) -> CompilerResult<FunctionContext> {
+        let mut opt_type = self.check_expr_type(opt_expr)?;
+        self.canonize_type(&mut opt_type)?;
+        let inner_type = match &opt_type {
+            Type::Optional(inner_type) if inner_type.deref() != &Type::Auto => inner_type.deref().clone(),
+            _ => return Err(CompilerError::NotAnOptional(opt_type))
+        };
+
+        self.compile_expr(opt_expr)?;
+        let ptr_reg = self.get_last_register()?;
+        let zero_reg = self.get_next_register()?;
+        let lda_instr = Instruction::new(Opcode::LDA)
+            .with_operand::<u64>(0)
+            .with_operand::<u8>(zero_reg.clone().into());
+        let res_reg = self.get_next_register()?;
+        let neqi_instr = Instruction::new(Opcode::NEQI)
+            .with_operand::<u8>(ptr_reg.clone().into())
+            .with_operand::<u8>(zero_reg.into())
+            .with_operand::<u8>(res_reg.clone().into());
+        self.builder.push_instr(lda_instr);
+        self.builder.push_instr(neqi_instr);
+
+        let jmpf_instr = Instruction::new(Opcode::JMPF)
+            .with_operand::<u8>(res_reg.into())
+            .with_operand(tag_next);
+        self.builder.tag(tag_next);
+        self.builder.push_instr(jmpf_instr);
+
+        // The reference is pushed as the first variable of the if block
+        let stack_inc_instr = Instruction::new_inc_stack(8);
+        let mova_instr = Instruction::new(Opcode::MOVA_RA)
+            .with_operand::<u8>(ptr_reg.into())
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(-8);
+        self.builder.push_instr(stack_inc_instr);
+        self.builder.push_instr(mova_instr);
+
+        let mut if_fn_ctx = {
+            let fn_ctx = self.get_current_function()?;
+            FunctionContext::new_weak(fn_ctx)?
+        };
+        if_fn_ctx.stack_size = 8;
+        if_fn_ctx.set_stack_var((String::from(var_name), inner_type), 0)?;
+        Ok(if_fn_ctx)
+    }
+
+    /// Returns the variable and its unwrapped type if an expression checks an optional variable
+    /// against "none", e.g. "p != none"
+    fn get_checked_optional(&self, expr: &Expression) -> Option<(String, Type)> {
+        let var_name = match expr {
+            Expression::NotEquals(lhs, rhs) => match (lhs.deref(), rhs.deref()) {
+                (Expression::Variable(var_name), Expression::NoneLiteral) |
+                (Expression::NoneLiteral, Expression::Variable(var_name)) => var_name,
+                _ => return None
+            },
+            _ => return None
+        };
+        match self.get_current_function().ok()?.get_declared_var_type(var_name).ok()? {
+            Type::Optional(inner_type) => {
+                let mut inner_type = inner_type.deref().clone();
+                self.canonize_type(&mut inner_type).ok()?;
+                Some((var_name.clone(), inner_type))
+            },
+            _ => None
+        }
+    }
+
+    /// Compiles a try statement.
+    /// The try block runs with a handler registered. A panic unwinds the stack to the
+    /// start of the statement, where the catch block finds the message on top of it.
+    pub fn compile_try_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let try_stmt_args: &TryStatementArgs = match stmt {
+            Statement::Try(try_stmt_args) => try_stmt_args,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let tag_catch = self.uid_generator.generate();
+        let try_instr = Instruction::new(Opcode::TRY)
+            .with_operand(tag_catch);
+        self.builder.tag(tag_catch);
+        self.builder.push_instr(try_instr);
+
+        let mut try_fn_ctx = {
+            let fn_ctx = self.get_current_function()?;
+            FunctionContext::new_weak(fn_ctx)?
+        };
+        try_fn_ctx.is_try = true;
+        self.push_function_context(try_fn_ctx);
+
+        self.compile_stmt_list(&try_stmt_args.try_block)?;
+
+        try_fn_ctx = self.pop_function_context()?;
+        self.compile_stack_cleanup_block(&try_fn_ctx)?;
+        self.compile_try_end(1);
+
+        let tag_end = self.uid_generator.generate();
+        let jmp_end_instr = Instruction::new(Opcode::JMP)
+            .with_operand(tag_end);
+        self.builder.tag(tag_end);
+        self.builder.push_instr(jmp_end_instr);
+
+        let pos_catch = self.builder.get_current_offset();
+        self.patch_jmp_tag(tag_catch, pos_catch as u64)?;
+
+        // The message string is pushed by the VM when unwinding
+        let mut catch_fn_ctx = {
+            let fn_ctx = self.get_current_function()?;
+            FunctionContext::new_weak(fn_ctx)?
+        };
+        catch_fn_ctx.stack_size = 16;
+        catch_fn_ctx.set_stack_var((try_stmt_args.catch_var.clone(), Type::String), 0)?;
+        self.push_function_context(catch_fn_ctx);
+
+        self.compile_stmt_list(&try_stmt_args.catch_block)?;
+
+        catch_fn_ctx = self.pop_function_context()?;
+        self.compile_stack_cleanup_block(&catch_fn_ctx)?;
+
+        let pos_end = self.builder.get_current_offset();
+        self.patch_jmp_tag(tag_end, pos_end as u64)?;
+
+        Ok(())
+    }
+
+    /// Compiles the removal of the handlers of the given number of try blocks
+    fn compile_try_end(&mut self, try_count: usize) {
+        for _ in 0..try_count {
+            let try_end_instr = Instruction::new(Opcode::TRY_END);
+            self.builder.push_instr(try_end_instr);
+        }
+    }
+
+    /// Compiles a panic statement, which unwinds to the innermost handler
+    pub fn compile_panic_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let msg_expr = match stmt {
+            Statement::Panic(msg_expr) => msg_expr,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let msg_type = self.check_expr_type(msg_expr)?;
+        if msg_type != Type::String {
+            return Err(CompilerError::TypeMismatch(Type::String, msg_type));
+        }
+
+        let before_stack_size = self.get_stack_size()?;
+        self.compile_expr(msg_expr)?;
+
+        let msg_reg = self.get_next_register()?;
+        let subui_instr = Instruction::new(Opcode::SUBU_I)
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<u64>(16)
+            .with_operand::<u8>(msg_reg.clone().into());
+        let panic_instr = Instruction::new(Opcode::PANIC)
+            .with_operand::<u8>(msg_reg.into());
+        self.builder.push_instr(subui_instr);
+        self.builder.push_instr(panic_instr);
+
+        // Execution does not continue after a panic
+        let fn_ctx = self.get_current_function_mut()?;
+        fn_ctx.stack_size = before_stack_size;
+
+        Ok(())
+    }
+
+    /// Compiles a while statement
+    pub fn compile_while_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let (while_expr, while_stmt_list) = match stmt {
+            Statement::While(while_expr, while_stmt_list) => (while_expr, while_stmt_list),
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let while_fn_ctx = FunctionContext::new_loop(self.get_current_function()?)?;
+        self.push_function_context(while_fn_ctx);
+        let while_start_pos = self.builder.get_current_offset();
+        let tag_end = self.uid_generator.generate();
+        let mut while_loop_ctx = LoopContext::new(while_start_pos, tag_end);
+        self.push_loop_context(while_loop_ctx);
+
+        // Check type of while expr
+        let expr_type = self.check_expr_type(while_expr)?;
+        // Only boolean expr are allowed
+        if expr_type != Type::Bool {
+            return Err(CompilerError::TypeMismatch(Type::Bool, expr_type.clone()));
+        }
+
+        // Compile the expression
+        self.compile_expr(while_expr)?;
+
+        let last_reg = {
+            self.get_current_function()?
+                .register_allocator
+                .get_last_temp_register()?
+        };
+
+        self.builder.tag(tag_end);
+        let jmpf_instr = Instruction::new(Opcode::JMPF)
+            .with_operand::<u8>(last_reg.into())
+            .with_operand(tag_end);
+        self.builder.push_instr(jmpf_instr);
+
+        // Compile the statement list
+        self.compile_stmt_list(while_stmt_list)?;
+
+        // Compile a continue statement
+        self.compile_continue_stmt(&Statement::Continue)?;
+
+        // This is the end of this while loop
+        let while_end_pos = self.builder.get_current_offset();
+        
+        // Pop the while loop off the stack
+        while_loop_ctx = self.pop_loop_context()?;
+        let instr_pos_list = self.builder.get_tag(&while_loop_ctx.tag_end)
+            .ok_or(CompilerError::Unknown)?;
+        
+        // Update with correct end position
+        for instr_pos in instr_pos_list {
+            let jmpf_instr = self.builder.get_instr(&instr_pos)
+                .ok_or(CompilerError::Unknown)?;
+            jmpf_instr.remove_operand_bytes(8);
+            jmpf_instr.append_operand::<u64>(while_end_pos as u64);
+        }
+
+        // Pop this while loops fn context off the stack
+        self.pop_function_context()?;
+
+        Ok(())
+    }
+
+    /// Compiles a code block with its own variable scope
+    pub fn compile_code_block_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let stmt_list = match stmt {
+            Statement::CodeBlock(stmt_list) => stmt_list,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        // Create new weak function context
+        let mut block_fn_ctx = {
+            let fn_ctx = self.get_current_function()?;
+            FunctionContext::new_weak(fn_ctx)?
+        };
+        // And push it on the stack
+        self.push_function_context(block_fn_ctx);
+
+        // Compile the statement list
+        self.compile_stmt_list(stmt_list)?;
+
+        // Pop the function context off the stack again
+        block_fn_ctx = self.pop_function_context()?;
+
+        self.compile_stack_cleanup_block(&block_fn_ctx)?;
+
+        Ok(())
+    }
+
+    /// Compiles an infinite loop statement
+    pub fn compile_loop_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let loop_stmt_list = match stmt {
+            Statement::Loop(loop_stmt_list) => loop_stmt_list,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let loop_fn_ctx = FunctionContext::new_loop(self.get_current_function()?)?;
+        self.push_function_context(loop_fn_ctx);
+        let loop_start_pos = self.builder.get_current_offset();
+        let tag_end = self.uid_generator.generate();
+        let mut loop_ctx = LoopContext::new(loop_start_pos, tag_end);
+        self.push_loop_context(loop_ctx);
+
+        // Compile the statement list
+        self.compile_stmt_list(loop_stmt_list)?;
+
+        // Compile a continue statement
+        self.compile_continue_stmt(&Statement::Continue)?;
+
+        // This is the end of this loop
+        let loop_end_pos = self.builder.get_current_offset();
+
+        // Pop the loop off the stack
+        loop_ctx = self.pop_loop_context()?;
+
+        // Update every break with the correct end position.
+        // A loop without a break has no tagged instructions.
+        let instr_pos_list = self.builder.get_tag(&loop_ctx.tag_end)
+            .unwrap_or_default();
+        for instr_pos in instr_pos_list {
+            let jmp_instr = self.builder.get_instr(&instr_pos)
+                .ok_or(CompilerError::Unknown)?;
+            jmp_instr.remove_operand_bytes(8);
+            jmp_instr.append_operand::<u64>(loop_end_pos as u64);
+        }
+
+        // Pop this loops fn context off the stack
+        self.pop_function_context()?;
+
+        Ok(())
+    }
+
+    /// Compiles a range based for statement
+    pub fn compile_for_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let for_stmt_args: &ForStatementArgs = match stmt {
+            Statement::For(for_stmt_args) => for_stmt_args,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        // Only integer ranges are allowed
+        let start_type = self.check_expr_type(&for_stmt_args.range_start)?;
+        if start_type != Type::Int {
+            return Err(CompilerError::TypeMismatch(Type::Int, start_type));
+        }
+        let end_type = self.check_expr_type(&for_stmt_args.range_end)?;
+        if end_type != Type::Int {
+            return Err(CompilerError::TypeMismatch(Type::Int, end_type));
+        }
+        let step_expr = for_stmt_args.step.clone()
+            .unwrap_or(Expression::IntLiteral(1));
+        let step_type = self.check_expr_type(&step_expr)?;
+        if step_type != Type::Int {
+            return Err(CompilerError::TypeMismatch(Type::Int, step_type));
+        }
+
+        // The direction of a constant step is known at compile time
+        let step_sign = match self.fold_const_expr(&step_expr) {
+            Ok(Expression::IntLiteral(0)) => return Err(CompilerError::ZeroForStep),
+            Ok(Expression::IntLiteral(step)) => Some(step.signum()),
+            _ => None
+        };
+
+        let tag_end = self.uid_generator.generate();
+        let tag_check = self.uid_generator.generate();
+
+        // Names of the hidden range variables. "#" cannot appear
+        // in an identifier, so these never clash with script variables.
+        let end_var_name = format!("for#{}#end", tag_end);
+        let step_var_name = format!("for#{}#step", tag_end);
+
+        // Weak context holding the counter and the range bounds
+        let for_fn_ctx = {
+            let fn_ctx = self.get_current_function()?;
+            FunctionContext::new_weak(fn_ctx)?
+        };
+        self.push_function_context(for_fn_ctx);
+
+        let range_var_decls = [
+            (for_stmt_args.var_name.clone(), for_stmt_args.range_start.clone()),
+            (end_var_name.clone(), for_stmt_args.range_end.clone()),
+            (step_var_name.clone(), step_expr)
+        ];
+        for (var_name, var_expr) in range_var_decls.iter() {
+            let var_decl_args = VariableDeclArgs {
+                var_type: Type::Int,
+                name: var_name.clone(),
+                assignment: Box::new(var_expr.clone())
+            };
+            self.compile_var_decl_stmt(&Statement::VariableDecl(var_decl_args))?;
+        }
+
+        // A step of zero would loop forever, so it panics instead
+        if step_sign.is_none() {
+            let zero_check_stmt = Statement::If(IfStatementArgs {
+                if_expr: Expression::Equals(
+                    Box::new(Expression::Variable(step_var_name.clone())),
+                    Box::new(Expression::IntLiteral(0))
+                ),
+                binding: None,
+                if_block: vec![Statement::Panic(Box::new(
+                    Expression::StringLiteral(String::from("\"for loop step must not be zero\""))
+                ))],
+                else_block: None,
+                else_if_list: None
+            });
+            self.compile_stmt(&zero_check_stmt)?;
+        }
+
+        // Skip the increment on the first iteration
+        self.builder.tag(tag_check);
+        let jmp_check_instr = Instruction::new(Opcode::JMP)
+            .with_operand::<u64>(tag_check);
+        self.builder.push_instr(jmp_check_instr);
+
+        let loop_fn_ctx = FunctionContext::new_loop(self.get_current_function()?)?;
+        self.push_function_context(loop_fn_ctx);
+        // "continue" jumps here, so the counter is always incremented
+        let for_start_pos = self.builder.get_current_offset();
+        let mut for_loop_ctx = LoopContext::new(for_start_pos, tag_end);
+        self.push_loop_context(for_loop_ctx);
+
+        // Increment the counter by the step
+        let counter_var = Expression::Variable(for_stmt_args.var_name.clone());
+        let inc_expr = Expression::Addition(
+            Box::new(counter_var.clone()),
+            Box::new(Expression::Variable(step_var_name.clone()))
+        );
+        self.compile_expr(&inc_expr)?;
+        let inc_reg = self.get_last_register()?;
+        let counter_offset = self.get_sp_offset_of_var(&for_stmt_args.var_name)?;
+        let mov_counter_instr = Instruction::new(Opcode::MOVI_RA)
+            .with_operand::<u8>(inc_reg.into())
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(counter_offset as i16);
+        self.builder.push_instr(mov_counter_instr);
+
+        // Update the initial jump to point at the range check
+        let for_check_pos = self.builder.get_current_offset();
+        let instr_pos_list = self.builder.get_tag(&tag_check)
+            .ok_or(CompilerError::Unknown)?;
+        for instr_pos in instr_pos_list {
+            let jmp_instr = self.builder.get_instr(&instr_pos)
+                .ok_or(CompilerError::Unknown)?;
+            jmp_instr.remove_operand_bytes(8);
+            jmp_instr.append_operand::<u64>(for_check_pos as u64);
+        }
+
+        // Compile the range check, a negative step counts down to the end
+        let counter_var = Box::new(counter_var);
+        let end_var = Box::new(Expression::Variable(end_var_name));
+        let (up_check_expr, down_check_expr) = if for_stmt_args.inclusive {
+            (
+                Expression::LessThanEquals(counter_var.clone(), end_var.clone()),
+                Expression::GreaterThanEquals(counter_var, end_var)
+            )
+        } else {
+            (
+                Expression::LessThan(counter_var.clone(), end_var.clone()),
+                Expression::GreaterThan(counter_var, end_var)
+            )
+        };
+        let check_expr = match step_sign {
+            Some(1) => up_check_expr,
+            Some(_) => down_check_expr,
+            None => {
+                let step_var = Box::new(Expression::Variable(step_var_name));
+                let zero = Box::new(Expression::IntLiteral(0));
+                Expression::Or(
+                    Box::new(Expression::And(
+                        Box::new(Expression::GreaterThan(step_var.clone(), zero.clone())),
+                        Box::new(up_check_expr)
+                    )),
+                    Box::new(Expression::And(
+                        Box::new(Expression::LessThan(step_var, zero)),
+                        Box::new(down_check_expr)
+                    ))
+                )
+            }
+        };
+        self.compile_expr(&check_expr)?;
+
+        let last_reg = {
+            self.get_current_function()?
+                .register_allocator
+                .get_last_temp_register()?
+        };
+
+        self.builder.tag(tag_end);
+        let jmpf_instr = Instruction::new(Opcode::JMPF)
+            .with_operand::<u8>(last_reg.into())
+            .with_operand(tag_end);
+        self.builder.push_instr(jmpf_instr);
+
+        // Compile the statement list
+        self.compile_stmt_list(&for_stmt_args.stmt_list)?;
+
+        // Compile a continue statement
+        self.compile_continue_stmt(&Statement::Continue)?;
+
+        // This is the end of this for loop
+        let for_end_pos = self.builder.get_current_offset();
+
+        // Pop the for loop off the stack
+        for_loop_ctx = self.pop_loop_context()?;
+        let instr_pos_list = self.builder.get_tag(&for_loop_ctx.tag_end)
+            .ok_or(CompilerError::Unknown)?;
+
+        // Update with correct end position
+        for instr_pos in instr_pos_list {
+            let jmpf_instr = self.builder.get_instr(&instr_pos)
+                .ok_or(CompilerError::Unknown)?;
+            jmpf_instr.remove_operand_bytes(8);
+            jmpf_instr.append_operand::<u64>(for_end_pos as u64);
+        }
+
+        // Pop the loop context, then clean up the range variables
+        self.pop_function_context()?;
+        let for_fn_ctx = self.pop_function_context()?;
+        self.compile_stack_cleanup_block(&for_fn_ctx)?;
+
+        Ok(())
+    }
+
+    /// Compiles a break statement
+    pub fn compile_break_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        if *stmt != Statement::Break {
+            return Err(CompilerError::Unknown);
+        }
+
+        // Compile the stack cleanup
+        self.compile_stack_loop()?;
+
+        let tag_end = {
+            self.get_current_loop()?
+                .tag_end
+        };
+
+        // Tag this instruction
+        self.builder.tag(tag_end);
+        // JMP to end instr
+        let jmp_end_instr = Instruction::new(Opcode::JMP)
+            .with_operand::<u64>(tag_end);
+        self.builder.push_instr(jmp_end_instr);
+
+        Ok(())
+    }
+
+    /// Compiles a continue statement
+    pub fn compile_continue_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        if *stmt != Statement::Continue {
+            return Err(CompilerError::Unknown);
+        }
+
+        // Compile the stack cleanup
+        self.compile_stack_loop()?;
+
+        let loop_start_pos = {
+            self.get_current_loop()?
+                .pos_start
+        };
+
+        // JMP to begin instr
+        let jmp_begin_instr = Instruction::new(Opcode::JMP)
+            .with_operand::<u64>(loop_start_pos as u64);
+        self.builder.push_instr(jmp_begin_instr);
+        
+        Ok(())
+    }
+
+    /// Compiles a return statement
+    pub fn compile_return_stmt(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let return_expr_opt = match stmt {
+            Statement::Return(ret_expr) => ret_expr,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let mut return_expr_type = Type::Void;
+        let before_stack_size = self.get_stack_size()?;
+
+        if return_expr_opt.is_some() {
+            let return_expr_ref = return_expr_opt.as_ref().unwrap();
+            return_expr_type = self.check_expr_type(return_expr_ref)?;
+        }
+
+        let mut fn_ret_type = {
+            let fn_ctx = self.get_parent_function()?;
+            fn_ctx.get_ret_type()?
+        };
+
+        // Types are compared by their canonical names
+        self.canonize_type(&mut fn_ret_type)?;
+        self.canonize_type(&mut return_expr_type)?;
+        if let Some(return_expr) = return_expr_opt.as_ref() {
+            if self.is_optional_coercion(return_expr, &fn_ret_type) {
+                return_expr_type = fn_ret_type.clone();
+            }
+        }
+
+        if fn_ret_type != return_expr_type {
+            return Err(CompilerError::TypeMismatch(fn_ret_type, return_expr_type));
+        }
+
+        if return_expr_opt.is_some() {
+            let return_expr = return_expr_opt.as_ref().unwrap();
+            let ret_expr_type = self.check_expr_type(return_expr)?;
+            //println!("Ret expr type: {:?}", ret_expr_type);
+            //println!("Ret expr: {:?}", return_expr);
+            self.compile_expr(return_expr)?;
+
+            // Move to R0 register if type is primitive
+            if ret_expr_type.is_primitive() {
+                match fn_ret_type {
+                    Type::Int => {
+                        let last_reg = {
+                            let fn_ctx = self.get_current_function()?;
+                            fn_ctx.register_allocator.get_last_temp_register()?
+                        };
+                        // Instruction for doing so
+                        let mov_ret_instr = Instruction::new(Opcode::MOVI)
+                            .with_operand::<u8>(last_reg.into())
+                            .with_operand::<u8>(Register::R0.into());
+                        self.builder.push_instr(mov_ret_instr);
+                    },
+                    Type::Float => {
+                        let last_reg = {
+                            let fn_ctx = self.get_current_function()?;
+                            fn_ctx.register_allocator.get_last_temp_register()?
+                        };
+                        // Instruction for doing so
+                        let mov_ret_instr = Instruction::new(Opcode::MOVF)
+                            .with_operand::<u8>(last_reg.into())
+                            .with_operand::<u8>(Register::R0.into());
+                        self.builder.push_instr(mov_ret_instr);
+                    },
+                    Type::Bool => {
+                        let last_reg = {
+                            let fn_ctx = self.get_current_function()?;
+                            fn_ctx.register_allocator.get_last_temp_register()?
+                        };
+                        // Instruction for doing so
+                        let mov_ret_instr = Instruction::new(Opcode::MOVB)
+                            .with_operand::<u8>(last_reg.into())
+                            .with_operand::<u8>(Register::R0.into());
+                        self.builder.push_instr(mov_ret_instr);
+                    },
+                    Type::Reference(_) | Type::AutoArray(_) | Type::Optional(_) => {
+                        let last_reg = {
+                            let fn_ctx = self.get_current_function()?;
+                            fn_ctx.register_allocator.get_last_temp_register()?
+                        };
+                        // Instruction for doing so
+                        let mov_ret_instr = Instruction::new(Opcode::MOVA)
+                            .with_operand::<u8>(last_reg.into())
+                            .with_operand::<u8>(Register::R0.into());
+                        self.builder.push_instr(mov_ret_instr);
+                    },
+                    _ => {}
+                };
+            }
+        }
+
+        // Clean up the stack.
+        self.compile_stack_cleanup_return()?;
+
+        // Add the RET function
+        let ret_instr = Instruction::new(Opcode::RET);
+        self.builder.push_instr(ret_instr);
+
+        // Code following the return still sees the stack as it was before
+        let fn_ctx = self.get_current_function_mut()?;
+        fn_ctx.stack_size = before_stack_size;
+
+        Ok(())
+    }
+
+    /// Compiles a variable assign statement expression
+    pub fn compile_var_assign_stmt_expr(&mut self, assign_expr: &Expression) -> CompilerResult<()> {
+        let (lhs_expr, rhs_expr) = match assign_expr {
+            Expression::Assign(lhs, rhs) => (lhs.deref().clone(), rhs.deref().clone()),
+            Expression::AddAssign(lhs, rhs) => {
+                let rhs_expr = Expression::Addition(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            Expression::SubAssign(lhs, rhs) => {
+                let rhs_expr = Expression::Subtraction(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            Expression::DivAssign(lhs, rhs) => {
+                let rhs_expr = Expression::Division(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            Expression::MulAssign(lhs, rhs) => {
+                let rhs_expr = Expression::Multiplication(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            Expression::ModAssign(lhs, rhs) => {
+                let rhs_expr = Expression::Modulo(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            Expression::BitAndAssign(lhs, rhs) => {
+                let rhs_expr = Expression::BitAnd(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            Expression::BitOrAssign(lhs, rhs) => {
+                let rhs_expr = Expression::BitOr(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            Expression::BitXorAssign(lhs, rhs) => {
+                let rhs_expr = Expression::BitXor(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            Expression::ShiftLeftAssign(lhs, rhs) => {
+                let rhs_expr = Expression::ShiftLeft(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            Expression::ShiftRightAssign(lhs, rhs) => {
+                let rhs_expr = Expression::ShiftRight(lhs.clone(), rhs.clone());
+                (lhs.deref().clone(), rhs_expr)
+            },
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        // Compile the left hand side of this expression
+        let lhs_expr_type = self.compile_lhs_assign_expr(&lhs_expr)?;
+
+        //println!("Type to be assigned to: {:?}", lhs_expr_type);
+        // Get the result register
+        let mut lhs_reg = {
+            let fn_ctx = self.get_current_function_mut()?;
+            fn_ctx.register_allocator.get_last_temp_register()?
+        };
+
+        // Save the result pointer to the stack;
+        let stack_inc_instr = Instruction::new_inc_stack(8);
+        self.inc_stack(8)?;
+
+        let save_stack_instr = Instruction::new(Opcode::MOVA_RA)
+            .with_operand::<u8>(lhs_reg.into())
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(-8);
+        
+        self.builder.push_instr(stack_inc_instr);
+        self.builder.push_instr(save_stack_instr);
+
+        let lhs_ptr_pos = {
+            self.get_current_function()?
+                .stack_size - 8
+        };
+
+        // Check the type of the rhs expression
+        let mut rhs_expr_type = self.check_expr_type(&rhs_expr)?;
+        if self.is_interface_coercion(&rhs_expr, &lhs_expr_type) || self.is_optional_coercion(&rhs_expr, &lhs_expr_type) {
+            rhs_expr_type = lhs_expr_type.clone();
+        }
+        // Array literals are compiled into fixed size arrays, which checks their size,
+        // while empty array literals take the element type they are assigned to
+        match (&rhs_expr, &lhs_expr_type) {
+            (Expression::ArrayLiteral(_), Type::Array(_, _)) => rhs_expr_type = lhs_expr_type.clone(),
+            (Expression::ArrayLiteral(elements), Type::AutoArray(_)) if elements.is_empty() => rhs_expr_type = lhs_expr_type.clone(),
+            _ => {}
+        }
+
+        // Check for type mismatch, declared types may be canonical while expression types are not
+        let mut lhs_canon_type = lhs_expr_type.clone();
+        let mut rhs_canon_type = rhs_expr_type.clone();
+        self.canonize_type(&mut lhs_canon_type)?;
+        self.canonize_type(&mut rhs_canon_type)?;
+        if lhs_canon_type != rhs_canon_type {
+            return Err(CompilerError::TypeMismatch(lhs_expr_type, rhs_expr_type));
+        }
+
+        let mut stack_size = self.get_stack_size()?;
+
+        //println!("Stack size before assign expr: {}", stack_size);
+
+        // Compile the right hand of this expression
+        self.compile_expr_with_type(&rhs_expr, &lhs_expr_type)?;
+        stack_size = self.get_stack_size()?;
+        //println!("Stack size after assign expr: {}", stack_size);
+
+        // Last register used may contain the assignment value
+        let rhs_reg = self.get_last_register()?;
+
+        lhs_reg = self.get_next_register()?;
+
+        let stack_offset: i16 = {
+            let curr_stack_size = self.get_stack_size()?;
+            -((curr_stack_size - lhs_ptr_pos) as i16)
+        };
+
+        // Move the pointer from the stack into the lhs register
+        let mov_stack_instr = Instruction::new(Opcode::MOVA_AR)
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(stack_offset)
+            .with_operand::<u8>(lhs_reg.clone().into());
+        self.builder.push_instr(mov_stack_instr);
+
+        // Move the value to the assignment destination
+        let assign_instr = match rhs_expr_type {
+            Type::Int => {
+                //println!("Moving value from {:?} to the address in {:?}", rhs_reg, lhs_reg);
+                Instruction::new(Opcode::MOVI_RA)
+                    .with_operand::<u8>(rhs_reg.into())
+                    .with_operand::<u8>(lhs_reg.into())
+                    .with_operand::<i16>(0)
+            },
+            Type::Float => {
+                Instruction::new(Opcode::MOVF_RA)
+                    .with_operand::<u8>(rhs_reg.into())
+                    .with_operand::<u8>(lhs_reg.into())
+                    .with_operand::<i16>(0)
+            },
+            Type::Bool => {
+                Instruction::new(Opcode::MOVB_RA)
+                    .with_operand::<u8>(rhs_reg.into())
+                    .with_operand::<u8>(lhs_reg.into())
+                    .with_operand::<i16>(0)
+            },
+            Type::AutoArray(_) | Type::Optional(_) => {
+                Instruction::new(Opcode::MOVA_RA)
+                    .with_operand::<u8>(rhs_reg.into())
+                    .with_operand::<u8>(lhs_reg.into())
+                    .with_operand::<i16>(0)
+            },
+            Type::Reference(inner) => {
+                match inner.deref() {
+                    Type::AutoArray(_) | Type::Interface(_) => {
+                        Instruction::new(Opcode::MOVN_A)
+                            .with_operand::<u8>(Register::SP.into())
+                            .with_operand::<i16>(-16)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<i16>(0)
+                            .with_operand::<u32>(16)
+                    },
+                    _ => {
+                        Instruction::new(Opcode::MOVA_RA)
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<i16>(0)
+                    }
+                }
+            },
+            _ => {
+                let size = self.get_size_of_type(&rhs_expr_type)?;
+                Instruction::new(Opcode::MOVN_A)
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(-(size as i16))
+                    .with_operand::<u8>(lhs_reg.into())
+                    .with_operand::<i16>(0)
+                    .with_operand::<u32>(size as u32)
+            }
+        };
+
+        self.builder.push_instr(assign_instr);
+
+        // A variable narrowed by a check may no longer hold what was checked
+        if let Expression::Variable(var_name) = &lhs_expr {
+            for fn_ctx in self.fn_context_stack.iter_mut() {
+                fn_ctx.widen_var_type(var_name);
+            }
+        }
+        Ok(())
+        //Err(CompilerError::Unimplemented(format!("Var assign compilation not implemented!")))
+    }
+
+    /// Compiles the left hand side of an assignment expression
+    pub fn compile_lhs_assign_expr(&mut self, expr: &Expression) -> CompilerResult<Type> {
+        if let Some(member_expr) = self.get_this_member_expr(expr) {
+            return self.compile_lhs_assign_expr(&member_expr);
+        }
+        let expr_type = match expr {
+            Expression::Variable(var_name) => {
+                self.compile_var_addr(var_name)?;
+                self.get_declared_type_of_var(var_name)?
+            },
+            Expression::MemberAccess(lhs_expr, rhs_expr) => {
+                let var_name = match lhs_expr.deref() {
+                    Expression::Variable(var_name) => var_name,
+                    _ => return Err(CompilerError::UnsupportedExpression(lhs_expr.deref().clone()))
+                };
+                let var_type = self.get_type_of_var(var_name)?;
+
+                let cont_def = match var_type {
+                    Type::Other(cont_name) => {
+                        self.compile_var_addr(var_name)?;
+                        self.resolve_container(&cont_name)?
+                    },
+                    Type::Reference(inner_type) => {
+                        match inner_type.deref() {
+                            Type::Other(cont_name) => {
+                                let (base_reg, var_offset) = self.compile_var_location(var_name)?;
+                                let lhs_ptr_reg = self.get_next_register()?;
+                                let mova_instr = Instruction::new(Opcode::MOVA_AR)
+                                    .with_operand::<u8>(base_reg.into())
+                                    .with_operand::<i16>(var_offset as i16)
+                                    .with_operand::<u8>(lhs_ptr_reg.into());
+                                self.builder.push_instr(mova_instr);
+                                self.resolve_container(cont_name)?
+                            },
+                            _ => return Err(CompilerError::Unknown)
+                        }
+                    },
+                    _ => return Err(CompilerError::Unknown)
+                };
+
+                self.compile_lhs_assign_member_expr(rhs_expr, &cont_def)?
+            },
+            Expression::Index(array_expr, index_expr) => {
+                self.compile_index_addr_expr(array_expr, index_expr)?;
+                self.check_expr_type(expr)?
+            },
+            Expression::TupleAccess(tuple_expr, index) => {
+                self.compile_tuple_access_addr_expr(tuple_expr, *index)?;
+                self.check_expr_type(expr)?
+            },
+            _ => return Err(CompilerError::UnsupportedExpression(expr.clone()))
+        };
+        Ok(expr_type)
+    }
+
+    pub fn compile_lhs_assign_member_expr(&mut self, rhs_expr: &Expression, cont_def: &ContainerDef) -> CompilerResult<Type> {
+        match rhs_expr {
+            Expression::Variable(var_name) => {
+                let last_reg = self.get_last_register()?;
+                let next_reg = self.get_next_register()?;
+
+                let member_offset = cont_def.get_member_offset(self, var_name)?;
+
+                let addui_instr = Instruction::new(Opcode::ADDU_I)
+                    .with_operand::<u8>(last_reg.into())
+                    .with_operand::<u64>(member_offset as u64)
+                    .with_operand::<u8>(next_reg.into());
+                
+                self.builder.push_instr(addui_instr);
+                cont_def.get_member_type(var_name)
+            },
+            Expression::MemberAccess(lhs_expr, rhs_expr) => {
+                let var_name;
+                if let Expression::Variable(name) = lhs_expr.deref() {
+                    var_name = name;
+                } else {
+                    return Err(CompilerError::UnsupportedExpression(lhs_expr.deref().clone()));
+                }
+
+                let member_offset = cont_def.get_member_offset(self, var_name)?;
+                let member_type = cont_def.get_member_type(var_name)?;
+
+                let last_reg = self.get_last_register()?;
+                let next_reg = self.get_next_register()?;
+
+                let cont_def = match member_type {
+                    Type::Other(cont_name) => {
+                        let addui_instr = Instruction::new(Opcode::ADDU_I)
+                            .with_operand::<u8>(last_reg.into())
+                            .with_operand::<u64>(member_offset as u64)
+                            .with_operand::<u8>(next_reg.into());
+                        self.builder.push_instr(addui_instr);
+                        self.resolve_container(&cont_name)?
+                    },
+                    Type::Reference(inner_type) => {
+                        match inner_type.deref() {
+                            Type::Other(cont_name) => {
+                                let mova_instr = Instruction::new(Opcode::MOVA_AR)
+                                    .with_operand::<u8>(last_reg.into())
+                                    .with_operand::<i16>(member_offset as i16)
+                                    .with_operand::<u8>(next_reg.into());
+                                self.builder.push_instr(mova_instr);
+                                self.resolve_container(cont_name)?
+                            },
+                            _ => return Err(CompilerError::Unknown)
+                        }
+                    },
+                    _ => return Err(CompilerError::Unknown)
+                };
+
+                self.compile_lhs_assign_member_expr(rhs_expr, &cont_def)
+            },
+            _ => return Err(CompilerError::UnsupportedExpression(rhs_expr.deref().clone()))
+        }
+    }
+
+    /// Compiles an expression
+    pub fn compile_expr(&mut self, expr: &Expression) -> CompilerResult<()> {
+        if let Some(member_expr) = self.get_this_member_expr(expr) {
+            return self.compile_expr(&member_expr);
+        }
+        if let Some(method_expr) = self.get_member_method_expr(expr) {
+            return self.compile_expr(&method_expr);
+        }
+        if let Some(const_expr) = self.get_const_expr(expr) {
+            return self.compile_expr(&const_expr);
+        }
+        let expr_type = self.check_expr_type(expr)?;
+        let expr_size = self.get_size_of_type(&expr_type)?;
+        //println!("Expr size: {}", expr_size);
+        let before_stack_size = self.get_stack_size()?;
+        match expr {
+            Expression::IntLiteral(int) => {
+                let reg = {
+                    let fn_ctx = self.get_current_function_mut()?;
+                    fn_ctx.register_allocator.get_temp_register()?
+                };
+
+                let ldi_instr = Instruction::new(Opcode::LDI)
+                    .with_operand::<i64>(*int)
+                    .with_operand::<u8>(reg.into());
+
+                self.builder.push_instr(ldi_instr);
+            },
+            Expression::FloatLiteral(float) => {
+                let reg = {
+                    let fn_ctx = self.get_current_function_mut()?;
+                    fn_ctx.register_allocator.get_temp_register()?
+                };
+
+                let ldf_instr = Instruction::new(Opcode::LDF)
+                    .with_operand::<f32>(*float)
+                    .with_operand::<u8>(reg.into());
+                    
                 self.builder.push_instr(ldf_instr);
             },
             Expression::BoolLiteral(boolean) => {
@@ -1994,6 +4767,14 @@ impl Compiler {
                     
                 self.builder.push_instr(ldb_instr);
             },
+            // "none" is the null pointer
+            Expression::NoneLiteral => {
+                let reg = self.get_next_register()?;
+                let lda_instr = Instruction::new(Opcode::LDA)
+                    .with_operand::<u64>(0)
+                    .with_operand::<u8>(reg.into());
+                self.builder.push_instr(lda_instr);
+            },
             Expression::StringLiteral(string) => {
                 let string = String::from(&string[1..string.len() - 1]);
                 let (string_size, string_addr) = self.data.get_string_slice(&string);
@@ -2027,12 +4808,36 @@ impl Compiler {
             Expression::ContainerInstance(_, _) => {
                 self.compile_cont_instance_expr(expr)?;
             },
+            Expression::Call(path, args) if self.is_enum_variant(path) => {
+                self.compile_enum_variant_expr(path, args)?;
+            },
+            Expression::TypeCall(_, _, _) => {
+                self.compile_expr(&self.get_instance_call_expr(expr)?)?;
+            },
+            Expression::Variable(path) if self.is_enum_variant(path) => {
+                self.compile_enum_variant_expr(path, &[])?;
+            },
+            Expression::Variable(fn_name) if self.is_fn_ref_expr(fn_name) => {
+                self.compile_fn_ref_expr(fn_name)?;
+            },
             Expression::Variable(_) => {
                 self.compile_var_expr(expr)?;
             },
+            Expression::Closure(fn_decl_args) => {
+                self.compile_closure_expr(fn_decl_args)?;
+            },
+            Expression::Cast(op_expr, cast_type) => {
+                self.compile_cast_expr(op_expr, cast_type)?;
+            },
+            Expression::Try(op_expr) => {
+                self.compile_try_expr(op_expr)?;
+            },
             Expression::Ref(op_expr) => {
                 self.compile_lhs_assign_expr(op_expr)?;
             },
+            Expression::Box(op_expr) => {
+                self.compile_box_expr(op_expr)?;
+            },
             Expression::Deref(op_expr) => {
                 let expr_type = self.check_expr_type(op_expr)?;
                 self.compile_expr(op_expr)?;
@@ -2076,6 +4881,33 @@ impl Compiler {
                     return Err(CompilerError::Unimplemented(format!("Deref of non-primitive pointer types")));
                 }
             },
+            Expression::ArrayLiteral(_) => {
+                self.compile_array_literal_expr(expr)?;
+            },
+            Expression::ArrayRepeat(value_expr, size) => {
+                self.compile_array_repeat_expr(value_expr, *size)?;
+            },
+            Expression::Tuple(elements) => {
+                self.compile_tuple_expr(elements)?;
+            },
+            Expression::TupleAccess(tuple_expr, index) => {
+                self.compile_tuple_access_expr(tuple_expr, *index)?;
+            },
+            Expression::Index(array_expr, index_expr) => {
+                self.compile_index_addr_expr(array_expr, index_expr)?;
+                let addr_reg = self.get_last_register()?;
+                self.compile_load_from_addr(&expr_type, addr_reg)?;
+            },
+            Expression::MemberAccess(array_expr, call_expr) if self.is_array_expr(array_expr) => {
+                self.compile_array_method_expr(array_expr, call_expr)?;
+            },
+            Expression::MemberAccess(string_expr, call_expr) if self.is_string_expr(string_expr) => {
+                self.compile_string_method_expr(string_expr, call_expr)?;
+            },
+            Expression::MemberAccess(_, _) if self.is_interface_call_expr(expr) => {
+                self.compile_interface_call_expr(expr)?;
+                self.force_temp_register(Register::R0)?;
+            },
             Expression::MemberAccess(_, _) => {
                 self.compile_member_access_expr(expr)?;
                 // Last register contains the source/dest address
@@ -2104,7 +4936,7 @@ impl Compiler {
                                 .with_operand::<u8>(next_reg.into());
                             self.builder.push_instr(movf_instr);
                         },
-                        Type::Reference(_) => {
+                        Type::Reference(_) | Type::AutoArray(_) | Type::Optional(_) => {
                             let mova_instr = Instruction::new(Opcode::MOVA_AR)
                                 .with_operand::<u8>(last_reg.into())
                                 .with_operand::<i16>(0)
@@ -2113,7 +4945,8 @@ impl Compiler {
                         },
                         _ => {}
                     };
-                } else if !expr_type.is_primitive() {
+                } else if !expr_type.is_primitive() && !expr.ends_in_call() {
+                    // Non primitive call results are already on top of the stack
                     let stack_inc_instr = Instruction::new_inc_stack(expr_size);
                     self.inc_stack(expr_size)?;
                     let movn_instr = Instruction::new(Opcode::MOVN_A)
@@ -2126,22 +4959,276 @@ impl Compiler {
                     self.builder.push_instr(movn_instr);
                 }
             },
-            Expression::Call(fn_name, _) => {
+            Expression::Call(fn_name, fn_arg_exprs) => {
                 //println!("Compiling call expr. {:?}", expr);
                 //println!("Stack size before call expr: {}", self.get_stack_size()?);
                 self.compile_call_expr(expr)?;
                 let fn_ret_type = {
-                    let fn_def = self.resolve_function(fn_name)?;
+                    let fn_def = self.resolve_call(fn_name, fn_arg_exprs)?;
                     fn_def.ret_type.clone()
                 };
-                if fn_ret_type.is_primitive() {
-                    self.get_current_function_mut()?
-                        .register_allocator
-                        .force_temp_register(Register::R0);
-                }
-                //println!("Stack size after call expr: {}", self.get_stack_size()?);
+                if fn_ret_type.is_primitive() {
+                    self.get_current_function_mut()?
+                        .register_allocator
+                        .force_temp_register(Register::R0);
+                }
+                //println!("Stack size after call expr: {}", self.get_stack_size()?);
+            },
+            Expression::Addition(lhs, rhs) if expr_type == Type::String => {
+                self.compile_string_concat_expr(lhs, rhs)?;
+            },
+            _ if self.resolve_operator_fn(expr).is_some() => {
+                let fn_def = self.resolve_operator_fn(expr)
+                    .ok_or(CompilerError::Unknown)?;
+                self.compile_operator_expr(expr, &fn_def)?;
+            },
+            Expression::Interpolation(segments) => {
+                self.compile_interpolation_expr(segments)?;
+            },
+            Expression::Equals(lhs, rhs) |
+            Expression::NotEquals(lhs, rhs) |
+            Expression::LessThan(lhs, rhs) |
+            Expression::GreaterThan(lhs, rhs) |
+            Expression::LessThanEquals(lhs, rhs) |
+            Expression::GreaterThanEquals(lhs, rhs) if self.is_string_expr(lhs) => {
+                self.compile_string_compare_expr(expr, lhs, rhs)?;
+            },
+            Expression::Slice(string_expr, start_expr, end_expr) => {
+                self.compile_string_slice_expr(string_expr, start_expr.as_deref(), end_expr.as_deref())?;
+            },
+            Expression::Addition(lhs, rhs) => {
+                let expr_type = self.check_expr_type(lhs)?;
+                self.compile_expr(lhs)?;
+                let lhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                self.compile_expr(rhs)?;
+                let rhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                //println!("Adding registers {:?} and {:?}", lhs_reg, rhs_reg);
+                match expr_type {
+                    Type::Int => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        //println!("Saved result into {:?}", res_reg);
+                        let addi_instr = Instruction::new(Opcode::ADDI)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(addi_instr);
+                    },
+                    Type::Float => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let addf_instr = Instruction::new(Opcode::ADDF)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(addf_instr);
+                    },
+                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
+                };
+            },
+            Expression::Subtraction(lhs, rhs) => {
+                let expr_type = self.check_expr_type(lhs)?;
+                self.compile_expr(lhs)?;
+                let lhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                self.compile_expr(rhs)?;
+                let rhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                match expr_type {
+                    Type::Int => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let subi_instr = Instruction::new(Opcode::SUBI)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(subi_instr);
+                    },
+                    Type::Float => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let subf_instr = Instruction::new(Opcode::SUBF)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(subf_instr);
+                    },
+                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
+                };
+            },
+            Expression::Multiplication(lhs, rhs) => {
+                let expr_type = self.check_expr_type(lhs)?;
+                self.compile_expr(lhs)?;
+                let lhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                self.compile_expr(rhs)?;
+                let rhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                match expr_type {
+                    Type::Int => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let muli_instr = Instruction::new(Opcode::MULI)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(muli_instr);
+                    },
+                    Type::Float => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let mulf_instr = Instruction::new(Opcode::MULF)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(mulf_instr);
+                    },
+                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
+                };
+            },
+            Expression::Division(lhs, rhs) => {
+                let expr_type = self.check_expr_type(lhs)?;
+                self.compile_expr(lhs)?;
+                let lhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                self.compile_expr(rhs)?;
+                let rhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                match expr_type {
+                    Type::Int => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let divi_instr = Instruction::new(Opcode::DIVI)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(divi_instr);
+                    },
+                    Type::Float => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let divf_instr = Instruction::new(Opcode::DIVF)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(divf_instr);
+                    },
+                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
+                };
+            },
+            Expression::LessThan(lhs, rhs) => {
+                let expr_type = self.check_expr_type(lhs)?;
+                self.compile_expr(lhs)?;
+                let lhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                self.compile_expr(rhs)?;
+                let rhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                match expr_type {
+                    Type::Int => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let lti_instr = Instruction::new(Opcode::LTI)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(lti_instr);
+                    },
+                    Type::Float => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let ltf_instr = Instruction::new(Opcode::LTF)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(ltf_instr);
+                    },
+                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
+                };
+            },
+
+            Expression::GreaterThan(lhs, rhs) => {
+                let expr_type = self.check_expr_type(lhs)?;
+                self.compile_expr(lhs)?;
+                let lhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                self.compile_expr(rhs)?;
+                let rhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                match expr_type {
+                    Type::Int => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let gti_instr = Instruction::new(Opcode::GTI)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(gti_instr);
+                    },
+                    Type::Float => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let gtf_instr = Instruction::new(Opcode::GTF)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(gtf_instr);
+                    },
+                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
+                };
             },
-            Expression::Addition(lhs, rhs) => {
+
+            Expression::LessThanEquals(lhs, rhs) => {
                 let expr_type = self.check_expr_type(lhs)?;
                 self.compile_expr(lhs)?;
                 let lhs_reg = {
@@ -2153,35 +5240,34 @@ impl Compiler {
                     let fn_ctx = self.get_current_function()?;
                     fn_ctx.register_allocator.get_last_temp_register()?
                 };
-                //println!("Adding registers {:?} and {:?}", lhs_reg, rhs_reg);
                 match expr_type {
                     Type::Int => {
                         let res_reg = {
                             let fn_ctx = self.get_current_function_mut()?;
                             fn_ctx.register_allocator.get_temp_register()?
                         };
-                        //println!("Saved result into {:?}", res_reg);
-                        let addi_instr = Instruction::new(Opcode::ADDI)
+                        let lteqi_instr = Instruction::new(Opcode::LTEQI)
                             .with_operand::<u8>(lhs_reg.into())
                             .with_operand::<u8>(rhs_reg.into())
                             .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(addi_instr);
+                        self.builder.push_instr(lteqi_instr);
                     },
                     Type::Float => {
                         let res_reg = {
                             let fn_ctx = self.get_current_function_mut()?;
                             fn_ctx.register_allocator.get_temp_register()?
                         };
-                        let addf_instr = Instruction::new(Opcode::ADDF)
+                        let lteqf_instr = Instruction::new(Opcode::LTEQF)
                             .with_operand::<u8>(lhs_reg.into())
                             .with_operand::<u8>(rhs_reg.into())
                             .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(addf_instr);
+                        self.builder.push_instr(lteqf_instr);
                     },
                     _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
                 };
             },
-            Expression::Subtraction(lhs, rhs) => {
+
+            Expression::GreaterThanEquals(lhs, rhs) => {
                 let expr_type = self.check_expr_type(lhs)?;
                 self.compile_expr(lhs)?;
                 let lhs_reg = {
@@ -2199,400 +5285,1128 @@ impl Compiler {
                             let fn_ctx = self.get_current_function_mut()?;
                             fn_ctx.register_allocator.get_temp_register()?
                         };
-                        let subi_instr = Instruction::new(Opcode::SUBI)
+                        let gteqi_instr = Instruction::new(Opcode::GTEQI)
                             .with_operand::<u8>(lhs_reg.into())
                             .with_operand::<u8>(rhs_reg.into())
                             .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(subi_instr);
+                        self.builder.push_instr(gteqi_instr);
                     },
                     Type::Float => {
                         let res_reg = {
                             let fn_ctx = self.get_current_function_mut()?;
                             fn_ctx.register_allocator.get_temp_register()?
                         };
-                        let subf_instr = Instruction::new(Opcode::SUBF)
+                        let gteqf_instr = Instruction::new(Opcode::GTEQF)
                             .with_operand::<u8>(lhs_reg.into())
                             .with_operand::<u8>(rhs_reg.into())
                             .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(subf_instr);
+                        self.builder.push_instr(gteqf_instr);
+                    },
+                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
+                };
+            },
+
+            Expression::Equals(lhs, rhs) => {
+                let expr_type = self.check_expr_type(lhs)?;
+                self.compile_expr(lhs)?;
+                let lhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                self.compile_expr(rhs)?;
+                let rhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                match expr_type {
+                    Type::Int | Type::Optional(_) => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let eqi_instr = Instruction::new(Opcode::EQI)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(eqi_instr);
+                    },
+                    Type::Float => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let eqf_instr = Instruction::new(Opcode::EQF)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(eqf_instr);
+                    },
+                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
+                };
+            },
+            Expression::NotEquals(lhs, rhs) => {
+                let expr_type = self.check_expr_type(lhs)?;
+                self.compile_expr(lhs)?;
+                let lhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                self.compile_expr(rhs)?;
+                let rhs_reg = {
+                    let fn_ctx = self.get_current_function()?;
+                    fn_ctx.register_allocator.get_last_temp_register()?
+                };
+                match expr_type {
+                    Type::Int | Type::Optional(_) => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let neqi_instr = Instruction::new(Opcode::NEQI)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(neqi_instr);
+                    },
+                    Type::Float => {
+                        let res_reg = {
+                            let fn_ctx = self.get_current_function_mut()?;
+                            fn_ctx.register_allocator.get_temp_register()?
+                        };
+                        let neqf_instr = Instruction::new(Opcode::NEQF)
+                            .with_operand::<u8>(lhs_reg.into())
+                            .with_operand::<u8>(rhs_reg.into())
+                            .with_operand::<u8>(res_reg.into());
+                        self.builder.push_instr(neqf_instr);
                     },
                     _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
                 };
             },
-            Expression::Multiplication(lhs, rhs) => {
-                let expr_type = self.check_expr_type(lhs)?;
-                self.compile_expr(lhs)?;
-                let lhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                self.compile_expr(rhs)?;
-                let rhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                match expr_type {
-                    Type::Int => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let muli_instr = Instruction::new(Opcode::MULI)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(muli_instr);
-                    },
-                    Type::Float => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let mulf_instr = Instruction::new(Opcode::MULF)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(mulf_instr);
-                    },
-                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
-                };
+            Expression::Modulo(lhs, rhs) |
+            Expression::BitAnd(lhs, rhs) |
+            Expression::BitOr(lhs, rhs) |
+            Expression::BitXor(lhs, rhs) |
+            Expression::ShiftLeft(lhs, rhs) |
+            Expression::ShiftRight(lhs, rhs) => {
+                self.compile_int_binary_expr(expr, lhs, rhs)?;
+            },
+            Expression::Not(op) => {
+                let opcode = match self.check_expr_type(op)? {
+                    Type::Int => Opcode::NOTI,
+                    _ => Opcode::NOT
+                };
+                self.compile_expr(op)?;
+                let (op_reg, target_reg) = {
+                    let fn_ctx = self.get_current_function_mut()?;
+                    let op_reg = fn_ctx.register_allocator.get_last_temp_register()?;
+                    let target_reg = fn_ctx.register_allocator.get_temp_register()?;
+                    (op_reg, target_reg)
+                };
+                let not_instr = Instruction::new(opcode)
+                    .with_operand::<u8>(op_reg.into())
+                    .with_operand::<u8>(target_reg.into());
+                self.builder.push_instr(not_instr);
+            },
+            Expression::Negate(op) => {
+                let op_type = self.check_expr_type(op)?;
+                self.compile_expr(op)?;
+                let op_reg = self.get_last_register()?;
+                let target_reg = self.get_next_register()?;
+                // Negation is a multiplication by -1
+                let neg_instr = match op_type {
+                    Type::Int => Instruction::new(Opcode::MULI_I)
+                        .with_operand::<u8>(op_reg.into())
+                        .with_operand::<i64>(-1)
+                        .with_operand::<u8>(target_reg.into()),
+                    Type::Float => Instruction::new(Opcode::MULF_I)
+                        .with_operand::<u8>(op_reg.into())
+                        .with_operand::<f32>(-1.0)
+                        .with_operand::<u8>(target_reg.into()),
+                    _ => return Err(CompilerError::TypeMismatch(Type::Int, op_type))
+                };
+                self.builder.push_instr(neg_instr);
+            },
+            Expression::And(lhs, rhs) => {
+                self.compile_expr(lhs)?;
+                let lhs_reg = self.get_last_register()?;
+                self.compile_expr(rhs)?;
+                let rhs_reg = self.get_last_register()?;
+                let target_reg = self.get_next_register()?;
+                let and_instr = Instruction::new(Opcode::AND)
+                    .with_operand::<u8>(lhs_reg.into())
+                    .with_operand::<u8>(rhs_reg.into())
+                    .with_operand::<u8>(target_reg.into());
+                self.builder.push_instr(and_instr);
+            },
+            Expression::Or(lhs, rhs) => {
+                self.compile_expr(lhs)?;
+                let lhs_reg = self.get_last_register()?;
+                self.compile_expr(rhs)?;
+                let rhs_reg = self.get_last_register()?;
+                let target_reg = self.get_next_register()?;
+                let or_instr = Instruction::new(Opcode::OR)
+                    .with_operand::<u8>(lhs_reg.into())
+                    .with_operand::<u8>(rhs_reg.into())
+                    .with_operand::<u8>(target_reg.into());
+                self.builder.push_instr(or_instr);
+            },
+            _ => return Err(CompilerError::UnsupportedExpression(expr.clone()))
+        };
+
+        let after_stack_size = self.get_stack_size()?;
+        let stack_diff = after_stack_size - before_stack_size;
+        //println!("Stack diff: {}, is expr primitive: {}", stack_diff, expr_type.is_primitive());
+        let mut pop_size = stack_diff;
+        //println!("{}", pop_size > expr_size);
+
+        if !expr_type.is_primitive() {
+            pop_size -= expr_size;
+            if pop_size > 0 {
+                let mov_stack_instr = Instruction::new(Opcode::MOVN_A)
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(-(expr_size as i16))
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(-(stack_diff as i16))
+                    .with_operand::<u32>(expr_size as u32);
+                self.builder.push_instr(mov_stack_instr);
+            }
+        }
+        if pop_size > 0 {
+            //println!("Popping {} bytes off the stack after compile_expr().", pop_size);
+            let pop_stack_instr = Instruction::new_dec_stack(pop_size);
+            self.dec_stack(pop_size)?;
+            self.builder.push_instr(pop_stack_instr);
+        }
+        Ok(())
+        //Err(CompilerError::Unimplemented(format!("Expr compilation not implemented!")))
+    }
+
+    /// Compiles a heap allocation of a value.
+    /// The heap address ends up in the last register.
+    pub fn compile_box_expr(&mut self, expr: &Expression) -> CompilerResult<()> {
+        let expr_type = self.check_expr_type(expr)?;
+        let size = self.get_size_of_type(&expr_type)?;
+
+        self.compile_expr(expr)?;
+
+        let value_reg = self.get_last_register()?;
+        let ptr_reg = self.get_next_register()?;
+
+        let alloc_instr = Instruction::new(Opcode::ALLOC_I)
+            .with_operand::<u64>(size as u64)
+            .with_operand::<u8>(ptr_reg.clone().into());
+        self.builder.push_instr(alloc_instr);
+
+        // Copy the value into the allocation
+        let mov_instr = match expr_type {
+            Type::Int => {
+                Instruction::new(Opcode::MOVI_RA)
+                    .with_operand::<u8>(value_reg.into())
+                    .with_operand::<u8>(ptr_reg.clone().into())
+                    .with_operand::<i16>(0)
+            },
+            Type::Float => {
+                Instruction::new(Opcode::MOVF_RA)
+                    .with_operand::<u8>(value_reg.into())
+                    .with_operand::<u8>(ptr_reg.clone().into())
+                    .with_operand::<i16>(0)
+            },
+            Type::Bool => {
+                Instruction::new(Opcode::MOVB_RA)
+                    .with_operand::<u8>(value_reg.into())
+                    .with_operand::<u8>(ptr_reg.clone().into())
+                    .with_operand::<i16>(0)
+            },
+            Type::Reference(_) | Type::Optional(_) => {
+                Instruction::new(Opcode::MOVA_RA)
+                    .with_operand::<u8>(value_reg.into())
+                    .with_operand::<u8>(ptr_reg.clone().into())
+                    .with_operand::<i16>(0)
+            },
+            _ => {
+                // Non primitive values are on top of the stack
+                Instruction::new(Opcode::MOVN_A)
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(-(size as i16))
+                    .with_operand::<u8>(ptr_reg.clone().into())
+                    .with_operand::<i16>(0)
+                    .with_operand::<u32>(size as u32)
+            }
+        };
+        self.builder.push_instr(mov_instr);
+
+        // Register the destructor if the container implements core::Destroy
+        if let Type::Other(cont_name) = &expr_type {
+            // Enums have no destructors
+            let cont_def = match self.resolve_container(cont_name) {
+                Ok(cont_def) => cont_def,
+                Err(_) => return Ok(())
+            };
+            if cont_def.does_implement(&String::from(DESTROY_INTERFACE)) {
+                let destroy_fn_def = cont_def.get_member_function(&String::from("destroy"))?;
+                let dtor_instr = Instruction::new(Opcode::DTOR)
+                    .with_operand::<u8>(ptr_reg.into())
+                    .with_operand::<u64>(destroy_fn_def.uid);
+                self.builder.push_instr(dtor_instr);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compiles an array literal.
+    /// The address of the new array ends up in the last register.
+    pub fn compile_array_literal_expr(&mut self, expr: &Expression) -> CompilerResult<()> {
+        let elements = match expr {
+            Expression::ArrayLiteral(elements) => elements,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let elem_type = match self.check_expr_type(expr)? {
+            Type::AutoArray(elem_type) => elem_type.deref().clone(),
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let array_reg = self.get_next_register()?;
+        let arr_new_instr = Instruction::new(Opcode::ARR_NEW)
+            .with_operand::<u8>(array_reg.clone().into());
+        self.builder.push_instr(arr_new_instr);
+
+        // Keep the array address on the stack while the elements are compiled
+        let stack_inc_instr = Instruction::new_inc_stack(8);
+        self.inc_stack(8)?;
+        let save_array_instr = Instruction::new(Opcode::MOVA_RA)
+            .with_operand::<u8>(array_reg.into())
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(-8);
+        self.builder.push_instr(stack_inc_instr);
+        self.builder.push_instr(save_array_instr);
+        let array_pos = self.get_stack_size()? - 8;
+
+        for element in elements.iter() {
+            self.compile_expr(element)?;
+            let value_reg = self.get_last_register()?;
+
+            let array_reg = self.get_next_register()?;
+            let array_offset = -((self.get_stack_size()? - array_pos) as i16);
+            let load_array_instr = Instruction::new(Opcode::MOVA_AR)
+                .with_operand::<u8>(Register::SP.into())
+                .with_operand::<i16>(array_offset)
+                .with_operand::<u8>(array_reg.clone().into());
+            self.builder.push_instr(load_array_instr);
+
+            self.compile_array_push(&elem_type, value_reg, array_reg)?;
+        }
+
+        // Leave the array address in the last register, the stack slot is popped by compile_expr()
+        let array_reg = self.get_next_register()?;
+        let array_offset = -((self.get_stack_size()? - array_pos) as i16);
+        let load_array_instr = Instruction::new(Opcode::MOVA_AR)
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(array_offset)
+            .with_operand::<u8>(array_reg.into());
+        self.builder.push_instr(load_array_instr);
+
+        Ok(())
+    }
+
+    /// Appends a compiled value to an array.
+    /// Primitive values are taken from the given register, others from the top of the stack,
+    /// which is popped afterwards.
+    fn compile_array_push(&mut self, elem_type: &Type, value_reg: Register, array_reg: Register) -> CompilerResult<()> {
+        let elem_size = self.get_size_of_type(elem_type)?;
+        let slot_reg = self.get_next_register()?;
+
+        let arr_push_instr = Instruction::new(Opcode::ARR_PUSH)
+            .with_operand::<u8>(array_reg.into())
+            .with_operand::<u32>(elem_size as u32)
+            .with_operand::<u8>(slot_reg.clone().into());
+        self.builder.push_instr(arr_push_instr);
+
+        self.compile_store_to_addr(elem_type, value_reg, slot_reg, 0)?;
+
+        Ok(())
+    }
+
+    /// Stores a compiled value at the given address.
+    /// Primitive values are taken from the given register, others from the top of the stack,
+    /// which is popped afterwards.
+    fn compile_store_to_addr(&mut self, value_type: &Type, value_reg: Register, addr_reg: Register, offset: i16) -> CompilerResult<()> {
+        let value_size = self.get_size_of_type(value_type)?;
+        let store_instr = match value_type {
+            Type::Int => {
+                Instruction::new(Opcode::MOVI_RA)
+                    .with_operand::<u8>(value_reg.into())
+                    .with_operand::<u8>(addr_reg.into())
+                    .with_operand::<i16>(offset)
+            },
+            Type::Float => {
+                Instruction::new(Opcode::MOVF_RA)
+                    .with_operand::<u8>(value_reg.into())
+                    .with_operand::<u8>(addr_reg.into())
+                    .with_operand::<i16>(offset)
+            },
+            Type::Bool => {
+                Instruction::new(Opcode::MOVB_RA)
+                    .with_operand::<u8>(value_reg.into())
+                    .with_operand::<u8>(addr_reg.into())
+                    .with_operand::<i16>(offset)
+            },
+            _ if value_type.is_primitive() => {
+                Instruction::new(Opcode::MOVA_RA)
+                    .with_operand::<u8>(value_reg.into())
+                    .with_operand::<u8>(addr_reg.into())
+                    .with_operand::<i16>(offset)
+            },
+            _ => {
+                Instruction::new(Opcode::MOVN_A)
+                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<i16>(-(value_size as i16))
+                    .with_operand::<u8>(addr_reg.into())
+                    .with_operand::<i16>(offset)
+                    .with_operand::<u32>(value_size as u32)
+            }
+        };
+        self.builder.push_instr(store_instr);
+
+        if !value_type.is_primitive() && value_size > 0 {
+            let stack_dec_instr = Instruction::new_dec_stack(value_size);
+            self.dec_stack(value_size)?;
+            self.builder.push_instr(stack_dec_instr);
+        }
+
+        Ok(())
+    }
+
+    /// Compiles an expression, building array literals inline if a fixed size array is expected.
+    /// Returns the type of the compiled value.
+    pub fn compile_expr_with_type(&mut self, expr: &Expression, expected_type: &Type) -> CompilerResult<Type> {
+        match (expr, expected_type) {
+            (Expression::ArrayLiteral(elements), Type::Array(elem_type, size)) => {
+                self.compile_fixed_array_literal_expr(elements, elem_type, *size)?;
+                Ok(expected_type.clone())
             },
-            Expression::Division(lhs, rhs) => {
-                let expr_type = self.check_expr_type(lhs)?;
-                self.compile_expr(lhs)?;
-                let lhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                self.compile_expr(rhs)?;
-                let rhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                match expr_type {
-                    Type::Int => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let divi_instr = Instruction::new(Opcode::DIVI)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(divi_instr);
-                    },
-                    Type::Float => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let divf_instr = Instruction::new(Opcode::DIVF)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(divf_instr);
-                    },
-                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
-                };
+            // Empty array literals take the element type they are assigned to
+            (Expression::ArrayLiteral(elements), Type::AutoArray(_)) if elements.is_empty() => {
+                self.compile_expr(expr)?;
+                Ok(expected_type.clone())
             },
-            Expression::LessThan(lhs, rhs) => {
-                let expr_type = self.check_expr_type(lhs)?;
-                self.compile_expr(lhs)?;
-                let lhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                self.compile_expr(rhs)?;
-                let rhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                match expr_type {
-                    Type::Int => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let lti_instr = Instruction::new(Opcode::LTI)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(lti_instr);
-                    },
-                    Type::Float => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let ltf_instr = Instruction::new(Opcode::LTF)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(ltf_instr);
-                    },
-                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
-                };
+            (_, Type::Reference(intf_type)) if self.is_interface_coercion(expr, expected_type) => {
+                self.compile_interface_ref_expr(expr, intf_type)?;
+                Ok(expected_type.clone())
+            },
+            // Optionals share the representation of the reference they wrap
+            (_, Type::Optional(_)) if self.is_optional_coercion(expr, expected_type) => {
+                self.compile_expr(expr)?;
+                Ok(expected_type.clone())
+            },
+            _ => {
+                self.compile_expr(expr)?;
+                self.check_expr_type(expr)
+            }
+        }
+    }
+
+    /// Returns true if an expression is a container reference which has to be
+    /// turned into a reference to an interface implemented by the container
+    pub fn is_interface_coercion(&self, expr: &Expression, expected_type: &Type) -> bool {
+        let intf_name = match expected_type {
+            Type::Reference(inner_type) => match inner_type.deref() {
+                Type::Interface(intf_name) => intf_name,
+                _ => return false
             },
+            _ => return false
+        };
+        let mut expr_type = match self.check_expr_type(expr) {
+            Ok(expr_type) => expr_type,
+            Err(_) => return false
+        };
+        if self.canonize_type(&mut expr_type).is_err() || !expr_type.is_cont_reference() {
+            return false;
+        }
+        expr_type.get_cont_name()
+            .and_then(|cont_name| self.resolve_container(cont_name).ok())
+            .map(|cont_def| cont_def.does_implement(intf_name))
+            .unwrap_or(false)
+    }
 
-            Expression::GreaterThan(lhs, rhs) => {
-                let expr_type = self.check_expr_type(lhs)?;
-                self.compile_expr(lhs)?;
-                let lhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                self.compile_expr(rhs)?;
-                let rhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                match expr_type {
-                    Type::Int => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let gti_instr = Instruction::new(Opcode::GTI)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(gti_instr);
-                    },
-                    Type::Float => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let gtf_instr = Instruction::new(Opcode::GTF)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(gtf_instr);
-                    },
-                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
-                };
+    /// Returns true if an expression is "none" or a reference which is implicitly
+    /// turned into the expected optional
+    pub fn is_optional_coercion(&self, expr: &Expression, expected_type: &Type) -> bool {
+        let inner_type = match expected_type {
+            Type::Optional(inner_type) => inner_type.deref(),
+            _ => return false
+        };
+        let mut expr_type = match self.check_expr_type(expr) {
+            Ok(expr_type) => expr_type,
+            Err(_) => return false
+        };
+        if self.canonize_type(&mut expr_type).is_err() {
+            return false;
+        }
+        let mut inner_type = inner_type.clone();
+        if self.canonize_type(&mut inner_type).is_err() {
+            return false;
+        }
+        match expr_type {
+            Type::Optional(expr_inner_type) => *expr_inner_type == Type::Auto,
+            _ => expr_type == inner_type
+        }
+    }
+
+    /// Compiles a container reference into an interface reference on top of the stack.
+    /// Interface references consist of the container pointer followed by the vtable address.
+    pub fn compile_interface_ref_expr(&mut self, expr: &Expression, intf_type: &Type) -> CompilerResult<()> {
+        let intf_name = match intf_type {
+            Type::Interface(intf_name) => intf_name,
+            _ => return Err(CompilerError::Unknown)
+        };
+        let mut expr_type = self.check_expr_type(expr)?;
+        self.canonize_type(&mut expr_type)?;
+        let cont_name = expr_type.get_cont_name()
+            .ok_or(CompilerError::Unknown)?;
+        let cont_def = self.resolve_container(cont_name)?;
+        let intf_def = self.resolve_interface(intf_name)?;
+        let vtable_addr = self.get_vtable_addr(&cont_def, &intf_def)?;
+
+        self.compile_expr(expr)?;
+        let ptr_reg = self.get_last_register()?;
+        let vtable_reg = self.get_next_register()?;
+
+        let stack_inc_instr = Instruction::new_inc_stack(16);
+        self.inc_stack(16)?;
+        let mov_ptr_instr = Instruction::new(Opcode::MOVA_RA)
+            .with_operand::<u8>(ptr_reg.into())
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(-16);
+        let vtable_lda_instr = Instruction::new(Opcode::LDA)
+            .with_operand::<u64>(vtable_addr)
+            .with_operand::<u8>(vtable_reg.clone().into());
+        let mov_vtable_instr = Instruction::new(Opcode::MOVA_RA)
+            .with_operand::<u8>(vtable_reg.into())
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<i16>(-8);
+
+        self.builder.push_instr(stack_inc_instr);
+        self.builder.push_instr(mov_ptr_instr);
+        self.builder.push_instr(vtable_lda_instr);
+        self.builder.push_instr(mov_vtable_instr);
+
+        Ok(())
+    }
+
+    /// Returns the address of the vtable of a container for an interface it implements.
+    /// The vtable holds the uids of the member functions, ordered by the vtable offsets of the interface.
+    pub fn get_vtable_addr(&mut self, cont_def: &ContainerDef, intf_def: &InterfaceDef) -> CompilerResult<u64> {
+        let mut fn_uids = vec![0; intf_def.functions.len()];
+        for (fn_name, vtable_offset) in intf_def.vtable_offsets.iter() {
+            let fn_def = cont_def.get_member_function(fn_name)?;
+            fn_uids[vtable_offset / 8] = fn_def.uid;
+        }
+        let vtable_name = format!("{}::{}", cont_def.canonical_name, intf_def.canonical_name);
+        Ok(self.data.get_vtable(&vtable_name, &fn_uids))
+    }
+
+    /// Compiles an array literal into a fixed size array on top of the stack
+    pub fn compile_fixed_array_literal_expr(&mut self, elements: &[Expression], elem_type: &Type, size: usize) -> CompilerResult<()> {
+        if elements.len() != size {
+            return Err(CompilerError::ArraySizeMismatch(size, elements.len()));
+        }
+        let elem_size = self.get_size_of_type(elem_type)?;
+
+        let array_size = self.get_fixed_array_size(elem_size, size)?;
+        let stack_inc_instr = Instruction::new_inc_stack(array_size);
+        self.inc_stack(array_size)?;
+        self.builder.push_instr(stack_inc_instr);
+        let array_pos = self.get_stack_size()? - array_size;
+
+        for (i, element) in elements.iter().enumerate() {
+            let element_type = self.compile_expr_with_type(element, elem_type)?;
+            if element_type != *elem_type {
+                return Err(CompilerError::TypeMismatch(elem_type.clone(), element_type));
+            }
+            let value_reg = self.get_last_register()?;
+            let slot_offset = self.get_array_slot_offset(array_pos + i * elem_size)?;
+            self.compile_store_to_addr(elem_type, value_reg, Register::SP, slot_offset)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the byte size of a fixed size array on the stack,
+    /// which has to be addressable with 16 bit offsets to SP
+    fn get_fixed_array_size(&self, elem_size: usize, size: usize) -> CompilerResult<usize> {
+        elem_size.checked_mul(size)
+            .filter(|array_size| i16::try_from(*array_size).is_ok())
+            .ok_or(CompilerError::ArrayTooLarge(elem_size.saturating_mul(size)))
+    }
+
+    /// Returns the offset to SP of an array slot at the given stack position
+    fn get_array_slot_offset(&self, slot_pos: usize) -> CompilerResult<i16> {
+        let distance = self.get_stack_size()? - slot_pos;
+        i16::try_from(distance)
+            .map(|distance| -distance)
+            .map_err(|_| CompilerError::ArrayTooLarge(distance))
+    }
+
+    /// Compiles a repeat initialiser, e.g. "[0; 16]", into a fixed size array on top of the stack
+    pub fn compile_array_repeat_expr(&mut self, value_expr: &Expression, size: usize) -> CompilerResult<()> {
+        let elem_type = self.check_expr_type(value_expr)?;
+        let elem_size = self.get_size_of_type(&elem_type)?;
+
+        let array_size = self.get_fixed_array_size(elem_size, size)?;
+        let stack_inc_instr = Instruction::new_inc_stack(array_size);
+        self.inc_stack(array_size)?;
+        self.builder.push_instr(stack_inc_instr);
+        if size == 0 {
+            return Ok(());
+        }
+        let array_pos = self.get_stack_size()? - array_size;
+
+        // Store the value in the first slot
+        self.compile_expr(value_expr)?;
+        let value_reg = self.get_last_register()?;
+        let slot_offset = self.get_array_slot_offset(array_pos)?;
+        self.compile_store_to_addr(&elem_type, value_reg, Register::SP, slot_offset)?;
+
+        // Then fill the rest by doubling the initialised part, all offsets
+        // lie within the array, whose size fits into an i16
+        let array_offset = -(array_size as i16);
+        let mut filled = 1;
+        while filled < size {
+            let count = filled.min(size - filled);
+            let movn_instr = Instruction::new(Opcode::MOVN_A)
+                .with_operand::<u8>(Register::SP.into())
+                .with_operand::<i16>(array_offset)
+                .with_operand::<u8>(Register::SP.into())
+                .with_operand::<i16>(array_offset + (filled * elem_size) as i16)
+                .with_operand::<u32>((count * elem_size) as u32);
+            self.builder.push_instr(movn_instr);
+            filled += count;
+        }
+
+        Ok(())
+    }
+
+    /// Compiles the address of an array element into the last register
+    pub fn compile_index_addr_expr(&mut self, array_expr: &Expression, index_expr: &Expression) -> CompilerResult<()> {
+        let elem_type = match self.check_expr_type(array_expr)? {
+            Type::AutoArray(elem_type) => elem_type.deref().clone(),
+            Type::Array(elem_type, size) => {
+                return self.compile_fixed_index_addr_expr(array_expr, index_expr, &elem_type, size);
             },
+            array_type => return Err(CompilerError::CannotIndexNonArray(array_type))
+        };
+        let elem_size = self.get_size_of_type(&elem_type)?;
+
+        self.compile_expr(array_expr)?;
+        let array_reg = self.get_last_register()?;
+        self.compile_expr(index_expr)?;
+        let index_reg = self.get_last_register()?;
+        let addr_reg = self.get_next_register()?;
+
+        let arr_idx_instr = Instruction::new(Opcode::ARR_IDX)
+            .with_operand::<u8>(array_reg.into())
+            .with_operand::<u8>(index_reg.into())
+            .with_operand::<u32>(elem_size as u32)
+            .with_operand::<u8>(addr_reg.into());
+        self.builder.push_instr(arr_idx_instr);
+
+        Ok(())
+    }
+
+    /// Compiles the address of a fixed size array element into the last register
+    fn compile_fixed_index_addr_expr(&mut self, array_expr: &Expression, index_expr: &Expression, elem_type: &Type, size: usize) -> CompilerResult<()> {
+        if let Expression::IntLiteral(index) = index_expr {
+            if *index < 0 || *index as usize >= size {
+                return Err(CompilerError::IndexOutOfBounds(*index, size));
+            }
+        }
+        let elem_size = self.get_size_of_type(elem_type)?;
+
+        self.compile_addr_expr(array_expr)?;
+        let base_reg = self.get_last_register()?;
+
+        self.compile_expr(index_expr)?;
+        let index_reg = self.get_last_register()?;
+        let addr_reg = self.get_next_register()?;
+
+        let arr_idx_instr = Instruction::new(Opcode::ARR_IDX_F)
+            .with_operand::<u8>(base_reg.into())
+            .with_operand::<u8>(index_reg.into())
+            .with_operand::<u32>(elem_size as u32)
+            .with_operand::<u64>(size as u64)
+            .with_operand::<u8>(addr_reg.into());
+        self.builder.push_instr(arr_idx_instr);
+
+        Ok(())
+    }
+
+    /// Computes the address of a value on the stack into the next register.
+    /// The offset is counted downwards from the stack pointer.
+    fn compile_stack_addr(&mut self, offset: usize) -> CompilerResult<Register> {
+        let addr_reg = self.get_next_register()?;
+        let subui_instr = Instruction::new(Opcode::SUBU_I)
+            .with_operand::<u8>(Register::SP.into())
+            .with_operand::<u64>(offset as u64)
+            .with_operand::<u8>(addr_reg.clone().into());
+        self.builder.push_instr(subui_instr);
+        Ok(addr_reg)
+    }
+
+    /// Compiles a string concatenation.
+    /// The new string ends up on top of the stack.
+    pub fn compile_string_concat_expr(&mut self, lhs: &Expression, rhs: &Expression) -> CompilerResult<()> {
+        self.compile_expr(lhs)?;
+        self.compile_expr(rhs)?;
+        self.compile_stack_string_concat()
+    }
+
+    /// Concatenates the two strings on top of the stack into one
+    fn compile_stack_string_concat(&mut self) -> CompilerResult<()> {
+        let lhs_reg = self.compile_stack_addr(32)?;
+        let rhs_reg = self.compile_stack_addr(16)?;
+
+        // The result replaces the left hand side string
+        let str_cat_instr = Instruction::new(Opcode::STR_CAT)
+            .with_operand::<u8>(lhs_reg.clone().into())
+            .with_operand::<u8>(rhs_reg.into())
+            .with_operand::<u8>(lhs_reg.into());
+        self.builder.push_instr(str_cat_instr);
+
+        let stack_dec_instr = Instruction::new_dec_stack(16);
+        self.dec_stack(16)?;
+        self.builder.push_instr(stack_dec_instr);
+        Ok(())
+    }
+
+    /// Compiles an interpolated string.
+    /// Every segment is formatted and appended, the new string ends up on top of the stack.
+    pub fn compile_interpolation_expr(&mut self, segments: &[Expression]) -> CompilerResult<()> {
+        if segments.is_empty() {
+            return self.compile_expr(&Expression::StringLiteral(String::from("\"\"")));
+        }
+        for (i, segment) in segments.iter().enumerate() {
+            self.compile_format_expr(segment)?;
+            if i > 0 {
+                self.compile_stack_string_concat()?;
+            }
+        }
+        Ok(())
+    }
 
-            Expression::LessThanEquals(lhs, rhs) => {
-                let expr_type = self.check_expr_type(lhs)?;
-                self.compile_expr(lhs)?;
-                let lhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                self.compile_expr(rhs)?;
-                let rhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                match expr_type {
-                    Type::Int => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let lteqi_instr = Instruction::new(Opcode::LTEQI)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(lteqi_instr);
-                    },
-                    Type::Float => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let lteqf_instr = Instruction::new(Opcode::LTEQF)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(lteqf_instr);
-                    },
-                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
-                };
+    /// Compiles the string representation of an expression onto the stack.
+    /// Containers have to implement core::Display.
+    pub fn compile_format_expr(&mut self, expr: &Expression) -> CompilerResult<()> {
+        let mut expr_type = self.check_expr_type(expr)?;
+        self.canonize_type(&mut expr_type)?;
+        let from_opcode = match &expr_type {
+            Type::String => return self.compile_expr(expr),
+            Type::Int => Opcode::STR_FROM_I,
+            Type::Float => Opcode::STR_FROM_F,
+            Type::Bool => Opcode::STR_FROM_B,
+            Type::Other(cont_name) => {
+                let implements_display = self.resolve_container(cont_name)
+                    .map(|cont_def| cont_def.does_implement(&String::from(DISPLAY_INTERFACE)))
+                    .unwrap_or(false);
+                if !implements_display {
+                    return Err(CompilerError::CannotFormat(expr_type));
+                }
+                let to_string_expr = Expression::MemberAccess(
+                    Box::new(expr.clone()),
+                    Box::new(Expression::Call(String::from("to_string"), Vec::new()))
+                );
+                return self.compile_expr(&to_string_expr);
             },
+            _ => return Err(CompilerError::CannotFormat(expr_type))
+        };
 
-            Expression::GreaterThanEquals(lhs, rhs) => {
-                let expr_type = self.check_expr_type(lhs)?;
-                self.compile_expr(lhs)?;
-                let lhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                self.compile_expr(rhs)?;
-                let rhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                match expr_type {
-                    Type::Int => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let gteqi_instr = Instruction::new(Opcode::GTEQI)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(gteqi_instr);
-                    },
-                    Type::Float => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let gteqf_instr = Instruction::new(Opcode::GTEQF)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(gteqf_instr);
-                    },
-                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
-                };
+        self.compile_expr(expr)?;
+        let value_reg = self.get_last_register()?;
+
+        let stack_inc_instr = Instruction::new_inc_stack(16);
+        self.inc_stack(16)?;
+        self.builder.push_instr(stack_inc_instr);
+        let target_reg = self.compile_stack_addr(16)?;
+
+        let from_instr = Instruction::new(from_opcode)
+            .with_operand::<u8>(value_reg.into())
+            .with_operand::<u8>(target_reg.into());
+        self.builder.push_instr(from_instr);
+        Ok(())
+    }
+
+    /// Compiles a comparison of two strings by their contents
+    pub fn compile_string_compare_expr(&mut self, expr: &Expression, lhs: &Expression, rhs: &Expression) -> CompilerResult<()> {
+        let cmp_opcode = match expr {
+            Expression::Equals(_, _) => Opcode::EQI,
+            Expression::NotEquals(_, _) => Opcode::NEQI,
+            Expression::LessThan(_, _) => Opcode::LTI,
+            Expression::GreaterThan(_, _) => Opcode::GTI,
+            Expression::LessThanEquals(_, _) => Opcode::LTEQI,
+            Expression::GreaterThanEquals(_, _) => Opcode::GTEQI,
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        self.compile_expr(lhs)?;
+        self.compile_expr(rhs)?;
+        let lhs_reg = self.compile_stack_addr(32)?;
+        let rhs_reg = self.compile_stack_addr(16)?;
+
+        let ordering_reg = self.get_next_register()?;
+        let str_cmp_instr = Instruction::new(Opcode::STR_CMP)
+            .with_operand::<u8>(lhs_reg.into())
+            .with_operand::<u8>(rhs_reg.into())
+            .with_operand::<u8>(ordering_reg.clone().into());
+        self.builder.push_instr(str_cmp_instr);
+
+        // Compare the ordering against 0 with the matching integer comparison
+        let zero_reg = self.get_next_register()?;
+        let ldi_instr = Instruction::new(Opcode::LDI)
+            .with_operand::<i64>(0)
+            .with_operand::<u8>(zero_reg.clone().into());
+        let res_reg = self.get_next_register()?;
+        let cmp_instr = Instruction::new(cmp_opcode)
+            .with_operand::<u8>(ordering_reg.into())
+            .with_operand::<u8>(zero_reg.into())
+            .with_operand::<u8>(res_reg.into());
+        self.builder.push_instr(ldi_instr);
+        self.builder.push_instr(cmp_instr);
+        Ok(())
+    }
+
+    /// Compiles a substring, e.g. "text[1..4]".
+    /// The slice ends up on top of the stack and shares its data with the original string.
+    pub fn compile_string_slice_expr(&mut self, string_expr: &Expression, start_expr: Option<&Expression>, end_expr: Option<&Expression>) -> CompilerResult<()> {
+        self.compile_expr(string_expr)?;
+
+        let start_reg = match start_expr {
+            Some(start_expr) => {
+                self.compile_expr(start_expr)?;
+                self.get_last_register()?
             },
+            None => {
+                let start_reg = self.get_next_register()?;
+                let ldi_instr = Instruction::new(Opcode::LDI)
+                    .with_operand::<i64>(0)
+                    .with_operand::<u8>(start_reg.clone().into());
+                self.builder.push_instr(ldi_instr);
+                start_reg
+            }
+        };
 
-            Expression::Equals(lhs, rhs) => {
-                let expr_type = self.check_expr_type(lhs)?;
-                self.compile_expr(lhs)?;
-                let lhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                self.compile_expr(rhs)?;
-                let rhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                match expr_type {
-                    Type::Int => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let eqi_instr = Instruction::new(Opcode::EQI)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(eqi_instr);
-                    },
-                    Type::Float => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let eqf_instr = Instruction::new(Opcode::EQF)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(eqf_instr);
-                    },
-                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
-                };
+        let end_reg = match end_expr {
+            Some(end_expr) => {
+                self.compile_expr(end_expr)?;
+                self.get_last_register()?
             },
-            Expression::NotEquals(lhs, rhs) => {
-                let expr_type = self.check_expr_type(lhs)?;
-                self.compile_expr(lhs)?;
-                let lhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                self.compile_expr(rhs)?;
-                let rhs_reg = {
-                    let fn_ctx = self.get_current_function()?;
-                    fn_ctx.register_allocator.get_last_temp_register()?
-                };
-                match expr_type {
-                    Type::Int => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let neqi_instr = Instruction::new(Opcode::NEQI)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(neqi_instr);
-                    },
-                    Type::Float => {
-                        let res_reg = {
-                            let fn_ctx = self.get_current_function_mut()?;
-                            fn_ctx.register_allocator.get_temp_register()?
-                        };
-                        let neqf_instr = Instruction::new(Opcode::NEQF)
-                            .with_operand::<u8>(lhs_reg.into())
-                            .with_operand::<u8>(rhs_reg.into())
-                            .with_operand::<u8>(res_reg.into());
-                        self.builder.push_instr(neqf_instr);
-                    },
-                    _ => return Err(CompilerError::UnsupportedExpression(lhs.deref().clone()))
-                };
+            None => {
+                // Slice up to the length of the string
+                let string_reg = self.compile_stack_addr(16)?;
+                let end_reg = self.get_next_register()?;
+                let movi_instr = Instruction::new(Opcode::MOVI_AR)
+                    .with_operand::<u8>(string_reg.into())
+                    .with_operand::<i16>(0)
+                    .with_operand::<u8>(end_reg.clone().into());
+                self.builder.push_instr(movi_instr);
+                end_reg
+            }
+        };
+
+        let string_reg = self.compile_stack_addr(16)?;
+        let str_slice_instr = Instruction::new(Opcode::STR_SLICE)
+            .with_operand::<u8>(string_reg.into())
+            .with_operand::<u8>(start_reg.into())
+            .with_operand::<u8>(end_reg.into());
+        self.builder.push_instr(str_slice_instr);
+        Ok(())
+    }
+
+    /// Compiles a builtin string method call ("len", "find", "contains", "starts_with" or "split")
+    pub fn compile_string_method_expr(&mut self, string_expr: &Expression, call_expr: &Expression) -> CompilerResult<()> {
+        self.check_string_method_expr_type(string_expr, call_expr)?;
+        let (fn_name, fn_args) = match call_expr {
+            Expression::Call(fn_name, fn_args) => (fn_name, fn_args),
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        self.compile_expr(string_expr)?;
+
+        if fn_name == "len" {
+            let string_reg = self.compile_stack_addr(16)?;
+            let len_reg = self.get_next_register()?;
+            let movi_instr = Instruction::new(Opcode::MOVI_AR)
+                .with_operand::<u8>(string_reg.into())
+                .with_operand::<i16>(0)
+                .with_operand::<u8>(len_reg.into());
+            self.builder.push_instr(movi_instr);
+            return Ok(());
+        }
+
+        self.compile_expr(&fn_args[0])?;
+        let string_reg = self.compile_stack_addr(32)?;
+        let arg_reg = self.compile_stack_addr(16)?;
+        let res_reg = self.get_next_register()?;
+
+        if fn_name == "split" {
+            let str_split_instr = Instruction::new(Opcode::STR_SPLIT)
+                .with_operand::<u8>(string_reg.into())
+                .with_operand::<u8>(arg_reg.into())
+                .with_operand::<u8>(res_reg.into());
+            self.builder.push_instr(str_split_instr);
+            return Ok(());
+        }
+
+        let str_find_instr = Instruction::new(Opcode::STR_FIND)
+            .with_operand::<u8>(string_reg.into())
+            .with_operand::<u8>(arg_reg.into())
+            .with_operand::<u8>(res_reg.clone().into());
+        self.builder.push_instr(str_find_instr);
+
+        // "contains" and "starts_with" compare the position of the first occurrence against 0
+        let cmp_opcode = match fn_name.as_str() {
+            "contains" => Opcode::GTEQI,
+            "starts_with" => Opcode::EQI,
+            _ => return Ok(())
+        };
+        let zero_reg = self.get_next_register()?;
+        let ldi_instr = Instruction::new(Opcode::LDI)
+            .with_operand::<i64>(0)
+            .with_operand::<u8>(zero_reg.clone().into());
+        let bool_reg = self.get_next_register()?;
+        let cmp_instr = Instruction::new(cmp_opcode)
+            .with_operand::<u8>(res_reg.into())
+            .with_operand::<u8>(zero_reg.into())
+            .with_operand::<u8>(bool_reg.into());
+        self.builder.push_instr(ldi_instr);
+        self.builder.push_instr(cmp_instr);
+        Ok(())
+    }
+
+    /// Returns true if the address of an expression can be taken with compile_addr_expr()
+    fn is_addressable_expr(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::Variable(_) | Expression::MemberAccess(_, _) | Expression::Index(_, _) => true,
+            Expression::TupleAccess(tuple_expr, _) => self.is_addressable_expr(tuple_expr),
+            _ => false
+        }
+    }
+
+    /// Compiles the address of a stack allocated value into the last register
+    fn compile_addr_expr(&mut self, expr: &Expression) -> CompilerResult<()> {
+        if let Some(member_expr) = self.get_this_member_expr(expr) {
+            return self.compile_addr_expr(&member_expr);
+        }
+        match expr {
+            Expression::Variable(var_name) => {
+                self.compile_var_addr(var_name)?;
             },
-            Expression::Not(op) => {
-                self.compile_expr(op)?;
-                let (op_reg, target_reg) = {
-                    let fn_ctx = self.get_current_function_mut()?;
-                    let op_reg = fn_ctx.register_allocator.get_last_temp_register()?;
-                    let target_reg = fn_ctx.register_allocator.get_temp_register()?;
-                    (op_reg, target_reg)
-                };
-                let not_instr = Instruction::new(Opcode::NOT)
-                    .with_operand::<u8>(op_reg.into())
-                    .with_operand::<u8>(target_reg.into());
-                self.builder.push_instr(not_instr);
+            Expression::MemberAccess(_, _) => {
+                self.compile_member_access_expr(expr)?;
             },
-            Expression::And(lhs, rhs) => {
-                self.compile_expr(lhs)?;
-                let lhs_reg = self.get_last_register()?;
-                self.compile_expr(rhs)?;
-                let rhs_reg = self.get_last_register()?;
-                let target_reg = self.get_next_register()?;
-                let and_instr = Instruction::new(Opcode::AND)
-                    .with_operand::<u8>(lhs_reg.into())
-                    .with_operand::<u8>(rhs_reg.into())
-                    .with_operand::<u8>(target_reg.into());
-                self.builder.push_instr(and_instr);
+            Expression::Index(array_expr, index_expr) => {
+                self.compile_index_addr_expr(array_expr, index_expr)?;
             },
-            Expression::Or(lhs, rhs) => {
-                self.compile_expr(lhs)?;
-                let lhs_reg = self.get_last_register()?;
-                self.compile_expr(rhs)?;
-                let rhs_reg = self.get_last_register()?;
-                let target_reg = self.get_next_register()?;
-                let or_instr = Instruction::new(Opcode::OR)
-                    .with_operand::<u8>(lhs_reg.into())
-                    .with_operand::<u8>(rhs_reg.into())
-                    .with_operand::<u8>(target_reg.into());
-                self.builder.push_instr(or_instr);
+            Expression::TupleAccess(tuple_expr, index) => {
+                self.compile_tuple_access_addr_expr(tuple_expr, *index)?;
             },
             _ => return Err(CompilerError::UnsupportedExpression(expr.clone()))
         };
+        Ok(())
+    }
+
+    /// Compiles a tuple literal onto the top of the stack
+    pub fn compile_tuple_expr(&mut self, elements: &[Expression]) -> CompilerResult<()> {
+        let mut elem_types = Vec::new();
+        for element in elements.iter() {
+            elem_types.push(self.check_expr_type(element)?);
+        }
+
+        let tuple_size = self.get_size_of_type(&Type::Tuple(elem_types.clone()))?;
+        let stack_inc_instr = Instruction::new_inc_stack(tuple_size);
+        self.inc_stack(tuple_size)?;
+        self.builder.push_instr(stack_inc_instr);
+        let tuple_pos = self.get_stack_size()? - tuple_size;
+
+        for (i, element) in elements.iter().enumerate() {
+            self.compile_expr(element)?;
+            let value_reg = self.get_last_register()?;
+            let elem_offset = self.get_tuple_elem_offset(&elem_types, i)?;
+            let slot_offset = -((self.get_stack_size()? - tuple_pos - elem_offset) as i16);
+            self.compile_store_to_addr(&elem_types[i], value_reg, Register::SP, slot_offset)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compiles an enum variant, e.g. "Shape::Rect(1.0, 2.0)", onto the top of the stack
+    pub fn compile_enum_variant_expr(&mut self, path: &str, args: &[Expression]) -> CompilerResult<()> {
+        let (enum_def, tag) = self.resolve_enum_variant(path)?;
+        let payload_types = enum_def.variants[tag].1.clone();
+        if payload_types.len() != args.len() {
+            return Err(CompilerError::ArgumentMismatch(String::from(path)));
+        }
+
+        let enum_size = enum_def.get_size(self)?;
+        let stack_inc_instr = Instruction::new_inc_stack(enum_size);
+        self.inc_stack(enum_size)?;
+        self.builder.push_instr(stack_inc_instr);
+        let enum_pos = self.get_stack_size()? - enum_size;
+
+        let tag_reg = self.get_next_register()?;
+        let ldi_instr = Instruction::new(Opcode::LDI)
+            .with_operand::<i64>(tag as i64)
+            .with_operand::<u8>(tag_reg.clone().into());
+        self.builder.push_instr(ldi_instr);
+        self.compile_store_to_addr(&Type::Int, tag_reg, Register::SP, -(enum_size as i16))?;
+
+        for (i, arg) in args.iter().enumerate() {
+            let mut payload_type = payload_types[i].clone();
+            self.canonize_type(&mut payload_type)?;
+            let mut arg_type = self.check_expr_type(arg)?;
+            self.canonize_type(&mut arg_type)?;
+            if payload_type != arg_type {
+                return Err(CompilerError::TypeMismatch(payload_type, arg_type));
+            }
+
+            self.compile_expr(arg)?;
+            let value_reg = self.get_last_register()?;
+            let payload_offset = 8 + self.get_tuple_elem_offset(&payload_types, i)?;
+            let slot_offset = -((self.get_stack_size()? - enum_pos - payload_offset) as i16);
+            self.compile_store_to_addr(&payload_type, value_reg, Register::SP, slot_offset)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compiles the address of a tuple element into the last register
+    fn compile_tuple_access_addr_expr(&mut self, tuple_expr: &Expression, index: usize) -> CompilerResult<()> {
+        let elem_types = match self.check_expr_type(tuple_expr)? {
+            Type::Tuple(elem_types) => elem_types,
+            tuple_type => return Err(CompilerError::TupleAccessOnNonTuple(tuple_type))
+        };
+        let elem_offset = self.get_tuple_elem_offset(&elem_types, index)?;
+
+        self.compile_addr_expr(tuple_expr)?;
+        if elem_offset > 0 {
+            let tuple_reg = self.get_last_register()?;
+            let addr_reg = self.get_next_register()?;
+            let addui_instr = Instruction::new(Opcode::ADDU_I)
+                .with_operand::<u8>(tuple_reg.into())
+                .with_operand::<u64>(elem_offset as u64)
+                .with_operand::<u8>(addr_reg.into());
+            self.builder.push_instr(addui_instr);
+        }
+        Ok(())
+    }
 
-        let after_stack_size = self.get_stack_size()?;
-        let stack_diff = after_stack_size - before_stack_size;
-        //println!("Stack diff: {}, is expr primitive: {}", stack_diff, expr_type.is_primitive());
-        let mut pop_size = stack_diff;
-        //println!("{}", pop_size > expr_size);
+    /// Compiles a tuple element access, e.g. "pair.0"
+    pub fn compile_tuple_access_expr(&mut self, tuple_expr: &Expression, index: usize) -> CompilerResult<()> {
+        let tuple_type = self.check_expr_type(tuple_expr)?;
+        let elem_types = match &tuple_type {
+            Type::Tuple(elem_types) => elem_types.clone(),
+            _ => return Err(CompilerError::TupleAccessOnNonTuple(tuple_type))
+        };
+        let elem_offset = self.get_tuple_elem_offset(&elem_types, index)?;
 
-        if !expr_type.is_primitive() {
-            pop_size -= expr_size;
-            if pop_size > 0 {
-                let mov_stack_instr = Instruction::new(Opcode::MOVN_A)
-                    .with_operand::<u8>(Register::SP.into())
-                    .with_operand::<i16>(-(expr_size as i16))
-                    .with_operand::<u8>(Register::SP.into())
-                    .with_operand::<i16>(-(stack_diff as i16))
-                    .with_operand::<u32>(16);
-                self.builder.push_instr(mov_stack_instr);
-            }
+        if self.is_addressable_expr(tuple_expr) {
+            self.compile_tuple_access_addr_expr(tuple_expr, index)?;
+        } else {
+            // Temporary tuples are compiled onto the stack, which compile_expr() cleans up
+            self.compile_expr(tuple_expr)?;
+            let tuple_size = self.get_size_of_type(&tuple_type)?;
+            let addr_reg = self.get_next_register()?;
+            let subui_instr = Instruction::new(Opcode::SUBU_I)
+                .with_operand::<u8>(Register::SP.into())
+                .with_operand::<u64>((tuple_size - elem_offset) as u64)
+                .with_operand::<u8>(addr_reg.into());
+            self.builder.push_instr(subui_instr);
         }
-        if pop_size > 0 {
-            //println!("Popping {} bytes off the stack after compile_expr().", pop_size);
-            let pop_stack_instr = Instruction::new_dec_stack(pop_size);
-            self.dec_stack(pop_size)?;
-            self.builder.push_instr(pop_stack_instr);
+
+        let addr_reg = self.get_last_register()?;
+        self.compile_load_from_addr(&elem_types[index], addr_reg)?;
+        Ok(())
+    }
+
+    /// Loads a value of the given type from the address in a register.
+    /// Primitive values end up in the last register, others on top of the stack.
+    fn compile_load_from_addr(&mut self, value_type: &Type, addr_reg: Register) -> CompilerResult<()> {
+        let value_size = self.get_size_of_type(value_type)?;
+        if !value_type.is_primitive() {
+            let stack_inc_instr = Instruction::new_inc_stack(value_size);
+            self.inc_stack(value_size)?;
+            let movn_instr = Instruction::new(Opcode::MOVN_A)
+                .with_operand::<u8>(addr_reg.into())
+                .with_operand::<i16>(0)
+                .with_operand::<u8>(Register::SP.into())
+                .with_operand::<i16>(-(value_size as i16))
+                .with_operand::<u32>(value_size as u32);
+            self.builder.push_instr(stack_inc_instr);
+            self.builder.push_instr(movn_instr);
+            return Ok(());
         }
+
+        let value_reg = self.get_next_register()?;
+        let opcode = match value_type {
+            Type::Int => Opcode::MOVI_AR,
+            Type::Float => Opcode::MOVF_AR,
+            Type::Bool => Opcode::MOVB_AR,
+            _ => Opcode::MOVA_AR
+        };
+        let load_instr = Instruction::new(opcode)
+            .with_operand::<u8>(addr_reg.into())
+            .with_operand::<i16>(0)
+            .with_operand::<u8>(value_reg.into());
+        self.builder.push_instr(load_instr);
+
+        Ok(())
+    }
+
+    /// Compiles a builtin array method call ("len", "push" or "pop")
+    pub fn compile_array_method_expr(&mut self, array_expr: &Expression, call_expr: &Expression) -> CompilerResult<()> {
+        let ret_type = self.check_array_method_expr_type(array_expr, call_expr)?;
+        let (fn_name, fn_args) = match call_expr {
+            Expression::Call(fn_name, fn_args) => (fn_name, fn_args),
+            _ => return Err(CompilerError::Unknown)
+        };
+        let elem_type = match self.check_expr_type(array_expr)? {
+            Type::AutoArray(elem_type) => elem_type.deref().clone(),
+            _ => return Err(CompilerError::Unknown)
+        };
+        let elem_size = self.get_size_of_type(&elem_type)?;
+
+        match fn_name.as_str() {
+            "len" => {
+                self.compile_expr(array_expr)?;
+                let array_reg = self.get_last_register()?;
+                let len_reg = self.get_next_register()?;
+                let arr_len_instr = Instruction::new(Opcode::ARR_LEN)
+                    .with_operand::<u8>(array_reg.into())
+                    .with_operand::<u8>(len_reg.into());
+                self.builder.push_instr(arr_len_instr);
+            },
+            "push" => {
+                // The value is compiled first, so non primitive values are on top of the stack
+                self.compile_expr(&fn_args[0])?;
+                let value_reg = self.get_last_register()?;
+                self.compile_expr(array_expr)?;
+                let array_reg = self.get_last_register()?;
+                self.compile_array_push(&elem_type, value_reg, array_reg)?;
+            },
+            _ => {
+                self.compile_expr(array_expr)?;
+                let array_reg = self.get_last_register()?;
+                let addr_reg = self.get_next_register()?;
+                let arr_pop_instr = Instruction::new(Opcode::ARR_POP)
+                    .with_operand::<u8>(array_reg.into())
+                    .with_operand::<u32>(elem_size as u32)
+                    .with_operand::<u8>(addr_reg.clone().into());
+                self.builder.push_instr(arr_pop_instr);
+                self.compile_load_from_addr(&ret_type, addr_reg)?;
+            }
+        };
+
         Ok(())
-        //Err(CompilerError::Unimplemented(format!("Expr compilation not implemented!")))
     }
 
     /// Compiles a member access expression
@@ -2613,24 +6427,20 @@ impl Compiler {
                 if var_name == "pos" {
                     //println!("Accessing var pos.");
                 }
-                let var_offset = self.get_sp_offset_of_var(var_name)?;
-                let next_reg = self.get_next_register()?;
-                // If its a reference on the stack
+                // If its a reference
                 if is_cont_reference {
                     // Instruction for moving this pointer into the next register
+                    let (base_reg, var_offset) = self.compile_var_location(var_name)?;
+                    let next_reg = self.get_next_register()?;
                     let mov_ptr_instr = Instruction::new(Opcode::MOVA_AR)
-                        .with_operand::<u8>(Register::SP.into())
+                        .with_operand::<u8>(base_reg.into())
                         .with_operand::<i16>(var_offset as i16)
-                        .with_operand::<u8>(next_reg.clone().into());
+                        .with_operand::<u8>(next_reg.into());
                     self.builder.push_instr(mov_ptr_instr);
                 }
-                // If its a normal stack allocated variable
+                // If its a normal stack allocated or static variable
                 else {
-                    let mov_sp_instr = Instruction::new(Opcode::SUBU_I)
-                        .with_operand::<u8>(Register::SP.into())
-                        .with_operand::<u64>(var_offset.abs() as u64)
-                        .with_operand::<u8>(next_reg.into());
-                    self.builder.push_instr(mov_sp_instr);
+                    self.compile_var_addr(var_name)?;
                 }
             },
             _ => return Err(CompilerError::UnsupportedExpression(lhs_expr.deref().clone()))
@@ -2727,8 +6537,6 @@ impl Compiler {
 
         let fn_def = cont_def.get_member_function(fn_name)?;
 
-        let fn_ret_size = self.get_size_of_type(&fn_def.ret_type)?;
-
         if fn_arg_exprs.len() + 1 != fn_def.arguments.len() {
             return Err(CompilerError::UnknownFunction(fn_name.clone()));
         }
@@ -2743,6 +6551,14 @@ impl Compiler {
             return Err(CompilerError::TypeMismatch(fn_def_first_arg_type, fn_args_first_arg_type));
         }
 
+        self.compile_this_call_expr(fn_def, fn_arg_exprs, None)
+    }
+
+    /// Compiles a call of a member function, with the "this" pointer in the last register.
+    /// If an interface reference variable and vtable offset are given, the function is looked up in the vtable.
+    fn compile_this_call_expr(&mut self, fn_def: &FunctionDef, fn_arg_exprs: &[Expression], vtable_var: Option<(&String, usize)>) -> CompilerResult<()> {
+        let fn_ret_size = self.get_size_of_type(&fn_def.ret_type)?;
+
         let before_stack_size = self.get_stack_size()?;
 
         let last_reg = self.get_last_register()?;
@@ -2762,13 +6578,16 @@ impl Compiler {
             let mut expr_type = self.check_expr_type(&fn_arg_exprs[i])?;
             self.canonize_type(&mut expr_type)?;
             let fn_arg_type = &fn_def.arguments[i + 1].1;
+            if self.is_interface_coercion(&fn_arg_exprs[i], fn_arg_type) || self.is_optional_coercion(&fn_arg_exprs[i], fn_arg_type) {
+                expr_type = fn_arg_type.clone();
+            }
 
             if *fn_arg_type != expr_type {
                 return Err(CompilerError::TypeMismatch(fn_arg_type.clone(), expr_type.clone()));
             }
 
             // Compile this expr
-            self.compile_expr(&fn_arg_exprs[i])?;
+            self.compile_expr_with_type(&fn_arg_exprs[i], fn_arg_type)?;
 
             let curr_stack_size = self.get_stack_size()?;
 
@@ -2830,7 +6649,13 @@ impl Compiler {
                         .with_operand::<u8>(Register::SP.into())
                         .with_operand::<i16>(-(size as i16)))
                 },
-                Type::String => None,
+                _ if !expr_type.is_primitive() => None,
+                Type::AutoArray(_) | Type::Optional(_) => {
+                    Some(Instruction::new(Opcode::MOVA_RA)
+                        .with_operand::<u8>(last_reg.into())
+                        .with_operand::<u8>(Register::SP.into())
+                        .with_operand::<i16>(-(size as i16)))
+                },
                 Type::Reference(inner_type) => {
                     match inner_type.deref() {
                         Type::AutoArray(_) => None,
@@ -2857,9 +6682,29 @@ impl Compiler {
             stack_size = self.get_stack_size()?;
         }
 
-        let call_instr = Instruction::new(Opcode::CALL)
-            .with_operand::<u64>(fn_def.uid);
-        self.builder.push_instr(call_instr);
+        if let Some((var_name, vtable_offset)) = vtable_var {
+            // Load the function uid from the vtable of the interface reference
+            let (base_reg, var_offset) = self.compile_var_location(var_name)?;
+            let vtable_reg = self.get_next_register()?;
+            let uid_reg = self.get_next_register()?;
+            let mov_vtable_instr = Instruction::new(Opcode::MOVA_AR)
+                .with_operand::<u8>(base_reg.into())
+                .with_operand::<i16>((var_offset + 8) as i16)
+                .with_operand::<u8>(vtable_reg.clone().into());
+            let mov_uid_instr = Instruction::new(Opcode::MOVA_AR)
+                .with_operand::<u8>(vtable_reg.into())
+                .with_operand::<i16>(vtable_offset as i16)
+                .with_operand::<u8>(uid_reg.clone().into());
+            let call_instr = Instruction::new(Opcode::CALL_R)
+                .with_operand::<u8>(uid_reg.into());
+            self.builder.push_instr(mov_vtable_instr);
+            self.builder.push_instr(mov_uid_instr);
+            self.builder.push_instr(call_instr);
+        } else {
+            let call_instr = Instruction::new(Opcode::CALL)
+                .with_operand::<u64>(fn_def.uid);
+            self.builder.push_instr(call_instr);
+        }
         if !fn_def.ret_type.is_primitive() {
             self.inc_stack(fn_ret_size)?;
         }
@@ -2889,6 +6734,148 @@ impl Compiler {
         Ok(())
     }
 
+    /// Returns the equivalent "this" member access, if an expression refers to
+    /// a member of the container whose member function is being compiled,
+    /// e.g. "x" becomes "this.x" and "length()" becomes "this.length()"
+    pub fn get_this_member_expr(&self, expr: &Expression) -> Option<Expression> {
+        let (name, is_call) = match expr {
+            Expression::Variable(name) => (name, false),
+            Expression::Call(name, _) => (name, true),
+            Expression::MemberAccess(lhs_expr, _) => match lhs_expr.deref() {
+                Expression::Variable(name) => (name, false),
+                Expression::Call(name, _) => (name, true),
+                _ => return None
+            },
+            _ => return None
+        };
+        if name.contains("::") {
+            return None;
+        }
+        // Local variables and free functions shadow members
+        if (!is_call && self.is_local_var(name)) || (is_call && self.resolve_function(name).is_ok()) {
+            return None;
+        }
+        let this_type = self.get_type_of_var(&String::from("this")).ok()?;
+        let cont_def = self.resolve_container(this_type.get_cont_name()?).ok()?;
+        if is_call {
+            cont_def.get_member_function(name).ok()?;
+        } else {
+            cont_def.get_member_type(name).ok()?;
+        }
+        Some(
+            Expression::MemberAccess(
+                Box::new(Expression::Variable(String::from("this"))),
+                Box::new(expr.clone())
+            )
+        )
+    }
+
+    /// Returns a builtin method call on an array or string member with the member as the
+    /// receiver, e.g. "p.items.push(x)" becomes "(p.items).push(x)"
+    pub fn get_member_method_expr(&self, expr: &Expression) -> Option<Expression> {
+        let (lhs_expr, mut rhs_expr) = match expr {
+            Expression::MemberAccess(lhs, rhs) => (lhs.deref(), rhs.deref()),
+            _ => return None
+        };
+        // Member accesses are nested to the right, so the call is at the end of the chain
+        let mut path = vec![lhs_expr.clone()];
+        while let Expression::MemberAccess(member_expr, next_expr) = rhs_expr {
+            path.push(member_expr.deref().clone());
+            rhs_expr = next_expr.deref();
+        }
+        let fn_name = match rhs_expr {
+            Expression::Call(fn_name, _) if path.len() >= 2 => fn_name.as_str(),
+            _ => return None
+        };
+        // Only the names of builtin methods need the type of the receiver
+        let is_array_method = matches!(fn_name, "len" | "push" | "pop");
+        let is_string_method = matches!(fn_name, "len" | "find" | "contains" | "starts_with" | "split");
+        if !is_array_method && !is_string_method {
+            return None;
+        }
+        let receiver_expr = path.into_iter()
+            .rev()
+            .reduce(|rhs, lhs| Expression::MemberAccess(Box::new(lhs), Box::new(rhs)))?;
+        match self.check_expr_type(&receiver_expr) {
+            Ok(Type::AutoArray(_)) if is_array_method => {},
+            Ok(Type::String) if is_string_method => {},
+            _ => return None
+        }
+        Some(Expression::MemberAccess(Box::new(receiver_expr), Box::new(rhs_expr.clone())))
+    }
+
+    /// Returns true if an expression calls a function on an interface reference variable
+    pub fn is_interface_call_expr(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::MemberAccess(lhs_expr, rhs_expr) => {
+                match (lhs_expr.deref(), rhs_expr.deref()) {
+                    (Expression::Variable(var_name), Expression::Call(_, _)) => {
+                        match self.get_type_of_var(var_name) {
+                            Ok(Type::Reference(inner_type)) => matches!(inner_type.deref(), Type::Interface(_)),
+                            _ => false
+                        }
+                    },
+                    _ => false
+                }
+            },
+            _ => false
+        }
+    }
+
+    /// Returns the interface definition and function definition called by an interface call expression
+    fn resolve_interface_call(&self, expr: &Expression) -> CompilerResult<(InterfaceDef, FunctionDef)> {
+        let (var_name, fn_name) = match expr {
+            Expression::MemberAccess(lhs_expr, rhs_expr) => {
+                match (lhs_expr.deref(), rhs_expr.deref()) {
+                    (Expression::Variable(var_name), Expression::Call(fn_name, _)) => (var_name, fn_name),
+                    _ => return Err(CompilerError::Unknown)
+                }
+            },
+            _ => return Err(CompilerError::Unknown)
+        };
+        let intf_name = match self.get_type_of_var(var_name)? {
+            Type::Reference(inner_type) => match inner_type.deref() {
+                Type::Interface(intf_name) => intf_name.clone(),
+                _ => return Err(CompilerError::Unknown)
+            },
+            _ => return Err(CompilerError::Unknown)
+        };
+        let intf_def = self.resolve_interface(&intf_name)?;
+        let fn_def = intf_def.get_function(fn_name)?.clone();
+        Ok((intf_def, fn_def))
+    }
+
+    /// Compiles a call on an interface reference, which is dispatched at runtime via the vtable
+    pub fn compile_interface_call_expr(&mut self, expr: &Expression) -> CompilerResult<()> {
+        let (var_name, fn_name, fn_arg_exprs) = match expr {
+            Expression::MemberAccess(lhs_expr, rhs_expr) => {
+                match (lhs_expr.deref(), rhs_expr.deref()) {
+                    (Expression::Variable(var_name), Expression::Call(fn_name, fn_args)) => (var_name, fn_name, fn_args),
+                    _ => return Err(CompilerError::Unknown)
+                }
+            },
+            _ => return Err(CompilerError::Unknown)
+        };
+
+        let (intf_def, fn_def) = self.resolve_interface_call(expr)?;
+        if fn_arg_exprs.len() + 1 != fn_def.arguments.len() {
+            return Err(CompilerError::UnknownFunction(fn_name.clone()));
+        }
+        let vtable_offset = *intf_def.vtable_offsets.get(fn_name)
+            .ok_or(CompilerError::UnknownFunction(fn_name.clone()))?;
+
+        // The container pointer is the first half of the interface reference
+        let (base_reg, var_offset) = self.compile_var_location(var_name)?;
+        let ptr_reg = self.get_next_register()?;
+        let mov_ptr_instr = Instruction::new(Opcode::MOVA_AR)
+            .with_operand::<u8>(base_reg.into())
+            .with_operand::<i16>(var_offset as i16)
+            .with_operand::<u8>(ptr_reg.into());
+        self.builder.push_instr(mov_ptr_instr);
+
+        self.compile_this_call_expr(&fn_def, fn_arg_exprs, Some((var_name, vtable_offset)))
+    }
+
     /// Compiles a cont instance expression
     pub fn compile_cont_instance_expr(&mut self, expr: &Expression) -> CompilerResult<()> {
         //println!("Line 2638");
@@ -2901,19 +6888,19 @@ impl Compiler {
         let mut member_map_ordered = BTreeMap::new();
 
         // Resolve the container definition
-        let cont_def = self.resolve_container(cont_name)?;
+        let cont_def = self.resolve_cont_instance(cont_name, cont_memper_map)?;
 
         // Insert the expressions at the correct position
         for (name, expr) in cont_memper_map.iter() {
             // Retrieve position from container def
             let index = cont_def.get_member_index(name)?;
-            member_map_ordered.insert(index, expr);
+            member_map_ordered.insert(index, (name, expr));
         }
 
         // Finally, compile the expressions in the correct order
-        for (_, expr) in member_map_ordered.iter() {
-            let expr_type = self.check_expr_type(expr)?;
-            self.compile_expr(expr)?;
+        for (_, (name, expr)) in member_map_ordered.iter() {
+            let member_type = cont_def.get_member_type(name)?;
+            let expr_type = self.compile_expr_with_type(expr, &member_type)?;
             // Special handling for copying register type values on the stack
             let last_reg = self.get_last_register()?;
             match expr_type {
@@ -2947,6 +6934,16 @@ impl Compiler {
                     self.builder.push_instr(stack_inc_instr);
                     self.builder.push_instr(movf_instr);
                 },
+                Type::AutoArray(_) | Type::Optional(_) => {
+                    let stack_inc_instr = Instruction::new_inc_stack(8);
+                    self.inc_stack(8)?;
+                    let mova_instr = Instruction::new(Opcode::MOVA_RA)
+                        .with_operand::<u8>(last_reg.clone().into())
+                        .with_operand::<u8>(Register::SP.into())
+                        .with_operand::<i16>(-8);
+                    self.builder.push_instr(stack_inc_instr);
+                    self.builder.push_instr(mova_instr);
+                },
                 Type::Reference(inner_type) => {
                     match inner_type.deref() {
                         Type::AutoArray(_) => {},
@@ -2979,7 +6976,7 @@ impl Compiler {
 
         //println!("Compiling call expr");
 
-        let fn_def = self.resolve_function(fn_name)?;
+        let fn_def = self.resolve_call(fn_name, fn_arg_exprs)?;
 
         let fn_ret_size = self.get_size_of_type(&fn_def.ret_type)?;
 
@@ -2988,12 +6985,22 @@ impl Compiler {
         }
         
         let before_call_stack_size = self.get_stack_size()?;
-        let mut stack_size = before_call_stack_size;
+
+        // Function values go first, so their environment pointer becomes the first argument
+        let is_fn_value_call = self.get_fn_value_type(fn_name).is_some();
+        if is_fn_value_call {
+            self.compile_expr(&Expression::Variable(fn_name.clone()))?;
+        }
+
+        let mut stack_size = self.get_stack_size()?;
 
         for i in 0..fn_def.arguments.len() {
             let mut expr_type = self.check_expr_type(&fn_arg_exprs[i])?;
             self.canonize_type(&mut expr_type)?;
             let fn_arg_type = &fn_def.arguments[i].1;
+            if self.is_interface_coercion(&fn_arg_exprs[i], fn_arg_type) || self.is_optional_coercion(&fn_arg_exprs[i], fn_arg_type) {
+                expr_type = fn_arg_type.clone();
+            }
             if *fn_arg_type != expr_type {
                 return Err(CompilerError::TypeMismatch(fn_arg_type.clone(), expr_type.clone()));
             }
@@ -3002,7 +7009,7 @@ impl Compiler {
             //println!("Type of call expr: {:?}, size: {}", expr_type, self.get_size_of_type(&expr_type)?);
 
             // Compile this expr
-            self.compile_expr(&fn_arg_exprs[i])?;
+            self.compile_expr_with_type(&fn_arg_exprs[i], fn_arg_type)?;
 
 
             //println!("Compiled call expr arg. Stack size: {}", self.get_stack_size()?);
@@ -3066,7 +7073,13 @@ impl Compiler {
                         .with_operand::<u8>(Register::SP.into())
                         .with_operand::<i16>(-(size as i16)))
                 },
-                Type::String => None,
+                _ if !expr_type.is_primitive() => None,
+                Type::AutoArray(_) | Type::Optional(_) => {
+                    Some(Instruction::new(Opcode::MOVA_RA)
+                        .with_operand::<u8>(last_reg.into())
+                        .with_operand::<u8>(Register::SP.into())
+                        .with_operand::<i16>(-(size as i16)))
+                },
                 Type::Reference(inner_type) => {
                     match inner_type.deref() {
                         Type::AutoArray(_) => None,
@@ -3093,9 +7106,23 @@ impl Compiler {
             stack_size = self.get_stack_size()?;
         }
 
-        let call_instr = Instruction::new(Opcode::CALL)
-            .with_operand::<u64>(fn_def.uid);
-        self.builder.push_instr(call_instr);
+        if is_fn_value_call {
+            // Load the function uid from the bottom of the function value
+            let uid_offset = before_call_stack_size as i64 - self.get_stack_size()? as i64;
+            let uid_reg = self.get_next_register()?;
+            let mov_uid_instr = Instruction::new(Opcode::MOVA_AR)
+                .with_operand::<u8>(Register::SP.into())
+                .with_operand::<i16>(uid_offset as i16)
+                .with_operand::<u8>(uid_reg.clone().into());
+            let call_instr = Instruction::new(Opcode::CALL_R)
+                .with_operand::<u8>(uid_reg.into());
+            self.builder.push_instr(mov_uid_instr);
+            self.builder.push_instr(call_instr);
+        } else {
+            let call_instr = Instruction::new(Opcode::CALL)
+                .with_operand::<u64>(fn_def.uid);
+            self.builder.push_instr(call_instr);
+        }
         if !fn_def.ret_type.is_primitive() {
             self.inc_stack(fn_ret_size)?;
         }
@@ -3132,7 +7159,9 @@ impl Compiler {
         //println!("Compiling var expr");
 
         let var_type = self.get_type_of_var(var_name)?;
-        let mut var_offset = self.get_sp_offset_of_var(var_name)?;
+        let (base_reg, mut var_offset) = self.compile_var_location(var_name)?;
+        // Static variables are addressed relative to a register, which does not move with SP
+        let is_stack_var = base_reg == Register::SP;
         match var_type {
             Type::Int => {
                 let reg = {
@@ -3140,7 +7169,7 @@ impl Compiler {
                     fn_ctx.register_allocator.get_temp_register()?
                 };
                 let movi_instr = Instruction::new(Opcode::MOVI_AR)
-                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<u8>(base_reg.clone().into())
                     .with_operand::<i16>(var_offset as i16)
                     .with_operand::<u8>(reg.into());
                 self.builder.push_instr(movi_instr);
@@ -3151,7 +7180,7 @@ impl Compiler {
                     fn_ctx.register_allocator.get_temp_register()?
                 };
                 let movf_instr = Instruction::new(Opcode::MOVF_AR)
-                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<u8>(base_reg.clone().into())
                     .with_operand::<i16>(var_offset as i16)
                     .with_operand::<u8>(reg.into());
                 self.builder.push_instr(movf_instr);
@@ -3162,19 +7191,32 @@ impl Compiler {
                     fn_ctx.register_allocator.get_temp_register()?
                 };
                 let movb_instr = Instruction::new(Opcode::MOVB_AR)
-                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<u8>(base_reg.clone().into())
                     .with_operand::<i16>(var_offset as i16)
                     .with_operand::<u8>(reg.into());
                 self.builder.push_instr(movb_instr);
             },
+            Type::AutoArray(_) | Type::Optional(_) => {
+                let reg = {
+                    let fn_ctx = self.get_current_function_mut()?;
+                    fn_ctx.register_allocator.get_temp_register()?
+                };
+                let mova_instr = Instruction::new(Opcode::MOVA_AR)
+                    .with_operand::<u8>(base_reg.clone().into())
+                    .with_operand::<i16>(var_offset as i16)
+                    .with_operand::<u8>(reg.into());
+                self.builder.push_instr(mova_instr);
+            },
             Type::Reference(inner_type) => {
                 match inner_type.deref() {
-                    Type::AutoArray(_) => {
+                    Type::AutoArray(_) | Type::Interface(_) => {
                         let stack_inc_instr = Instruction::new_inc_stack(16);
                         self.inc_stack(16)?;
-                        var_offset -= 16;
+                        if is_stack_var {
+                            var_offset -= 16;
+                        }
                         let movn_instr = Instruction::new(Opcode::MOVN_A)
-                            .with_operand::<u8>(Register::SP.into())
+                            .with_operand::<u8>(base_reg.into())
                             .with_operand::<i16>(var_offset as i16)
                             .with_operand::<u8>(Register::SP.into())
                             .with_operand::<i16>(-16)
@@ -3188,24 +7230,25 @@ impl Compiler {
                             fn_ctx.register_allocator.get_temp_register()?
                         };
                         let mova_instr = Instruction::new(Opcode::MOVA_AR)
-                            .with_operand::<u8>(Register::SP.into())
+                            .with_operand::<u8>(base_reg.into())
                             .with_operand::<i16>(var_offset as i16)
                             .with_operand::<u8>(reg.into());
                         self.builder.push_instr(mova_instr);
                     }
                 };
             },
-            Type::Other(cont_name) => {
-                let cont_def = self.resolve_container(&cont_name)?;
-                let size = cont_def.get_size(self)?;
+            _ if !var_type.is_primitive() => {
+                let size = self.get_size_of_type(&var_type)?;
 
                 let stack_inc_instr = Instruction::new_inc_stack(size);
                 self.inc_stack(size)?;
 
-                var_offset -= size as i64;
+                if is_stack_var {
+                    var_offset -= size as i64;
+                }
 
                 let movn_instr = Instruction::new(Opcode::MOVN_A)
-                    .with_operand::<u8>(Register::SP.into())
+                    .with_operand::<u8>(base_reg.into())
                     .with_operand::<i16>(var_offset as i16)
                     .with_operand::<u8>(Register::SP.into())
                     .with_operand::<i16>(-(size as i16))
@@ -3226,15 +7269,36 @@ impl Compiler {
     /// Returns the type of an expression and checks for type mismatches
     pub fn check_expr_type(&self, expr: &Expression) -> CompilerResult<Type> {
         //println!("Checking type of expr: {:?}", expr);
+        if let Some(member_expr) = self.get_this_member_expr(expr) {
+            return self.check_expr_type(&member_expr);
+        }
+        if let Some(method_expr) = self.get_member_method_expr(expr) {
+            return self.check_expr_type(&method_expr);
+        }
+        if let Some(const_expr) = self.get_const_expr(expr) {
+            return self.check_expr_type(&const_expr);
+        }
         let expr_type = match expr {
             Expression::IntLiteral(_) => Type::Int,
             Expression::FloatLiteral(_) => Type::Float,
             Expression::BoolLiteral(_) => Type::Bool,
+            Expression::NoneLiteral => Type::Optional(Box::new(Type::Auto)),
             Expression::StringLiteral(_) => Type::String,
+            Expression::Interpolation(_) => Type::String,
             Expression::Ref(expr) => {
                 let expr_type = self.check_expr_type(expr)?;
                 Type::Reference(Box::new(expr_type))
             },
+            Expression::Box(expr) => {
+                let expr_type = self.check_expr_type(expr)?;
+                // Slices cannot be boxed
+                if let Type::Reference(inner_type) = &expr_type {
+                    if let Type::AutoArray(_) = inner_type.deref() {
+                        return Err(CompilerError::UnsupportedExpression(expr.deref().clone()));
+                    }
+                }
+                Type::Reference(Box::new(expr_type))
+            },
             Expression::Deref(expr) => {
                 let expr_type = self.check_expr_type(expr)?;
                 match expr_type {
@@ -3244,22 +7308,124 @@ impl Compiler {
                             _ => return Ok(inner_type.deref().clone())
                         };
                     },
+                    Type::Optional(_) => return Err(CompilerError::UncheckedOptional(expr_type)),
                     _ => return Err(CompilerError::CannotDerefNonPointer)
                 };
             },
-            Expression::Call(fn_name, _) => {
-                let fn_def = self.resolve_function(fn_name)?;
+            Expression::Call(path, _) |
+            Expression::Variable(path) if self.is_enum_variant(path) => {
+                let (enum_def, _) = self.resolve_enum_variant(path)?;
+                Type::Other(enum_def.canonical_name)
+            },
+            Expression::Call(fn_name, fn_arg_exprs) => {
+                let fn_def = self.resolve_call(fn_name, fn_arg_exprs)?;
                 fn_def.ret_type
             },
+            Expression::TypeCall(_, _, _) => {
+                self.check_expr_type(&self.get_instance_call_expr(expr)?)?
+            },
+            Expression::Variable(fn_name) if self.is_fn_ref_expr(fn_name) => {
+                let fn_def = self.resolve_function(fn_name)?;
+                self.get_fn_type(&fn_def)?
+            },
             Expression::Variable(var_name) => {
                 self.get_type_of_var(var_name)?
             },
+            Expression::Closure(fn_decl_args) => {
+                self.get_fn_type(&FunctionDef::from(fn_decl_args.deref()))?
+            },
+            Expression::Cast(op_expr, cast_type) => {
+                let op_type = self.check_expr_type(op_expr)?;
+                Compiler::get_cast_opcode(&op_type, cast_type)?;
+                cast_type.clone()
+            },
+            Expression::Try(op_expr) => {
+                let mut op_type = self.check_expr_type(op_expr)?;
+                self.canonize_type(&mut op_type)?;
+                let (enum_def, ok_tag, _) = self.resolve_result_enum(&op_type)?;
+                self.get_result_payload_type(&enum_def, ok_tag)?
+            },
+            _ if self.resolve_operator_fn(expr).is_some() => {
+                let fn_def = self.resolve_operator_fn(expr)
+                    .ok_or(CompilerError::Unknown)?;
+                self.check_operator_expr_type(expr, &fn_def)?
+            },
+            Expression::MemberAccess(array_expr, call_expr) if self.is_array_expr(array_expr) => {
+                self.check_array_method_expr_type(array_expr, call_expr)?
+            },
+            Expression::MemberAccess(string_expr, call_expr) if self.is_string_expr(string_expr) => {
+                self.check_string_method_expr_type(string_expr, call_expr)?
+            },
+            Expression::Slice(string_expr, start_expr, end_expr) => {
+                let string_type = self.check_expr_type(string_expr)?;
+                if string_type != Type::String {
+                    return Err(CompilerError::TypeMismatch(Type::String, string_type));
+                }
+                for bound_expr in start_expr.iter().chain(end_expr.iter()) {
+                    let bound_type = self.check_expr_type(bound_expr)?;
+                    if bound_type != Type::Int {
+                        return Err(CompilerError::TypeMismatch(Type::Int, bound_type));
+                    }
+                }
+                Type::String
+            },
+            Expression::MemberAccess(_, _) if self.is_interface_call_expr(expr) => {
+                self.resolve_interface_call(expr)?.1.ret_type
+            },
             Expression::MemberAccess(_, _) => {
                 self.check_member_access_expr_type(expr)?
             },
+            // Generic container instances are typed as the instance inferred from their members
+            Expression::ContainerInstance(cont_name, member_map) if self.resolve_container(cont_name).is_err() && self.resolve_generic(cont_name).is_ok() => {
+                Type::Other(self.resolve_cont_instance(cont_name, member_map)?.canonical_name)
+            },
             Expression::ContainerInstance(cont_name, _) => {
                 Type::Other(cont_name.clone())
             },
+            Expression::ArrayLiteral(elements) => {
+                let mut elem_type = Type::Auto;
+                for element in elements.iter() {
+                    let element_type = self.check_expr_type(element)?;
+                    if elem_type == Type::Auto {
+                        elem_type = element_type;
+                    } else if elem_type != element_type {
+                        return Err(CompilerError::TypeMismatch(elem_type, element_type));
+                    }
+                }
+                Type::AutoArray(Box::new(elem_type))
+            },
+            Expression::Index(array_expr, index_expr) => {
+                let index_type = self.check_expr_type(index_expr)?;
+                if index_type != Type::Int {
+                    return Err(CompilerError::TypeMismatch(Type::Int, index_type));
+                }
+                match self.check_expr_type(array_expr)? {
+                    Type::AutoArray(elem_type) => elem_type.deref().clone(),
+                    Type::Array(elem_type, _) => elem_type.deref().clone(),
+                    array_type => return Err(CompilerError::CannotIndexNonArray(array_type))
+                }
+            },
+            Expression::ArrayRepeat(value_expr, size) => {
+                let elem_type = self.check_expr_type(value_expr)?;
+                Type::Array(Box::new(elem_type), *size)
+            },
+            Expression::Tuple(elements) => {
+                let mut elem_types = Vec::new();
+                for element in elements.iter() {
+                    elem_types.push(self.check_expr_type(element)?);
+                }
+                Type::Tuple(elem_types)
+            },
+            Expression::TupleAccess(tuple_expr, index) => {
+                match self.check_expr_type(tuple_expr)? {
+                    Type::Tuple(elem_types) => {
+                        elem_types.get(*index)
+                            .cloned()
+                            .ok_or(CompilerError::IndexOutOfBounds(*index as i64, elem_types.len()))?
+                    },
+                    tuple_type => return Err(CompilerError::TupleAccessOnNonTuple(tuple_type))
+                }
+            },
             Expression::Assign(lhs, rhs) => {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
@@ -3272,7 +7438,7 @@ impl Compiler {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
                 if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 lhs_type
             },
@@ -3280,7 +7446,7 @@ impl Compiler {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
                 if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 lhs_type
             },
@@ -3288,7 +7454,7 @@ impl Compiler {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
                 if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 lhs_type
             },
@@ -3296,7 +7462,7 @@ impl Compiler {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
                 if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 lhs_type
             },
@@ -3304,7 +7470,7 @@ impl Compiler {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
                 if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 Type::Bool
             },
@@ -3312,7 +7478,7 @@ impl Compiler {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
                 if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 Type::Bool
             },
@@ -3320,7 +7486,7 @@ impl Compiler {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
                 if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 Type::Bool
             },
@@ -3328,32 +7494,56 @@ impl Compiler {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
                 if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 Type::Bool
             },
             Expression::Equals(lhs, rhs) => {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
-                if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                if lhs_type != rhs_type && !self.is_optional_coercion(rhs, &lhs_type) {
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 Type::Bool
             },
             Expression::NotEquals(lhs, rhs) => {
                 let lhs_type = self.check_expr_type(lhs)?;
                 let rhs_type = self.check_expr_type(rhs)?;
-                if lhs_type != rhs_type {
-                    return Err(CompilerError::TypeMismatch(lhs_type, rhs_type));
+                if lhs_type != rhs_type && !self.is_optional_coercion(rhs, &lhs_type) {
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
                 }
                 Type::Bool
             },
+            Expression::Modulo(lhs, rhs) |
+            Expression::BitAnd(lhs, rhs) |
+            Expression::BitOr(lhs, rhs) |
+            Expression::BitXor(lhs, rhs) |
+            Expression::ShiftLeft(lhs, rhs) |
+            Expression::ShiftRight(lhs, rhs) => {
+                let lhs_type = self.check_expr_type(lhs)?;
+                let rhs_type = self.check_expr_type(rhs)?;
+                if lhs_type != rhs_type {
+                    return Err(Compiler::operand_type_error(lhs_type, rhs_type));
+                }
+                if lhs_type != Type::Int {
+                    return Err(CompilerError::TypeMismatch(Type::Int, lhs_type));
+                }
+                Type::Int
+            },
             Expression::Not(op) => {
+                // "!" is a logical not for bools and a bitwise not for ints
                 let op_type = self.check_expr_type(op)?;
-                if Type::Bool != op_type {
+                if Type::Bool != op_type && Type::Int != op_type {
                     return Err(CompilerError::TypeMismatch(Type::Bool, op_type));
                 }
-                Type::Bool
+                op_type
+            },
+            Expression::Negate(op) => {
+                let op_type = self.check_expr_type(op)?;
+                if Type::Int != op_type && Type::Float != op_type {
+                    return Err(CompilerError::TypeMismatch(Type::Int, op_type));
+                }
+                op_type
             },
             Expression::And(lhs, rhs) => {
                 let lhs_type = self.check_expr_type(lhs)?;
@@ -3377,6 +7567,84 @@ impl Compiler {
         //Err(CompilerError::Unimplemented(format!("Expr type checking not implemented!")))
     }
 
+    /// Returns true if an expression is a dynamically sized array
+    pub fn is_array_expr(&self, expr: &Expression) -> bool {
+        matches!(self.check_expr_type(expr), Ok(Type::AutoArray(_)))
+    }
+
+    /// Returns the type of a builtin array method call, e.g. "arr.push(x)"
+    pub fn check_array_method_expr_type(&self, array_expr: &Expression, call_expr: &Expression) -> CompilerResult<Type> {
+        let (fn_name, fn_args) = match call_expr {
+            Expression::Call(fn_name, fn_args) => (fn_name, fn_args),
+            _ => return Err(CompilerError::UnsupportedExpression(call_expr.clone()))
+        };
+
+        let elem_type = match self.check_expr_type(array_expr)? {
+            Type::AutoArray(elem_type) => elem_type.deref().clone(),
+            array_type => return Err(CompilerError::CannotIndexNonArray(array_type))
+        };
+
+        let arg_count = match fn_name.as_str() {
+            "len" | "pop" => 0,
+            "push" => 1,
+            _ => return Err(CompilerError::UnknownFunction(fn_name.clone()))
+        };
+        if fn_args.len() != arg_count {
+            return Err(CompilerError::ArgumentMismatch(fn_name.clone()));
+        }
+
+        let ret_type = match fn_name.as_str() {
+            "len" => Type::Int,
+            "pop" => elem_type,
+            _ => {
+                let arg_type = self.check_expr_type(&fn_args[0])?;
+                if arg_type != elem_type {
+                    return Err(CompilerError::TypeMismatch(elem_type, arg_type));
+                }
+                Type::Void
+            }
+        };
+
+        Ok(ret_type)
+    }
+
+    /// Returns true if an expression is a string
+    pub fn is_string_expr(&self, expr: &Expression) -> bool {
+        matches!(self.check_expr_type(expr), Ok(Type::String))
+    }
+
+    /// Returns the type of a builtin string method call, e.g. "text.contains(x)"
+    pub fn check_string_method_expr_type(&self, string_expr: &Expression, call_expr: &Expression) -> CompilerResult<Type> {
+        let (fn_name, fn_args) = match call_expr {
+            Expression::Call(fn_name, fn_args) => (fn_name, fn_args),
+            _ => return Err(CompilerError::UnsupportedExpression(call_expr.clone()))
+        };
+
+        let string_type = self.check_expr_type(string_expr)?;
+        if string_type != Type::String {
+            return Err(CompilerError::TypeMismatch(Type::String, string_type));
+        }
+
+        let (arg_count, ret_type) = match fn_name.as_str() {
+            "len" => (0, Type::Int),
+            "find" => (1, Type::Int),
+            "contains" | "starts_with" => (1, Type::Bool),
+            "split" => (1, Type::AutoArray(Box::new(Type::String))),
+            _ => return Err(CompilerError::UnknownFunction(fn_name.clone()))
+        };
+        if fn_args.len() != arg_count {
+            return Err(CompilerError::ArgumentMismatch(fn_name.clone()));
+        }
+        for arg in fn_args.iter() {
+            let arg_type = self.check_expr_type(arg)?;
+            if arg_type != Type::String {
+                return Err(CompilerError::TypeMismatch(Type::String, arg_type));
+            }
+        }
+
+        Ok(ret_type)
+    }
+
     pub fn check_member_access_expr_type(&self, expr: &Expression) -> CompilerResult<Type> {
         let (lhs_expr, rhs_expr) = match expr {
             Expression::MemberAccess(lhs, rhs) => (lhs.deref(), rhs.deref()),
@@ -3388,10 +7656,12 @@ impl Compiler {
         match lhs_expr {
             Expression::Variable(var_name) => {
                 let var_type = self.get_type_of_var(var_name)?;
+                Compiler::check_not_optional(&var_type)?;
                 cont_name = var_type.get_cont_name().ok_or(CompilerError::MemberAccessOnNonContainer)?.clone();
             },
             Expression::Call(_, _) => {
                 let call_ret_type = self.check_expr_type(lhs_expr)?;
+                Compiler::check_not_optional(&call_ret_type)?;
                 cont_name = call_ret_type.get_cont_name().ok_or(CompilerError::MemberAccessOnNonContainer)?.clone();
                 
             },
@@ -3403,6 +7673,14 @@ impl Compiler {
         self.check_member_access_expr_type_rhs(rhs_expr, &cont_def) 
     }
 
+    /// Optionals have to be unwrapped by a check against "none" before they can be accessed
+    fn check_not_optional(var_type: &Type) -> CompilerResult<()> {
+        match var_type {
+            Type::Optional(_) => Err(CompilerError::UncheckedOptional(var_type.clone())),
+            _ => Ok(())
+        }
+    }
+
     pub fn check_member_access_expr_type_rhs(&self, expr: &Expression, cont_def: &ContainerDef) -> CompilerResult<Type> {
         match expr {
             Expression::Variable(member_name) => cont_def.get_member_type(member_name),
@@ -3421,6 +7699,7 @@ impl Compiler {
                     },
                     _ => return Err(CompilerError::UnsupportedExpression(lhs_expr.deref().clone()))
                 };
+                Compiler::check_not_optional(&member_type)?;
                 let cont_name = member_type.get_cont_name().ok_or(CompilerError::MemberAccessOnNonContainer)?;
                 let cont_def = self.resolve_container(&cont_name)?;
                 self.check_member_access_expr_type_rhs(rhs_expr, &cont_def)
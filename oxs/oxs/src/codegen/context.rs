@@ -2,8 +2,10 @@ use crate::{
     codegen::{
         def::{
             ContainerDef,
+            EnumDef,
             FunctionDef,
-            InterfaceDef
+            InterfaceDef,
+            GenericDef
         },
         register::{
             Register,
@@ -34,7 +36,9 @@ pub struct ModuleContext {
     pub modules: HashMap<String, ModuleContext>,
     pub functions: HashMap<String, FunctionDef>,
     pub containers: HashMap<String, ContainerDef>,
+    pub enums: HashMap<String, EnumDef>,
     pub interfaces: HashMap<String, InterfaceDef>,
+    pub generics: HashMap<String, GenericDef>,
     pub imports: HashMap<String, String>
 }
 
@@ -46,7 +50,9 @@ impl ModuleContext {
             modules: HashMap::new(),
             functions: HashMap::new(),
             containers: HashMap::new(),
+            enums: HashMap::new(),
             interfaces: HashMap::new(),
+            generics: HashMap::new(),
             imports: HashMap::new()
         }
     }
@@ -84,6 +90,29 @@ impl ModuleContext {
         Ok(())
     }
 
+    /// Adds an enum definition to a module context.
+    /// Throws a DuplicateContainerError if a container or enum with the
+    /// same name already exists.
+    pub fn add_enum(&mut self, enum_def: EnumDef) -> CompilerResult<()> {
+        if self.enums.contains_key(&enum_def.name) || self.containers.contains_key(&enum_def.name) {
+            return Err(CompilerError::DuplicateContainer(enum_def.name));
+        }
+        self.enums.insert(enum_def.name.clone(), enum_def);
+        Ok(())
+    }
+
+    /// Adds a generic declaration to a module context.
+    /// Throws a DuplicateContainerError if a generic declaration, container
+    /// or enum with the same name already exists.
+    pub fn add_generic(&mut self, generic_def: GenericDef) -> CompilerResult<()> {
+        let name = &generic_def.name;
+        if self.generics.contains_key(name) || self.containers.contains_key(name) || self.enums.contains_key(name) {
+            return Err(CompilerError::DuplicateContainer(generic_def.name));
+        }
+        self.generics.insert(generic_def.name.clone(), generic_def);
+        Ok(())
+    }
+
     /// Adds an import declaration to a module context
     /// Throws a DuplicateImportError if an import with the same
     /// "import_as" name already exists.
@@ -140,8 +169,12 @@ pub struct FunctionContext {
     pub def: Option<FunctionDef>,
     pub weak: bool,
     pub is_loop: bool,
+    /// Whether this is the block of a try statement, which has a handler registered
+    pub is_try: bool,
     pub stack_size: usize,
     variable_types: HashMap<String, Type>,
+    /// Types of variables narrowed for reads, e.g. optionals checked against "none"
+    narrowed_types: HashMap<String, Type>,
     variable_positions: HashMap<String, i64>,
     pub register_allocator: RegisterAllocator
 }
@@ -169,8 +202,10 @@ impl FunctionContext {
                 def: Some(def),
                 weak: false,
                 is_loop: false,
+                is_try: false,
                 stack_size: 0,
                 variable_types: variable_types,
+                narrowed_types: HashMap::new(),
                 variable_positions: variable_positions,
                 register_allocator: RegisterAllocator::new()
             }
@@ -195,8 +230,10 @@ impl FunctionContext {
                 def: None,
                 weak: true,
                 is_loop: false,
+                is_try: false,
                 stack_size: 0,
                 variable_types: fn_ctx.variable_types.clone(),
+                narrowed_types: fn_ctx.narrowed_types.clone(),
                 variable_positions: variable_positions,
                 register_allocator: RegisterAllocator::new()
             }
@@ -221,8 +258,10 @@ impl FunctionContext {
                 def: None,
                 weak: true,
                 is_loop: true,
+                is_try: false,
                 stack_size: 0,
                 variable_types: fn_ctx.variable_types.clone(),
+                narrowed_types: fn_ctx.narrowed_types.clone(),
                 variable_positions: variable_positions,
                 register_allocator: RegisterAllocator::new()
             }
@@ -240,7 +279,30 @@ impl FunctionContext {
         Ok(())
     }
 
+    /// Narrows the type of a known variable for reads, e.g. an optional after a check.
+    /// Assignments to the variable still use its declared type.
+    pub fn narrow_var_type(&mut self, var_name: &str, var_type: Type) -> CompilerResult<()> {
+        if !self.variable_types.contains_key(var_name) {
+            return Err(CompilerError::UnknownVariable(String::from(var_name)));
+        }
+        self.narrowed_types.insert(String::from(var_name), var_type);
+        Ok(())
+    }
+
+    /// Drops the narrowed type of a variable, e.g. after it was assigned to
+    pub fn widen_var_type(&mut self, var_name: &str) {
+        self.narrowed_types.remove(var_name);
+    }
+
     pub fn get_var_type(&self, var_name: &String) -> CompilerResult<Type> {
+        self.narrowed_types.get(var_name)
+            .or_else(|| self.variable_types.get(var_name))
+            .cloned()
+            .ok_or(CompilerError::UnknownVariable(var_name.clone()))
+    }
+
+    /// Returns the declared type of a variable, ignoring any narrowing
+    pub fn get_declared_var_type(&self, var_name: &String) -> CompilerResult<Type> {
         self.variable_types.get(var_name)
             .cloned()
             .ok_or(CompilerError::UnknownVariable(var_name.clone()))
@@ -1,14 +1,18 @@
 use std::{
     collections::{
-        HashMap
-    }
+        HashMap,
+        BTreeMap
+    },
+    ops::Range
 };
 
 /// Manager struct for static data
 #[derive(Clone)]
 pub struct Data {
     pub bytes: Vec<u8>,
-    strings: HashMap<String, usize>
+    strings: HashMap<String, usize>,
+    vtables: HashMap<String, usize>,
+    statics: HashMap<String, Range<usize>>
 }
 
 impl Data {
@@ -16,7 +20,9 @@ impl Data {
     pub fn new() -> Data {
         Self {
             bytes: Vec::new(),
-            strings: HashMap::new()
+            strings: HashMap::new(),
+            vtables: HashMap::new(),
+            statics: HashMap::new()
         }
     }
 
@@ -33,4 +39,38 @@ impl Data {
         self.strings.insert(string.clone(), addr);
         (byte_len, addr as u64)
     }
+
+    /// Returns the address of the vtable with the given name.
+    /// The function uids are only appended the first time a vtable is requested.
+    pub fn get_vtable(&mut self, name: &str, fn_uids: &[u64]) -> u64 {
+        if let Some(addr) = self.vtables.get(name) {
+            return *addr as u64;
+        }
+        let addr = self.bytes.len();
+        for fn_uid in fn_uids.iter() {
+            self.bytes.extend_from_slice(&fn_uid.to_le_bytes());
+        }
+        self.vtables.insert(String::from(name), addr);
+        addr as u64
+    }
+
+    /// Returns the address of the zeroed, 8 byte aligned storage of a static variable.
+    /// The storage is only reserved the first time a static variable is requested.
+    pub fn get_static(&mut self, name: &str, size: usize) -> u64 {
+        if let Some(range) = self.statics.get(name) {
+            return range.start as u64;
+        }
+        // Address 0 is never handed out, so references to statics are never null
+        let addr = self.bytes.len().div_ceil(8).max(1) * 8;
+        self.bytes.resize(addr + size, 0);
+        self.statics.insert(String::from(name), addr..addr + size);
+        addr as u64
+    }
+
+    /// Returns the storage ranges of all static variables, keyed by their address
+    pub fn get_static_ranges(&self) -> BTreeMap<usize, Range<usize>> {
+        self.statics.values()
+            .map(|range| (range.start, range.clone()))
+            .collect()
+    }
 }
\ No newline at end of file
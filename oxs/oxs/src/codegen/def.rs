@@ -2,8 +2,13 @@ use crate::{
     parser::{
         ast::{
             Type,
+            TypeParam,
+            Declaration,
             FunctionDeclArgs,
-            ContainerDeclArgs
+            ContainerDeclArgs,
+            EnumDeclArgs,
+            VariableDeclArgs,
+            Expression
         }
     },
     codegen::{
@@ -89,7 +94,9 @@ pub struct ContainerDef {
     /// Map of member functions
     pub member_functions: HashMap<String, FunctionDef>,
     /// Map of interface implements
-    pub interfaces: HashSet<String>
+    pub interfaces: HashSet<String>,
+    /// Canonical name of the generic container and the type arguments this container was instantiated with
+    pub generic: Option<(String, Vec<Type>)>
 }
 
 impl ContainerDef {
@@ -101,7 +108,8 @@ impl ContainerDef {
             member_indices: BTreeMap::new(),
             member_functions: HashMap::new(),
             member_variables: HashMap::new(),
-            interfaces: HashSet::new()
+            interfaces: HashSet::new(),
+            generic: None
         }
     }
 
@@ -139,13 +147,12 @@ impl ContainerDef {
     pub fn get_member_offset(&self, compiler: &Compiler, var_name: &String) -> CompilerResult<usize> {
         let target_index = self.get_member_index(var_name)?;
         let mut offset = 0;
+        // Members are laid out in declaration order, not in name order
         for (member_name, member_index) in self.member_indices.iter() {
-            let member_type = self.get_member_type(member_name)?;
-            let member_size = compiler.get_size_of_type(&member_type)?;
-            if *member_index == target_index {
-                break;
+            if *member_index < target_index {
+                let member_type = self.get_member_type(member_name)?;
+                offset += compiler.get_size_of_type(&member_type)?;
             }
-            offset += member_size;
         }
         Ok(offset)
     }
@@ -194,6 +201,46 @@ impl ContainerDef {
     }
 }
 
+/// An enum definition, stored as a tagged union
+#[derive(Clone, Debug)]
+pub struct EnumDef {
+    /// Name of the enum
+    pub name: String,
+    /// Name of the enum, including full module path
+    pub canonical_name: String,
+    /// Variants and their payload types, the index of a variant is its tag
+    pub variants: Vec<(String, Vec<Type>)>
+}
+
+impl EnumDef {
+    /// Creates a new EnumDef from a declaration
+    pub fn from_decl(item: &EnumDeclArgs, canon_name: String) -> EnumDef {
+        EnumDef {
+            name: item.name.clone(),
+            canonical_name: canon_name,
+            variants: item.variants.clone()
+        }
+    }
+
+    /// Returns the tag and the payload types of a variant
+    pub fn get_variant(&self, name: &str) -> CompilerResult<(usize, &Vec<Type>)> {
+        self.variants.iter()
+            .position(|(variant_name, _)| variant_name == name)
+            .map(|tag| (tag, &self.variants[tag].1))
+            .ok_or(CompilerError::UnknownVariant(format!("{}::{}", self.canonical_name, name)))
+    }
+
+    /// Returns the byte size of this enum, the tag followed by the largest payload
+    pub fn get_size(&self, compiler: &Compiler) -> CompilerResult<usize> {
+        let mut payload_size = 0;
+        for (_, payload_types) in self.variants.iter() {
+            let size = compiler.get_size_of_type(&Type::Tuple(payload_types.clone()))?;
+            payload_size = payload_size.max(size);
+        }
+        Ok(8 + payload_size)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct InterfaceDef {
     pub name: String,
@@ -222,4 +269,94 @@ impl InterfaceDef {
     pub fn get_function(&self, fn_name: &str) -> CompilerResult<&FunctionDef> {
         self.functions.get(fn_name).ok_or(CompilerError::UnknownFunction(String::from(fn_name)))
     }
-}
\ No newline at end of file
+}
+
+/// A generic container, function or interface declaration, which is
+/// monomorphised for every distinct list of type arguments it is used with
+#[derive(Clone, Debug)]
+pub struct GenericDef {
+    /// Name of the generic declaration
+    pub name: String,
+    /// Name of the generic declaration, including full module path
+    pub canonical_name: String,
+    /// Type parameters and the interfaces bounding them. Plain interfaces are canonical,
+    /// generic ones are resolved for the type arguments of an instance.
+    pub type_params: Vec<TypeParam>,
+    /// The generic declaration itself
+    pub decl: Declaration,
+    /// Impls of a generic container: the type parameter names used by the impl,
+    /// the implemented interface (if any) and the declarations
+    pub impls: Vec<(Vec<String>, Option<Type>, Vec<Declaration>)>
+}
+
+impl GenericDef {
+    /// Creates a new GenericDef from a declaration
+    pub fn from_decl(name: String, canon_name: String, type_params: Vec<TypeParam>, decl: &Declaration) -> GenericDef {
+        GenericDef {
+            name,
+            canonical_name: canon_name,
+            type_params,
+            decl: decl.clone(),
+            impls: Vec::new()
+        }
+    }
+
+    /// Returns the names of the type parameters
+    pub fn get_type_param_names(&self) -> Vec<String> {
+        self.type_params.iter()
+            .map(|(param_name, _)| param_name.clone())
+            .collect()
+    }
+
+    /// Returns the module path of the generic declaration, with trailing "::"
+    pub fn get_module_path(&self) -> &str {
+        &self.canonical_name[..self.canonical_name.len() - self.name.len()]
+    }
+
+    /// Returns the canonical name of an instance, e.g. "root::Pair<int, string>"
+    pub fn get_instance_name(&self, type_args: &[Type]) -> String {
+        let type_args: Vec<String> = type_args.iter().map(Type::to_string).collect();
+        format!("{}<{}>", self.canonical_name, type_args.join(", "))
+    }
+
+    /// Maps type parameter names to type arguments
+    pub fn get_type_map(param_names: &[String], type_args: &[Type]) -> HashMap<String, Type> {
+        param_names.iter()
+            .cloned()
+            .zip(type_args.iter().cloned())
+            .collect()
+    }
+}
+
+/// A module-level variable stored in the data segment, or a constant folded at compile time
+#[derive(Clone, Debug)]
+pub struct StaticDef {
+    /// Name of the variable
+    pub name: String,
+    /// Name of the variable, including full module path
+    pub canonical_name: String,
+    /// Type of the variable, which is inferred if it was omitted
+    pub var_type: Type,
+    /// Address of a static variable in the data segment
+    pub addr: u64,
+    /// Folded value of a constant
+    pub const_value: Option<Expression>
+}
+
+impl StaticDef {
+    /// Creates a new StaticDef from a declaration
+    pub fn from_decl(item: &VariableDeclArgs, canon_name: String) -> StaticDef {
+        StaticDef {
+            name: item.name.clone(),
+            canonical_name: canon_name,
+            var_type: item.var_type.clone(),
+            addr: 0,
+            const_value: None
+        }
+    }
+
+    /// Returns the module path of the variable, with trailing "::"
+    pub fn get_module_path(&self) -> &str {
+        &self.canonical_name[..self.canonical_name.len() - self.name.len()]
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    api::{
+        adapter::Adapter,
+        function::Function,
+        module::Module
+    },
+    parser::{
+        ast::Type
+    },
+    vm::{
+        core::{
+            ArithmeticMode,
+            CoreResult
+        }
+    }
+};
+
+use std::{
+    convert::TryFrom
+};
+
+/// Maximum byte length of strings built by the library, e.g. by "string::repeat"
+const MAX_STRING_LEN: usize = 1 << 24;
+
+/// Creates the "core" library module, which every engine registers on creation.
+/// It extends the built-in "core" module of the compiler, which declares the core interfaces.
+/// Its modules can be used without the "core::" prefix, e.g. "float::sqrt(x)".
+pub fn create_module() -> Module {
+    Module::new("core")
+        .with_module(create_float_module())
+        .with_module(create_int_module())
+        .with_module(create_string_module())
+        .with_module(create_assert_module())
+}
+
+/// Creates the "float" module with basic math and conversions
+fn create_float_module() -> Module {
+    let to_string_function = Function::new("to_string")
+        .with_arg(Type::Float)
+        .with_ret_type(Type::String)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let value: f32 = adapter.get_arg(0);
+            let res = adapter.return_string(&value.to_string());
+            panic_on_err(adapter, res);
+        }));
+    let parse_function = Function::new("parse")
+        .with_arg(Type::String)
+        .with_ret_type(Type::Other(String::from("Parsed")))
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let text: String = adapter.get_arg(0);
+            let res = match text.trim().parse::<f32>() {
+                Ok(value) => adapter.return_ok(value),
+                Err(err) => adapter.return_err(err.to_string())
+            };
+            panic_on_err(adapter, res);
+        }));
+
+    Module::new("float")
+        .with_enum("Parsed", vec![
+            (String::from("Ok"), vec![Type::Float]),
+            (String::from("Err"), vec![Type::String])
+        ])
+        .with_function(unary_float_function("sqrt", f32::sqrt))
+        .with_function(unary_float_function("abs", f32::abs))
+        .with_function(unary_float_function("floor", f32::floor))
+        .with_function(unary_float_function("ceil", f32::ceil))
+        .with_function(unary_float_function("round", f32::round))
+        .with_function(binary_float_function("pow", f32::powf))
+        .with_function(binary_float_function("min", f32::min))
+        .with_function(binary_float_function("max", f32::max))
+        .with_function(to_string_function)
+        .with_function(parse_function)
+}
+
+/// Creates the "int" module with basic math and conversions
+fn create_int_module() -> Module {
+    let abs_function = Function::new("abs")
+        .with_arg(Type::Int)
+        .with_ret_type(Type::Int)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let value: i64 = adapter.get_arg(0);
+            return_int_result(adapter, "abs", value.checked_abs(), value.wrapping_abs(), value.saturating_abs());
+        }));
+    let pow_function = Function::new("pow")
+        .with_arg(Type::Int)
+        .with_arg(Type::Int)
+        .with_ret_type(Type::Int)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let base: i64 = adapter.get_arg(0);
+            let exp: i64 = adapter.get_arg(1);
+            match u32::try_from(exp) {
+                Ok(exp) => return_int_result(adapter, "pow", base.checked_pow(exp), base.wrapping_pow(exp), base.saturating_pow(exp)),
+                Err(_) => adapter.panic(format!("invalid exponent {}", exp))
+            }
+        }));
+    let to_string_function = Function::new("to_string")
+        .with_arg(Type::Int)
+        .with_ret_type(Type::String)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let value: i64 = adapter.get_arg(0);
+            let res = adapter.return_string(&value.to_string());
+            panic_on_err(adapter, res);
+        }));
+    let parse_function = Function::new("parse")
+        .with_arg(Type::String)
+        .with_ret_type(Type::Other(String::from("Parsed")))
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let text: String = adapter.get_arg(0);
+            let res = match text.trim().parse::<i64>() {
+                Ok(value) => adapter.return_ok(value),
+                Err(err) => adapter.return_err(err.to_string())
+            };
+            panic_on_err(adapter, res);
+        }));
+
+    Module::new("int")
+        .with_enum("Parsed", vec![
+            (String::from("Ok"), vec![Type::Int]),
+            (String::from("Err"), vec![Type::String])
+        ])
+        .with_function(abs_function)
+        .with_function(binary_int_function("min", i64::min))
+        .with_function(binary_int_function("max", i64::max))
+        .with_function(pow_function)
+        .with_function(to_string_function)
+        .with_function(parse_function)
+}
+
+/// Creates the "string" module with string helpers
+fn create_string_module() -> Module {
+    let find_function = Function::new("find")
+        .with_arg(Type::String)
+        .with_arg(Type::String)
+        .with_ret_type(Type::Int)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let text: String = adapter.get_arg(0);
+            let pattern: String = adapter.get_arg(1);
+            let index = text.find(&pattern)
+                .map(|index| index as i64)
+                .unwrap_or(-1);
+            adapter.return_value(index);
+        }));
+    let repeat_function = Function::new("repeat")
+        .with_arg(Type::String)
+        .with_arg(Type::Int)
+        .with_ret_type(Type::String)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let text: String = adapter.get_arg(0);
+            let count: i64 = adapter.get_arg(1);
+            let count = count.max(0) as usize;
+            match text.len().checked_mul(count) {
+                Some(len) if len <= MAX_STRING_LEN => {
+                    let res = adapter.return_string(&text.repeat(count));
+                    panic_on_err(adapter, res);
+                },
+                _ => adapter.panic(format!("repeated string exceeds {} bytes", MAX_STRING_LEN))
+            };
+        }));
+
+    Module::new("string")
+        .with_function(unary_string_function("to_upper", |text| text.to_uppercase()))
+        .with_function(unary_string_function("to_lower", |text| text.to_lowercase()))
+        .with_function(unary_string_function("trim", |text| String::from(text.trim())))
+        .with_function(string_predicate_function("contains", |text, pattern| text.contains(pattern)))
+        .with_function(string_predicate_function("starts_with", |text, pattern| text.starts_with(pattern)))
+        .with_function(string_predicate_function("ends_with", |text, pattern| text.ends_with(pattern)))
+        .with_function(find_function)
+        .with_function(repeat_function)
+}
+
+/// Creates the "assert" module, whose functions panic if an assertion fails
+fn create_assert_module() -> Module {
+    let that_function = Function::new("that")
+        .with_arg(Type::Bool)
+        .with_arg(Type::String)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let condition: bool = adapter.get_arg(0);
+            if !condition {
+                let msg: String = adapter.get_arg(1);
+                adapter.panic(format!("assertion failed: {}", msg));
+            }
+        }));
+    let equal_int_function = Function::new("equal_int")
+        .with_arg(Type::Int)
+        .with_arg(Type::Int)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let lhs: i64 = adapter.get_arg(0);
+            let rhs: i64 = adapter.get_arg(1);
+            if lhs != rhs {
+                adapter.panic(format!("assertion failed: {} != {}", lhs, rhs));
+            }
+        }));
+    let equal_float_function = Function::new("equal_float")
+        .with_arg(Type::Float)
+        .with_arg(Type::Float)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let lhs: f32 = adapter.get_arg(0);
+            let rhs: f32 = adapter.get_arg(1);
+            if lhs != rhs {
+                adapter.panic(format!("assertion failed: {} != {}", lhs, rhs));
+            }
+        }));
+    let equal_string_function = Function::new("equal_string")
+        .with_arg(Type::String)
+        .with_arg(Type::String)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let lhs: String = adapter.get_arg(0);
+            let rhs: String = adapter.get_arg(1);
+            if lhs != rhs {
+                adapter.panic(format!("assertion failed: \"{}\" != \"{}\"", lhs, rhs));
+            }
+        }));
+
+    Module::new("assert")
+        .with_function(that_function)
+        .with_function(equal_int_function)
+        .with_function(equal_float_function)
+        .with_function(equal_string_function)
+}
+
+fn unary_float_function(name: &str, op: fn(f32) -> f32) -> Function {
+    Function::new(name)
+        .with_arg(Type::Float)
+        .with_ret_type(Type::Float)
+        .with_closure(Box::new(move |adapter: &mut Adapter| {
+            let value: f32 = adapter.get_arg(0);
+            adapter.return_value(op(value));
+        }))
+}
+
+fn binary_float_function(name: &str, op: fn(f32, f32) -> f32) -> Function {
+    Function::new(name)
+        .with_arg(Type::Float)
+        .with_arg(Type::Float)
+        .with_ret_type(Type::Float)
+        .with_closure(Box::new(move |adapter: &mut Adapter| {
+            let lhs: f32 = adapter.get_arg(0);
+            let rhs: f32 = adapter.get_arg(1);
+            adapter.return_value(op(lhs, rhs));
+        }))
+}
+
+fn binary_int_function(name: &str, op: fn(i64, i64) -> i64) -> Function {
+    Function::new(name)
+        .with_arg(Type::Int)
+        .with_arg(Type::Int)
+        .with_ret_type(Type::Int)
+        .with_closure(Box::new(move |adapter: &mut Adapter| {
+            let lhs: i64 = adapter.get_arg(0);
+            let rhs: i64 = adapter.get_arg(1);
+            adapter.return_value(op(lhs, rhs));
+        }))
+}
+
+fn unary_string_function(name: &str, op: fn(&str) -> String) -> Function {
+    Function::new(name)
+        .with_arg(Type::String)
+        .with_ret_type(Type::String)
+        .with_closure(Box::new(move |adapter: &mut Adapter| {
+            let text: String = adapter.get_arg(0);
+            let res = adapter.return_string(&op(&text));
+            panic_on_err(adapter, res);
+        }))
+}
+
+fn string_predicate_function(name: &str, op: fn(&str, &str) -> bool) -> Function {
+    Function::new(name)
+        .with_arg(Type::String)
+        .with_arg(Type::String)
+        .with_ret_type(Type::Bool)
+        .with_closure(Box::new(move |adapter: &mut Adapter| {
+            let text: String = adapter.get_arg(0);
+            let pattern: String = adapter.get_arg(1);
+            adapter.return_value(op(&text, &pattern));
+        }))
+}
+
+/// Returns the result of an integer operation according to the arithmetic mode of the engine,
+/// an overflow in checked mode makes the script panic
+fn return_int_result(adapter: &mut Adapter, name: &str, checked: Option<i64>, wrapping: i64, saturating: i64) {
+    match adapter.core.get_arithmetic_mode() {
+        ArithmeticMode::Checked => match checked {
+            Some(value) => adapter.return_value(value),
+            None => adapter.panic(format!("integer overflow in {}", name))
+        },
+        ArithmeticMode::Wrapping => adapter.return_value(wrapping),
+        ArithmeticMode::Saturating => adapter.return_value(saturating)
+    }
+}
+
+/// Makes the script panic if the return value could not be written
+fn panic_on_err(adapter: &mut Adapter, res: CoreResult<()>) {
+    if let Err(err) = res {
+        adapter.panic(err.to_string());
+    }
+}
@@ -5,7 +5,9 @@ use crate::{
     vm::{
         core::{
             Core,
-            CoreError
+            CoreError,
+            GcStats,
+            ArithmeticMode
         },
         register::{
             RegisterAccess,
@@ -73,6 +75,17 @@ pub enum EngineError {
     CoreError(CoreError),
     ParseError(ParseError),
     CompileError(CompilerError),
+    /// A script panicked without a surrounding try block, carrying the message
+    Panic(String)
+}
+
+impl From<CoreError> for EngineError {
+    fn from(err: CoreError) -> EngineError {
+        match err {
+            CoreError::Panic(msg) => EngineError::Panic(msg),
+            err => EngineError::CoreError(err)
+        }
+    }
 }
 
 impl Display for EngineError {
@@ -87,6 +100,8 @@ impl Error for EngineError {
 impl Engine {
     pub fn new(stack_size: usize) -> Engine {
         let mut compiler = Compiler::new();
+        compiler.register_foreign_root_module(core::create_module())
+            .expect("Failed to register core module");
         Engine {
             core: Core::new(stack_size),
             compiler: compiler,
@@ -124,6 +139,11 @@ impl Engine {
         let program = self.compiler.get_program()
             .map_err(|c| Box::new(EngineError::CompileError(c)))?;
         self.core.load_program(program);
+        // Initialise the static variables, which are zeroed in the freshly loaded program
+        for init_uid in self.compiler.get_static_init_uids().to_vec() {
+            self.core.run_fn(init_uid)
+                .map_err(|c| Box::new(EngineError::from(c)))?;
+        }
         Ok(())
     }
 
@@ -187,9 +207,32 @@ impl Engine {
         let fn_uid = self.compiler.get_function_uid(&name)  
             .map_err(|ce| EngineError::CompileError(ce))?;
         self.core.run_fn(fn_uid)
+            .map_err(|c| Box::new(EngineError::from(c)))
+    }
+
+    /// Runs the garbage collector, returning the number of reclaimed heap objects
+    pub fn collect_garbage(&mut self) -> EngineResult<usize> {
+        self.core.collect_garbage()
             .map_err(|c| Box::new(EngineError::CoreError(c)))
     }
 
+    /// Returns the statistics of the garbage collector
+    pub fn get_gc_stats(&self) -> GcStats {
+        self.core.get_gc_stats()
+    }
+
+    /// Sets the amount of heap bytes allocated between automatic collections.
+    /// A threshold of 0 disables automatic collection.
+    pub fn set_gc_threshold(&mut self, threshold: usize) {
+        self.core.set_gc_threshold(threshold);
+    }
+
+    /// Sets how integer overflow in scripts is handled, wrapping by default.
+    /// Division by zero always fails with a `CoreError::DivisionByZero`.
+    pub fn set_arithmetic_mode(&mut self, mode: ArithmeticMode) {
+        self.core.set_arithmetic_mode(mode);
+    }
+
     pub fn register_module(&mut self, module: Module) -> EngineResult<()> {
         self.compiler.register_foreign_root_module(module)
             .map_err(|ce| Box::new(EngineError::CompileError(ce)))
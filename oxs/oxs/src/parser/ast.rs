@@ -3,7 +3,15 @@ use std::{
         HashMap,
         BTreeMap
     },
-    ops::Deref
+    fmt::{
+        Display,
+        Formatter,
+        Result as FmtResult
+    },
+    ops::{
+        Deref,
+        DerefMut
+    }
 };
 
 #[derive(PartialEq, Debug, Clone)]
@@ -11,18 +19,39 @@ pub enum Expression {
     IntLiteral(i64),
     FloatLiteral(f32),
     StringLiteral(String),
+    Interpolation(Vec<Expression>),
     BoolLiteral(bool),
+    NoneLiteral,
     Variable(String),
     ContainerInstance(String, HashMap<String, Expression>),
+    ArrayLiteral(Vec<Expression>),
+    ArrayRepeat(Box<Expression>, usize),
+    Tuple(Vec<Expression>),
+    TupleAccess(Box<Expression>, usize),
+    Index(Box<Expression>, Box<Expression>),
+    Slice(Box<Expression>, Option<Box<Expression>>, Option<Box<Expression>>),
     MemberAccess(Box<Expression>, Box<Expression>),
     Deref(Box<Expression>),
     Ref(Box<Expression>),
+    Box(Box<Expression>),
     Call(String, Vec<Expression>),
+    /// Call of an associated function of a generic container with explicit type arguments, e.g. "Stack<int>::new()"
+    TypeCall(Type, String, Vec<Expression>),
+    Closure(Box<FunctionDeclArgs>),
+    Cast(Box<Expression>, Type),
+    Try(Box<Expression>),
     Addition(Box<Expression>, Box<Expression>),
     Subtraction(Box<Expression>, Box<Expression>),
     Multiplication(Box<Expression>, Box<Expression>),
     Division(Box<Expression>, Box<Expression>),
+    Modulo(Box<Expression>, Box<Expression>),
+    BitAnd(Box<Expression>, Box<Expression>),
+    BitOr(Box<Expression>, Box<Expression>),
+    BitXor(Box<Expression>, Box<Expression>),
+    ShiftLeft(Box<Expression>, Box<Expression>),
+    ShiftRight(Box<Expression>, Box<Expression>),
     Not(Box<Expression>),
+    Negate(Box<Expression>),
     And(Box<Expression>, Box<Expression>),
     Or(Box<Expression>, Box<Expression>),
     Equals(Box<Expression>, Box<Expression>),
@@ -36,6 +65,12 @@ pub enum Expression {
     SubAssign(Box<Expression>, Box<Expression>),
     MulAssign(Box<Expression>, Box<Expression>),
     DivAssign(Box<Expression>, Box<Expression>),
+    ModAssign(Box<Expression>, Box<Expression>),
+    BitAndAssign(Box<Expression>, Box<Expression>),
+    BitOrAssign(Box<Expression>, Box<Expression>),
+    BitXorAssign(Box<Expression>, Box<Expression>),
+    ShiftLeftAssign(Box<Expression>, Box<Expression>),
+    ShiftRightAssign(Box<Expression>, Box<Expression>),
 }
 
 impl Expression {
@@ -133,10 +168,183 @@ impl Expression {
             Expression::MemberAccess(_, rhs) => {
                 rhs.ends_in_call()
             },
-            Expression::Call(_, _) => true,
+            Expression::Call(_, _) |
+            Expression::TypeCall(_, _, _) => true,
             _ => false
         }
     }
+
+    /// Returns the sub-expressions of an expression. Member names on the rhs
+    /// of a member access are not expressions of their own, so only the
+    /// arguments of member calls and index expressions are returned for them.
+    pub fn get_child_exprs(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Interpolation(exprs) |
+            Expression::ArrayLiteral(exprs) |
+            Expression::Tuple(exprs) |
+            Expression::Call(_, exprs) |
+            Expression::TypeCall(_, _, exprs) => exprs.iter().collect(),
+            Expression::ContainerInstance(_, member_map) => member_map.values().collect(),
+            Expression::ArrayRepeat(expr, _) |
+            Expression::TupleAccess(expr, _) |
+            Expression::Deref(expr) |
+            Expression::Ref(expr) |
+            Expression::Box(expr) |
+            Expression::Cast(expr, _) |
+            Expression::Try(expr) |
+            Expression::Not(expr) |
+            Expression::Negate(expr) => vec![expr],
+            Expression::Slice(expr, start_expr, end_expr) => {
+                let mut ret = vec![expr.deref()];
+                ret.extend(start_expr.iter().map(Box::deref));
+                ret.extend(end_expr.iter().map(Box::deref));
+                ret
+            },
+            Expression::MemberAccess(lhs, rhs) => {
+                let mut ret = vec![lhs.deref()];
+                ret.append(&mut rhs.get_member_child_exprs());
+                ret
+            },
+            Expression::Index(lhs, rhs) |
+            Expression::Addition(lhs, rhs) |
+            Expression::Subtraction(lhs, rhs) |
+            Expression::Multiplication(lhs, rhs) |
+            Expression::Division(lhs, rhs) |
+            Expression::Modulo(lhs, rhs) |
+            Expression::BitAnd(lhs, rhs) |
+            Expression::BitOr(lhs, rhs) |
+            Expression::BitXor(lhs, rhs) |
+            Expression::ShiftLeft(lhs, rhs) |
+            Expression::ShiftRight(lhs, rhs) |
+            Expression::And(lhs, rhs) |
+            Expression::Or(lhs, rhs) |
+            Expression::Equals(lhs, rhs) |
+            Expression::NotEquals(lhs, rhs) |
+            Expression::GreaterThan(lhs, rhs) |
+            Expression::LessThan(lhs, rhs) |
+            Expression::GreaterThanEquals(lhs, rhs) |
+            Expression::LessThanEquals(lhs, rhs) |
+            Expression::Assign(lhs, rhs) |
+            Expression::AddAssign(lhs, rhs) |
+            Expression::SubAssign(lhs, rhs) |
+            Expression::MulAssign(lhs, rhs) |
+            Expression::DivAssign(lhs, rhs) |
+            Expression::ModAssign(lhs, rhs) |
+            Expression::BitAndAssign(lhs, rhs) |
+            Expression::BitOrAssign(lhs, rhs) |
+            Expression::BitXorAssign(lhs, rhs) |
+            Expression::ShiftLeftAssign(lhs, rhs) |
+            Expression::ShiftRightAssign(lhs, rhs) => vec![lhs, rhs],
+            _ => Vec::new()
+        }
+    }
+
+    /// Returns the mutable sub-expressions of an expression
+    pub fn get_child_exprs_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Expression::Interpolation(exprs) |
+            Expression::ArrayLiteral(exprs) |
+            Expression::Tuple(exprs) |
+            Expression::Call(_, exprs) |
+            Expression::TypeCall(_, _, exprs) => exprs.iter_mut().collect(),
+            Expression::ContainerInstance(_, member_map) => member_map.values_mut().collect(),
+            Expression::ArrayRepeat(expr, _) |
+            Expression::TupleAccess(expr, _) |
+            Expression::Deref(expr) |
+            Expression::Ref(expr) |
+            Expression::Box(expr) |
+            Expression::Cast(expr, _) |
+            Expression::Try(expr) |
+            Expression::Not(expr) |
+            Expression::Negate(expr) => vec![expr],
+            Expression::Slice(expr, start_expr, end_expr) => {
+                let mut ret = vec![expr.deref_mut()];
+                ret.extend(start_expr.iter_mut().map(Box::deref_mut));
+                ret.extend(end_expr.iter_mut().map(Box::deref_mut));
+                ret
+            },
+            Expression::MemberAccess(lhs, rhs) |
+            Expression::Index(lhs, rhs) |
+            Expression::Addition(lhs, rhs) |
+            Expression::Subtraction(lhs, rhs) |
+            Expression::Multiplication(lhs, rhs) |
+            Expression::Division(lhs, rhs) |
+            Expression::Modulo(lhs, rhs) |
+            Expression::BitAnd(lhs, rhs) |
+            Expression::BitOr(lhs, rhs) |
+            Expression::BitXor(lhs, rhs) |
+            Expression::ShiftLeft(lhs, rhs) |
+            Expression::ShiftRight(lhs, rhs) |
+            Expression::And(lhs, rhs) |
+            Expression::Or(lhs, rhs) |
+            Expression::Equals(lhs, rhs) |
+            Expression::NotEquals(lhs, rhs) |
+            Expression::GreaterThan(lhs, rhs) |
+            Expression::LessThan(lhs, rhs) |
+            Expression::GreaterThanEquals(lhs, rhs) |
+            Expression::LessThanEquals(lhs, rhs) |
+            Expression::Assign(lhs, rhs) |
+            Expression::AddAssign(lhs, rhs) |
+            Expression::SubAssign(lhs, rhs) |
+            Expression::MulAssign(lhs, rhs) |
+            Expression::DivAssign(lhs, rhs) |
+            Expression::ModAssign(lhs, rhs) |
+            Expression::BitAndAssign(lhs, rhs) |
+            Expression::BitOrAssign(lhs, rhs) |
+            Expression::BitXorAssign(lhs, rhs) |
+            Expression::ShiftLeftAssign(lhs, rhs) |
+            Expression::ShiftRightAssign(lhs, rhs) => vec![lhs, rhs],
+            _ => Vec::new()
+        }
+    }
+
+    /// Replaces the type parameters in the closures, casts and typed calls of an expression with their type arguments
+    pub fn substitute_types(&mut self, type_map: &HashMap<String, Type>) {
+        match self {
+            Expression::Closure(fn_decl_args) => {
+                **fn_decl_args = fn_decl_args.substitute_types(type_map);
+                return;
+            },
+            Expression::Cast(_, expr_type) |
+            Expression::TypeCall(expr_type, _, _) => *expr_type = expr_type.substitute(type_map),
+            _ => {}
+        }
+        for child_expr in self.get_child_exprs_mut() {
+            child_expr.substitute_types(type_map);
+        }
+    }
+
+    /// Collects the names of the variables and functions an expression refers to,
+    /// including those referred to in the bodies of closures
+    pub fn collect_names<'a>(&'a self, names: &mut Vec<&'a String>) {
+        match self {
+            Expression::Variable(name) |
+            Expression::Call(name, _) => names.push(name),
+            Expression::Closure(fn_decl_args) => {
+                for stmt in fn_decl_args.code_block.iter().flatten() {
+                    stmt.collect_names(names);
+                }
+            },
+            _ => {}
+        };
+        for child_expr in self.get_child_exprs() {
+            child_expr.collect_names(names);
+        }
+    }
+
+    /// Returns the sub-expressions of the rhs of a member access
+    fn get_member_child_exprs(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Call(_, args) => args.iter().collect(),
+            Expression::Index(_, index_expr) => vec![index_expr],
+            Expression::MemberAccess(lhs, rhs) => {
+                let mut ret = lhs.get_member_child_exprs();
+                ret.append(&mut rhs.get_member_child_exprs());
+                ret
+            },
+            _ => Vec::new()
+        }
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -156,29 +364,61 @@ pub enum Operator {
     Not
 }
 
+/// A type parameter and the interfaces bounding it, e.g. "T: Ord + Into<int>"
+pub type TypeParam = (String, Vec<Type>);
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct FunctionDeclArgs {
     pub name: String,
+    pub type_params: Vec<TypeParam>,
     pub arguments: Vec<(String, Type)>,
     pub returns: Type,
     pub code_block: Option<Vec<Statement>>
 }
 
+impl FunctionDeclArgs {
+    /// Returns a monomorphic copy of a generic function declaration
+    pub fn substitute_types(&self, type_map: &HashMap<String, Type>) -> FunctionDeclArgs {
+        let mut code_block = self.code_block.clone();
+        for stmt in code_block.iter_mut().flatten() {
+            stmt.substitute_types(type_map);
+        }
+        FunctionDeclArgs {
+            name: self.name.clone(),
+            type_params: Vec::new(),
+            arguments: self.arguments.iter()
+                .map(|(arg_name, arg_type)| (arg_name.clone(), arg_type.substitute(type_map)))
+                .collect(),
+            returns: self.returns.substitute(type_map),
+            code_block
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct ContainerDeclArgs {
     pub name: String,
+    pub type_params: Vec<TypeParam>,
     pub members: Vec<(String, Type)>
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
+pub struct EnumDeclArgs {
+    pub name: String,
+    pub variants: Vec<(String, Vec<Type>)>
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub enum Declaration {
     Function(FunctionDeclArgs),
     Module(String, Vec<Declaration>),
     Container(ContainerDeclArgs),
+    Enum(EnumDeclArgs),
     Import(String, String),
-    Impl(String, String, Vec<Declaration>),
-    Interface(String, Vec<Declaration>),
-    StaticVar(VariableDeclArgs)
+    Impl(Type, Type, Vec<Declaration>),
+    Interface(String, Vec<TypeParam>, Vec<Declaration>),
+    StaticVar(VariableDeclArgs),
+    Constant(VariableDeclArgs)
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -188,27 +428,191 @@ pub struct VariableDeclArgs {
     pub assignment: Box<Expression>
 }
 
+#[derive(PartialEq, Debug, Clone)]
+pub struct TupleDeclArgs {
+    pub names: Vec<String>,
+    pub assignment: Box<Expression>
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct IfStatementArgs {
     pub if_expr: Expression,
+    pub binding: Option<String>,
     pub if_block: Vec<Statement>,
     pub else_block: Option<Vec<Statement>>,
     pub else_if_list: Option<Vec<(Expression, Vec<Statement>)>>
 }
 
+#[derive(PartialEq, Debug, Clone)]
+pub struct ForStatementArgs {
+    pub var_name: String,
+    pub range_start: Expression,
+    pub range_end: Expression,
+    pub inclusive: bool,
+    pub step: Option<Expression>,
+    pub stmt_list: Vec<Statement>
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum MatchPattern {
+    Variant(String, Vec<String>),
+    Int(i64),
+    Wildcard
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct MatchStatementArgs {
+    pub match_expr: Expression,
+    pub arms: Vec<(MatchPattern, Vec<Statement>)>
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct TryStatementArgs {
+    pub try_block: Vec<Statement>,
+    /// Name of the string variable holding the panic message
+    pub catch_var: String,
+    pub catch_block: Vec<Statement>
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum Statement {
     VariableDecl(VariableDeclArgs),
+    TupleDecl(TupleDeclArgs),
     Assignment(String, Box<Expression>),
     Call(String, Vec<Expression>),
     Return(Option<Expression>),
     CodeBlock(Vec<Statement>),
     Loop(Vec<Statement>),
     While(Box<Expression>, Vec<Statement>),
+    For(ForStatementArgs),
     Break,
     Continue,
     Expression(Expression),
-    If(IfStatementArgs)
+    If(IfStatementArgs),
+    Match(MatchStatementArgs),
+    Try(TryStatementArgs),
+    Panic(Box<Expression>)
+}
+
+impl Statement {
+    /// Returns the expressions of a statement, excluding those in nested blocks
+    pub fn get_exprs(&self) -> Vec<&Expression> {
+        match self {
+            Statement::VariableDecl(var_decl_args) => vec![&var_decl_args.assignment],
+            Statement::TupleDecl(tuple_decl_args) => vec![&tuple_decl_args.assignment],
+            Statement::Assignment(_, expr) |
+            Statement::Panic(expr) |
+            Statement::While(expr, _) => vec![expr],
+            Statement::Expression(expr) |
+            Statement::Return(Some(expr)) => vec![expr],
+            Statement::For(for_stmt_args) => {
+                let mut ret = vec![&for_stmt_args.range_start, &for_stmt_args.range_end];
+                ret.extend(for_stmt_args.step.iter());
+                ret
+            },
+            Statement::If(if_stmt_args) => {
+                let mut ret = vec![&if_stmt_args.if_expr];
+                ret.extend(if_stmt_args.else_if_list.iter().flatten().map(|(expr, _)| expr));
+                ret
+            },
+            Statement::Match(match_stmt_args) => vec![&match_stmt_args.match_expr],
+            _ => Vec::new()
+        }
+    }
+
+    /// Returns the mutable expressions of a statement, excluding those in nested blocks
+    pub fn get_exprs_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Statement::VariableDecl(var_decl_args) => vec![&mut var_decl_args.assignment],
+            Statement::TupleDecl(tuple_decl_args) => vec![&mut tuple_decl_args.assignment],
+            Statement::Assignment(_, expr) |
+            Statement::Panic(expr) |
+            Statement::While(expr, _) => vec![expr],
+            Statement::Expression(expr) |
+            Statement::Return(Some(expr)) => vec![expr],
+            Statement::For(for_stmt_args) => {
+                let mut ret = vec![&mut for_stmt_args.range_start, &mut for_stmt_args.range_end];
+                ret.extend(for_stmt_args.step.iter_mut());
+                ret
+            },
+            Statement::If(if_stmt_args) => {
+                let mut ret = vec![&mut if_stmt_args.if_expr];
+                ret.extend(if_stmt_args.else_if_list.iter_mut().flatten().map(|(expr, _)| expr));
+                ret
+            },
+            Statement::Match(match_stmt_args) => vec![&mut match_stmt_args.match_expr],
+            _ => Vec::new()
+        }
+    }
+
+    /// Collects the names of the variables and functions a statement and its nested blocks refer to
+    pub fn collect_names<'a>(&'a self, names: &mut Vec<&'a String>) {
+        for expr in self.get_exprs() {
+            expr.collect_names(names);
+        }
+        for stmt_list in self.get_blocks() {
+            for stmt in stmt_list.iter() {
+                stmt.collect_names(names);
+            }
+        }
+    }
+
+    /// Returns the nested blocks of a statement
+    pub fn get_blocks(&self) -> Vec<&Vec<Statement>> {
+        match self {
+            Statement::CodeBlock(stmt_list) |
+            Statement::Loop(stmt_list) |
+            Statement::While(_, stmt_list) => vec![stmt_list],
+            Statement::For(for_stmt_args) => vec![&for_stmt_args.stmt_list],
+            Statement::If(if_stmt_args) => {
+                let mut ret = vec![&if_stmt_args.if_block];
+                ret.extend(if_stmt_args.else_block.iter());
+                ret.extend(if_stmt_args.else_if_list.iter().flatten().map(|(_, stmt_list)| stmt_list));
+                ret
+            },
+            Statement::Match(match_stmt_args) => match_stmt_args.arms.iter()
+                .map(|(_, stmt_list)| stmt_list)
+                .collect(),
+            Statement::Try(try_stmt_args) => vec![&try_stmt_args.try_block, &try_stmt_args.catch_block],
+            _ => Vec::new()
+        }
+    }
+
+    /// Replaces the type parameters in the variable declarations and closures of a
+    /// statement and its nested blocks with their type arguments
+    pub fn substitute_types(&mut self, type_map: &HashMap<String, Type>) {
+        for expr in self.get_exprs_mut() {
+            expr.substitute_types(type_map);
+        }
+        let stmt_lists: Vec<&mut Vec<Statement>> = match self {
+            Statement::VariableDecl(var_decl_args) => {
+                var_decl_args.var_type = var_decl_args.var_type.substitute(type_map);
+                Vec::new()
+            },
+            Statement::CodeBlock(stmt_list) |
+            Statement::Loop(stmt_list) |
+            Statement::While(_, stmt_list) => vec![stmt_list],
+            Statement::For(for_stmt_args) => vec![&mut for_stmt_args.stmt_list],
+            Statement::If(if_stmt_args) => {
+                let mut ret = vec![&mut if_stmt_args.if_block];
+                ret.extend(if_stmt_args.else_block.iter_mut());
+                if let Some(else_if_list) = if_stmt_args.else_if_list.as_mut() {
+                    ret.extend(else_if_list.iter_mut().map(|(_, stmt_list)| stmt_list));
+                }
+                ret
+            },
+            Statement::Match(match_stmt_args) => match_stmt_args.arms.iter_mut()
+                .map(|(_, stmt_list)| stmt_list)
+                .collect(),
+            Statement::Try(try_stmt_args) => vec![&mut try_stmt_args.try_block, &mut try_stmt_args.catch_block],
+            _ => Vec::new()
+        };
+        for stmt_list in stmt_lists {
+            for stmt in stmt_list.iter_mut() {
+                stmt.substitute_types(type_map);
+            }
+        }
+    }
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -222,8 +626,12 @@ pub enum Type {
     Array(Box<Type>, usize),
     AutoArray(Box<Type>),
     Other(String),
+    Interface(String),
+    Generic(String, Vec<Type>),
+    Function(Vec<Type>, Box<Type>),
     Tuple(Vec<Type>),
-    Reference(Box<Type>)
+    Reference(Box<Type>),
+    Optional(Box<Type>)
 }
 
 impl Type {
@@ -232,9 +640,12 @@ impl Type {
             Type::Bool => true,
             Type::Int => true,
             Type::Float => true,
+            Type::AutoArray(_) => true,
+            Type::Optional(_) => true,
             Type::Reference(inner_type) => {
                 match inner_type.deref() {
                     Type::AutoArray(_) => false,
+                    Type::Interface(_) => false,
                     _ => true
                 }
             },
@@ -242,6 +653,20 @@ impl Type {
         }
     }
 
+    /// Returns true if the type describes a value, i.e. it is not void and does not contain "auto"
+    pub fn is_known(&self) -> bool {
+        match self {
+            Type::Void | Type::Auto => false,
+            Type::Array(inner_type, _) |
+            Type::AutoArray(inner_type) |
+            Type::Reference(inner_type) |
+            Type::Optional(inner_type) => inner_type.is_known(),
+            Type::Generic(_, types) |
+            Type::Tuple(types) => types.iter().all(|inner_type| inner_type.is_known()),
+            _ => true
+        }
+    }
+
     pub fn get_ref_type(&self) -> Type {
         match self {
             Type::Reference(inner_type) => {
@@ -263,6 +688,39 @@ impl Type {
         }
     }
 
+    /// Checks if a type mentions a generic type, e.g. "&Pair<int, int>"
+    pub fn is_generic(&self) -> bool {
+        match self {
+            Type::Generic(_, _) => true,
+            Type::Array(inner_type, _) |
+            Type::AutoArray(inner_type) |
+            Type::Reference(inner_type) |
+            Type::Optional(inner_type) => inner_type.is_generic(),
+            Type::Tuple(elem_types) => elem_types.iter().any(Type::is_generic),
+            Type::Function(arg_types, ret_type) => arg_types.iter().any(Type::is_generic) || ret_type.is_generic(),
+            _ => false
+        }
+    }
+
+    /// Replaces the type parameters in a type with their type arguments
+    pub fn substitute(&self, type_map: &HashMap<String, Type>) -> Type {
+        match self {
+            Type::Other(name) => type_map.get(name).cloned().unwrap_or_else(|| self.clone()),
+            Type::Generic(name, type_args) => {
+                Type::Generic(name.clone(), type_args.iter().map(|t| t.substitute(type_map)).collect())
+            },
+            Type::Array(inner_type, size) => Type::Array(Box::new(inner_type.substitute(type_map)), *size),
+            Type::AutoArray(inner_type) => Type::AutoArray(Box::new(inner_type.substitute(type_map))),
+            Type::Reference(inner_type) => Type::Reference(Box::new(inner_type.substitute(type_map))),
+            Type::Optional(inner_type) => Type::Optional(Box::new(inner_type.substitute(type_map))),
+            Type::Tuple(elem_types) => Type::Tuple(elem_types.iter().map(|t| t.substitute(type_map)).collect()),
+            Type::Function(arg_types, ret_type) => {
+                Type::Function(arg_types.iter().map(|t| t.substitute(type_map)).collect(), Box::new(ret_type.substitute(type_map)))
+            },
+            _ => self.clone()
+        }
+    }
+
     pub fn get_cont_name(&self) -> Option<&String> {
         match self {
             Type::Other(cont_name) => Some(cont_name),
@@ -276,3 +734,37 @@ impl Type {
         }
     }
 }
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Type::Void => write!(f, "void"),
+            Type::Int => write!(f, "int"),
+            Type::String => write!(f, "string"),
+            Type::Float => write!(f, "float"),
+            Type::Bool => write!(f, "bool"),
+            Type::Auto => write!(f, "auto"),
+            Type::Array(inner_type, size) => write!(f, "[{}; {}]", inner_type, size),
+            Type::AutoArray(inner_type) => write!(f, "[{}]", inner_type),
+            Type::Other(name) |
+            Type::Interface(name) => write!(f, "{}", name),
+            Type::Generic(name, type_args) => {
+                let type_args: Vec<String> = type_args.iter().map(Type::to_string).collect();
+                write!(f, "{}<{}>", name, type_args.join(", "))
+            },
+            Type::Tuple(elem_types) => {
+                let elem_types: Vec<String> = elem_types.iter().map(Type::to_string).collect();
+                write!(f, "({})", elem_types.join(", "))
+            },
+            Type::Function(arg_types, ret_type) => {
+                let arg_types: Vec<String> = arg_types.iter().map(Type::to_string).collect();
+                match ret_type.deref() {
+                    Type::Void => write!(f, "fn({})", arg_types.join(", ")),
+                    _ => write!(f, "fn({}) ~ {}", arg_types.join(", "), ret_type)
+                }
+            },
+            Type::Reference(inner_type) => write!(f, "&{}", inner_type),
+            Type::Optional(inner_type) => write!(f, "?{}", inner_type)
+        }
+    }
+}
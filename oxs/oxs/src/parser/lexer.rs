@@ -24,6 +24,14 @@ pub enum Token {
     #[prio = 1]
     Var,
 
+    #[token = "static"]
+    #[prio = 1]
+    Static,
+
+    #[token = "const"]
+    #[prio = 1]
+    Const,
+
     #[token = "mod"]
     #[prio = 1]
     Mod,
@@ -40,6 +48,14 @@ pub enum Token {
     #[prio = 1]
     Impl,
 
+    #[token = "enum"]
+    #[prio = 1]
+    Enum,
+
+    #[token = "match"]
+    #[prio = 1]
+    Match,
+
     #[token = "int"]
     #[prio = 1]
     Int,
@@ -56,6 +72,22 @@ pub enum Token {
     #[prio = 1]
     For,
 
+    #[token = "in"]
+    #[prio = 1]
+    In,
+
+    #[token = "by"]
+    #[prio = 1]
+    By,
+
+    #[token = "as"]
+    #[prio = 1]
+    As,
+
+    #[token = "box"]
+    #[prio = 1]
+    Box,
+
     #[token = "loop"]
     #[prio = 1]
     Loop,
@@ -76,6 +108,10 @@ pub enum Token {
     #[prio = 1]
     False,
 
+    #[token = "none"]
+    #[prio = 1]
+    None,
+
     #[token = "if"]
     #[prio = 1]
     If,
@@ -95,6 +131,18 @@ pub enum Token {
     #[prio = 1]
     Continue,
 
+    #[token = "try"]
+    #[prio = 1]
+    Try,
+
+    #[token = "catch"]
+    #[prio = 1]
+    Catch,
+
+    #[token = "panic"]
+    #[prio = 1]
+    Panic,
+
     #[regex = "([a-zA-Z_][a-zA-Z0-9_]*)"]
     Text,
 
@@ -107,6 +155,10 @@ pub enum Token {
     #[regex = "\"([^\"]|\\.)*\""]
     StringLiteral,
 
+    #[regex = "\"([^\"]|\\.)*\\{([^\"]|\\.)*\""]
+    #[prio = 1]
+    InterpolatedStringLiteral,
+
     #[token = "("]
     OpenParan,
 
@@ -158,6 +210,24 @@ pub enum Token {
     #[token = "/="]
     DivAssign,
 
+    #[token = "%="]
+    ModAssign,
+
+    #[token = "&="]
+    AndAssign,
+
+    #[token = "|="]
+    OrAssign,
+
+    #[token = "^="]
+    XorAssign,
+
+    #[token = "<<="]
+    ShiftLeftAssign,
+
+    #[token = ">>="]
+    ShiftRightAssign,
+
     #[token = "+"]
     Plus,
     
@@ -170,9 +240,24 @@ pub enum Token {
     #[token = "/"]
     Divide,
 
+    #[token = "%"]
+    Percent,
+
+    #[token = "|"]
+    Pipe,
+
+    #[token = "^"]
+    Caret,
+
     #[token = "=="]
     Equals,
 
+    #[token = "=>"]
+    FatArrow,
+
+    #[token = "?"]
+    Question,
+
     #[token = "!="]
     NotEquals,
 
@@ -188,6 +273,12 @@ pub enum Token {
     #[token = ">="]
     GreaterThanEquals,
 
+    #[token = "<<"]
+    ShiftLeft,
+
+    #[token = ">>"]
+    ShiftRight,
+
     #[token = "~"]
     Tilde,
 
@@ -200,6 +291,9 @@ pub enum Token {
     #[token = ".."]
     DoubleDot,
 
+    #[token = "..="]
+    DoubleDotAssign,
+
     #[token = "return"]
     #[prio = 1]
     Return,
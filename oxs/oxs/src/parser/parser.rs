@@ -11,7 +11,6 @@ use super::{
 use std::{
     collections::{
         HashMap,
-        VecDeque,
         HashSet,
         BTreeMap
     },
@@ -60,6 +59,9 @@ pub enum ParseErrorType {
     UnknownStatement,
     ExpectedVarName,
     ExpectedWhile,
+    ExpectedFor,
+    ExpectedIn,
+    ExpectedRange,
     ExpectedAssignment,
     ExpectedSemicolon,
     UnsupportedExpression,
@@ -74,6 +76,7 @@ pub enum ParseErrorType {
     ExpectedContainerName,
     ExpectedArraySize,
     ExpectedCloseBracket,
+    ExpectedOpenBracket,
     NotInFileMode,
     AmbiguousModuleFile(String),
     NoModuleFile(String),
@@ -89,7 +92,21 @@ pub enum ParseErrorType {
     ExpectedImplType,
     ExpectedThis,
     ThisOnlyAllowedInImpls,
-    MalformedImport
+    MalformedImport,
+    MalformedInterpolation,
+    ExpectedEnumName,
+    ExpectedVariantName,
+    ExpectedMatch,
+    ExpectedFatArrow,
+    ExpectedPattern,
+    ExpectedTypeParamName,
+    ExpectedCloseAngleBracket,
+    ExpectedStatic,
+    ExpectedExpression,
+    UnexpectedToken(Token),
+    ExpectedTry,
+    ExpectedCatch,
+    ExpectedPanic
 }
 
 #[derive(Debug)]
@@ -125,103 +142,109 @@ pub type ParseResult<T> = Result<T, ParseError>;
 
 pub struct Parser {
     code: String,
-    current_cont: RefCell<String>,
+    current_cont: RefCell<Option<Type>>,
     script_root_dir: RefCell<Option<PathBuf>>
 }
 
-fn is_op(token: &Token) -> bool {
+/// Returns the binding power of a binary operator, higher binds tighter.
+/// Prefix operators bind tighter than every binary operator except "as",
+/// postfix calls, member accesses and indexing bind tighter than everything.
+fn binary_op_prec(token: &Token) -> Option<u8> {
     match token {
-        Token::Times => true,
-        Token::Divide => true,
-        Token::Plus => true,
-        Token::Minus => true,
-        Token::Equals => true,
-        Token::NotEquals => true,
-        Token::GreaterThan => true,
-        Token::GreaterThanEquals => true,
-        Token::LessThan => true,
-        Token::LessThanEquals => true,
-        Token::Not => true,
-        Token::Tilde => true,
-        Token::And => true,
-        Token::Dot => true,
-        Token::Assign => true,
-        Token::AddAssign => true,
-        Token::MulAssign => true,
-        Token::SubAssign => true,
-        Token::DivAssign => true,
-        Token::DoubleDot => true,
-        Token::Or => true,
-        Token::DoubleAnd => true,
-        _ => false
+        Token::Assign |
+        Token::AddAssign |
+        Token::SubAssign |
+        Token::MulAssign |
+        Token::DivAssign |
+        Token::ModAssign |
+        Token::AndAssign |
+        Token::OrAssign |
+        Token::XorAssign |
+        Token::ShiftLeftAssign |
+        Token::ShiftRightAssign => Some(1),
+        Token::Or => Some(2),
+        Token::DoubleAnd => Some(3),
+        Token::Equals |
+        Token::NotEquals |
+        Token::GreaterThan |
+        Token::GreaterThanEquals |
+        Token::LessThan |
+        Token::LessThanEquals => Some(4),
+        Token::Pipe => Some(5),
+        Token::Caret => Some(6),
+        Token::And => Some(7),
+        Token::ShiftLeft |
+        Token::ShiftRight => Some(8),
+        Token::Plus |
+        Token::Minus => Some(9),
+        Token::Times |
+        Token::Divide |
+        Token::Percent => Some(10),
+        Token::As => Some(11),
+        _ => None
     }
 }
 
-fn op_prec(token: &Token) -> i8 {
-    match token {
-        Token::Times => 3,
-        Token::Divide => 3,
-        Token::Plus => 2,
-        Token::Minus => 2,
-        Token::Equals => 1,
-        Token::NotEquals => 1,
-        Token::GreaterThan => 1,
-        Token::GreaterThanEquals => 1,
-        Token::LessThan => 1,
-        Token::LessThanEquals => 1,
-        Token::Not => 4,
-        Token::And => 2,
-        Token::Tilde => 2,
-        Token::Dot => 5,
-        Token::Assign => 0,
-        Token::AddAssign => 0,
-        Token::MulAssign => 0,
-        Token::SubAssign => 0,
-        Token::DivAssign => 0,
-        Token::DoubleDot => 0,
-        Token::Or => 0,
-        Token::DoubleAnd => 0,
-        _ => {
-            panic!("ERROR! Not an operator");
-        }
-    }
+fn is_op_right_assoc(token: &Token) -> bool {
+    matches!(token,
+        Token::Assign |
+        Token::AddAssign |
+        Token::SubAssign |
+        Token::MulAssign |
+        Token::DivAssign |
+        Token::ModAssign |
+        Token::AndAssign |
+        Token::OrAssign |
+        Token::XorAssign |
+        Token::ShiftLeftAssign |
+        Token::ShiftRightAssign
+    )
 }
 
-fn is_op_right_assoc(token: &Token) -> bool {
-    match token {
-        Token::Times => true,
-        Token::Divide => false,
-        Token::Plus => false,
-        Token::Minus => false,
-        Token::Equals => false,
-        Token::NotEquals => false,
-        Token::GreaterThan => false,
-        Token::GreaterThanEquals => false,
-        Token::LessThan => false,
-        Token::LessThanEquals => false,
-        Token::Not => true,
-        Token::Tilde => true,
-        Token::And => true,
-        Token::Dot => true,
-        Token::Assign => true,
-        Token::AddAssign => true,
-        Token::MulAssign => true,
-        Token::SubAssign => true,
-        Token::DivAssign => true,
-        Token::DoubleDot => false,
-        Token::Or => false,
-        Token::DoubleAnd => false,
-        _ => {
-            panic!("ERROR! Not an operator");
-        }
-    }
+/// Builds the expression for a binary operator, "as" is handled by the parser since its rhs is a type
+fn make_binary_expr(token: &Token, lhs: Expression, rhs: Expression) -> Option<Expression> {
+    let lhs = Box::new(lhs);
+    let rhs = Box::new(rhs);
+    let expr = match token {
+        Token::Plus => Expression::Addition(lhs, rhs),
+        Token::Minus => Expression::Subtraction(lhs, rhs),
+        Token::Times => Expression::Multiplication(lhs, rhs),
+        Token::Divide => Expression::Division(lhs, rhs),
+        Token::Percent => Expression::Modulo(lhs, rhs),
+        Token::Pipe => Expression::BitOr(lhs, rhs),
+        Token::Caret => Expression::BitXor(lhs, rhs),
+        Token::And => Expression::BitAnd(lhs, rhs),
+        Token::ShiftLeft => Expression::ShiftLeft(lhs, rhs),
+        Token::ShiftRight => Expression::ShiftRight(lhs, rhs),
+        Token::Equals => Expression::Equals(lhs, rhs),
+        Token::NotEquals => Expression::NotEquals(lhs, rhs),
+        Token::GreaterThan => Expression::GreaterThan(lhs, rhs),
+        Token::GreaterThanEquals => Expression::GreaterThanEquals(lhs, rhs),
+        Token::LessThan => Expression::LessThan(lhs, rhs),
+        Token::LessThanEquals => Expression::LessThanEquals(lhs, rhs),
+        Token::DoubleAnd => Expression::And(lhs, rhs),
+        Token::Or => Expression::Or(lhs, rhs),
+        Token::Assign => Expression::Assign(lhs, rhs),
+        Token::AddAssign => Expression::AddAssign(lhs, rhs),
+        Token::SubAssign => Expression::SubAssign(lhs, rhs),
+        Token::MulAssign => Expression::MulAssign(lhs, rhs),
+        Token::DivAssign => Expression::DivAssign(lhs, rhs),
+        Token::ModAssign => Expression::ModAssign(lhs, rhs),
+        Token::AndAssign => Expression::BitAndAssign(lhs, rhs),
+        Token::OrAssign => Expression::BitOrAssign(lhs, rhs),
+        Token::XorAssign => Expression::BitXorAssign(lhs, rhs),
+        Token::ShiftLeftAssign => Expression::ShiftLeftAssign(lhs, rhs),
+        Token::ShiftRightAssign => Expression::ShiftRightAssign(lhs, rhs),
+        _ => return None
+    };
+    Some(expr)
 }
 
 impl Parser {
     pub fn new(code: String) -> Self {
         Parser {
             code: code,
-            current_cont: RefCell::new(String::new()),
+            current_cont: RefCell::new(None),
             script_root_dir: RefCell::new(None)
         }
     }
@@ -259,6 +282,9 @@ impl Parser {
                 Token::Container => {
                     ret.push(self.parse_container_decl(lexer)?);
                 },
+                Token::Enum => {
+                    ret.push(self.parse_enum_decl(lexer)?);
+                },
                 Token::Import => {
                     let mut import_decls = self.parse_import_decl(lexer)?;
                     ret.append(&mut import_decls);
@@ -272,6 +298,10 @@ impl Parser {
                 Token::Interface => {
                     ret.push(self.parse_intf_decl(lexer)?);
                 },
+                Token::Static |
+                Token::Const => {
+                    ret.push(self.parse_static_decl(lexer)?);
+                },
                 _ => {
                     return Err(ParseError::new(ParseErrorType::ExpectedMod, lexer.range()));
                 }
@@ -303,14 +333,20 @@ impl Parser {
         // Swallow intf name
         lexer.advance();
 
+        let type_params = self.parse_type_params(lexer)?;
+
         if lexer.token != Token::OpenBlock {
             return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBlock);
         }
         // Swallow "{"
         lexer.advance();
 
+        *(self.current_cont.borrow_mut()) = Some(self.get_generic_type(&intf_name, &type_params));
+
         let decl_list = self.parse_decl_list(lexer, &[Token::CloseBlock])?;
 
+        *(self.current_cont.borrow_mut()) = None;
+
         if lexer.token != Token::CloseBlock {
             return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBlock);
         }
@@ -318,7 +354,7 @@ impl Parser {
         lexer.advance();
 
         Ok(
-            Declaration::Interface(intf_name, decl_list)
+            Declaration::Interface(intf_name, type_params, decl_list)
         )
     }
 
@@ -341,14 +377,18 @@ impl Parser {
             return make_parse_error!(lexer, ParseErrorType::ExpectedImplType);
         }
 
-        let impl_type = self.parse_mod_path(lexer)?;
+        let impl_type = self.parse_type(lexer)?;
         let mut impl_for = impl_type.clone();
 
         if lexer.token == Token::For {
             // Swallow "for" if its next
             lexer.advance();
 
-            impl_for = self.parse_mod_path(lexer)?;
+            if lexer.token != Token::Text {
+                return make_parse_error!(lexer, ParseErrorType::ExpectedImplType);
+            }
+
+            impl_for = self.parse_type(lexer)?;
         }
 
         if lexer.token != Token::OpenBlock {
@@ -358,11 +398,11 @@ impl Parser {
         // Swallow "{"
         lexer.advance();
 
-        *(self.current_cont.borrow_mut()) = impl_type.clone();
+        *(self.current_cont.borrow_mut()) = Some(impl_for.clone());
 
         let decl_list = self.parse_decl_list(lexer, &[Token::CloseBlock])?;
 
-        *(self.current_cont.borrow_mut()) = String::new();
+        *(self.current_cont.borrow_mut()) = None;
 
         // Swallow "}"
         lexer.advance();
@@ -618,6 +658,8 @@ impl Parser {
         let fn_name = String::from(lexer.slice());
         lexer.advance();
 
+        let type_params = self.parse_type_params(lexer)?;
+
         // Parse "("
         if lexer.token != Token::OpenParan {
             return Err(ParseError::new(ParseErrorType::OpenParanMissing, lexer.range()));
@@ -666,6 +708,7 @@ impl Parser {
 
         let fn_raw = FunctionDeclArgs {
             name: fn_name,
+            type_params,
             arguments: fn_args,
             returns: fn_return_type,
             code_block: code_block_opt
@@ -724,11 +767,11 @@ impl Parser {
             }
             
             let arg_name = String::from("this");
-            let cont_name = self.current_cont.borrow().clone();
-            if cont_name.is_empty() {
-                return make_parse_error!(lexer, ParseErrorType::ThisOnlyAllowedInImpls);
-            }
-            let arg_type = Type::Reference(Box::new(Type::Other(cont_name)));
+            let cont_type = match self.current_cont.borrow().clone() {
+                Some(cont_type) => cont_type,
+                None => return make_parse_error!(lexer, ParseErrorType::ThisOnlyAllowedInImpls)
+            };
+            let arg_type = Type::Reference(Box::new(cont_type));
 
             // Swallow "this"
             lexer.advance();
@@ -779,6 +822,8 @@ impl Parser {
         // Swallow container name
         lexer.advance();
 
+        let type_params = self.parse_type_params(lexer)?;
+
         if lexer.token != Token::OpenBlock {
             return Err(ParseError::new(ParseErrorType::ExpectedOpenBlock, lexer.range()));
         }
@@ -793,6 +838,7 @@ impl Parser {
 
         let container_args = ContainerDeclArgs {
             name: container_name,
+            type_params,
             members: members
         };
 
@@ -801,6 +847,92 @@ impl Parser {
         )
     }
 
+    pub fn parse_enum_decl(&self, lexer: &mut Lexer) -> ParseResult<Declaration> {
+        if lexer.token != Token::Enum {
+            return make_parse_error!(lexer, ParseErrorType::Unknown);
+        }
+        // Swallow "enum"
+        lexer.advance();
+
+        if lexer.token != Token::Colon {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedColon);
+        }
+        // Swallow ":"
+        lexer.advance();
+
+        if lexer.token != Token::Text {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedEnumName);
+        }
+        let enum_name = String::from(lexer.slice());
+        // Swallow enum name
+        lexer.advance();
+
+        if lexer.token != Token::OpenBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBlock);
+        }
+        // Swallow "{"
+        lexer.advance();
+
+        let mut variants: Vec<(String, Vec<Type>)> = Vec::new();
+        while lexer.token != Token::CloseBlock &&
+            lexer.token != Token::End &&
+            lexer.token != Token::Error {
+            if lexer.token != Token::Text {
+                return make_parse_error!(lexer, ParseErrorType::ExpectedVariantName);
+            }
+            let variant_name = String::from(lexer.slice());
+            if variants.iter().any(|(name, _)| *name == variant_name) {
+                return make_parse_error!(lexer, ParseErrorType::DuplicateMember);
+            }
+            // Swallow variant name
+            lexer.advance();
+
+            // Payload types, e.g. "Rect(float, float)"
+            let mut payload_types = Vec::new();
+            if lexer.token == Token::OpenParan {
+                // Swallow "("
+                lexer.advance();
+                while lexer.token != Token::CloseParan &&
+                    lexer.token != Token::End &&
+                    lexer.token != Token::Error {
+                    payload_types.push(self.parse_type(lexer)?);
+                    if lexer.token == Token::Comma {
+                        lexer.advance(); // Swallow "," if its there
+                    } else {
+                        break;
+                    }
+                }
+                if lexer.token != Token::CloseParan {
+                    return make_parse_error!(lexer, ParseErrorType::ExpectedCloseParan);
+                }
+                // Swallow ")"
+                lexer.advance();
+            }
+            variants.push((variant_name, payload_types));
+
+            if lexer.token == Token::Comma {
+                lexer.advance(); // Swallow "," if its there
+            } else {
+                break;
+            }
+        }
+
+        if lexer.token != Token::CloseBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBlock);
+        }
+        // Swallow "}"
+        lexer.advance();
+
+        let enum_args = EnumDeclArgs {
+            name: enum_name,
+            variants
+        };
+
+        Ok(
+            Declaration::Enum(enum_args)
+        )
+    }
+
     pub fn parse_type(&self, lexer: &mut Lexer) -> ParseResult<Type> {
         let ret_type = match lexer.token {
             Token::Int => {
@@ -825,6 +957,65 @@ impl Parser {
                 let inner_type = self.parse_type(lexer)?;
                 Type::Reference(Box::new(inner_type))
             },
+            Token::Question => {
+                // Swallow "?"
+                lexer.advance();
+                let inner_type = self.parse_type(lexer)?;
+                Type::Optional(Box::new(inner_type))
+            },
+            Token::OpenParan => {
+                // Swallow "("
+                lexer.advance();
+                let mut elem_types = Vec::new();
+                while lexer.token != Token::CloseParan &&
+                    lexer.token != Token::End &&
+                    lexer.token != Token::Error {
+                    elem_types.push(self.parse_type(lexer)?);
+                    if lexer.token == Token::Comma {
+                        lexer.advance(); // Swallow "," if its there
+                    } else {
+                        break;
+                    }
+                }
+                if lexer.token != Token::CloseParan {
+                    return make_parse_error!(lexer, ParseErrorType::ExpectedCloseParan);
+                }
+                // Swallow ")"
+                lexer.advance();
+                Type::Tuple(elem_types)
+            },
+            Token::Fn => {
+                // Swallow "fn"
+                lexer.advance();
+                if lexer.token != Token::OpenParan {
+                    return make_parse_error!(lexer, ParseErrorType::ExpectedOpenParan);
+                }
+                // Swallow "("
+                lexer.advance();
+                let mut arg_types = Vec::new();
+                while lexer.token != Token::CloseParan &&
+                    lexer.token != Token::End &&
+                    lexer.token != Token::Error {
+                    arg_types.push(self.parse_type(lexer)?);
+                    if lexer.token == Token::Comma {
+                        lexer.advance(); // Swallow "," if its there
+                    } else {
+                        break;
+                    }
+                }
+                if lexer.token != Token::CloseParan {
+                    return make_parse_error!(lexer, ParseErrorType::ExpectedCloseParan);
+                }
+                // Swallow ")"
+                lexer.advance();
+                let mut ret_type = Type::Void;
+                if lexer.token == Token::Tilde {
+                    // Swallow "~"
+                    lexer.advance();
+                    ret_type = self.parse_type(lexer)?;
+                }
+                Type::Function(arg_types, Box::new(ret_type))
+            },
             Token::OpenBracket => {
                 // Swallow "["
                 lexer.advance();
@@ -857,20 +1048,111 @@ impl Parser {
             Token::Text => {
                 let mut typename = String::new();
                 while lexer.token == Token::Text ||
-                    lexer.token == Token::DoubleColon {
+                    lexer.token == Token::DoubleColon ||
+                    (Parser::is_primitive_type_token(&lexer.token) && self.is_path(lexer)) {
                     typename += lexer.slice();
                     lexer.advance();
                 }
                 if typename.ends_with("::") {
                     return make_parse_error!(lexer, ParseErrorType::InvalidTypename(typename));
                 }
-                Type::Other(typename)
+                if lexer.token == Token::LessThan {
+                    // Swallow "<"
+                    lexer.advance();
+                    let mut type_args = Vec::new();
+                    while lexer.token != Token::GreaterThan &&
+                        lexer.token != Token::ShiftRight &&
+                        lexer.token != Token::End &&
+                        lexer.token != Token::Error {
+                        type_args.push(self.parse_type(lexer)?);
+                        if lexer.token == Token::Comma {
+                            lexer.advance(); // Swallow "," if its there
+                        } else {
+                            break;
+                        }
+                    }
+                    if lexer.token == Token::ShiftRight {
+                        // Nested type arguments end in ">>", the second ">" closes the enclosing list
+                        lexer.token = Token::GreaterThan;
+                        lexer.token_begin += 1;
+                    } else if lexer.token == Token::GreaterThan {
+                        // Swallow ">"
+                        lexer.advance();
+                    } else {
+                        return make_parse_error!(lexer, ParseErrorType::ExpectedCloseAngleBracket);
+                    }
+                    Type::Generic(typename, type_args)
+                } else {
+                    Type::Other(typename)
+                }
             },
             _ => return make_parse_error!(lexer, ParseErrorType::InvalidTokenInTypename(lexer.token.clone()))
         };
         Ok(ret_type)
     }
 
+    /// Parses an optional type parameter list, e.g. "<T: Ord + Into<int>, U>"
+    pub fn parse_type_params(&self, lexer: &mut Lexer) -> ParseResult<Vec<TypeParam>> {
+        let mut ret: Vec<TypeParam> = Vec::new();
+        if lexer.token != Token::LessThan {
+            return Ok(ret);
+        }
+        // Swallow "<"
+        lexer.advance();
+
+        while lexer.token != Token::GreaterThan &&
+            lexer.token != Token::End &&
+            lexer.token != Token::Error {
+            if lexer.token != Token::Text {
+                return make_parse_error!(lexer, ParseErrorType::ExpectedTypeParamName);
+            }
+            let param_name = String::from(lexer.slice());
+            if ret.iter().any(|(name, _)| *name == param_name) {
+                return make_parse_error!(lexer, ParseErrorType::DuplicateArg);
+            }
+            // Swallow param name
+            lexer.advance();
+
+            let mut bounds = Vec::new();
+            if lexer.token == Token::Colon {
+                // Swallow ":"
+                lexer.advance();
+                bounds.push(self.parse_type(lexer)?);
+                while lexer.token == Token::Plus {
+                    // Swallow "+"
+                    lexer.advance();
+                    bounds.push(self.parse_type(lexer)?);
+                }
+            }
+            ret.push((param_name, bounds));
+
+            if lexer.token == Token::Comma {
+                lexer.advance(); // Swallow "," if its there
+            } else {
+                break;
+            }
+        }
+
+        if lexer.token != Token::GreaterThan {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseAngleBracket);
+        }
+        // Swallow ">"
+        lexer.advance();
+
+        Ok(ret)
+    }
+
+    /// Returns the type of "this" in a possibly generic declaration, e.g. "Pair<A, B>"
+    fn get_generic_type(&self, name: &str, type_params: &[TypeParam]) -> Type {
+        if type_params.is_empty() {
+            return Type::Other(String::from(name));
+        }
+        let type_args = type_params.iter()
+            .map(|(param_name, _)| Type::Other(param_name.clone()))
+            .collect();
+        Type::Generic(String::from(name), type_args)
+    }
+
     pub fn parse_container_members(&self, lexer: &mut Lexer) -> ParseResult<Vec<(String, Type)>> {
         let mut ret = Vec::new();
         let mut members = HashSet::new();
@@ -952,6 +1234,28 @@ impl Parser {
         )
     }
 
+    pub fn parse_code_block(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
+        if lexer.token != Token::OpenBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBlock);
+        }
+
+        // Swallow "{"
+        lexer.advance();
+
+        let stmt_list = self.parse_statement_list(lexer)?;
+
+        if lexer.token != Token::CloseBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBlock);
+        }
+
+        // Swallow "}"
+        lexer.advance();
+
+        Ok(
+            Statement::CodeBlock(stmt_list)
+        )
+    }
+
     pub fn parse_while(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
         if lexer.token != Token::While {
             return Err(ParseError::new(ParseErrorType::ExpectedWhile, lexer.range()));
@@ -990,6 +1294,89 @@ impl Parser {
         )
     }
 
+    pub fn parse_for(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
+        if lexer.token != Token::For {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedFor);
+        }
+
+        // Swallow "for"
+        lexer.advance();
+
+        if lexer.token != Token::Text {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedVarName);
+        }
+
+        let var_name = String::from(lexer.slice());
+
+        // Swallow var name
+        lexer.advance();
+
+        if lexer.token != Token::In {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedIn);
+        }
+
+        // Swallow "in"
+        lexer.advance();
+
+        let range_start = self.parse_expr(lexer, &[
+            Token::DoubleDot,
+            Token::DoubleDotAssign,
+            Token::OpenBlock
+        ])?;
+
+        let inclusive = match lexer.token {
+            Token::DoubleDot => false,
+            Token::DoubleDotAssign => true,
+            _ => return make_parse_error!(lexer, ParseErrorType::ExpectedRange)
+        };
+
+        // Swallow ".." or "..="
+        lexer.advance();
+
+        let range_end = self.parse_expr(lexer, &[
+            Token::By,
+            Token::OpenBlock
+        ])?;
+
+        let mut step = None;
+
+        if lexer.token == Token::By {
+            // Swallow "by"
+            lexer.advance();
+
+            step = Some(self.parse_expr(lexer, &[Token::OpenBlock])?);
+        }
+
+        if lexer.token != Token::OpenBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBlock);
+        }
+
+        // Swallow "{"
+        lexer.advance();
+
+        let stmt_list = self.parse_statement_list(lexer)?;
+
+        if lexer.token != Token::CloseBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBlock);
+        }
+
+        // Swallow "}"
+        lexer.advance();
+
+        let for_stmt_args = ForStatementArgs {
+            var_name: var_name,
+            range_start: range_start,
+            range_end: range_end,
+            inclusive: inclusive,
+            step: step,
+            stmt_list: stmt_list
+        };
+
+        Ok(
+            Statement::For(for_stmt_args)
+        )
+    }
+
     pub fn parse_if(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
         if lexer.token != Token::If {
             return Err(ParseError::new(ParseErrorType::ExpectedIf, lexer.range()));
@@ -997,6 +1384,24 @@ impl Parser {
         // Swallow "if"
         lexer.advance();
 
+        // "if var x = maybe { }" binds the unwrapped reference of an optional
+        let mut binding = None;
+        if lexer.token == Token::Var {
+            // Swallow "var"
+            lexer.advance();
+            if lexer.token != Token::Text {
+                return make_parse_error!(lexer, ParseErrorType::ExpectedVarName);
+            }
+            binding = Some(String::from(lexer.slice()));
+            // Swallow the variable name
+            lexer.advance();
+            if lexer.token != Token::Assign {
+                return make_parse_error!(lexer, ParseErrorType::ExpectedAssignment);
+            }
+            // Swallow "="
+            lexer.advance();
+        }
+
         let if_expr = self.parse_expr(lexer, &[
             Token::OpenBlock,
             Token::Semicolon
@@ -1070,6 +1475,7 @@ impl Parser {
 
         let if_stmt_args = IfStatementArgs {
             if_expr: if_expr,
+            binding,
             if_block: stmt_list,
             else_block: else_opt,
             else_if_list: else_if_list_opt
@@ -1080,35 +1486,160 @@ impl Parser {
         )
     }
 
-    pub fn parse_statement_list(&self, lexer: &mut Lexer) -> ParseResult<Vec<Statement>> {
-        let mut ret = Vec::new();
+    pub fn parse_match(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
+        if lexer.token != Token::Match {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedMatch);
+        }
+        // Swallow "match"
+        lexer.advance();
+
+        let match_expr = self.parse_expr(lexer, &[
+            Token::OpenBlock,
+            Token::Semicolon
+        ])?;
+
+        if lexer.token != Token::OpenBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBlock);
+        }
+        // Swallow "{"
+        lexer.advance();
 
+        let mut arms = Vec::new();
         while lexer.token != Token::CloseBlock &&
             lexer.token != Token::End &&
             lexer.token != Token::Error {
-            match lexer.token {
-                Token::Var => {
-                    ret.push(self.parse_var_decl(lexer)?);
-                },
-                Token::Return => {
-                    ret.push(self.parse_return(lexer)?);
-                },
-                Token::If => {
-                    ret.push(self.parse_if(lexer)?);
-                },
-                Token::Continue => {
-                    ret.push(self.parse_continue(lexer)?);
-                },
-                Token::Break => {
-                    ret.push(self.parse_break(lexer)?);
-                },
-                Token::While => {
-                    ret.push(self.parse_while(lexer)?);
-                },
-                Token::Loop => {
-                    ret.push(self.parse_loop(lexer)?);
-                },
-                _ => {
+            let pattern = self.parse_match_pattern(lexer)?;
+
+            if lexer.token != Token::FatArrow {
+                return make_parse_error!(lexer, ParseErrorType::ExpectedFatArrow);
+            }
+            // Swallow "=>"
+            lexer.advance();
+
+            if lexer.token != Token::OpenBlock {
+                return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBlock);
+            }
+            // Swallow "{"
+            lexer.advance();
+
+            let stmt_list = self.parse_statement_list(lexer)?;
+
+            if lexer.token != Token::CloseBlock {
+                return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBlock);
+            }
+            // Swallow "}"
+            lexer.advance();
+
+            if lexer.token == Token::Comma {
+                lexer.advance(); // Swallow "," if its there
+            }
+
+            arms.push((pattern, stmt_list));
+        }
+
+        if lexer.token != Token::CloseBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBlock);
+        }
+        // Swallow "}"
+        lexer.advance();
+
+        let match_stmt_args = MatchStatementArgs {
+            match_expr,
+            arms
+        };
+
+        Ok(
+            Statement::Match(match_stmt_args)
+        )
+    }
+
+    /// Parses a match arm pattern, e.g. "Shape::Rect(w, h)", "42" or "_"
+    pub fn parse_match_pattern(&self, lexer: &mut Lexer) -> ParseResult<MatchPattern> {
+        match lexer.token {
+            Token::IntLiteral => {
+                let int = String::from(lexer.slice()).parse::<i64>()
+                    .map_err(|_| ParseError::new(ParseErrorType::Unknown, lexer.range()))?;
+                // Swallow int
+                lexer.advance();
+                Ok(MatchPattern::Int(int))
+            },
+            Token::Text if lexer.slice() == "_" => {
+                // Swallow "_"
+                lexer.advance();
+                Ok(MatchPattern::Wildcard)
+            },
+            Token::Text => {
+                let variant_path = self.parse_mod_path(lexer)?;
+                let mut bindings = Vec::new();
+                if lexer.token == Token::OpenParan {
+                    // Swallow "("
+                    lexer.advance();
+                    while lexer.token == Token::Text {
+                        bindings.push(String::from(lexer.slice()));
+                        // Swallow binding name
+                        lexer.advance();
+                        if lexer.token == Token::Comma {
+                            lexer.advance(); // Swallow "," if its there
+                        } else {
+                            break;
+                        }
+                    }
+                    if lexer.token != Token::CloseParan {
+                        return make_parse_error!(lexer, ParseErrorType::ExpectedCloseParan);
+                    }
+                    // Swallow ")"
+                    lexer.advance();
+                }
+                Ok(MatchPattern::Variant(variant_path, bindings))
+            },
+            _ => make_parse_error!(lexer, ParseErrorType::ExpectedPattern)
+        }
+    }
+
+    pub fn parse_statement_list(&self, lexer: &mut Lexer) -> ParseResult<Vec<Statement>> {
+        let mut ret = Vec::new();
+
+        while lexer.token != Token::CloseBlock &&
+            lexer.token != Token::End &&
+            lexer.token != Token::Error {
+            match lexer.token {
+                Token::Var => {
+                    ret.push(self.parse_var_decl(lexer)?);
+                },
+                Token::Return => {
+                    ret.push(self.parse_return(lexer)?);
+                },
+                Token::If => {
+                    ret.push(self.parse_if(lexer)?);
+                },
+                Token::Match => {
+                    ret.push(self.parse_match(lexer)?);
+                },
+                Token::Continue => {
+                    ret.push(self.parse_continue(lexer)?);
+                },
+                Token::Break => {
+                    ret.push(self.parse_break(lexer)?);
+                },
+                Token::While => {
+                    ret.push(self.parse_while(lexer)?);
+                },
+                Token::Loop => {
+                    ret.push(self.parse_loop(lexer)?);
+                },
+                Token::For => {
+                    ret.push(self.parse_for(lexer)?);
+                },
+                Token::OpenBlock => {
+                    ret.push(self.parse_code_block(lexer)?);
+                },
+                Token::Try => {
+                    ret.push(self.parse_try(lexer)?);
+                },
+                Token::Panic => {
+                    ret.push(self.parse_panic(lexer)?);
+                },
+                _ => {
                     let expr = self.parse_expr(lexer, &[Token::Semicolon])?;
                     // Swallow ";"
                     lexer.advance();
@@ -1245,15 +1776,89 @@ impl Parser {
         )
     }
 
-    pub fn parse_return(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
-        // Swallow "return"
+    /// Parses a try statement, e.g. "try { ... } catch err { ... }"
+    pub fn parse_try(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
+        if lexer.token != Token::Try {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedTry);
+        }
+        // Swallow "try"
+        lexer.advance();
+
+        let try_block = match self.parse_code_block(lexer)? {
+            Statement::CodeBlock(stmt_list) => stmt_list,
+            _ => return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBlock)
+        };
+
+        if lexer.token != Token::Catch {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCatch);
+        }
+        // Swallow "catch"
         lexer.advance();
 
-        let ret_expr = self.parse_expr(lexer, &[Token::Semicolon])?;
+        if lexer.token != Token::Text {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedVarName);
+        }
+        let catch_var = String::from(lexer.slice());
+        // Swallow the variable name
+        lexer.advance();
 
+        let catch_block = match self.parse_code_block(lexer)? {
+            Statement::CodeBlock(stmt_list) => stmt_list,
+            _ => return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBlock)
+        };
+
+        let try_stmt_args = TryStatementArgs {
+            try_block,
+            catch_var,
+            catch_block
+        };
+
+        Ok(
+            Statement::Try(try_stmt_args)
+        )
+    }
+
+    /// Parses a panic statement, e.g. "panic("out of range");"
+    pub fn parse_panic(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
+        if lexer.token != Token::Panic {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedPanic);
+        }
+        // Swallow "panic"
+        lexer.advance();
+
+        if lexer.token != Token::OpenParan {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedOpenParan);
+        }
+        // Swallow "("
+        lexer.advance();
+
+        let msg_expr = self.parse_expr(lexer, &[Token::CloseParan])?;
+        // Swallow ")"
+        lexer.advance();
+
+        if lexer.token != Token::Semicolon {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedSemicolon);
+        }
         // Swallow ";"
         lexer.advance();
 
+        Ok(
+            Statement::Panic(Box::new(msg_expr))
+        )
+    }
+
+    pub fn parse_return(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
+        // Swallow "return"
+        lexer.advance();
+
+        // The ";" may be left out if the return ends the block
+        let ret_expr = self.parse_expr(lexer, &[Token::Semicolon, Token::CloseBlock])?;
+
+        if lexer.token == Token::Semicolon {
+            // Swallow ";"
+            lexer.advance();
+        }
+
         Ok(
             Statement::Return(Some(ret_expr))
         )
@@ -1264,6 +1869,10 @@ impl Parser {
 
         // Swallow "var"
         lexer.advance();
+
+        if lexer.token == Token::OpenParan {
+            return self.parse_tuple_decl(lexer);
+        }
         
         if lexer.token != Token::Text {
             *lexer = lexer_backup;
@@ -1309,6 +1918,77 @@ impl Parser {
         )
     }
 
+    /// Parses a module-level variable, e.g. "static counter: int = 0;" or "const LIMIT: int = 10;"
+    pub fn parse_static_decl(&self, lexer: &mut Lexer) -> ParseResult<Declaration> {
+        let is_const = match lexer.token {
+            Token::Static => false,
+            Token::Const => true,
+            _ => return make_parse_error!(lexer, ParseErrorType::ExpectedStatic)
+        };
+
+        // The rest of the declaration reads like a variable declaration
+        let var_decl_args = match self.parse_var_decl(lexer)? {
+            Statement::VariableDecl(var_decl_args) => var_decl_args,
+            _ => return make_parse_error!(lexer, ParseErrorType::ExpectedVarName)
+        };
+
+        if is_const {
+            Ok(Declaration::Constant(var_decl_args))
+        } else {
+            Ok(Declaration::StaticVar(var_decl_args))
+        }
+    }
+
+    /// Parses a destructuring declaration, e.g. "var (a, b) = f();".
+    /// Expects the lexer to point at the "(" following "var".
+    pub fn parse_tuple_decl(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
+        // Swallow "("
+        lexer.advance();
+
+        let mut names = Vec::new();
+        while lexer.token != Token::CloseParan &&
+            lexer.token != Token::End &&
+            lexer.token != Token::Error {
+            if lexer.token != Token::Text {
+                return make_parse_error!(lexer, ParseErrorType::ExpectedVarName);
+            }
+            names.push(String::from(lexer.slice()));
+            // Swallow var name
+            lexer.advance();
+            if lexer.token == Token::Comma {
+                lexer.advance(); // Swallow "," if its there
+            } else {
+                break;
+            }
+        }
+
+        if lexer.token != Token::CloseParan {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseParan);
+        }
+        // Swallow ")"
+        lexer.advance();
+
+        if lexer.token != Token::Assign {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedAssignment);
+        }
+        // Swallow "="
+        lexer.advance();
+
+        let expr = self.parse_expr(lexer, &[Token::Semicolon])?;
+
+        let tuple_decl_args = TupleDeclArgs {
+            names,
+            assignment: Box::new(expr)
+        };
+
+        // Swallow ";"
+        lexer.advance();
+
+        Ok(
+            Statement::TupleDecl(tuple_decl_args)
+        )
+    }
+
     pub fn parse_var_assign(&self, lexer: &mut Lexer) -> ParseResult<Statement> {
         if lexer.token != Token::Text {
             return Err(ParseError::new(ParseErrorType::UnknownStatement, lexer.range()));
@@ -1377,128 +2057,11 @@ impl Parser {
         )
     }
 
-    pub fn parse_expr_push(&self, lexer: &mut Lexer, operand_stack: &mut VecDeque<Expression>, operator_stack: &mut VecDeque<Token>) -> ParseResult<Expression> {
-        //println!("parse_expr_push(): operator stack len {}", operator_stack.len());
-        //println!("parse_expr_push(): operand stack len {}", operand_stack.len());
-        let op = operator_stack.pop_front().unwrap();
-        //println!("parse_expr_push(): operator {:?}", op);
-        //println!("parse_expr_push() start");
-        let expr = match op {
-            Token::Plus => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::Addition(Box::new(lhs), Box::new(rhs))
-            },
-            Token::Minus => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::Subtraction(Box::new(lhs), Box::new(rhs))
-            },
-            Token::Times => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::Multiplication(Box::new(lhs), Box::new(rhs))
-            },
-            Token::Divide => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::Division(Box::new(lhs), Box::new(rhs))
-            },
-            Token::Equals => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::Equals(Box::new(lhs), Box::new(rhs))
-            },
-            Token::NotEquals => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::NotEquals(Box::new(lhs), Box::new(rhs))
-            },
-            Token::GreaterThan => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::GreaterThan(Box::new(lhs), Box::new(rhs))
-            },
-            Token::GreaterThanEquals => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::GreaterThanEquals(Box::new(lhs), Box::new(rhs))
-            },
-            Token::LessThan => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::LessThan(Box::new(lhs), Box::new(rhs))
-            },
-            Token::LessThanEquals => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::LessThanEquals(Box::new(lhs), Box::new(rhs))
-            },
-            Token::Not => {
-                let op = operand_stack.pop_front().unwrap();
-                Expression::Not(Box::new(op))
-            },
-            Token::Tilde => {
-                let op = operand_stack.pop_front().unwrap();
-                Expression::Deref(Box::new(op))
-            },
-            Token::And => {
-                let op = operand_stack.pop_front().unwrap();
-                Expression::Ref(Box::new(op))
-            },
-            Token::Dot => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::MemberAccess(Box::new(lhs), Box::new(rhs))
-            },
-            Token::Assign => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::Assign(Box::new(lhs), Box::new(rhs))
-            },
-            Token::AddAssign => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::AddAssign(Box::new(lhs), Box::new(rhs))
-            },
-            Token::SubAssign => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::SubAssign(Box::new(lhs), Box::new(rhs))
-            },
-            Token::MulAssign => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::MulAssign(Box::new(lhs), Box::new(rhs))
-            },
-            Token::DivAssign => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::DivAssign(Box::new(lhs), Box::new(rhs))
-            },
-            Token::DoubleAnd => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::And(Box::new(lhs), Box::new(rhs))
-            },
-            Token::Or => {
-                let rhs = operand_stack.pop_front().unwrap();
-                let lhs = operand_stack.pop_front().unwrap();
-                Expression::Or(Box::new(lhs), Box::new(rhs))
-            },
-            _ => {
-                return Err(ParseError::new(ParseErrorType::UnsupportedExpression, lexer.range()));
-            }
-        };
-
-        //println!("parse_expr_push() end");
-        Ok(expr)
-    }
-
     pub fn parse_mod_path(&self, lexer: &mut Lexer) -> ParseResult<String> {
         let mut name = String::new();
         while lexer.token == Token::Text ||
-            lexer.token == Token::DoubleColon {
+            lexer.token == Token::DoubleColon ||
+            (Parser::is_primitive_type_token(&lexer.token) && self.is_path(lexer)) {
             name += lexer.slice();
             lexer.advance();
         }
@@ -1510,6 +2073,25 @@ impl Parser {
         Ok(name)
     }
 
+    /// Checks if a token is the name of a primitive type, which may also name a module, e.g. "float::sqrt"
+    pub fn is_primitive_type_token(token: &Token) -> bool {
+        matches!(token, Token::Int | Token::Float | Token::String | Token::Bool)
+    }
+
+    /// Checks if the lexer points at a path with multiple segments, e.g. "Shape::Empty"
+    pub fn is_path(&self, lexer: &Lexer) -> bool {
+        let mut lookahead = lexer.clone();
+        lookahead.advance();
+        lookahead.token == Token::DoubleColon
+    }
+
+    /// Checks if the lexer points at an anonymous function, e.g. "fn(x: int) { ... }"
+    pub fn is_closure(&self, lexer: &Lexer) -> bool {
+        let mut lookahead = lexer.clone();
+        lookahead.advance();
+        lexer.token == Token::Fn && lookahead.token == Token::OpenParan
+    }
+
     pub fn try_parse_cont_instance(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
         let lexer_backup = lexer.clone();
         
@@ -1583,12 +2165,31 @@ impl Parser {
     pub fn try_parse_call_expr(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
         let lexer_backup = lexer.clone(); // Create lexer backup for backtracking
 
-        let full_fn_name = self.parse_mod_path(lexer)?;
+        let mut full_fn_name = self.parse_mod_path(lexer)?;
 
         if full_fn_name.is_empty() {
             return Err(ParseError::new(ParseErrorType::ExpectedFunctionName, lexer.range()));
         }
 
+        // Associated functions of generic containers may be called with explicit type arguments,
+        // e.g. "Stack<int>::new()", anything else starting with "<" is a comparison
+        let mut type_opt = None;
+        if lexer.token == Token::LessThan {
+            *lexer = lexer_backup.clone();
+            match self.parse_type(lexer) {
+                Ok(generic_type @ Type::Generic(_, _)) if lexer.token == Token::DoubleColon => {
+                    // Swallow "::"
+                    lexer.advance();
+                    full_fn_name = self.parse_mod_path(lexer)?;
+                    type_opt = Some(generic_type);
+                },
+                _ => {
+                    *lexer = lexer_backup;
+                    return Err(ParseError::new(ParseErrorType::ExpectedOpenParan, lexer.range()));
+                }
+            };
+        }
+
         if lexer.token != Token::OpenParan {
             *lexer = lexer_backup;
             return Err(ParseError::new(ParseErrorType::ExpectedOpenParan, lexer.range()));
@@ -1615,155 +2216,488 @@ impl Parser {
         // Swallow ")"
         lexer.advance();
 
+        match type_opt {
+            Some(generic_type) => Ok(Expression::TypeCall(generic_type, full_fn_name, params)),
+            None => Ok(Expression::Call(full_fn_name, params))
+        }
+    }
+
+    /// Parses an anonymous function, e.g. "fn(x: int) ~ int { return x * 2; }"
+    pub fn parse_closure(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
+        if lexer.token != Token::Fn {
+            return make_parse_error!(lexer, ParseErrorType::FnMissing);
+        }
+        // Swallow "fn"
+        lexer.advance();
+
+        if lexer.token != Token::OpenParan {
+            return make_parse_error!(lexer, ParseErrorType::OpenParanMissing);
+        }
+        // Swallow "("
+        lexer.advance();
+
+        let arguments = self.parse_fn_args(lexer)?;
+
+        if lexer.token != Token::CloseParan {
+            return make_parse_error!(lexer, ParseErrorType::CloseParanMissing);
+        }
+        // Swallow ")"
+        lexer.advance();
+
+        let mut returns = Type::Void;
+        if lexer.token == Token::Tilde {
+            // Swallow "~"
+            lexer.advance();
+            returns = self.parse_type(lexer)?;
+        }
+
+        if lexer.token != Token::OpenBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBlock);
+        }
+        // Swallow "{"
+        lexer.advance();
+
+        let statements = self.parse_statement_list(lexer)?;
+
+        if lexer.token != Token::CloseBlock {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBlock);
+        }
+        // Swallow "}"
+        lexer.advance();
+
         Ok(
-            Expression::Call(full_fn_name, params)
+            Expression::Closure(Box::new(FunctionDeclArgs {
+                name: String::from("{closure}"),
+                type_params: Vec::new(),
+                arguments,
+                returns,
+                code_block: Some(statements)
+            }))
         )
     }
 
-    pub fn parse_expr(&self, lexer: &mut Lexer, delims: &[Token]) -> ParseResult<Expression> {
-        let mut operator_stack = VecDeque::new();
-        let mut operand_stack = VecDeque::new();
+    /// Tries to parse a tuple literal, e.g. "(1, true)".
+    /// Restores the lexer if the parantheses only group an expression.
+    pub fn try_parse_tuple(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
+        let lexer_backup = lexer.clone(); // Create lexer backup for backtracking
+        let tuple_res = self.parse_tuple(lexer);
+        if tuple_res.is_err() {
+            *lexer = lexer_backup;
+        }
+        tuple_res
+    }
+
+    pub fn parse_tuple(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
+        if lexer.token != Token::OpenParan {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedOpenParan);
+        }
+        // Swallow "("
+        lexer.advance();
 
-        // Counter for handling ")" being used as delim
-        let mut open_paran_count = 0;
-        let mut dec_paran_count = false;
+        let mut elements = Vec::new();
 
-        while lexer.token != Token::End &&
+        while lexer.token != Token::CloseParan &&
+            lexer.token != Token::End &&
             lexer.token != Token::Error {
-
-            // If Token is delimiter
-            if delims.contains(&lexer.token) {
-                // Special case if ")" is a delimiter
-                if lexer.token == Token::CloseParan && open_paran_count == 0 {
-                    break;
-                } else if lexer.token != Token::CloseParan {
-                    break; // Break if delim is hit
-                }
+            let element = self.parse_expr(lexer, &[
+                Token::Comma,
+                Token::CloseParan
+            ])?;
+            elements.push(element);
+            if lexer.token == Token::Comma {
+                lexer.advance(); // Swallow "," if its there
+            } else if elements.len() == 1 {
+                // Not a tuple, just a parenthesized expression
+                return make_parse_error!(lexer, ParseErrorType::UnsupportedExpression);
             }
+        }
 
-            if lexer.token == Token::True {
-                let expr = Expression::BoolLiteral(true);
-                operand_stack.push_front(expr);
-            }
+        if lexer.token != Token::CloseParan {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseParan);
+        }
+        // Swallow ")"
+        lexer.advance();
 
-            if lexer.token == Token::False {
-                let expr = Expression::BoolLiteral(false);
-                operand_stack.push_front(expr);
-            }
-            
-            if lexer.token == Token::Text {
-                let expr;
-                let call_expr_res = self.try_parse_call_expr(lexer);
-                if call_expr_res.is_ok() {
-                    expr = call_expr_res.unwrap();
-                } else {
-                    let cont_inst_expr_res = self.try_parse_cont_instance(lexer);
-                    if cont_inst_expr_res.is_ok() {
-                        expr = cont_inst_expr_res.unwrap();
-                    } else {
-                        let mut var_name = String::from(lexer.slice());
-                        expr = Expression::Variable(var_name);
-                    }
-                }
-                operand_stack.push_front(expr);
-            }
+        Ok(
+            Expression::Tuple(elements)
+        )
+    }
 
-            if lexer.token == Token::IntLiteral {
-                let int = String::from(lexer.slice()).parse::<i64>()
-                    .map_err(|_| ParseError::new(ParseErrorType::Unknown, lexer.range()))?;
-                let expr = Expression::IntLiteral(int);
-                operand_stack.push_front(expr);
-            }
+    pub fn parse_array_literal(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
+        if lexer.token != Token::OpenBracket {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBracket);
+        }
+        // Swallow "["
+        lexer.advance();
 
-            if lexer.token == Token::FloatLiteral {
-                let float = String::from(lexer.slice()).parse::<f32>()
+        let mut elements = Vec::new();
+
+        while lexer.token != Token::CloseBracket &&
+            lexer.token != Token::End &&
+            lexer.token != Token::Error {
+            let element = self.parse_expr(lexer, &[
+                Token::Comma,
+                Token::Semicolon,
+                Token::CloseBracket
+            ])?;
+            // Repeat initialiser, e.g. "[0; 16]"
+            if lexer.token == Token::Semicolon && elements.is_empty() {
+                // Swallow ";"
+                lexer.advance();
+                if lexer.token != Token::IntLiteral {
+                    return make_parse_error!(lexer, ParseErrorType::ExpectedArraySize);
+                }
+                let arr_size = String::from(lexer.slice())
+                    .parse::<usize>()
                     .map_err(|_| ParseError::new(ParseErrorType::Unknown, lexer.range()))?;
-                let expr = Expression::FloatLiteral(float);
-                operand_stack.push_front(expr);
+                // Swallow arr size
+                lexer.advance();
+                if lexer.token != Token::CloseBracket {
+                    return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBracket);
+                }
+                // Swallow "]"
+                lexer.advance();
+                return Ok(
+                    Expression::ArrayRepeat(Box::new(element), arr_size)
+                );
             }
-
-            if lexer.token == Token::StringLiteral {
-                let string = String::from(lexer.slice());
-                //println!("Parsing string literal {}", string);
-                let expr = Expression::StringLiteral(string);
-                operand_stack.push_front(expr);
+            if lexer.token == Token::Comma {
+                lexer.advance(); // Swallow "," if its there
             }
+            elements.push(element);
+        }
 
-            if is_op(&lexer.token) {
-                loop {
-                    let op_opt = operator_stack.get(0);
-                    if op_opt.is_none() {
-                        break; // Break if operator stack is empty
-                    }
-                    let op = op_opt.unwrap();
-                    if *op == Token::OpenParan {
-                        break; // Break if operator is a "("
+        if lexer.token != Token::CloseBracket {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBracket);
+        }
+        // Swallow "]"
+        lexer.advance();
+
+        Ok(
+            Expression::ArrayLiteral(elements)
+        )
+    }
+
+    /// Parses an interpolated string literal, e.g. "Hello {name}".
+    /// The literal is split into string literal and expression segments, "{{" and "}}" escape braces.
+    pub fn parse_interpolated_string(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
+        if lexer.token != Token::InterpolatedStringLiteral {
+            return make_parse_error!(lexer, ParseErrorType::UnsupportedExpression);
+        }
+        let literal = String::from(lexer.slice());
+        let content = &literal[1..literal.len() - 1];
+
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = content.char_indices().peekable();
+        while let Some((pos, c)) = chars.next() {
+            let next = chars.peek().map(|(_, next)| *next);
+            match c {
+                '{' if next == Some('{') => {
+                    chars.next();
+                    text.push('{');
+                },
+                '}' if next == Some('}') => {
+                    chars.next();
+                    text.push('}');
+                },
+                '{' => {
+                    // Find the matching "}", the expression may contain blocks itself
+                    let mut depth = 1;
+                    let mut end = None;
+                    for (pos, c) in chars.by_ref() {
+                        if c == '{' {
+                            depth += 1;
+                        } else if c == '}' {
+                            depth -= 1;
+                            if depth == 0 {
+                                end = Some(pos);
+                                break;
+                            }
+                        }
                     }
+                    let end = end
+                        .ok_or(ParseError::new(ParseErrorType::MalformedInterpolation, lexer.range()))?;
 
-                    if !(op_prec(&lexer.token) - op_prec(op) < 0) &&
-                        !(op_prec(&lexer.token) == op_prec(op) && !is_op_right_assoc(op)) {
-                        break; // Break if there is no operator of greater precedence on the stack or of equal precedence and right assoc
+                    if !text.is_empty() {
+                        segments.push(Expression::StringLiteral(format!("\"{}\"", text)));
+                        text.clear();
                     }
 
-                    let expr = self.parse_expr_push(lexer, &mut operand_stack, &mut operator_stack)?;
-                    operand_stack.push_front(expr);
-                }
-                operator_stack.push_front(lexer.token.clone());
+                    let mut expr_lexer = Token::lexer(&content[pos + 1..end]);
+                    if expr_lexer.token == Token::End {
+                        return make_parse_error!(lexer, ParseErrorType::MalformedInterpolation);
+                    }
+                    let expr = self.parse_expr(&mut expr_lexer, &[])
+                        .map_err(|err| ParseError::new(err.error_type, lexer.range()))?;
+                    if expr_lexer.token != Token::End {
+                        return make_parse_error!(lexer, ParseErrorType::MalformedInterpolation);
+                    }
+                    segments.push(expr);
+                },
+                '}' => {
+                    return make_parse_error!(lexer, ParseErrorType::MalformedInterpolation);
+                },
+                _ => text.push(c)
             }
+        }
+        if !text.is_empty() {
+            segments.push(Expression::StringLiteral(format!("\"{}\"", text)));
+        }
 
-            if lexer.token == Token::OpenParan {
-                operator_stack.push_front(lexer.token.clone());
-                open_paran_count += 1;
-            }
+        Ok(
+            Expression::Interpolation(segments)
+        )
+    }
 
-            if lexer.token == Token::CloseParan {
-                let mut pop = false;               
-                while operator_stack.len() > 0 {
-                    {
-                        let op_ref = operator_stack.get(0).unwrap();
-                        if *op_ref == Token::OpenParan {
-                            dec_paran_count = true;
-                            pop = true;
-                            break;
-                        }
-                    }
-                    let expr = self.parse_expr_push(lexer, &mut operand_stack, &mut operator_stack)?;
-                    operand_stack.push_front(expr);
-                }
+    /// Parses an expression up to one of the delimiters, which is not swallowed.
+    /// Without delimiters the expression has to span the whole input.
+    pub fn parse_expr(&self, lexer: &mut Lexer, delims: &[Token]) -> ParseResult<Expression> {
+        let expr = self.parse_binary_expr(lexer, delims, 0)?;
 
-                if pop {
-                    operator_stack.pop_front();
-                }
-            }
+        let is_delim = if delims.is_empty() {
+            lexer.token == Token::End
+        } else {
+            delims.contains(&lexer.token)
+        };
+        if !is_delim {
+            return make_parse_error!(lexer, ParseErrorType::UnexpectedToken(lexer.token.clone()));
+        }
 
-            // If Token is delimiter
-            if delims.contains(&lexer.token) {
-                // Special case if ")" is a delimiter
-                if lexer.token == Token::CloseParan && open_paran_count == 0 {
-                    break;
-                } else if lexer.token != Token::CloseParan {
-                    break; // Break if delim is hit
-                }
+        Ok(expr)
+    }
+
+    /// Precedence climbing over the binary operators, only operators binding at least as tight as "min_prec" are consumed
+    fn parse_binary_expr(&self, lexer: &mut Lexer, delims: &[Token], min_prec: u8) -> ParseResult<Expression> {
+        let mut lhs = self.parse_unary_expr(lexer)?;
+
+        while !delims.contains(&lexer.token) {
+            let op_token = lexer.token.clone();
+            let op_prec = match binary_op_prec(&op_token) {
+                Some(op_prec) if op_prec >= min_prec => op_prec,
+                _ => break
+            };
+            // Swallow operator
+            lexer.advance();
+
+            if op_token == Token::As {
+                let cast_type = self.parse_type(lexer)?;
+                lhs = Expression::Cast(Box::new(lhs), cast_type);
+                continue;
             }
 
-            // Workaround for properly decrementing open_paran_count
-            if dec_paran_count {
-                dec_paran_count = false;
-                open_paran_count -= 1;
+            let rhs_min_prec = if is_op_right_assoc(&op_token) {
+                op_prec
+            } else {
+                op_prec + 1
+            };
+            let rhs = self.parse_binary_expr(lexer, delims, rhs_min_prec)?;
+            lhs = make_binary_expr(&op_token, lhs, rhs)
+                .ok_or(ParseError::new(ParseErrorType::UnsupportedExpression, lexer.range()))?;
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parses the prefix operators "-", "!", "&", "*", "~" and "box"
+    fn parse_unary_expr(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
+        let op_token = lexer.token.clone();
+        match op_token {
+            Token::Minus |
+            Token::Not |
+            Token::And |
+            Token::Times |
+            Token::Tilde |
+            Token::Box => {},
+            _ => return self.parse_postfix_expr(lexer)
+        }
+        // Swallow operator
+        lexer.advance();
+
+        let op_expr = self.parse_unary_expr(lexer)?;
+        let expr = match op_token {
+            Token::Minus => match op_expr {
+                // Negative literals stay literals
+                Expression::IntLiteral(int) => Expression::IntLiteral(-int),
+                Expression::FloatLiteral(float) => Expression::FloatLiteral(-float),
+                _ => Expression::Negate(Box::new(op_expr))
+            },
+            Token::Not => Expression::Not(Box::new(op_expr)),
+            Token::And => Expression::Ref(Box::new(op_expr)),
+            Token::Times |
+            Token::Tilde => Expression::Deref(Box::new(op_expr)),
+            _ => Expression::Box(Box::new(op_expr))
+        };
+
+        Ok(expr)
+    }
+
+    /// Parses an operand followed by member accesses, tuple accesses, indexing and "?".
+    /// Member accesses nest to the right, e.g. "a.b.c" is parsed as "a.(b.c)".
+    fn parse_postfix_expr(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
+        let mut expr = self.parse_primary_expr(lexer)?;
+        let mut members = Vec::new();
+
+        loop {
+            match lexer.token {
+                Token::Dot => {
+                    // Swallow "."
+                    lexer.advance();
+
+                    match lexer.token {
+                        Token::Text => {
+                            let member_expr = match self.try_parse_call_expr(lexer) {
+                                Ok(call_expr) => call_expr,
+                                Err(_) => {
+                                    let member_name = String::from(lexer.slice());
+                                    // Swallow member name
+                                    lexer.advance();
+                                    Expression::Variable(member_name)
+                                }
+                            };
+                            members.push(member_expr);
+                        },
+                        // Tuple element access, e.g. "pair.0" or "nested.0.1" (which is lexed as a float)
+                        Token::IntLiteral | Token::FloatLiteral => {
+                            expr = make_member_access(expr, &mut members);
+                            for index_raw in lexer.slice().split('.') {
+                                let index = index_raw.parse::<usize>()
+                                    .map_err(|_| ParseError::new(ParseErrorType::Unknown, lexer.range()))?;
+                                expr = Expression::TupleAccess(Box::new(expr), index);
+                            }
+                            // Swallow index
+                            lexer.advance();
+                        },
+                        _ => return make_parse_error!(lexer, ParseErrorType::ExpectedMemberName)
+                    }
+                },
+                Token::OpenBracket => {
+                    let array_expr = make_member_access(expr, &mut members);
+                    expr = self.parse_index_expr(lexer, array_expr)?;
+                },
+                // Error propagation, e.g. "parse(text)?"
+                Token::Question => {
+                    expr = Expression::Try(Box::new(make_member_access(expr, &mut members)));
+                    // Swallow "?"
+                    lexer.advance();
+                },
+                _ => break
             }
-            
-            lexer.advance();
         }
 
-        while operator_stack.len() > 0 {
-            let expr = self.parse_expr_push(lexer, &mut operand_stack, &mut operator_stack)?;
-            operand_stack.push_front(expr);
+        Ok(make_member_access(expr, &mut members))
+    }
+
+    /// Parses an index or slice following "array_expr", e.g. "[i]" or "[1..]"
+    fn parse_index_expr(&self, lexer: &mut Lexer, array_expr: Expression) -> ParseResult<Expression> {
+        if lexer.token != Token::OpenBracket {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedOpenBracket);
+        }
+        // Swallow "["
+        lexer.advance();
+
+        let mut start_expr = None;
+        if lexer.token != Token::DoubleDot {
+            start_expr = Some(self.parse_expr(lexer, &[Token::CloseBracket, Token::DoubleDot])?);
         }
 
-        //println!("Operator stack: {:?}", operator_stack);
-        //println!("Operand stack: {:?}", operand_stack);
+        let expr = if lexer.token == Token::DoubleDot {
+            // Swallow ".."
+            lexer.advance();
+            let mut end_expr = None;
+            if lexer.token != Token::CloseBracket {
+                end_expr = Some(self.parse_expr(lexer, &[Token::CloseBracket])?);
+            }
+            Expression::Slice(Box::new(array_expr), start_expr.map(Box::new), end_expr.map(Box::new))
+        } else {
+            let index_expr = start_expr
+                .ok_or(ParseError::new(ParseErrorType::UnsupportedExpression, lexer.range()))?;
+            Expression::Index(Box::new(array_expr), Box::new(index_expr))
+        };
+
+        if lexer.token != Token::CloseBracket {
+            return make_parse_error!(lexer, ParseErrorType::ExpectedCloseBracket);
+        }
+        // Swallow "]"
+        lexer.advance();
+
+        Ok(expr)
+    }
+
+    /// Parses literals, variables, calls, container instances, closures and parenthesized expressions
+    fn parse_primary_expr(&self, lexer: &mut Lexer) -> ParseResult<Expression> {
+        let expr = match lexer.token {
+            Token::True => Expression::BoolLiteral(true),
+            Token::False => Expression::BoolLiteral(false),
+            Token::None => Expression::NoneLiteral,
+            Token::IntLiteral => {
+                let int = String::from(lexer.slice()).parse::<i64>()
+                    .map_err(|_| ParseError::new(ParseErrorType::Unknown, lexer.range()))?;
+                Expression::IntLiteral(int)
+            },
+            Token::FloatLiteral => {
+                let float = String::from(lexer.slice()).parse::<f32>()
+                    .map_err(|_| ParseError::new(ParseErrorType::Unknown, lexer.range()))?;
+                Expression::FloatLiteral(float)
+            },
+            Token::StringLiteral => Expression::StringLiteral(String::from(lexer.slice())),
+            Token::InterpolatedStringLiteral => self.parse_interpolated_string(lexer)?,
+            Token::Fn if self.is_closure(lexer) => {
+                // The lexer already points at the token after "}"
+                return self.parse_closure(lexer);
+            },
+            Token::OpenBracket => {
+                // The lexer already points at the token after "]"
+                return self.parse_array_literal(lexer);
+            },
+            Token::OpenParan => {
+                if let Ok(tuple_expr) = self.try_parse_tuple(lexer) {
+                    return Ok(tuple_expr);
+                }
+                // Swallow "("
+                lexer.advance();
+                let expr = self.parse_expr(lexer, &[Token::CloseParan])?;
+                // Swallow ")"
+                lexer.advance();
+                return Ok(expr);
+            },
+            Token::Int | Token::Float | Token::String | Token::Bool if self.is_path(lexer) => {
+                // Module paths starting with a primitive type name, e.g. "float::sqrt(x)"
+                return self.try_parse_call_expr(lexer);
+            },
+            Token::Text => {
+                // Calls and container instances swallow their closing token themselves
+                if let Ok(call_expr) = self.try_parse_call_expr(lexer) {
+                    return Ok(call_expr);
+                }
+                if let Ok(cont_inst_expr) = self.try_parse_cont_instance(lexer) {
+                    return Ok(cont_inst_expr);
+                }
+                if self.is_path(lexer) {
+                    // Paths without call or block, e.g. unit enum variants like "Shape::Empty"
+                    let path = self.parse_mod_path(lexer)?;
+                    return Ok(Expression::Variable(path));
+                }
+                Expression::Variable(String::from(lexer.slice()))
+            },
+            Token::End => return make_parse_error!(lexer, ParseErrorType::ExpectedExpression),
+            _ => return make_parse_error!(lexer, ParseErrorType::UnexpectedToken(lexer.token.clone()))
+        };
+        // Swallow the literal or variable name
+        lexer.advance();
+
+        Ok(expr)
+    }
+}
 
-        operand_stack.pop_front()
-            .ok_or(ParseError::new(ParseErrorType::UnsupportedExpression, lexer.range()))
+/// Binds the pending ".member" segments to "base", nesting them to the right
+fn make_member_access(base: Expression, members: &mut Vec<Expression>) -> Expression {
+    let mut member_expr = match members.pop() {
+        Some(member_expr) => member_expr,
+        None => return base
+    };
+    while let Some(outer_expr) = members.pop() {
+        member_expr = Expression::MemberAccess(Box::new(outer_expr), Box::new(member_expr));
     }
+    Expression::MemberAccess(Box::new(base), Box::new(member_expr))
 }
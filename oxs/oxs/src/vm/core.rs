@@ -27,7 +27,8 @@ use std::{
     collections::{
         VecDeque,
         HashMap,
-        HashSet
+        HashSet,
+        BTreeMap
     },
     mem::{
         size_of,
@@ -77,11 +78,27 @@ pub type CoreResult<T> = Result<T, CoreError>;
 pub const STACK_GROW_INCREMENT: usize = 1024;
 pub const STACK_GROW_THRESHOLD: usize = 64;
 pub const SWAP_SPACE_SIZE: usize = 64;
+pub const GC_DEFAULT_THRESHOLD: usize = 64 * 1024;
+pub const ARRAY_HEADER_SIZE: usize = 24;
+pub const ARRAY_MIN_CAPACITY: u64 = 4;
 
 pub struct Core {
     stack: Vec<u8>,
     heap: Vec<u8>,
-    heap_pointers: Vec<Range<usize>>,
+    /// Live allocations, mapping their start address to their end address
+    heap_pointers: BTreeMap<usize, usize>,
+    /// Free blocks inside the heap, mapping their start address to their end address
+    heap_free_list: BTreeMap<usize, usize>,
+    heap_destructors: HashMap<usize, u64>,
+    heap_roots: HashMap<u64, usize>,
+    gc_threshold: usize,
+    gc_allocated: usize,
+    gc_running: bool,
+    gc_stats: GcStats,
+    arithmetic_mode: ArithmeticMode,
+    panic_handlers: Vec<PanicHandler>,
+    /// Message of a panic raised by the running foreign function
+    foreign_panic: Option<String>,
     foreign_pointers: HashMap<u64, u64>,
     foreign_function_uids: HashSet<u64>,
     swap: Vec<u8>,
@@ -106,7 +123,108 @@ pub enum CoreError {
     InvalidStackPointer,
     InvalidRegister,
     NoReturnValue,
-    Halted(u8)
+    InvalidHeapPointer,
+    HeapOutOfBounds,
+    IndexOutOfBounds(i64, u64),
+    EmptyArray,
+    Halted(u8),
+    /// Integer division or remainder by zero at the contained instruction pointer
+    DivisionByZero(usize),
+    /// Integer overflow at the contained instruction pointer, only raised in checked mode
+    ArithmeticOverflow(usize),
+    /// A foreign function accessed an argument with the contained index it does not have
+    InvalidArgument(usize),
+    /// A foreign function returned a result variant, but its return type is not a result
+    NotAResult,
+    /// A script panicked with the contained message
+    Panic(String),
+    /// A handler was removed, but none is registered
+    NoPanicHandler,
+    /// A collection was requested while one is running, e.g. from a destructor
+    CollectionRunning
+}
+
+/// Selects how signed integer arithmetic handles overflow
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ArithmeticMode {
+    /// Overflow raises a `CoreError::ArithmeticOverflow`, float division by zero traps as well
+    Checked,
+    /// Overflow wraps around in two's complement
+    #[default]
+    Wrapping,
+    /// Overflow clamps to the minimum or maximum value
+    Saturating
+}
+
+impl ArithmeticMode {
+    /// Shifts a signed integer left. Checked shifts fail if the shift amount is not in 0..64
+    /// or bits are shifted out, saturating shifts treat negative amounts as 0 and clamp the result.
+    pub fn shl(self, lhs: i64, rhs: i64) -> Option<i64> {
+        match self {
+            ArithmeticMode::Checked => {
+                if !(0..64).contains(&rhs) || (lhs << rhs) >> rhs != lhs {
+                    return None;
+                }
+                Some(lhs << rhs)
+            },
+            ArithmeticMode::Wrapping => Some(lhs.wrapping_shl(rhs as u32)),
+            ArithmeticMode::Saturating => {
+                let bound = if lhs < 0 { i64::MIN } else { i64::MAX };
+                if lhs == 0 || rhs <= 0 {
+                    return Some(lhs);
+                }
+                if rhs >= 64 || (lhs << rhs) >> rhs != lhs {
+                    return Some(bound);
+                }
+                Some(lhs << rhs)
+            }
+        }
+    }
+
+    /// Shifts a signed integer right arithmetically. Checked shifts fail if the shift amount
+    /// is not in 0..64, saturating shifts clamp the shift amount into that range.
+    pub fn shr(self, lhs: i64, rhs: i64) -> Option<i64> {
+        match self {
+            ArithmeticMode::Checked => {
+                if !(0..64).contains(&rhs) {
+                    return None;
+                }
+                Some(lhs >> rhs)
+            },
+            ArithmeticMode::Wrapping => Some(lhs.wrapping_shr(rhs as u32)),
+            ArithmeticMode::Saturating => Some(lhs >> rhs.clamp(0, 63))
+        }
+    }
+}
+
+/// A "catch" block registered by the TRY instruction
+#[derive(Debug, Clone, Copy)]
+struct PanicHandler {
+    /// Code offset of the handler
+    ip: usize,
+    /// Stack pointer when the handler was registered
+    sp: Register,
+    /// Size of the call stack when the handler was registered
+    call_depth: usize
+}
+
+/// Statistics of the heap garbage collector
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GcStats {
+    /// Number of collections run so far
+    pub collections: usize,
+    /// Total number of heap objects reclaimed
+    pub objects_freed: usize,
+    /// Total number of heap bytes reclaimed
+    pub bytes_freed: usize,
+    /// Number of live heap objects
+    pub live_objects: usize,
+    /// Number of bytes held by live heap objects
+    pub live_bytes: usize,
+    /// Size of the heap, including free blocks
+    pub heap_size: usize,
+    /// Number of bytes allocated since the last collection
+    pub allocated_since_collection: usize
 }
 
 impl Display for CoreError {
@@ -133,7 +251,17 @@ impl Core {
             swap: swap,
             stack: stack,
             heap: Vec::new(),
-            heap_pointers: Vec::new(),
+            heap_pointers: BTreeMap::new(),
+            heap_free_list: BTreeMap::new(),
+            heap_destructors: HashMap::new(),
+            heap_roots: HashMap::new(),
+            gc_threshold: GC_DEFAULT_THRESHOLD,
+            gc_allocated: 0,
+            gc_running: false,
+            gc_stats: GcStats::default(),
+            arithmetic_mode: ArithmeticMode::default(),
+            panic_handlers: Vec::new(),
+            foreign_panic: None,
             foreign_pointers: HashMap::new(),
             foreign_function_uids: HashSet::new(),
             call_stack: VecDeque::new(),
@@ -197,12 +325,67 @@ impl Core {
         self.run_at(fn_offset)
     }
 
+    /// Runs the code at the given offset until the outermost function returns.
+    /// Errors unwind to the innermost handler registered during this run, an
+    /// unhandled error restores the call stack, stack pointer and registers.
     pub fn run_at(&mut self, offset: usize) -> CoreResult<()> {
+        let registers = self.registers;
+        let sp = self.sp;
+        let call_depth = self.call_stack.len();
+        let handler_base = self.panic_handlers.len();
+
         self.ip.set(offset);
+        loop {
+            let err = match self.run_instructions() {
+                Ok(()) => return Ok(()),
+                Err(err) => err
+            };
+            if self.panic_handlers.len() > handler_base {
+                self.unwind(err)?;
+                continue;
+            }
+
+            self.registers = registers;
+            self.sp = sp;
+            while self.call_stack.len() > call_depth {
+                self.call_stack.pop_front();
+            }
+            self.panic_handlers.truncate(handler_base);
+            return Err(err);
+        }
+    }
+
+    /// Jumps to the innermost handler, passing the panic message as a string on top of the stack
+    fn unwind(&mut self, err: CoreError) -> CoreResult<()> {
+        let handler = self.panic_handlers.pop()
+            .ok_or(CoreError::NoPanicHandler)?;
+        let msg = match err {
+            CoreError::Panic(msg) => msg,
+            err => err.to_string()
+        };
+
+        while self.call_stack.len() > handler.call_depth {
+            self.call_stack.pop_front();
+        }
+        self.sp = handler.sp;
+        let sp_real = Address::from(self.sp.get::<u64>()).real_address as usize;
+        if sp_real + 16 > self.stack.len() {
+            return Err(CoreError::StackOverflow);
+        }
+        let msg_addr = self.sp.get::<u64>();
+        self.string_store(msg.as_bytes(), msg_addr)?;
+        self.sp.inc(16usize);
+        self.ip.set(handler.ip);
+        Ok(())
+    }
+
+    fn run_instructions(&mut self) -> CoreResult<()> {
         let program_len = self.program_len()?;
         //println!("Program length: {}", program_len);
         while self.ip.get::<usize>() < program_len {
             //println!("ip: {}", self.ip.get::<usize>());
+            // Arithmetic traps report the address of the faulting instruction
+            let instr_ip = self.ip.get::<usize>();
             let opcode = self.get_opcode()?;
             //println!("opcode: {:?}", opcode);
             //println!("Stack values: {:?}", &self.stack[0..self.sp]);
@@ -437,7 +620,8 @@ impl Core {
                     let rhs: i64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs + rhs);
+                    let res = self.int_arith_result(instr_ip, lhs.checked_add(rhs), lhs.wrapping_add(rhs), lhs.saturating_add(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
                 Opcode::SUBI => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -449,7 +633,8 @@ impl Core {
                     let rhs: i64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs - rhs);
+                    let res = self.int_arith_result(instr_ip, lhs.checked_sub(rhs), lhs.wrapping_sub(rhs), lhs.saturating_sub(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
                 Opcode::MULI => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -461,7 +646,8 @@ impl Core {
                     let rhs: i64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs * rhs);
+                    let res = self.int_arith_result(instr_ip, lhs.checked_mul(rhs), lhs.wrapping_mul(rhs), lhs.saturating_mul(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
                 Opcode::DIVI => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -473,7 +659,11 @@ impl Core {
                     let rhs: i64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs / rhs)
+                    if rhs == 0 {
+                        return Err(CoreError::DivisionByZero(instr_ip));
+                    }
+                    let res = self.int_arith_result(instr_ip, lhs.checked_div(rhs), lhs.wrapping_div(rhs), lhs.saturating_div(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
                 Opcode::ADDI_I => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -482,7 +672,8 @@ impl Core {
                     let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs + rhs);
+                    let res = self.int_arith_result(instr_ip, lhs.checked_add(rhs), lhs.wrapping_add(rhs), lhs.saturating_add(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
                 Opcode::SUBI_I => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -491,7 +682,8 @@ impl Core {
                     let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs - rhs);
+                    let res = self.int_arith_result(instr_ip, lhs.checked_sub(rhs), lhs.wrapping_sub(rhs), lhs.saturating_sub(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
                 Opcode::MULI_I => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -500,7 +692,8 @@ impl Core {
                     let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs * rhs);
+                    let res = self.int_arith_result(instr_ip, lhs.checked_mul(rhs), lhs.wrapping_mul(rhs), lhs.saturating_mul(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
                 Opcode::DIVI_I => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -509,7 +702,11 @@ impl Core {
                     let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs / rhs);
+                    if rhs == 0 {
+                        return Err(CoreError::DivisionByZero(instr_ip));
+                    }
+                    let res = self.int_arith_result(instr_ip, lhs.checked_div(rhs), lhs.wrapping_div(rhs), lhs.saturating_div(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
                 Opcode::ADDU => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -521,7 +718,7 @@ impl Core {
                     let rhs: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs + rhs);
+                    self.reg(target_reg)?.set(lhs.wrapping_add(rhs));
                 },
                 Opcode::SUBU => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -533,7 +730,7 @@ impl Core {
                     let rhs: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs - rhs)
+                    self.reg(target_reg)?.set(lhs.wrapping_sub(rhs));
                 },
                 Opcode::MULU => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -545,7 +742,7 @@ impl Core {
                     let rhs: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs * rhs)
+                    self.reg(target_reg)?.set(lhs.wrapping_mul(rhs));
                 },
                 Opcode::DIVU => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -557,7 +754,10 @@ impl Core {
                     let rhs: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs / rhs)
+                    if rhs == 0 {
+                        return Err(CoreError::DivisionByZero(instr_ip));
+                    }
+                    self.reg(target_reg)?.set(lhs / rhs);
                 },
                 Opcode::ADDU_I => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -574,7 +774,7 @@ impl Core {
                             return Err(CoreError::StackOverflow);
                         }
                     }
-                    self.reg(target_reg)?.set(lhs + rhs);
+                    self.reg(target_reg)?.set(lhs.wrapping_add(rhs));
                     //println!("SP After ADDU_I: {}", Address::from(self.sp.get::<u64>()).real_address);
                 },
                 Opcode::SUBU_I => {
@@ -588,7 +788,7 @@ impl Core {
                         let lhs = Address::from(self.sp.get::<u64>()).real_address;
                         //println!("Decrementing SP(={}) by {}", lhs, rhs);
                     }
-                    self.reg(target_reg)?.set(lhs - rhs);
+                    self.reg(target_reg)?.set(lhs.wrapping_sub(rhs));
                 },
                 Opcode::MULU_I => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -597,7 +797,7 @@ impl Core {
                     let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs * rhs);
+                    self.reg(target_reg)?.set(lhs.wrapping_mul(rhs));
                 },
                 Opcode::DIVU_I => {
                     let lhs_reg: u8 = self.get_op()?;
@@ -606,264 +806,582 @@ impl Core {
                     let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
+                    if rhs == 0 {
+                        return Err(CoreError::DivisionByZero(instr_ip));
+                    }
                     self.reg(target_reg)?.set(lhs / rhs);
                 },
-                Opcode::ADDF => {
+                Opcode::MODI => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: f32 = {
+                    let rhs: i64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs + rhs);
+                    if rhs == 0 {
+                        return Err(CoreError::DivisionByZero(instr_ip));
+                    }
+                    let res = self.int_arith_result(instr_ip, lhs.checked_rem(rhs), lhs.wrapping_rem(rhs), lhs.wrapping_rem(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
-                Opcode::SUBF => {
+                Opcode::ANDI => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: f32 = {
+                    let rhs: i64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs - rhs);
+                    self.reg(target_reg)?.set(lhs & rhs);
                 },
-                Opcode::MULF => {
+                Opcode::ORI => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: f32 = {
+                    let rhs: i64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs * rhs);
+                    self.reg(target_reg)?.set(lhs | rhs);
                 },
-                Opcode::DIVF => {
+                Opcode::XORI => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: f32 = {
+                    let rhs: i64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs / rhs);
+                    self.reg(target_reg)?.set(lhs ^ rhs);
                 },
-                Opcode::ADDF_I => {
+                Opcode::SHLI => {
+                    // Shift amounts are taken modulo 64 in wrapping mode
                     let lhs_reg: u8 = self.get_op()?;
-                    let rhs: f32 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs + rhs);
+                    let rhs: i64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    let res = self.arithmetic_mode.shl(lhs, rhs)
+                        .ok_or(CoreError::ArithmeticOverflow(instr_ip))?;
+                    self.reg(target_reg)?.set(res);
                 },
-                Opcode::SUBF_I => {
+                Opcode::SHRI => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let rhs: f32 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs - rhs);
+                    let rhs: i64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    let res = self.arithmetic_mode.shr(lhs, rhs)
+                        .ok_or(CoreError::ArithmeticOverflow(instr_ip))?;
+                    self.reg(target_reg)?.set(res);
                 },
-                Opcode::MULF_I => {
+                Opcode::MODI_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let rhs: f32 = self.get_op()?;
+                    let rhs: i64 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs * rhs);
+                    if rhs == 0 {
+                        return Err(CoreError::DivisionByZero(instr_ip));
+                    }
+                    let res = self.int_arith_result(instr_ip, lhs.checked_rem(rhs), lhs.wrapping_rem(rhs), lhs.wrapping_rem(rhs))?;
+                    self.reg(target_reg)?.set(res);
                 },
-                Opcode::DIVF_I => {
+                Opcode::ANDI_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let rhs: f32 = self.get_op()?;
+                    let rhs: i64 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs / rhs);
-                },
-                Opcode::JMP => {
-                    let target_ip: u64 = self.get_op()?;
-                    self.ip.set(target_ip);
+                    self.reg(target_reg)?.set(lhs & rhs);
                 },
-                Opcode::JMPT => {
+                Opcode::ORI_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let target_ip: u64 = self.get_op()?;
-                    let lhs: bool = {
+                    let rhs: i64 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    if lhs {
-                        self.ip.set(target_ip);
-                    }
+                    self.reg(target_reg)?.set(lhs | rhs);
                 },
-                Opcode::JMPF => {
+                Opcode::XORI_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let target_ip: u64 = self.get_op()?;
-                    let lhs: bool = {
+                    let rhs: i64 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    if !lhs {
-                        self.ip.set(target_ip);
-                    }
+                    self.reg(target_reg)?.set(lhs ^ rhs);
                 },
-                Opcode::DJMP => {
+                Opcode::SHLI_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let target_ip: u64 = {
+                    let rhs: i64 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.ip.set(target_ip);
+                    let res = self.arithmetic_mode.shl(lhs, rhs)
+                        .ok_or(CoreError::ArithmeticOverflow(instr_ip))?;
+                    self.reg(target_reg)?.set(res);
                 },
-                Opcode::DJMPT => {
+                Opcode::SHRI_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let rhs_reg: u8 = self.get_op()?;
-                    let target_ip: u64 = {
-                        self.reg(rhs_reg)?.get()
-                    };
-                    let lhs: bool = {
+                    let rhs: i64 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    if lhs {
-                        self.ip.set(target_ip);
-                    }
+                    let res = self.arithmetic_mode.shr(lhs, rhs)
+                        .ok_or(CoreError::ArithmeticOverflow(instr_ip))?;
+                    self.reg(target_reg)?.set(res);
                 },
-                Opcode::DJMPF => {
+                Opcode::MODU => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
-                    let target_ip: u64 = {
-                        self.reg(rhs_reg)?.get()
-                    };
-                    let lhs: bool = {
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    if !lhs {
-                        self.ip.set(target_ip);
-                    }
-                },
-                Opcode::CALL => {
-                    self.call()?;
-                },
-                Opcode::RET => {
-                    // Special case if function was called externally, the callstack is empty
-                    if self.call_stack.len() == 0 {
-                        break;
+                    let rhs: u64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    if rhs == 0 {
+                        return Err(CoreError::DivisionByZero(instr_ip));
                     }
-                    self.ret()?;
+                    self.reg(target_reg)?.set(lhs % rhs);
                 },
-                Opcode::NOT => {
+                Opcode::ANDU => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
-                    let lhs: bool = {
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    self.reg(rhs_reg)?.set(!lhs);
+                    let rhs: u64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs & rhs);
                 },
-                Opcode::AND => {
+                Opcode::ORU => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: bool = {
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: bool = {
+                    let rhs: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs && rhs);
+                    self.reg(target_reg)?.set(lhs | rhs);
                 },
-                Opcode::OR => {
+                Opcode::XORU => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: bool = {
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: bool = {
+                    let rhs: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs || rhs);
+                    self.reg(target_reg)?.set(lhs ^ rhs);
                 },
-                Opcode::EQI => {
+                Opcode::SHLU => {
+                    // Shift amounts are taken modulo 64
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: i64 = {
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: i64 = {
+                    let rhs: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs == rhs);
+                    self.reg(target_reg)?.set(lhs.wrapping_shl(rhs as u32));
                 },
-                Opcode::NEQI => {
+                Opcode::SHRU => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: i64 = {
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: i64 = {
+                    let rhs: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs != rhs);
+                    self.reg(target_reg)?.set(lhs.wrapping_shr(rhs as u32));
                 },
-                Opcode::LTI => {
+                Opcode::MODU_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let rhs_reg: u8 = self.get_op()?;
+                    let rhs: u64 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: i64 = {
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: i64 = {
-                        self.reg(rhs_reg)?.get()
-                    };
-                    self.reg(target_reg)?.set(lhs < rhs);
+                    if rhs == 0 {
+                        return Err(CoreError::DivisionByZero(instr_ip));
+                    }
+                    self.reg(target_reg)?.set(lhs % rhs);
                 },
-                Opcode::GTI => {
+                Opcode::ANDU_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let rhs_reg: u8 = self.get_op()?;
+                    let rhs: u64 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: i64 = {
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: i64 = {
-                        self.reg(rhs_reg)?.get()
+                    self.reg(target_reg)?.set(lhs & rhs);
+                },
+                Opcode::ORU_I => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs: u64 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: u64 = {
+                        self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs > rhs);
+                    self.reg(target_reg)?.set(lhs | rhs);
                 },
-                Opcode::LTEQI => {
+                Opcode::XORU_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let rhs_reg: u8 = self.get_op()?;
+                    let rhs: u64 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: i64 = {
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: i64 = {
-                        self.reg(rhs_reg)?.get()
+                    self.reg(target_reg)?.set(lhs ^ rhs);
+                },
+                Opcode::SHLU_I => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs: u64 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: u64 = {
+                        self.reg(lhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs <= rhs);
+                    self.reg(target_reg)?.set(lhs.wrapping_shl(rhs as u32));
                 },
-                Opcode::GTEQI => {
+                Opcode::SHRU_I => {
                     let lhs_reg: u8 = self.get_op()?;
-                    let rhs_reg: u8 = self.get_op()?;
+                    let rhs: u64 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: i64 = {
+                    let lhs: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: i64 = {
-                        self.reg(rhs_reg)?.get()
+                    self.reg(target_reg)?.set(lhs.wrapping_shr(rhs as u32));
+                },
+                Opcode::NOTI => {
+                    let value_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let value: i64 = {
+                        self.reg(value_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs >= rhs);
+                    self.reg(target_reg)?.set(!value);
                 },
-                Opcode::EQF => {
+                Opcode::TRY => {
+                    let handler_ip: u64 = self.get_op()?;
+                    self.panic_handlers.push(PanicHandler {
+                        ip: handler_ip as usize,
+                        sp: self.sp,
+                        call_depth: self.call_stack.len()
+                    });
+                },
+                Opcode::TRY_END => {
+                    self.panic_handlers.pop()
+                        .ok_or(CoreError::NoPanicHandler)?;
+                },
+                Opcode::PANIC => {
+                    let msg_reg: u8 = self.get_op()?;
+                    let msg_addr: u64 = {
+                        self.reg(msg_reg)?.get()
+                    };
+                    let msg = self.mem_get_string(msg_addr)?;
+                    return Err(CoreError::Panic(msg));
+                },
+                Opcode::ADDF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: f32 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs + rhs);
+                },
+                Opcode::SUBF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: f32 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs - rhs);
+                },
+                Opcode::MULF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: f32 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs * rhs);
+                },
+                Opcode::DIVF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: f32 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    if rhs == 0.0 && self.arithmetic_mode == ArithmeticMode::Checked {
+                        return Err(CoreError::DivisionByZero(instr_ip));
+                    }
+                    self.reg(target_reg)?.set(lhs / rhs);
+                },
+                Opcode::ADDF_I => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs: f32 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs + rhs);
+                },
+                Opcode::SUBF_I => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs: f32 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs - rhs);
+                },
+                Opcode::MULF_I => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs: f32 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs * rhs);
+                },
+                Opcode::DIVF_I => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs: f32 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    if rhs == 0.0 && self.arithmetic_mode == ArithmeticMode::Checked {
+                        return Err(CoreError::DivisionByZero(instr_ip));
+                    }
+                    self.reg(target_reg)?.set(lhs / rhs);
+                },
+                Opcode::JMP => {
+                    let target_ip: u64 = self.get_op()?;
+                    self.ip.set(target_ip);
+                },
+                Opcode::JMPT => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let target_ip: u64 = self.get_op()?;
+                    let lhs: bool = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    if lhs {
+                        self.ip.set(target_ip);
+                    }
+                },
+                Opcode::JMPF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let target_ip: u64 = self.get_op()?;
+                    let lhs: bool = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    if !lhs {
+                        self.ip.set(target_ip);
+                    }
+                },
+                Opcode::DJMP => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let target_ip: u64 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    self.ip.set(target_ip);
+                },
+                Opcode::DJMPT => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_ip: u64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    let lhs: bool = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    if lhs {
+                        self.ip.set(target_ip);
+                    }
+                },
+                Opcode::DJMPF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_ip: u64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    let lhs: bool = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    if !lhs {
+                        self.ip.set(target_ip);
+                    }
+                },
+                Opcode::CALL => {
+                    self.call()?;
+                },
+                Opcode::CALL_R => {
+                    let uid_reg: u8 = self.get_op()?;
+                    let fn_uid: u64 = self.reg(uid_reg)?.get();
+                    self.call_uid(fn_uid)?;
+                },
+                Opcode::RET => {
+                    // Special case if function was called externally, the callstack is empty
+                    if self.call_stack.len() == 0 {
+                        break;
+                    }
+                    self.ret()?;
+                },
+                Opcode::NOT => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let lhs: bool = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    self.reg(rhs_reg)?.set(!lhs);
+                },
+                Opcode::AND => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: bool = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: bool = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs && rhs);
+                },
+                Opcode::OR => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: bool = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: bool = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs || rhs);
+                },
+                Opcode::EQI => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: i64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs == rhs);
+                },
+                Opcode::NEQI => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: i64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs != rhs);
+                },
+                Opcode::LTI => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: i64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs < rhs);
+                },
+                Opcode::GTI => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: i64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs > rhs);
+                },
+                Opcode::LTEQI => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: i64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs <= rhs);
+                },
+                Opcode::GTEQI => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: i64 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: i64 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs >= rhs);
+                },
+                Opcode::EQF => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
@@ -875,65 +1393,317 @@ impl Core {
                     };
                     self.reg(target_reg)?.set(lhs == rhs);
                 },
-                Opcode::NEQF => {
-                    let lhs_reg: u8 = self.get_op()?;
-                    let rhs_reg: u8 = self.get_op()?;
+                Opcode::NEQF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: f32 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs != rhs);
+                },
+                Opcode::LTF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: f32 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs < rhs);
+                },
+                Opcode::GTF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: f32 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs > rhs);
+                },
+                Opcode::LTEQF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: f32 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs <= rhs);
+                },
+                Opcode::GTEQF => {
+                    let lhs_reg: u8 = self.get_op()?;
+                    let rhs_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let lhs: f32 = {
+                        self.reg(lhs_reg)?.get()
+                    };
+                    let rhs: f32 = {
+                        self.reg(rhs_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(lhs >= rhs);
+                },
+                Opcode::ALLOC => {
+                    let size_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let size: u64 = {
+                        self.reg(size_reg)?.get()
+                    };
+                    self.collect_garbage_auto()?;
+                    let addr = self.heap_alloc(size as usize)?;
+                    self.reg(target_reg)?.set(addr);
+                },
+                Opcode::ALLOC_I => {
+                    let size: u64 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    self.collect_garbage_auto()?;
+                    let addr = self.heap_alloc(size as usize)?;
+                    self.reg(target_reg)?.set(addr);
+                },
+                Opcode::REALLOC => {
+                    let addr_reg: u8 = self.get_op()?;
+                    let size_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let addr: u64 = {
+                        self.reg(addr_reg)?.get()
+                    };
+                    let size: u64 = {
+                        self.reg(size_reg)?.get()
+                    };
+                    let new_addr = self.heap_realloc(addr, size as usize)?;
+                    self.reg(target_reg)?.set(new_addr);
+                },
+                Opcode::FREE => {
+                    let addr_reg: u8 = self.get_op()?;
+                    let addr: u64 = {
+                        self.reg(addr_reg)?.get()
+                    };
+                    self.heap_free(addr)?;
+                },
+                Opcode::DTOR => {
+                    let addr_reg: u8 = self.get_op()?;
+                    let fn_uid: u64 = self.get_op()?;
+                    let addr: u64 = {
+                        self.reg(addr_reg)?.get()
+                    };
+                    self.set_heap_destructor(addr, fn_uid)?;
+                },
+                Opcode::ARR_NEW => {
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
-                        self.reg(lhs_reg)?.get()
+                    self.collect_garbage_auto()?;
+                    let addr = self.array_new()?;
+                    self.reg(target_reg)?.set(addr);
+                },
+                Opcode::ARR_LEN => {
+                    let arr_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let addr: u64 = {
+                        self.reg(arr_reg)?.get()
                     };
-                    let rhs: f32 = {
-                        self.reg(rhs_reg)?.get()
+                    let len = self.array_len(addr)?;
+                    self.reg(target_reg)?.set(len as i64);
+                },
+                Opcode::ARR_IDX => {
+                    let arr_reg: u8 = self.get_op()?;
+                    let index_reg: u8 = self.get_op()?;
+                    let elem_size: u32 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let addr: u64 = {
+                        self.reg(arr_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs != rhs);
+                    let index: i64 = {
+                        self.reg(index_reg)?.get()
+                    };
+                    let elem_addr = self.array_index(addr, index, elem_size as u64)?;
+                    self.reg(target_reg)?.set(elem_addr);
                 },
-                Opcode::LTF => {
+                Opcode::ARR_IDX_F => {
+                    let base_reg: u8 = self.get_op()?;
+                    let index_reg: u8 = self.get_op()?;
+                    let elem_size: u32 = self.get_op()?;
+                    let len: u64 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let base_addr: u64 = {
+                        self.reg(base_reg)?.get()
+                    };
+                    let index: i64 = {
+                        self.reg(index_reg)?.get()
+                    };
+                    if index < 0 || index as u64 >= len {
+                        return Err(CoreError::IndexOutOfBounds(index, len));
+                    }
+                    self.reg(target_reg)?.set(base_addr + index as u64 * elem_size as u64);
+                },
+                Opcode::STR_CAT => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs_addr: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: f32 = {
+                    let rhs_addr: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs < rhs);
+                    let target_addr: u64 = {
+                        self.reg(target_reg)?.get()
+                    };
+                    self.collect_garbage_auto()?;
+                    self.string_concat(lhs_addr, rhs_addr, target_addr)?;
                 },
-                Opcode::GTF => {
+                Opcode::STR_CMP => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs_addr: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: f32 = {
+                    let rhs_addr: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs > rhs);
+                    let ordering = self.string_compare(lhs_addr, rhs_addr)?;
+                    self.reg(target_reg)?.set(ordering);
                 },
-                Opcode::LTEQF => {
+                Opcode::STR_FIND => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs_addr: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: f32 = {
+                    let rhs_addr: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs <= rhs);
+                    let position = self.string_find(lhs_addr, rhs_addr)?;
+                    self.reg(target_reg)?.set(position);
                 },
-                Opcode::GTEQF => {
+                Opcode::STR_SLICE => {
+                    let str_reg: u8 = self.get_op()?;
+                    let start_reg: u8 = self.get_op()?;
+                    let end_reg: u8 = self.get_op()?;
+                    let str_addr: u64 = {
+                        self.reg(str_reg)?.get()
+                    };
+                    let start: i64 = {
+                        self.reg(start_reg)?.get()
+                    };
+                    let end: i64 = {
+                        self.reg(end_reg)?.get()
+                    };
+                    self.string_slice(str_addr, start, end)?;
+                },
+                Opcode::STR_SPLIT => {
                     let lhs_reg: u8 = self.get_op()?;
                     let rhs_reg: u8 = self.get_op()?;
                     let target_reg: u8 = self.get_op()?;
-                    let lhs: f32 = {
+                    let lhs_addr: u64 = {
                         self.reg(lhs_reg)?.get()
                     };
-                    let rhs: f32 = {
+                    let rhs_addr: u64 = {
                         self.reg(rhs_reg)?.get()
                     };
-                    self.reg(target_reg)?.set(lhs >= rhs);
+                    self.collect_garbage_auto()?;
+                    let array_addr = self.string_split(lhs_addr, rhs_addr)?;
+                    self.reg(target_reg)?.set(array_addr);
+                },
+                Opcode::STR_FROM_I => {
+                    let value_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let value: i64 = {
+                        self.reg(value_reg)?.get()
+                    };
+                    let target_addr: u64 = {
+                        self.reg(target_reg)?.get()
+                    };
+                    self.collect_garbage_auto()?;
+                    self.string_store(value.to_string().as_bytes(), target_addr)?;
+                },
+                Opcode::STR_FROM_F => {
+                    let value_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let value: f32 = {
+                        self.reg(value_reg)?.get()
+                    };
+                    let target_addr: u64 = {
+                        self.reg(target_reg)?.get()
+                    };
+                    self.collect_garbage_auto()?;
+                    self.string_store(value.to_string().as_bytes(), target_addr)?;
+                },
+                Opcode::STR_FROM_B => {
+                    let value_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let value: bool = {
+                        self.reg(value_reg)?.get()
+                    };
+                    let target_addr: u64 = {
+                        self.reg(target_reg)?.get()
+                    };
+                    self.collect_garbage_auto()?;
+                    self.string_store(value.to_string().as_bytes(), target_addr)?;
+                },
+                Opcode::I2F => {
+                    let value_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let value: i64 = {
+                        self.reg(value_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(value as f32);
+                },
+                Opcode::F2I => {
+                    // Truncates towards zero and saturates at the bounds of i64, NaN becomes 0
+                    let value_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let value: f32 = {
+                        self.reg(value_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(value as i64);
+                },
+                Opcode::I2B => {
+                    let value_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let value: i64 = {
+                        self.reg(value_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(value != 0);
+                },
+                Opcode::B2I => {
+                    let value_reg: u8 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let value: bool = {
+                        self.reg(value_reg)?.get()
+                    };
+                    self.reg(target_reg)?.set(value as i64);
+                },
+                Opcode::ARR_PUSH => {
+                    let arr_reg: u8 = self.get_op()?;
+                    let elem_size: u32 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let addr: u64 = {
+                        self.reg(arr_reg)?.get()
+                    };
+                    self.collect_garbage_auto()?;
+                    let elem_addr = self.array_push(addr, elem_size as u64)?;
+                    self.reg(target_reg)?.set(elem_addr);
+                },
+                Opcode::ARR_POP => {
+                    let arr_reg: u8 = self.get_op()?;
+                    let elem_size: u32 = self.get_op()?;
+                    let target_reg: u8 = self.get_op()?;
+                    let addr: u64 = {
+                        self.reg(arr_reg)?.get()
+                    };
+                    let elem_addr = self.array_pop(addr, elem_size as u64)?;
+                    self.reg(target_reg)?.set(elem_addr);
                 },
                 _ => {
                     return Err(CoreError::UnimplementedOpcode(opcode));
@@ -963,6 +1733,10 @@ impl Core {
                 AddressType::Swap => {
                     &self.swap
                 },
+                AddressType::Heap => {
+                    self.check_heap_access(source_addr, n)?;
+                    &self.heap
+                },
                 _ => return Err(CoreError::Unknown)
             };
             
@@ -994,6 +1768,12 @@ impl Core {
                     self.swap[target_addr + i] = bytes[i];
                 }
             },
+            AddressType::Heap => {
+                self.check_heap_access(target_addr, n)?;
+                for i in 0..n {
+                    self.heap[target_addr + i] = bytes[i];
+                }
+            },
             _ => return Err(CoreError::Unknown)
         };
 
@@ -1022,6 +1802,10 @@ impl Core {
             AddressType::Swap => {
                 &self.swap
             },
+            AddressType::Heap => {
+                self.check_heap_access(source_addr, n)?;
+                &self.heap
+            },
             _ => return Err(CoreError::Unknown)
         };
 
@@ -1078,12 +1862,557 @@ impl Core {
                     program.code[target_addr + i] = data[i];
                 }
             },
+            AddressType::Heap => {
+                self.check_heap_access(target_addr, n)?;
+                for i in 0..n {
+                    self.heap[target_addr + i] = data[i];
+                }
+            },
             _ => return Err(CoreError::Unknown)
         };
 
         Ok(())
     }
 
+    /// Allocates a zeroed block of heap memory and returns its address.
+    /// Freed blocks are reused first-fit before the heap is grown.
+    pub fn heap_alloc(&mut self, size: usize) -> CoreResult<u64> {
+        // Zero sized allocations still need a unique address
+        let size = size.max(1);
+
+        let free_block_opt = self.heap_free_list.iter()
+            .find(|(start, end)| *end - *start >= size)
+            .map(|(start, end)| (*start, *end));
+
+        let start = match free_block_opt {
+            Some((free_start, free_end)) => {
+                self.heap_free_list.remove(&free_start);
+                if free_end - free_start > size {
+                    self.heap_free_list.insert(free_start + size, free_end);
+                }
+                for byte in self.heap[free_start..free_start + size].iter_mut() {
+                    *byte = 0;
+                }
+                free_start
+            },
+            None => {
+                let start = self.heap.len();
+                self.heap.resize(start + size, 0);
+                start
+            }
+        };
+
+        self.heap_pointers.insert(start, start + size);
+        self.gc_allocated += size;
+
+        Ok(
+            Address::new(start as u64, AddressType::Heap).into()
+        )
+    }
+
+    /// Resizes a heap allocation, moving it if it cannot grow in place.
+    /// Returns the (possibly new) address of the allocation.
+    pub fn heap_realloc(&mut self, addr: u64, size: usize) -> CoreResult<u64> {
+        let size = size.max(1);
+        let range = self.get_heap_range(addr)?;
+
+        // Shrink in place
+        if size <= range.len() {
+            self.heap_pointers.insert(range.start, range.start + size);
+            if size < range.len() {
+                self.release_heap_range(range.start + size..range.end);
+            }
+            self.gc_allocated = self.gc_allocated.saturating_sub(range.len() - size);
+            return Ok(addr);
+        }
+
+        let grow_size = size - range.len();
+
+        // Grow in place at the end of the heap
+        if range.end == self.heap.len() {
+            self.heap.resize(range.end + grow_size, 0);
+            self.heap_pointers.insert(range.start, range.start + size);
+            self.gc_allocated += grow_size;
+            return Ok(addr);
+        }
+
+        // Grow in place into an adjacent free block
+        let free_end_opt = self.heap_free_list.get(&range.end)
+            .cloned()
+            .filter(|free_end| free_end - range.end >= grow_size);
+        if let Some(free_end) = free_end_opt {
+            self.heap_free_list.remove(&range.end);
+            if free_end - range.end > grow_size {
+                self.heap_free_list.insert(range.end + grow_size, free_end);
+            }
+            for byte in self.heap[range.end..range.end + grow_size].iter_mut() {
+                *byte = 0;
+            }
+            self.heap_pointers.insert(range.start, range.start + size);
+            self.gc_allocated += grow_size;
+            return Ok(addr);
+        }
+
+        // Move the allocation, which only grows the allocated bytes by the size difference
+        let new_addr = self.heap_alloc(size)?;
+        let new_start = Address::from(new_addr).real_address as usize;
+        self.heap.copy_within(range.clone(), new_start);
+        let dtor_opt = self.heap_destructors.remove(&range.start);
+        self.heap_free(addr)?;
+        self.gc_allocated = self.gc_allocated.saturating_sub(range.len());
+        if let Some(fn_uid) = dtor_opt {
+            self.heap_destructors.insert(new_start, fn_uid);
+        }
+
+        Ok(new_addr)
+    }
+
+    /// Frees a heap allocation
+    pub fn heap_free(&mut self, addr: u64) -> CoreResult<()> {
+        let range = self.get_heap_range(addr)?;
+        self.heap_pointers.remove(&range.start);
+        self.heap_destructors.remove(&range.start);
+        self.release_heap_range(range);
+        Ok(())
+    }
+
+    /// Returns the number of bytes currently allocated on the heap
+    pub fn get_heap_allocated(&self) -> usize {
+        self.heap_pointers.iter()
+            .map(|(start, end)| end - start)
+            .sum()
+    }
+
+    /// Returns the number of live heap allocations
+    pub fn get_heap_allocation_count(&self) -> usize {
+        self.heap_pointers.len()
+    }
+
+    /// Gets the range of the allocation starting at the given heap address
+    fn get_heap_range(&self, addr: u64) -> CoreResult<Range<usize>> {
+        let heap_addr = Address::from(addr);
+        if heap_addr.address_type != AddressType::Heap {
+            return Err(CoreError::InvalidHeapPointer);
+        }
+        let start = heap_addr.real_address as usize;
+        self.heap_pointers.get(&start)
+            .map(|end| start..*end)
+            .ok_or(CoreError::InvalidHeapPointer)
+    }
+
+    /// Gets the range of the allocation containing the given real heap address
+    fn find_heap_range(&self, real_address: usize) -> Option<Range<usize>> {
+        self.heap_pointers.range(..=real_address)
+            .next_back()
+            .filter(|(_, end)| real_address < **end)
+            .map(|(start, end)| *start..*end)
+    }
+
+    /// Puts a range back on the free list, merging adjacent free blocks
+    /// and giving memory at the end of the heap back.
+    fn release_heap_range(&mut self, range: Range<usize>) {
+        let mut start = range.start;
+        let mut end = range.end;
+
+        // Merge with the following block
+        if let Some(next_end) = self.heap_free_list.remove(&end) {
+            end = next_end;
+        }
+
+        // Merge with the preceding block
+        let prev_opt = self.heap_free_list.range(..start)
+            .next_back()
+            .map(|(prev_start, prev_end)| (*prev_start, *prev_end));
+        if let Some((prev_start, prev_end)) = prev_opt {
+            if prev_end == start {
+                start = prev_start;
+            }
+        }
+
+        // Shrink the heap if the last block is free
+        if end == self.heap.len() {
+            self.heap_free_list.remove(&start);
+            self.heap.truncate(start);
+        } else {
+            self.heap_free_list.insert(start, end);
+        }
+    }
+
+    /// Checks that n bytes at a heap address lie in a live allocation
+    fn check_heap_access(&self, start: usize, n: usize) -> CoreResult<()> {
+        let valid = self.heap_pointers.range(..=start)
+            .next_back()
+            .is_some_and(|(_, end)| start + n <= *end);
+        if !valid {
+            return Err(CoreError::HeapOutOfBounds);
+        }
+        Ok(())
+    }
+
+    /// Allocates an empty dynamic array and returns its address.
+    /// Arrays are a heap header of length, capacity and data address;
+    /// the elements live in a separate allocation, so the array address stays stable.
+    pub fn array_new(&mut self) -> CoreResult<u64> {
+        self.heap_alloc(ARRAY_HEADER_SIZE)
+    }
+
+    /// Returns the length of a dynamic array
+    pub fn array_len(&self, addr: u64) -> CoreResult<u64> {
+        self.mem_get((addr, 0))
+    }
+
+    /// Returns the address of an array element, checking the bounds
+    pub fn array_index(&self, addr: u64, index: i64, elem_size: u64) -> CoreResult<u64> {
+        let len = self.array_len(addr)?;
+        if index < 0 || index as u64 >= len {
+            return Err(CoreError::IndexOutOfBounds(index, len));
+        }
+        let data_addr: u64 = self.mem_get((addr, 16))?;
+        Ok(data_addr + index as u64 * elem_size)
+    }
+
+    /// Appends an element to a dynamic array and returns its address
+    pub fn array_push(&mut self, addr: u64, elem_size: u64) -> CoreResult<u64> {
+        let len = self.array_len(addr)?;
+        let capacity: u64 = self.mem_get((addr, 8))?;
+        let mut data_addr: u64 = self.mem_get((addr, 16))?;
+
+        if len == capacity {
+            let new_capacity = (capacity * 2).max(ARRAY_MIN_CAPACITY);
+            let new_size = (new_capacity * elem_size) as usize;
+            data_addr = if capacity == 0 {
+                self.heap_alloc(new_size)?
+            } else {
+                self.heap_realloc(data_addr, new_size)?
+            };
+            self.mem_set((addr, 8), new_capacity)?;
+            self.mem_set((addr, 16), data_addr)?;
+        }
+
+        self.mem_set((addr, 0), len + 1)?;
+
+        Ok(data_addr + len * elem_size)
+    }
+
+    /// Removes the last element of a dynamic array and returns its address.
+    /// The element stays readable until the next push.
+    pub fn array_pop(&mut self, addr: u64, elem_size: u64) -> CoreResult<u64> {
+        let len = self.array_len(addr)?;
+        if len == 0 {
+            return Err(CoreError::EmptyArray);
+        }
+        self.mem_set((addr, 0), len - 1)?;
+        let data_addr: u64 = self.mem_get((addr, 16))?;
+        Ok(data_addr + (len - 1) * elem_size)
+    }
+
+    /// Returns the bytes of the string at the given address.
+    /// Strings are a pair of byte length and data address.
+    pub fn string_bytes(&self, addr: u64) -> CoreResult<Vec<u8>> {
+        let len: u64 = self.mem_get((addr, 0))?;
+        let data_addr: u64 = self.mem_get((addr, 8))?;
+        self.mem_get_n((data_addr, 0), len as usize)
+    }
+
+    /// Concatenates two strings into a new heap allocation,
+    /// storing the resulting string at the target address
+    pub fn string_concat(&mut self, lhs: u64, rhs: u64, target: u64) -> CoreResult<()> {
+        let mut bytes = self.string_bytes(lhs)?;
+        bytes.extend(self.string_bytes(rhs)?);
+        self.string_store(&bytes, target)
+    }
+
+    /// Copies the bytes into a new heap allocation and writes the resulting string to the target address
+    pub fn string_store(&mut self, bytes: &[u8], target: u64) -> CoreResult<()> {
+        let data_addr = self.heap_alloc(bytes.len())?;
+        let start = Address::from(data_addr).real_address as usize;
+        self.heap[start..start + bytes.len()].copy_from_slice(bytes);
+
+        self.mem_set((target, 0), bytes.len() as u64)?;
+        self.mem_set((target, 8), data_addr)
+    }
+
+    /// Compares the contents of two strings, returning -1, 0 or 1
+    pub fn string_compare(&self, lhs: u64, rhs: u64) -> CoreResult<i64> {
+        let lhs_bytes = self.string_bytes(lhs)?;
+        let rhs_bytes = self.string_bytes(rhs)?;
+        Ok(lhs_bytes.cmp(&rhs_bytes) as i64)
+    }
+
+    /// Returns the byte offset of the first occurrence of a substring, or -1
+    pub fn string_find(&self, haystack: u64, needle: u64) -> CoreResult<i64> {
+        let haystack_bytes = self.string_bytes(haystack)?;
+        let needle_bytes = self.string_bytes(needle)?;
+        if needle_bytes.is_empty() {
+            return Ok(0);
+        }
+        let position = haystack_bytes.windows(needle_bytes.len())
+            .position(|window| window == needle_bytes.as_slice());
+        Ok(position.map_or(-1, |position| position as i64))
+    }
+
+    /// Narrows the string at the given address to the byte range start..end.
+    /// The slice shares its data with the original string.
+    pub fn string_slice(&mut self, addr: u64, start: i64, end: i64) -> CoreResult<()> {
+        let len: u64 = self.mem_get((addr, 0))?;
+        if start < 0 || start > end {
+            return Err(CoreError::IndexOutOfBounds(start, len));
+        }
+        if end as u64 > len {
+            return Err(CoreError::IndexOutOfBounds(end, len));
+        }
+        let data_addr: u64 = self.mem_get((addr, 8))?;
+        self.mem_set((addr, 0), (end - start) as u64)?;
+        self.mem_set((addr, 8), data_addr + start as u64)
+    }
+
+    /// Splits a string at every occurrence of a separator.
+    /// Returns a dynamic array of slices into the original string.
+    pub fn string_split(&mut self, addr: u64, separator: u64) -> CoreResult<u64> {
+        let bytes = self.string_bytes(addr)?;
+        let separator_bytes = self.string_bytes(separator)?;
+        let data_addr: u64 = self.mem_get((addr, 8))?;
+
+        let mut parts = Vec::new();
+        let mut part_start = 0;
+        let mut i = 0;
+        while !separator_bytes.is_empty() && i + separator_bytes.len() <= bytes.len() {
+            if bytes[i..i + separator_bytes.len()] == separator_bytes[..] {
+                parts.push(part_start..i);
+                i += separator_bytes.len();
+                part_start = i;
+            } else {
+                i += 1;
+            }
+        }
+        parts.push(part_start..bytes.len());
+
+        let array_addr = self.array_new()?;
+        for part in parts {
+            let slot_addr = self.array_push(array_addr, 16)?;
+            self.mem_set((slot_addr, 0), part.len() as u64)?;
+            self.mem_set((slot_addr, 8), data_addr + part.start as u64)?;
+        }
+        Ok(array_addr)
+    }
+
+    /// Registers the destructor function of a heap allocation.
+    /// The collector calls it before the allocation is reclaimed.
+    pub fn set_heap_destructor(&mut self, addr: u64, fn_uid: u64) -> CoreResult<()> {
+        let range = self.get_heap_range(addr)?;
+        self.heap_destructors.insert(range.start, fn_uid);
+        Ok(())
+    }
+
+    /// Pins a heap allocation as a collector root, e.g. while
+    /// foreign code holds on to its address.
+    pub fn pin_heap_ptr(&mut self, addr: u64) -> CoreResult<()> {
+        self.get_heap_range(addr)?;
+        *self.heap_roots.entry(addr).or_insert(0) += 1;
+        Ok(())
+    }
+
+    /// Releases a heap allocation pinned by pin_heap_ptr
+    pub fn unpin_heap_ptr(&mut self, addr: u64) -> CoreResult<()> {
+        let pin_count = self.heap_roots.get_mut(&addr)
+            .ok_or(CoreError::InvalidHeapPointer)?;
+        *pin_count -= 1;
+        if *pin_count == 0 {
+            self.heap_roots.remove(&addr);
+        }
+        Ok(())
+    }
+
+    /// Sets the amount of bytes allocated after which the collector runs
+    /// automatically. A threshold of 0 disables automatic collection.
+    pub fn set_gc_threshold(&mut self, threshold: usize) {
+        self.gc_threshold = threshold;
+    }
+
+    /// Sets how signed integer arithmetic handles overflow.
+    /// Division by zero always raises an error.
+    pub fn set_arithmetic_mode(&mut self, mode: ArithmeticMode) {
+        self.arithmetic_mode = mode;
+    }
+
+    /// Returns how signed integer arithmetic handles overflow
+    pub fn get_arithmetic_mode(&self) -> ArithmeticMode {
+        self.arithmetic_mode
+    }
+
+    /// Picks the result of a signed integer operation according to the arithmetic mode
+    #[inline]
+    fn int_arith_result(&self, instr_ip: usize, checked: Option<i64>, wrapping: i64, saturating: i64) -> CoreResult<i64> {
+        match self.arithmetic_mode {
+            ArithmeticMode::Checked => checked.ok_or(CoreError::ArithmeticOverflow(instr_ip)),
+            ArithmeticMode::Wrapping => Ok(wrapping),
+            ArithmeticMode::Saturating => Ok(saturating)
+        }
+    }
+
+    /// Makes the running foreign function panic with the given message once it returns
+    pub fn set_foreign_panic(&mut self, msg: String) {
+        self.foreign_panic = Some(msg);
+    }
+
+    /// Returns the statistics of the garbage collector
+    pub fn get_gc_stats(&self) -> GcStats {
+        let mut stats = self.gc_stats;
+        stats.live_objects = self.get_heap_allocation_count();
+        stats.live_bytes = self.get_heap_allocated();
+        stats.heap_size = self.heap.len();
+        stats.allocated_since_collection = self.gc_allocated;
+        stats
+    }
+
+    /// Runs a mark & sweep collection over the heap and returns the number
+    /// of reclaimed allocations. Roots are taken conservatively from the stack,
+    /// the registers, the swap space and pinned heap pointers.
+    pub fn collect_garbage(&mut self) -> CoreResult<usize> {
+        // Destructors must not start a collection of their own
+        if self.gc_running {
+            return Err(CoreError::CollectionRunning);
+        }
+        self.gc_running = true;
+        let collect_res = self.mark_and_sweep();
+        self.gc_running = false;
+        self.gc_allocated = 0;
+        collect_res
+    }
+
+    /// Collects garbage if enough memory was allocated since the last collection
+    fn collect_garbage_auto(&mut self) -> CoreResult<()> {
+        if self.gc_threshold > 0 && self.gc_allocated >= self.gc_threshold && !self.gc_running {
+            self.collect_garbage()?;
+        }
+        Ok(())
+    }
+
+    fn mark_and_sweep(&mut self) -> CoreResult<usize> {
+        // Start addresses of the reachable allocations
+        let mut marked = HashSet::new();
+        let mut worklist = Vec::new();
+
+        // Mark everything reachable from the roots
+        let sp_real = self.get_stack_size();
+        self.mark_heap_refs(&self.stack[..sp_real], &mut marked, &mut worklist);
+        self.mark_heap_refs(&self.swap, &mut marked, &mut worklist);
+        for register in self.registers.iter() {
+            self.mark_heap_ref(register.get(), &mut marked, &mut worklist);
+        }
+        for addr in self.heap_roots.keys() {
+            self.mark_heap_ref(*addr, &mut marked, &mut worklist);
+        }
+        if let Some(program) = self.program.as_ref() {
+            for range in program.static_pointers.values() {
+                self.mark_heap_refs(&program.code[range.clone()], &mut marked, &mut worklist);
+            }
+        }
+        while let Some(range) = worklist.pop() {
+            self.mark_heap_refs(&self.heap[range], &mut marked, &mut worklist);
+        }
+
+        let garbage: Vec<u64> = self.heap_pointers.keys()
+            .filter(|start| !marked.contains(*start))
+            .map(|start| Address::new(*start as u64, AddressType::Heap).into())
+            .collect();
+
+        // Run all destructors before reclaiming anything,
+        // so destructors can still access other garbage
+        for addr in garbage.iter() {
+            let start = Address::from(*addr).real_address as usize;
+            if let Some(fn_uid) = self.heap_destructors.get(&start).cloned() {
+                self.run_destructor(*addr, fn_uid)?;
+            }
+        }
+
+        let mut objects_freed = 0;
+        let mut bytes_freed = 0;
+        for addr in garbage.iter() {
+            // Destructors may have freed the allocation already
+            if let Ok(range) = self.get_heap_range(*addr) {
+                bytes_freed += range.len();
+                objects_freed += 1;
+                self.heap_free(*addr)?;
+            }
+        }
+
+        self.gc_stats.collections += 1;
+        self.gc_stats.objects_freed += objects_freed;
+        self.gc_stats.bytes_freed += bytes_freed;
+
+        Ok(objects_freed)
+    }
+
+    /// Marks all allocations referenced by 8 byte values in the given memory
+    fn mark_heap_refs(&self, bytes: &[u8], marked: &mut HashSet<usize>, worklist: &mut Vec<Range<usize>>) {
+        for window in bytes.windows(8) {
+            let raw = u64::from_le_bytes([
+                window[0], window[1], window[2], window[3],
+                window[4], window[5], window[6], window[7]
+            ]);
+            self.mark_heap_ref(raw, marked, worklist);
+        }
+    }
+
+    /// Marks the allocation a value points into, if it is a heap address
+    fn mark_heap_ref(&self, raw: u64, marked: &mut HashSet<usize>, worklist: &mut Vec<Range<usize>>) {
+        if raw >> 61 != 2 {
+            return;
+        }
+        let real_address = Address::from(raw).real_address as usize;
+        if let Some(range) = self.find_heap_range(real_address) {
+            if marked.insert(range.start) {
+                worklist.push(range);
+            }
+        }
+    }
+
+    /// Calls a destructor with the given address as "this",
+    /// leaving the state of the running program untouched.
+    fn run_destructor(&mut self, addr: u64, fn_uid: u64) -> CoreResult<()> {
+        let args = serialize(&addr)
+            .map_err(|_| CoreError::OperatorSerialize)?;
+        self.run_nested(fn_uid, &args)?;
+        Ok(())
+    }
+
+    /// Calls a function value with its environment pointer and the serialized arguments,
+    /// leaving the state of the running program untouched. Returns the R0 register.
+    pub fn call_fn_value(&mut self, fn_uid: u64, env: u64, args: &[u8]) -> CoreResult<Register> {
+        let mut env_args = serialize(&env)
+            .map_err(|_| CoreError::OperatorSerialize)?;
+        env_args.extend_from_slice(args);
+        self.run_nested(fn_uid, &env_args)
+    }
+
+    /// Runs a function on top of the current stack with the given raw arguments
+    /// and restores the registers and the call stack afterwards
+    fn run_nested(&mut self, fn_uid: u64, args: &[u8]) -> CoreResult<Register> {
+        let sp_real = Address::from(self.sp.get::<u64>()).real_address as usize;
+        if sp_real + args.len() > self.stack.len() {
+            return Err(CoreError::InvalidStackPointer);
+        }
+
+        let registers = self.registers;
+        let ip = self.ip;
+        let sp = self.sp;
+        let call_stack = std::mem::take(&mut self.call_stack);
+
+        self.stack[sp_real..sp_real + args.len()].copy_from_slice(args);
+        self.sp.inc(args.len());
+
+        let run_res = self.run_fn(fn_uid);
+        let ret = self.registers[0];
+
+        self.registers = registers;
+        self.ip = ip;
+        self.sp = sp;
+        self.call_stack = call_stack;
+
+        run_res.map(|_| ret)
+    }
+
     #[inline]
     pub fn reg(&mut self, reg: u8) -> CoreResult<&mut Register> {
         if reg == 16 {
@@ -1103,6 +2432,11 @@ impl Core {
     #[inline]
     fn call(&mut self) -> CoreResult<()> {
         let fn_uid: u64 = self.get_op()?;
+        self.call_uid(fn_uid)
+    }
+
+    /// Calls the function with the given uid
+    fn call_uid(&mut self, fn_uid: u64) -> CoreResult<()> {
         if self.foreign_function_uids.contains(&fn_uid) {
             return self.call_foreign_fn(fn_uid);
         }
@@ -1139,9 +2473,10 @@ impl Core {
     pub fn insert_foreign_ptr<T>(&mut self, item: Arc<Mutex<T>>) -> CoreResult<u64> {
         let mut uid_gen = UIDGenerator::new();
 
-        let mut addr = Address::new(uid_gen.generate(), AddressType::Foreign);
+        // The address has to leave the 3 type bits free
+        let mut addr = Address::new(uid_gen.generate() >> 3, AddressType::Foreign);
         while self.foreign_pointers.contains_key(&addr.raw_address) {
-            addr = Address::new(uid_gen.generate(), AddressType::Foreign);
+            addr = Address::new(uid_gen.generate() >> 3, AddressType::Foreign);
         }
 
         let ptr = addr.into();
@@ -1170,6 +2505,21 @@ impl Core {
     }
 
     fn call_foreign_fn(&mut self, uid: u64) -> CoreResult<()> {
+        // Non-primitive return values are placed on top of the stack, zeroed until the function sets them
+        let ret_size = {
+            self.program.as_ref()
+                .ok_or(CoreError::NoProgram)?
+                .foreign_functions
+                .get(&uid)
+                .ok_or(CoreError::UnknownFunctionUid)?
+                .get_ret_size()
+        };
+        let sp_real = Address::from(self.sp.get::<u64>()).real_address as usize;
+        if sp_real + ret_size > self.stack.len() {
+            return Err(CoreError::StackOverflow);
+        }
+        self.stack[sp_real..sp_real + ret_size].fill(0);
+
         let function = {
             self.program.as_mut()
                 .ok_or(CoreError::NoProgram)?
@@ -1190,6 +2540,12 @@ impl Core {
             .foreign_functions
             .insert(uid, function);
 
+        if let Some(msg) = self.foreign_panic.take() {
+            return Err(CoreError::Panic(msg));
+        }
+
+        self.sp.inc(ret_size);
+
         Ok(())
     }
 
@@ -92,7 +92,59 @@ pub enum Opcode {
     LTF = 67,
     GTF = 68,
     LTEQF = 69,
-    GTEQF = 70
+    GTEQF = 70,
+    ALLOC = 71,
+    ALLOC_I = 72,
+    REALLOC = 73,
+    FREE = 74,
+    DTOR = 75,
+    ARR_NEW = 76,
+    ARR_LEN = 77,
+    ARR_IDX = 78,
+    ARR_PUSH = 79,
+    ARR_POP = 80,
+    ARR_IDX_F = 81,
+    STR_CAT = 82,
+    STR_CMP = 83,
+    STR_FIND = 84,
+    STR_SLICE = 85,
+    STR_SPLIT = 86,
+    STR_FROM_I = 87,
+    STR_FROM_F = 88,
+    STR_FROM_B = 89,
+    CALL_R = 90,
+    I2F = 91,
+    F2I = 92,
+    I2B = 93,
+    B2I = 94,
+    MODI = 95,
+    ANDI = 96,
+    ORI = 97,
+    XORI = 98,
+    SHLI = 99,
+    SHRI = 100,
+    MODI_I = 101,
+    ANDI_I = 102,
+    ORI_I = 103,
+    XORI_I = 104,
+    SHLI_I = 105,
+    SHRI_I = 106,
+    MODU = 107,
+    ANDU = 108,
+    ORU = 109,
+    XORU = 110,
+    SHLU = 111,
+    SHRU = 112,
+    MODU_I = 113,
+    ANDU_I = 114,
+    ORU_I = 115,
+    XORU_I = 116,
+    SHLU_I = 117,
+    SHRU_I = 118,
+    NOTI = 119,
+    TRY = 120,
+    TRY_END = 121,
+    PANIC = 122
 }
 
 impl TryFrom<u8> for Opcode {
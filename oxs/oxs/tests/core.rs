@@ -126,4 +126,389 @@ fn test_core_foreign_ptr() {
         let int = int_arc.lock().unwrap();
         assert_eq!(int.0, 10);
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_core_heap_alloc_free() {
+    let mut core = Core::new(1024);
+
+    let a = core.heap_alloc(16).unwrap();
+    let b = core.heap_alloc(8).unwrap();
+    assert_ne!(a, b);
+    assert_eq!(core.get_heap_allocated(), 24);
+
+    core.mem_set((b, 0), 42i64).unwrap();
+    assert_eq!(core.mem_get::<i64>((b, 0)).unwrap(), 42);
+
+    // Freed blocks are reused
+    core.heap_free(a).unwrap();
+    let c = core.heap_alloc(8).unwrap();
+    assert_eq!(a, c);
+    assert_eq!(core.get_heap_allocation_count(), 2);
+
+    // Realloc keeps the contents
+    let b = core.heap_realloc(b, 64).unwrap();
+    assert_eq!(core.mem_get::<i64>((b, 0)).unwrap(), 42);
+
+    // Access outside of an allocation fails
+    assert!(core.mem_get::<i64>((c, 8)).is_err());
+    assert!(core.heap_free(c + 1).is_err());
+
+    core.heap_free(b).unwrap();
+    core.heap_free(c).unwrap();
+    assert_eq!(core.get_heap_allocated(), 0);
+}
+
+#[test]
+fn test_core_heap_many_allocations() {
+    let mut core = Core::new(1024);
+
+    let addrs: Vec<u64> = (0..10000).map(|_| core.heap_alloc(16).unwrap()).collect();
+    for (i, addr) in addrs.iter().enumerate() {
+        core.mem_set((*addr, 8), i as i64).unwrap();
+    }
+    for (i, addr) in addrs.iter().enumerate() {
+        assert_eq!(core.mem_get::<i64>((*addr, 8)).unwrap(), i as i64);
+    }
+
+    // Neighbouring free blocks are merged and reused as one
+    for addr in addrs[..100].iter() {
+        core.heap_free(*addr).unwrap();
+    }
+    let merged = core.heap_alloc(1600).unwrap();
+    assert_eq!(merged, addrs[0]);
+
+    // Freeing everything gives the heap back
+    for addr in addrs[100..].iter().rev() {
+        core.heap_free(*addr).unwrap();
+    }
+    core.heap_free(merged).unwrap();
+    assert_eq!(core.get_heap_allocation_count(), 0);
+    assert_eq!(core.get_gc_stats().heap_size, 0);
+}
+
+#[test]
+fn test_core_heap_realloc_accounting() {
+    let mut core = Core::new(1024);
+
+    let mut addr = core.heap_alloc(16).unwrap();
+    let _blocker = core.heap_alloc(8).unwrap();
+    assert_eq!(core.get_gc_stats().allocated_since_collection, 24);
+
+    // Growing and shrinking again does not inflate the allocated bytes
+    for _ in 0..10 {
+        addr = core.heap_realloc(addr, 256).unwrap();
+        addr = core.heap_realloc(addr, 16).unwrap();
+    }
+    assert_eq!(core.get_gc_stats().allocated_since_collection, 24);
+
+    // The counter follows the size difference in both directions
+    addr = core.heap_realloc(addr, 8).unwrap();
+    assert_eq!(core.get_gc_stats().allocated_since_collection, 16);
+    addr = core.heap_realloc(addr, 32).unwrap();
+    assert_eq!(core.get_gc_stats().allocated_since_collection, 40);
+    assert_eq!(core.get_heap_allocated(), 40);
+    core.heap_free(addr).unwrap();
+}
+
+#[test]
+fn test_core_alloc_opcodes() {
+    let mut builder = Builder::new();
+
+    let alloc_instr = Instruction::new(Opcode::ALLOC_I) // ALLOC_I 8, r1
+        .with_operand(8u64)
+        .with_operand(1u8);
+    let ldi_instr = Instruction::new(Opcode::LDI) // LDI 7, r2
+        .with_operand(7i64)
+        .with_operand(2u8);
+    let mov_instr = Instruction::new(Opcode::MOVI_RA) // MOVI r2, [r1]
+        .with_operand(2u8)
+        .with_operand(1u8)
+        .with_operand::<i16>(0);
+    let lda_instr = Instruction::new(Opcode::LDA) // LDA 32, r3
+        .with_operand(32u64)
+        .with_operand(3u8);
+    let realloc_instr = Instruction::new(Opcode::REALLOC) // REALLOC r1, r3, r1
+        .with_operand(1u8)
+        .with_operand(3u8)
+        .with_operand(1u8);
+    let load_instr = Instruction::new(Opcode::MOVI_AR) // MOVI [r1], r0
+        .with_operand(1u8)
+        .with_operand::<i16>(0)
+        .with_operand(0u8);
+    let free_instr = Instruction::new(Opcode::FREE) // FREE r1
+        .with_operand(1u8);
+
+    builder.push_instr(alloc_instr);
+    builder.push_instr(ldi_instr);
+    builder.push_instr(mov_instr);
+    builder.push_instr(lda_instr);
+    builder.push_instr(realloc_instr);
+    builder.push_instr(load_instr);
+    builder.push_instr(free_instr);
+
+    let program = Program::new().with_code(builder.build());
+
+    let mut core = Core::new(1024);
+    core.load_program(program);
+    let run_res = core.run();
+    assert!(run_res.is_ok());
+    assert_eq!(core.reg(0).unwrap().get::<i64>(), 7);
+    assert_eq!(core.get_heap_allocation_count(), 0);
+}
+
+#[test]
+fn test_core_conversions() {
+    let mut builder = Builder::new();
+
+    let ldf_instr = Instruction::new(Opcode::LDF) // LDF -2.75, r0
+        .with_operand(-2.75f32)
+        .with_operand(0u8);
+    let f2i_instr = Instruction::new(Opcode::F2I) // F2I r0, r1
+        .with_operand(0u8)
+        .with_operand(1u8);
+    let i2f_instr = Instruction::new(Opcode::I2F) // I2F r1, r2
+        .with_operand(1u8)
+        .with_operand(2u8);
+    let i2b_instr = Instruction::new(Opcode::I2B) // I2B r1, r3
+        .with_operand(1u8)
+        .with_operand(3u8);
+    let b2i_instr = Instruction::new(Opcode::B2I) // B2I r3, r4
+        .with_operand(3u8)
+        .with_operand(4u8);
+
+    builder.push_instr(ldf_instr);
+    builder.push_instr(f2i_instr);
+    builder.push_instr(i2f_instr);
+    builder.push_instr(i2b_instr);
+    builder.push_instr(b2i_instr);
+
+    let program = Program::new().with_code(builder.build());
+
+    let mut core = Core::new(1024);
+    core.load_program(program);
+    let run_res = core.run();
+    assert!(run_res.is_ok());
+    assert_eq!(core.reg(1).unwrap().get::<i64>(), -2);
+    assert_eq!(core.reg(2).unwrap().get::<f32>(), -2.0);
+    assert!(core.reg(3).unwrap().get::<bool>());
+    assert_eq!(core.reg(4).unwrap().get::<i64>(), 1);
+}
+
+#[test]
+fn test_core_bitwise() {
+    let mut builder = Builder::new();
+
+    let ldi_instr0 = Instruction::new(Opcode::LDI) // LDI 29, r0
+        .with_operand(29i64)
+        .with_operand(0u8);
+    let ldi_instr1 = Instruction::new(Opcode::LDI) // LDI 12, r1
+        .with_operand(12i64)
+        .with_operand(1u8);
+    let modi_instr = Instruction::new(Opcode::MODI) // MODI r0, r1, r2
+        .with_operand(0u8)
+        .with_operand(1u8)
+        .with_operand(2u8);
+    let shli_instr = Instruction::new(Opcode::SHLI_I) // SHLI_I r2, 4, r3
+        .with_operand(2u8)
+        .with_operand(4i64)
+        .with_operand(3u8);
+    let xori_instr = Instruction::new(Opcode::XORI) // XORI r3, r0, r4
+        .with_operand(3u8)
+        .with_operand(0u8)
+        .with_operand(4u8);
+    let noti_instr = Instruction::new(Opcode::NOTI) // NOTI r4, r5
+        .with_operand(4u8)
+        .with_operand(5u8);
+    let shru_instr = Instruction::new(Opcode::SHRU_I) // SHRU_I r5, 60, r6
+        .with_operand(5u8)
+        .with_operand(60u64)
+        .with_operand(6u8);
+
+    builder.push_instr(ldi_instr0);
+    builder.push_instr(ldi_instr1);
+    builder.push_instr(modi_instr);
+    builder.push_instr(shli_instr);
+    builder.push_instr(xori_instr);
+    builder.push_instr(noti_instr);
+    builder.push_instr(shru_instr);
+
+    let program = Program::new().with_code(builder.build());
+
+    let mut core = Core::new(1024);
+    core.load_program(program);
+    let run_res = core.run();
+    assert!(run_res.is_ok());
+    assert_eq!(core.reg(2).unwrap().get::<i64>(), 5);
+    assert_eq!(core.reg(3).unwrap().get::<i64>(), 80);
+    assert_eq!(core.reg(4).unwrap().get::<i64>(), 80 ^ 29);
+    assert_eq!(core.reg(5).unwrap().get::<i64>(), !(80 ^ 29));
+    assert_eq!(core.reg(6).unwrap().get::<u64>(), 15);
+}
+
+#[test]
+fn test_core_arithmetic_traps() {
+    let mut builder = Builder::new();
+
+    let ldi_instr0 = Instruction::new(Opcode::LDI) // LDI i64::MAX, r0
+        .with_operand(i64::MAX)
+        .with_operand(0u8);
+    let addi_instr = Instruction::new(Opcode::ADDI_I) // ADDI_I r0, 1, r1
+        .with_operand(0u8)
+        .with_operand(1i64)
+        .with_operand(1u8);
+    let ldi_instr1 = Instruction::new(Opcode::LDI) // LDI 0, r2
+        .with_operand(0i64)
+        .with_operand(2u8);
+    let divi_instr = Instruction::new(Opcode::DIVI) // DIVI r0, r2, r3
+        .with_operand(0u8)
+        .with_operand(2u8)
+        .with_operand(3u8);
+
+    builder.push_instr(ldi_instr0);
+    builder.push_instr(addi_instr);
+    // Without the division, the program runs through and the result is kept
+    let overflow_code = builder.clone().build();
+    builder.push_instr(ldi_instr1);
+    builder.push_instr(divi_instr);
+    let code = builder.build();
+
+    let mut core = Core::new(1024);
+    core.load_program(Program::new().with_code(overflow_code.clone()));
+    core.set_arithmetic_mode(ArithmeticMode::Wrapping);
+    assert!(core.run().is_ok());
+    assert_eq!(core.reg(1).unwrap().get::<i64>(), i64::MIN);
+
+    core.load_program(Program::new().with_code(overflow_code));
+    core.set_arithmetic_mode(ArithmeticMode::Saturating);
+    assert!(core.run().is_ok());
+    assert_eq!(core.reg(1).unwrap().get::<i64>(), i64::MAX);
+
+    core.load_program(Program::new().with_code(code.clone()));
+    core.set_arithmetic_mode(ArithmeticMode::Wrapping);
+    match core.run() {
+        Err(CoreError::DivisionByZero(ip)) => assert_eq!(ip, 31),
+        other => panic!("Unexpected result: {:?}", other)
+    }
+
+    core.load_program(Program::new().with_code(code));
+    core.set_arithmetic_mode(ArithmeticMode::Checked);
+    match core.run() {
+        Err(CoreError::ArithmeticOverflow(ip)) => assert_eq!(ip, 10),
+        other => panic!("Unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn test_core_shift_traps() {
+    let mut builder = Builder::new();
+
+    let ldi_instr0 = Instruction::new(Opcode::LDI) // LDI 1, r0
+        .with_operand(1i64)
+        .with_operand(0u8);
+    let ldi_instr1 = Instruction::new(Opcode::LDI) // LDI 70, r1
+        .with_operand(70i64)
+        .with_operand(1u8);
+    let shli_instr = Instruction::new(Opcode::SHLI) // SHLI r0, r1, r2
+        .with_operand(0u8)
+        .with_operand(1u8)
+        .with_operand(2u8);
+    let shri_instr = Instruction::new(Opcode::SHRI_I) // SHRI_I r0, -1, r3
+        .with_operand(0u8)
+        .with_operand(-1i64)
+        .with_operand(3u8);
+
+    builder.push_instr(ldi_instr0);
+    builder.push_instr(ldi_instr1);
+    builder.push_instr(shli_instr);
+    builder.push_instr(shri_instr);
+    let code = builder.build();
+
+    let mut core = Core::new(1024);
+    core.load_program(Program::new().with_code(code.clone()));
+    core.set_arithmetic_mode(ArithmeticMode::Wrapping);
+    assert!(core.run().is_ok());
+    assert_eq!(core.reg(2).unwrap().get::<i64>(), 1 << 6);
+    assert_eq!(core.reg(3).unwrap().get::<i64>(), 0);
+
+    core.load_program(Program::new().with_code(code.clone()));
+    core.set_arithmetic_mode(ArithmeticMode::Saturating);
+    assert!(core.run().is_ok());
+    assert_eq!(core.reg(2).unwrap().get::<i64>(), i64::MAX);
+    assert_eq!(core.reg(3).unwrap().get::<i64>(), 1);
+
+    core.load_program(Program::new().with_code(code));
+    core.set_arithmetic_mode(ArithmeticMode::Checked);
+    match core.run() {
+        Err(CoreError::ArithmeticOverflow(ip)) => assert_eq!(ip, 20),
+        other => panic!("Unexpected result: {:?}", other)
+    }
+
+    assert_eq!(ArithmeticMode::Checked.shl(1, 62), Some(1 << 62));
+    assert_eq!(ArithmeticMode::Checked.shl(1, 63), None);
+    assert_eq!(ArithmeticMode::Checked.shl(-1, 63), Some(i64::MIN));
+    assert_eq!(ArithmeticMode::Checked.shr(-8, 64), None);
+    assert_eq!(ArithmeticMode::Saturating.shl(-3, 62), Some(i64::MIN));
+    assert_eq!(ArithmeticMode::Saturating.shr(-8, 100), Some(-1));
+}
+
+#[test]
+fn test_core_collect_garbage() {
+    let mut core = Core::new(1024);
+
+    let outer = core.heap_alloc(8).unwrap();
+    let inner = core.heap_alloc(16).unwrap();
+    let pinned = core.heap_alloc(4).unwrap();
+    core.heap_alloc(32).unwrap();
+
+    // outer is rooted on the stack and keeps inner alive
+    core.mem_set((outer, 0), inner).unwrap();
+    core.push_stack(outer).unwrap();
+    core.pin_heap_ptr(pinned).unwrap();
+
+    assert_eq!(core.collect_garbage().unwrap(), 1);
+    assert_eq!(core.get_heap_allocation_count(), 3);
+
+    core.pop_stack::<u64>().unwrap();
+    assert_eq!(core.collect_garbage().unwrap(), 2);
+
+    core.unpin_heap_ptr(pinned).unwrap();
+    assert_eq!(core.collect_garbage().unwrap(), 1);
+
+    let stats = core.get_gc_stats();
+    assert_eq!(stats.collections, 3);
+    assert_eq!(stats.objects_freed, 4);
+    assert_eq!(stats.bytes_freed, 60);
+    assert_eq!(stats.live_objects, 0);
+    assert_eq!(stats.heap_size, 0);
+}
+
+#[test]
+fn test_core_collect_garbage_foreign_ptr() {
+    use std::sync::{
+        Arc,
+        Mutex
+    };
+
+    let mut core = Core::new(1024);
+
+    // The host keeps heap addresses in its own containers and pins them
+    let held = core.heap_alloc(8).unwrap();
+    core.heap_alloc(16).unwrap();
+    core.pin_heap_ptr(held).unwrap();
+    let host_object = Arc::new(Mutex::new(vec![held]));
+    let foreign_ptr = core.insert_foreign_ptr(host_object.clone()).unwrap();
+
+    // Collection does not depend on the lock of the host object
+    {
+        let _guard = host_object.lock().unwrap();
+        assert_eq!(core.collect_garbage().unwrap(), 1);
+    }
+    assert_eq!(core.get_heap_allocation_count(), 1);
+
+    let host_object = core.remove_foreign_ptr::<Vec<u64>>(foreign_ptr).unwrap();
+    for addr in host_object.lock().unwrap().drain(..) {
+        core.unpin_heap_ptr(addr).unwrap();
+    }
+    assert_eq!(core.collect_garbage().unwrap(), 1);
+    assert_eq!(core.get_heap_allocation_count(), 0);
+}
@@ -1,20 +1,38 @@
 extern crate oxs;
 use oxs::{
     codegen::{
-        compiler::Compiler,
+        compiler::{
+            Compiler,
+            CompilerError
+        },
         register::Register
     },
     parser::{
         parser::Parser,
         ast::Type
     },
-    engine::Engine,
+    engine::{
+        Engine,
+        EngineError
+    },
+    vm::core::{
+        CoreError,
+        ArithmeticMode
+    },
     api::{
         module::Module,
-        function::Function,
+        function::{
+            Function,
+            Callback
+        },
         adapter::Adapter
     }
 };
+
+use std::sync::{
+    Arc,
+    Mutex
+};
 /*
 #[test]
 fn test_engine_simple_function() {
@@ -429,4 +447,2343 @@ fn test_engine_member_call() {
     assert_eq!(engine.get_stack_size(), 0);
     //println!("{:?}", run_res);
     assert!(run_res.is_ok());
-}
\ No newline at end of file
+}
+#[test]
+fn test_engine_for() {
+    let code = String::from("
+        fn: main() ~ int {
+            var x = 0;
+            for i in 0..10 {
+                if i == 3 {
+                    continue;
+                } else if i == 8 {
+                    break;
+                }
+                x += i;
+            }
+            for j in 1..=9 by 4 {
+                x += j * 100;
+            }
+            return x;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(25 + 1500, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_for_negative_step() {
+    let code = String::from("
+        fn: main() ~ int {
+            var x = 0;
+            for i in 5..0 by -1 {
+                x = x * 10 + i;
+            }
+            var step = -3;
+            for j in 9..=0 by step {
+                x = x * 10 + j;
+            }
+            return x;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(543219630, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_for_zero_step() {
+    let code = String::from("
+        fn: main() ~ int {
+            var x = 0;
+            for i in 0..10 by 0 {
+                x += i;
+            }
+            return x;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::ZeroForStep) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+
+    let code = String::from("
+        fn: main() ~ int {
+            var x = 0;
+            var step = 0;
+            for i in 0..10 by step {
+                x += i;
+            }
+            return x;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::Panic(msg) => assert_eq!("for loop step must not be zero", msg),
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a panic")
+    }
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_loop_block() {
+    let code = String::from("
+        fn: main() ~ int {
+            var x = 0;
+            loop {
+                var y = 2;
+                x += y;
+                if x < 6 {
+                    continue;
+                }
+                {
+                    var z = 100;
+                    x += z;
+                }
+                if x > 200 {
+                    break;
+                }
+            }
+            {
+                var w = 3;
+                x += w;
+            }
+            return x;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(211, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_box() {
+    let code = String::from("
+        cont: Vector {
+            x: float;
+            y: float;
+        }
+
+        fn: make_vector(x: float) ~ &Vector {
+            var vec = Vector {
+                x: x,
+                y: 2.0
+            };
+            return box vec;
+        }
+
+        fn: main() ~ float {
+            var vec = make_vector(3.0);
+            vec.x += 1.0;
+            var n = box 5;
+            if ~n == 5 {
+                vec.y *= 2.0;
+            }
+            return vec.x + vec.y;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<f32>(Register::R0);
+    assert_eq!(8.0, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_collect_garbage() {
+    let code = String::from("
+        cont: Node {
+            value: int;
+        }
+
+        fn: make_node(value: int) ~ &Node {
+            var node = Node {
+                value: value
+            };
+            return box node;
+        }
+
+        fn: main() ~ int {
+            var kept = make_node(1000);
+            var i = 0;
+            while i < 100 {
+                var node = make_node(1);
+                i += 1;
+            }
+            return kept.value;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    engine.set_gc_threshold(64);
+
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(1000, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+
+    // The kept node survived all automatic collections
+    let stats = engine.get_gc_stats();
+    assert!(stats.collections > 0);
+    assert!(stats.objects_freed > 0);
+
+    // Only stale registers can still reference a node
+    engine.collect_garbage().unwrap();
+    let stats = engine.get_gc_stats();
+    assert_eq!(stats.live_objects + stats.objects_freed, 101);
+    assert!(stats.objects_freed >= 100);
+}
+
+#[test]
+fn test_engine_array() {
+    let code = String::from("
+        fn: sum(values: [int]) ~ int {
+            var total = 0;
+            for i in 0..values.len() {
+                total += values[i];
+            }
+            return total;
+        }
+
+        fn: main() ~ int {
+            var values = [1, 2, 3];
+            values.push(4);
+            values[0] = 10;
+            values[1] += 5;
+            var last = values.pop();
+            var floats: [float] = [];
+            for i in 0..20 {
+                floats.push(0.5);
+            }
+            return sum(values) * 100 + last + floats.len();
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(2000 + 4 + 20, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_array_out_of_bounds() {
+    let code = String::from("
+        fn: main() ~ int {
+            var values = [1, 2, 3];
+            return values[3];
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::CoreError(CoreError::IndexOutOfBounds(3, 3)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected an out of bounds error")
+    }
+}
+
+#[test]
+fn test_engine_container_array() {
+    let code = String::from("
+        cont: Stack<T> {
+            items: [T];
+        }
+
+        impl: Stack<T> {
+            fn: push(&this, value: T) {
+                items.push(value);
+            }
+            fn: pop(&this) ~ T {
+                return items.pop();
+            }
+            fn: size(&this) ~ int {
+                return items.len();
+            }
+        }
+
+        cont: Shelf {
+            label: string;
+            stack: Stack<int>;
+        }
+
+        fn: main() ~ int {
+            var stack = Stack {
+                items: [1]
+            };
+            stack.push(2);
+            stack.push(3);
+            var top = stack.pop();
+            var shelf = Shelf {
+                label: \"books\",
+                stack: stack
+            };
+            shelf.stack.items.push(9);
+            return top * 1000 + shelf.stack.items.len() * 100 + shelf.label.len() * 10 + shelf.stack.items[2];
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(3000 + 300 + 50 + 9, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_fixed_array() {
+    let code = String::from("
+        cont: Table {
+            offset: int;
+            values: [int; 4];
+        }
+
+        fn: main() ~ int {
+            var primes: [int; 4] = [2, 3, 5, 7];
+            var squares = [0; 5];
+            for i in 0..5 {
+                squares[i] = i * i;
+            }
+            var grid = [[1; 3]; 2];
+            grid[1][2] = 40;
+            var table = Table {
+                offset: 100,
+                values: [1, 2, 3, 4]
+            };
+            table.values[3] += primes[3];
+            return primes[0] + squares[4] + grid[1][2] + grid[0][1] + table.values[3] + table.offset;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(2 + 16 + 40 + 1 + 11 + 100, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_large_fixed_array() {
+    let code = String::from("
+        fn: main() ~ int {
+            var values: [int; 4000] = [7; 4000];
+            values[3999] += 1;
+            return values[0] + values[2048] + values[3999];
+        }
+    ");
+
+    let mut engine = Engine::new(65536);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(7 + 7 + 8, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+
+    // Arrays beyond the 16 bit stack offsets are rejected
+    let code = String::from("
+        fn: main() ~ int {
+            var values: [int; 5000] = [7; 5000];
+            return values[0];
+        }
+    ");
+
+    let mut engine = Engine::new(65536);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::ArrayTooLarge(40000)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_fixed_array_bounds() {
+    let code = String::from("
+        fn: main() ~ int {
+            var values = [0; 4];
+            return values[4];
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::IndexOutOfBounds(4, 4)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+
+    let code = String::from("
+        fn: main() ~ int {
+            var values = [0; 4];
+            var i = 2;
+            return values[i * 2];
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::CoreError(CoreError::IndexOutOfBounds(4, 4)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected an out of bounds error")
+    }
+}
+
+#[test]
+fn test_engine_tuple() {
+    let code = String::from("
+        fn: divide(a: int, b: int) ~ (int, bool) {
+            if b == 0 {
+                return (0, false);
+            }
+            return (a / b, true);
+        }
+
+        fn: main() ~ int {
+            var (quotient, ok) = divide(84, 2);
+            var failed = divide(1, 0);
+            var pair = (1.5, (10, 20));
+            pair.1.0 += 5;
+            var x = 0;
+            if ok && !failed.1 {
+                x = divide(9, 3).0 + quotient + pair.1.0;
+            }
+            return x;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(42 + 15 + 3, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_string_ops() {
+    let code = String::from("
+        fn: greet(name: string) ~ string {
+            return \"Hello, \" + name;
+        }
+
+        fn: main() ~ int {
+            var greeting = greet(\"World\");
+            test::record(greeting + \"!\");
+            var score = 0;
+            if greeting == \"Hello, World\" {
+                score += 1;
+            }
+            if greeting != \"Hello\" {
+                score += 2;
+            }
+            if \"apple\" < \"banana\" && \"b\" >= \"a\" {
+                score += 4;
+            }
+            if greeting[7..] == \"World\" && greeting[..5] == \"Hello\" && greeting[1..3] == \"el\" {
+                score += 8;
+            }
+            if greeting.contains(\"lo, W\") && greeting.starts_with(\"Hell\") && !greeting.contains(\"xyz\") {
+                score += 16;
+            }
+            var parts = \"a,bb,,ccc\".split(\",\");
+            if parts.len() == 4 && parts[1] == \"bb\" && parts[2].len() == 0 {
+                score += 32;
+            }
+            return score * 1000 + greeting.len() * 10 + greeting.find(\"World\");
+        }
+    ");
+
+    let recorded = Arc::new(Mutex::new(String::new()));
+    let recorded_clone = recorded.clone();
+    let record_function = Function::new("record")
+        .with_arg(Type::String)
+        .with_closure(Box::new(move |adapter: &mut Adapter| {
+            let value: String = adapter.get_arg(0);
+            *recorded_clone.lock().unwrap() = value;
+        }));
+    let test_module = Module::new("test")
+        .with_function(record_function);
+
+    let mut engine = Engine::new(1024);
+    assert!(engine.register_module(test_module).is_ok());
+
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(63 * 1000 + 12 * 10 + 7, reg_val_res.unwrap());
+    assert_eq!("Hello, World!", recorded.lock().unwrap().as_str());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_string_interpolation() {
+    let code = String::from("
+        fn: main() ~ int {
+            var name = \"oxs\";
+            var count = 3;
+            var message = \"Hello {name}, {count + 1} files, {2.5}, {count > 2} {{}}\";
+            test::record(message);
+            return message.len();
+        }
+    ");
+
+    let recorded = Arc::new(Mutex::new(String::new()));
+    let recorded_clone = recorded.clone();
+    let record_function = Function::new("record")
+        .with_arg(Type::String)
+        .with_closure(Box::new(move |adapter: &mut Adapter| {
+            let value: String = adapter.get_arg(0);
+            *recorded_clone.lock().unwrap() = value;
+        }));
+    let test_module = Module::new("test")
+        .with_function(record_function);
+
+    let mut engine = Engine::new(1024);
+    assert!(engine.register_module(test_module).is_ok());
+
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let expected = "Hello oxs, 4 files, 2.5, true {}";
+    assert_eq!(expected, recorded.lock().unwrap().as_str());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(expected.len() as i64, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_enum_match() {
+    let code = String::from("
+        enum: Shape {
+            Circle(int),
+            Rect(int, int),
+            Named(string, int),
+            Empty
+        }
+
+        fn: area(shape: Shape) ~ int {
+            match shape {
+                Shape::Circle(r) => {
+                    return 3 * r * r;
+                }
+                Shape::Rect(w, h) => {
+                    return w * h;
+                }
+                Shape::Named(name, n) => {
+                    return name.len() * n;
+                }
+                Shape::Empty => {}
+            }
+            return 0;
+        }
+
+        fn: main() ~ int {
+            var circle = area(Shape::Circle(2));
+            var rect = area(Shape::Rect(3, 4));
+            var named = area(Shape::Named(\"abc\", 2));
+            var empty = area(Shape::Empty);
+            var total = circle + rect * 10 + named * 100 + empty;
+            var code = 3;
+            match code {
+                1 => {
+                    total += 10000;
+                }
+                3 => {
+                    total += 30000;
+                }
+                _ => {}
+            }
+            return total;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(30000 + 600 + 120 + 12, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_match_exhaustiveness() {
+    let code = String::from("
+        enum: Shape {
+            Circle(int),
+            Empty
+        }
+
+        fn: main() ~ int {
+            var shape = Shape::Empty;
+            match shape {
+                Shape::Empty => {
+                    return 1;
+                }
+            }
+            return 0;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::NonExhaustiveMatch(variant)) => {
+                assert_eq!("root::Shape::Circle", variant.as_str());
+            },
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_match_unreachable_arms() {
+    let cases = [
+        ("shape", "Shape::Circle(r) => { return r; } Shape::Empty => { return 1; } Shape::Circle(d) => { return d; }", "Shape::Circle"),
+        ("2", "1 => { return 1; } 2 => { return 2; } 1 => { return 3; } _ => { return 0; }", "1"),
+        ("2", "_ => { return 0; } 2 => { return 2; }", "2")
+    ];
+
+    for (match_expr, arms, unreachable) in cases.iter() {
+        let code = format!("
+            enum: Shape {{
+                Circle(int),
+                Empty
+            }}
+
+            fn: main() ~ int {{
+                var shape = Shape::Empty;
+                match {} {{
+                    {}
+                }}
+                return -1;
+            }}
+        ", match_expr, arms);
+
+        let mut engine = Engine::new(1024);
+        let load_res = engine.load_code(&code);
+        match load_res {
+            Err(err) => match *err {
+                EngineError::CompileError(CompilerError::UnreachableMatchArm(pattern)) => {
+                    assert_eq!(*unreachable, pattern.as_str());
+                },
+                other => panic!("Unexpected error: {:?}", other)
+            },
+            Ok(_) => panic!("Expected a compile error")
+        }
+    }
+}
+
+#[test]
+fn test_engine_interface_dispatch() {
+    let code = String::from("
+        intf: Shape {
+            fn: area(&this) ~ int;
+            fn: scale(&this, factor: int) ~ int;
+        }
+
+        cont: Square {
+            side: int;
+        }
+
+        cont: Rect {
+            w: int;
+            h: int;
+        }
+
+        impl: Shape for Square {
+            fn: area(&this) ~ int {
+                return this.side * this.side;
+            }
+            fn: scale(&this, factor: int) ~ int {
+                return this.side * factor;
+            }
+        }
+
+        impl: Shape for Rect {
+            fn: area(&this) ~ int {
+                return this.w * this.h;
+            }
+            fn: scale(&this, factor: int) ~ int {
+                return (this.w + this.h) * factor;
+            }
+        }
+
+        fn: area_of(shape: &Shape) ~ int {
+            return shape.area();
+        }
+
+        fn: main() ~ int {
+            var square = Square {
+                side: 3
+            };
+            var rect = Rect {
+                w: 2,
+                h: 5
+            };
+            var static_area = square.area();
+            var square_area = area_of(&square);
+            var shape: &Shape = &rect;
+            var rect_area = area_of(shape);
+            var rect_scaled = shape.scale(10);
+            shape = &square;
+            var square_scaled = shape.scale(2);
+            return static_area + square_area * 10 + rect_area * 100 + rect_scaled * 1000 + square_scaled * 100000;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(9 + 90 + 1000 + 70000 + 600000, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+#[test]
+fn test_engine_destroy() {
+    let code = String::from("
+        import: core::Destroy;
+
+        cont: Node {
+            value: int;
+        }
+
+        impl: Destroy for Node {
+            fn: destroy(&this) {
+                test::record(this.value);
+            }
+        }
+
+        fn: make_node(value: int) ~ &Node {
+            var node = Node {
+                value: value
+            };
+            return box node;
+        }
+
+        fn: main() ~ int {
+            var kept = make_node(1000);
+            var i = 0;
+            while i < 100 {
+                var node = make_node(1);
+                i += 1;
+            }
+            return kept.value;
+        }
+    ");
+
+    let destroyed = Arc::new(Mutex::new(0));
+    let destroyed_clone = destroyed.clone();
+    let record_function = Function::new("record")
+        .with_arg(Type::Int)
+        .with_closure(Box::new(move |adapter: &mut Adapter| {
+            let value: i64 = adapter.get_arg(0);
+            *destroyed_clone.lock().unwrap() += value;
+        }));
+    let test_module = Module::new("test")
+        .with_function(record_function);
+
+    let mut engine = Engine::new(1024);
+    engine.set_gc_threshold(64);
+    assert!(engine.register_module(test_module).is_ok());
+
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(1000, reg_val_res.unwrap());
+
+    // Every reclaimed node ran its destructor, the kept one did not
+    let stats = engine.get_gc_stats();
+    assert!(stats.objects_freed > 0);
+    assert_eq!(*destroyed.lock().unwrap(), stats.objects_freed as i64);
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_display_interpolation() {
+    let code = String::from("
+        import: core::Display;
+
+        cont: Point {
+            x: int;
+            y: int;
+        }
+
+        impl: Display for Point {
+            fn: to_string(&this) ~ string {
+                return \"({this.x}, {this.y})\";
+            }
+        }
+
+        fn: main() ~ int {
+            var point = Point {
+                x: 1,
+                y: 2
+            };
+            var message = \"at {point}\";
+            test::record(message);
+            return message.len();
+        }
+    ");
+
+    let recorded = Arc::new(Mutex::new(String::new()));
+    let recorded_clone = recorded.clone();
+    let record_function = Function::new("record")
+        .with_arg(Type::String)
+        .with_closure(Box::new(move |adapter: &mut Adapter| {
+            let value: String = adapter.get_arg(0);
+            *recorded_clone.lock().unwrap() = value;
+        }));
+    let test_module = Module::new("test")
+        .with_function(record_function);
+
+    let mut engine = Engine::new(1024);
+    assert!(engine.register_module(test_module).is_ok());
+
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let expected = "at (1, 2)";
+    assert_eq!(expected, recorded.lock().unwrap().as_str());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(expected.len() as i64, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+
+#[test]
+fn test_engine_this_methods() {
+    let code = String::from("
+        cont: Vector {
+            x: int;
+            y: int;
+        }
+
+        impl: Vector {
+            fn: new(x: int, y: int) ~ Vector {
+                return Vector {
+                    x: x,
+                    y: y
+                };
+            }
+            fn: sum(&this) ~ int {
+                return x + y;
+            }
+            fn: twice(&this) ~ int {
+                var s = sum();
+                return s * 2;
+            }
+            fn: set_x(&this, x: int) {
+                this.x = x;
+            }
+        }
+
+        cont: Body {
+            pos: Vector;
+            mass: int;
+        }
+
+        impl: Body {
+            fn: push(&this) {
+                pos.x += mass;
+                mass += 1;
+            }
+        }
+
+        fn: main() ~ int {
+            var vec = Vector::new(1, 2);
+            var twice = vec.twice();
+            vec.set_x(10);
+            var vec_ref = &vec;
+            vec_ref.set_x(vec.x + 1);
+            var body = Body {
+                pos: vec,
+                mass: 5
+            };
+            body.push();
+            return twice + vec.x * 10 + body.pos.x * 1000 + body.mass * 100000;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(6 + 110 + 16000 + 600000, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_generics() {
+    let code = String::from("
+        intf: Weighted {
+            fn: weight(&this) ~ int;
+        }
+
+        cont: Crate {
+            load: int;
+        }
+
+        impl: Weighted for Crate {
+            fn: weight(&this) ~ int {
+                return load;
+            }
+        }
+
+        cont: Pair<A, B> {
+            first: A;
+            second: B;
+        }
+
+        impl: Pair<A, B> {
+            fn: new(first: A, second: B) ~ Pair<A, B> {
+                return Pair {
+                    first: first,
+                    second: second
+                };
+            }
+            fn: get_first(&this) ~ A {
+                return first;
+            }
+            fn: swap(&this) ~ Pair<B, A> {
+                return Pair {
+                    first: second,
+                    second: first
+                };
+            }
+        }
+
+        fn: pick<T>(a: T, b: T, first: int) ~ T {
+            if first == 1 {
+                return a;
+            }
+            return b;
+        }
+
+        fn: heavier<T: Weighted>(a: T, b: T) ~ int {
+            var wa = a.weight();
+            var wb = b.weight();
+            if wa > wb {
+                return wa;
+            }
+            return wb;
+        }
+
+        fn: main() ~ int {
+            var pair = Pair::new(3, 4.5);
+            var swapped: Pair<float, int> = pair.swap();
+            var crates = Pair {
+                first: Crate { load: 2 },
+                second: Crate { load: 7 }
+            };
+            var picked = pick(1, 2, 0);
+            var heaviest = heavier(crates.first, crates.second);
+            return pair.get_first() + swapped.second * 10 + picked * 100 + heaviest * 1000;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(3 + 30 + 200 + 7000, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_generic_bounds() {
+    let code = String::from("
+        intf: Weighted {
+            fn: weight(&this) ~ int;
+        }
+
+        fn: heavier<T: Weighted>(a: T, b: T) ~ int {
+            return a.weight() + b.weight();
+        }
+
+        fn: main() ~ int {
+            return heavier(1, 2);
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::UnsatisfiedBound(_, bound)) => {
+                assert_eq!("root::Weighted", bound.as_str());
+            },
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_generic_interface_bounds() {
+    let code = String::from("
+        intf: Into<T> {
+            fn: into(&this) ~ T;
+        }
+
+        cont: Celsius {
+            degrees: int;
+        }
+
+        cont: Reading {
+            value: int;
+        }
+
+        impl: Into<int> for Celsius {
+            fn: into(&this) ~ int {
+                return degrees + 273;
+            }
+        }
+
+        fn: kelvin<T: Into<int>>(value: T) ~ int {
+            return value.into();
+        }
+
+        fn: main() ~ int {
+            return kelvin(Celsius { degrees: 7 });
+        }
+
+        fn: unconvertible() ~ int {
+            return kelvin(Reading { value: 7 });
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::UnsatisfiedBound(_, bound)) => {
+                assert_eq!("root::Into<int>", bound.as_str());
+            },
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+
+    let code = code.replace("return kelvin(Reading { value: 7 });", "return 0;");
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(280, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_generic_primitive_bounds() {
+    let code = String::from("
+        fn: max<T: Ord>(a: T, b: T) ~ T {
+            if a < b {
+                return b;
+            }
+            return a;
+        }
+
+        fn: sum<T: Add>(a: T, b: T) ~ T {
+            return a + b;
+        }
+
+        fn: main() ~ int {
+            var larger = max(3, 9);
+            var total = sum(1.5, 2.5);
+            if max(0.5, 0.25) == 0.5 {
+                return larger + total as int;
+            }
+            return 0;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(13, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+
+    // Bools cannot be ordered
+    let code = String::from("
+        fn: max<T: Ord>(a: T, b: T) ~ T {
+            if a < b {
+                return b;
+            }
+            return a;
+        }
+
+        fn: main() ~ int {
+            var larger = max(true, false);
+            return 0;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::UnsatisfiedBound(type_arg, bound)) => {
+                assert_eq!(Type::Bool, type_arg);
+                assert_eq!("root::core::Ord", bound.as_str());
+            },
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_generic_type_args() {
+    let code = String::from("
+        cont: Stack<T> {
+            items: [T];
+        }
+
+        impl: Stack<T> {
+            fn: new() ~ Stack<T> {
+                var items: [T] = [];
+                return Stack {
+                    items: items
+                };
+            }
+            fn: push(&this, item: T) {
+                items.push(item);
+            }
+            fn: size(&this) ~ int {
+                return items.len();
+            }
+        }
+
+        fn: main() ~ int {
+            var floats: Stack<float> = Stack::new();
+            floats.push(1.5);
+            var numbers = Stack<int>::new();
+            numbers.push(1);
+            numbers.push(2);
+            var float_count = floats.size();
+            var number_count = numbers.size();
+            return float_count + number_count * 10;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(21, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_closures() {
+    let code = String::from("
+        cont: Counter {
+            step: int;
+        }
+
+        impl: Counter {
+            fn: stepper(&this) ~ fn(int) ~ int {
+                return fn(x: int) ~ int {
+                    return x + step;
+                };
+            }
+        }
+
+        fn: double(x: int) ~ int {
+            return x * 2;
+        }
+
+        fn: adder(n: int) ~ fn(int) ~ int {
+            return fn(x: int) ~ int {
+                return x + n;
+            };
+        }
+
+        fn: compose(f: fn(int) ~ int, g: fn(int) ~ int) ~ fn(int) ~ int {
+            return fn(x: int) ~ int {
+                return g(f(x));
+            };
+        }
+
+        fn: sum_mapped(items: [int], f: fn(int) ~ int) ~ int {
+            var sum = 0;
+            for i in 0..items.len() {
+                sum += f(items[i]);
+            }
+            return sum;
+        }
+
+        fn: main() ~ int {
+            var add_three = adder(3);
+            var both = compose(double, add_three);
+            var counter = Counter { step: 100 };
+            var step = counter.stepper();
+            var mapped = sum_mapped([1, 2, 3], fn(x: int) ~ int {
+                return x * x;
+            });
+            var first = both(5);
+            return first + step(1000) + mapped * 10000;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(13 + 1100 + 140000, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_callback() {
+    let code = String::from("
+        fn: main() ~ int {
+            var offset = 5;
+            return test::apply_twice(fn(x: int) ~ int {
+                return x * 2 + offset;
+            }, 1);
+        }
+    ");
+
+    let apply_twice_function = Function::new("apply_twice")
+        .with_arg(Type::Function(vec![Type::Int], Box::new(Type::Int)))
+        .with_arg(Type::Int)
+        .with_ret_type(Type::Int)
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let callback: Callback = adapter.get_arg(0);
+            let arg: i64 = adapter.get_arg(1);
+            let once: i64 = adapter.call_callback(&callback, arg);
+            let twice: i64 = adapter.call_callback(&callback, once);
+            adapter.return_value(twice);
+        }));
+
+    let test_module = Module::new("test")
+        .with_function(apply_twice_function);
+
+    let mut engine = Engine::new(1024);
+    assert!(engine.register_module(test_module).is_ok());
+
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(19, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_statics() {
+    let code = String::from("
+        mod: config {
+            const BASE: int = 10 * 4 + 2;
+            static scale: int = 1;
+
+            fn: get() ~ int {
+                return BASE * scale;
+            }
+        }
+
+        static counter: int = 0;
+        static names: [int; 3] = [1, 2, 3];
+
+        fn: main() ~ int {
+            counter += 1;
+            names[0] += counter;
+            config::scale = counter;
+            return counter * 1000 + config::get() + names[0];
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    // Static state persists across calls
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(1000 + 42 + 2, reg_val_res.unwrap());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(2000 + 84 + 4, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_const_assign() {
+    let code = String::from("
+        const LIMIT: int = 3;
+
+        fn: main() ~ int {
+            LIMIT = 4;
+            return LIMIT;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::CannotAssignConst(name)) => {
+                assert_eq!("root::LIMIT", name.as_str());
+            },
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_type_inference() {
+    let code = String::from("
+        cont: Counter {
+            value: int;
+        }
+
+        fn: make_counter(value: int) ~ &Counter {
+            var counter = Counter {
+                value: value
+            };
+            return box counter;
+        }
+
+        fn: main() ~ int {
+            var count = 3;
+            var scale = 1.5;
+            var values = [1, 2, 3];
+            var counter = make_counter(10);
+            var floats: [float] = [];
+            floats.push(scale);
+            values = [];
+            values.push(count);
+            counter.value += values[0];
+            return counter.value + values.len() + floats.len();
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(13 + 1 + 1, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_inferred_type_mismatch() {
+    let code = String::from("
+        fn: main() ~ int {
+            var count = 3;
+            count = 1.5;
+            return count;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::TypeMismatch(expected, found)) => {
+                assert_eq!(Type::Int, expected);
+                assert_eq!(Type::Float, found);
+            },
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_casts() {
+    let code = String::from("
+        const HALF: float = 1 as float / 2.0;
+
+        fn: average(values: [int]) ~ float {
+            var total = 0.0;
+            for i in 0..values.len() {
+                total += values[i] as float;
+            }
+            return total / values.len() as float;
+        }
+
+        fn: main() ~ int {
+            var avg = average([1, 2, 4]);
+            var flag = 3 as bool;
+            return (avg * 100.0) as int + (HALF * 10.0) as int + flag as int;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(233 + 5 + 1, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_implicit_conversion() {
+    let code = String::from("
+        fn: main() ~ float {
+            var count = 3;
+            return count * 1.5;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::ImplicitConversion(lhs_type, rhs_type)) => {
+                assert_eq!(Type::Int, lhs_type);
+                assert_eq!(Type::Float, rhs_type);
+            },
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_bitwise_ops() {
+    let code = String::from("
+        const FLAG_A: int = 1 << 0;
+        const FLAG_B: int = 1 << 3;
+
+        fn: checksum(values: [int]) ~ int {
+            var hash = 5381;
+            for i in 0..values.len() {
+                hash = ((hash << 5) + hash) ^ values[i];
+            }
+            return hash & 65535;
+        }
+
+        fn: main() ~ int {
+            var flags = 0;
+            flags |= FLAG_A | FLAG_B;
+            flags ^= FLAG_A;
+            var shifted = flags;
+            shifted <<= 2;
+            shifted >>= 1;
+            shifted %= 7;
+            if flags & FLAG_B != 0 && flags & FLAG_A == 0 {
+                return checksum([1, 2, 3]) * 100 + shifted * 10 + (!flags & 1);
+            }
+            return 0;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(44773 * 100 + 2 * 10 + 1, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_unary_minus() {
+    let code = String::from("
+        const OFFSET: int = -(2 * 5);
+
+        fn: abs(x: int) ~ int {
+            if x < 0 {
+                return -x;
+            }
+            return x;
+        }
+
+        fn: main() ~ int {
+            var a = 3;
+            var b = 4;
+            var scale = -1.5;
+            var r = &a;
+            return abs(-(a + b) * 2) + -*r + (-scale * 2.0) as int + OFFSET;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(14 - 3 + 3 - 10, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_division_by_zero() {
+    let code = String::from("
+        fn: ratio(lhs: int, rhs: int) ~ int {
+            return lhs / rhs;
+        }
+
+        fn: main() ~ int {
+            return ratio(10, 0);
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::CoreError(CoreError::DivisionByZero(_)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a division by zero error")
+    }
+}
+
+#[test]
+fn test_engine_arithmetic_mode() {
+    let code = String::from("
+        fn: grow(value: int) ~ int {
+            return value * 4;
+        }
+
+        fn: main() ~ int {
+            return grow(4611686018427387904);
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    engine.set_arithmetic_mode(ArithmeticMode::Saturating);
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(i64::MAX, reg_val_res.unwrap());
+
+    engine.set_arithmetic_mode(ArithmeticMode::Checked);
+    let run_res = engine.run_fn("root::main");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::CoreError(CoreError::ArithmeticOverflow(_)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected an overflow error")
+    }
+}
+
+#[test]
+fn test_engine_shift_arithmetic_mode() {
+    let code = String::from("
+        fn: shift(value: int, amount: int) ~ int {
+            return value << amount;
+        }
+
+        fn: main() ~ int {
+            return shift(1, 70);
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    engine.set_arithmetic_mode(ArithmeticMode::Saturating);
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(i64::MAX, reg_val_res.unwrap());
+
+    engine.set_arithmetic_mode(ArithmeticMode::Checked);
+    let run_res = engine.run_fn("root::main");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::CoreError(CoreError::ArithmeticOverflow(_)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected an overflow error")
+    }
+}
+
+#[test]
+fn test_engine_constant_arithmetic_errors() {
+    let cases = [
+        ("const X: int = 9223372036854775807 + 1;", true),
+        ("const X: int = 1 << 70;", true),
+        ("const X: int = 1 >> (0 - 1);", true),
+        ("const X: int = 7 / (2 - 2);", false)
+    ];
+
+    for (const_decl, is_overflow) in cases.iter() {
+        let code = format!("
+            {}
+
+            fn: main() ~ int {{
+                return X;
+            }}
+        ", const_decl);
+
+        let mut engine = Engine::new(1024);
+        let load_res = engine.load_code(&code);
+        match load_res {
+            Err(err) => match (*err, is_overflow) {
+                (EngineError::CompileError(CompilerError::ArithmeticOverflow(_)), true) => {},
+                (EngineError::CompileError(CompilerError::DivisionByZero(_)), false) => {},
+                (other, _) => panic!("Unexpected error: {:?}", other)
+            },
+            Ok(_) => panic!("Expected a compile error")
+        }
+    }
+}
+
+#[test]
+fn test_engine_try_operator() {
+    let code = String::from("
+        enum: Parsed {
+            Ok(int),
+            Err(string)
+        }
+
+        enum: Scaled {
+            Ok(float),
+            Err(string)
+        }
+
+        fn: parse(value: int) ~ Parsed {
+            if value < 0 {
+                return Parsed::Err(\"negative\");
+            }
+            return Parsed::Ok(value * 2);
+        }
+
+        fn: sum(lhs: int, rhs: int) ~ Parsed {
+            var lhs_value = parse(lhs)?;
+            var rhs_value = parse(rhs)?;
+            return Parsed::Ok(lhs_value + rhs_value);
+        }
+
+        fn: scale(value: int) ~ Scaled {
+            var parsed = parse(value)?;
+            return Scaled::Ok(parsed as float * 1.5);
+        }
+
+        fn: main() ~ int {
+            var total = 0;
+            match sum(3, 4) {
+                Parsed::Ok(value) => { total += value; }
+                Parsed::Err(msg) => { return -1; }
+            }
+            match sum(3, -4) {
+                Parsed::Ok(value) => { return -1; }
+                Parsed::Err(msg) => { total += msg.len() * 100; }
+            }
+            match scale(-1) {
+                Scaled::Ok(value) => { return -1; }
+                Scaled::Err(msg) => { total += msg.len() * 1000; }
+            }
+            match scale(2) {
+                Scaled::Ok(value) => { total += value as int * 10000; }
+                Scaled::Err(msg) => { return -1; }
+            }
+            return total;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(0, engine.get_stack_size());
+    assert_eq!(60000 + 8000 + 800 + 14, reg_val_res.unwrap());
+}
+
+#[test]
+fn test_engine_try_not_a_result() {
+    let code = String::from("
+        fn: main() ~ int {
+            var value = 5?;
+            return value;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::NotAResult(Type::Int)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_foreign_result() {
+    let code = String::from("
+        fn: double(text: string) ~ num::Parsed {
+            return num::Parsed::Ok(num::parse(text)? * 2);
+        }
+
+        fn: main() ~ int {
+            var total = 0;
+            match double(\"21\") {
+                num::Parsed::Ok(value) => { total += value; }
+                num::Parsed::Err(msg) => { return -1; }
+            }
+            match double(\"abc\") {
+                num::Parsed::Ok(value) => { return -1; }
+                num::Parsed::Err(msg) => { total += msg.len() * 100; }
+            }
+            return total;
+        }
+    ");
+
+    let parse_function = Function::new("parse")
+        .with_arg(Type::String)
+        .with_ret_type(Type::Other(String::from("Parsed")))
+        .with_closure(Box::new(|adapter: &mut Adapter| {
+            let parsed = adapter.try_get_arg::<String>(0)
+                .map(|text| text.parse::<i64>());
+            match parsed {
+                Ok(Ok(value)) => adapter.return_ok(value),
+                _ => adapter.return_err("invalid number")
+            }.unwrap();
+        }));
+    let module = Module::new("num")
+        .with_enum("Parsed", vec![
+            (String::from("Ok"), vec![Type::Int]),
+            (String::from("Err"), vec![Type::String])
+        ])
+        .with_function(parse_function);
+
+    let mut engine = Engine::new(1024);
+    let reg_res = engine.register_module(module);
+    assert!(reg_res.is_ok());
+
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(0, engine.get_stack_size());
+    assert_eq!(1400 + 42, reg_val_res.unwrap());
+}
+
+#[test]
+fn test_engine_try_catch() {
+    let code = String::from("
+        fn: find(depth: int) ~ int {
+            var name = \"level\";
+            if depth == 0 {
+                panic(\"not found\");
+            }
+            return find(depth - 1) + 1;
+        }
+
+        fn: main() ~ int {
+            var total = 0;
+            try {
+                total += find(3);
+            } catch err {
+                total += err.len();
+            }
+            var i = 0;
+            while i < 4 {
+                try {
+                    if i == 1 {
+                        i += 1;
+                        continue;
+                    }
+                    total += 10 / (i - 2);
+                } catch err {
+                    total += 100;
+                }
+                i += 1;
+            }
+            return total;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(0, engine.get_stack_size());
+    assert_eq!(9 - 5 + 100 + 10, reg_val_res.unwrap());
+}
+
+#[test]
+fn test_engine_uncaught_panic() {
+    let code = String::from("
+        fn: check(value: int) ~ int {
+            if value < 0 {
+                panic(\"negative value\");
+            }
+            return value;
+        }
+
+        fn: main() ~ int {
+            return check(-1);
+        }
+
+        fn: other() ~ int {
+            return check(5);
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::Panic(msg) => assert_eq!("negative value", msg),
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a panic")
+    }
+    assert_eq!(0, engine.get_stack_size());
+
+    // The engine is still usable after the panic
+    let run_res = engine.run_fn("root::other");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(5, reg_val_res.unwrap());
+}
+
+#[test]
+fn test_engine_optional() {
+    let code = String::from("
+        cont: Node {
+            value: int;
+            next: ?&Node;
+        }
+
+        fn: sum(head: ?&Node) ~ int {
+            var total = 0;
+            var current = head;
+            while current != none {
+                if var node = current {
+                    total += node.value;
+                    current = node.next;
+                }
+            }
+            return total;
+        }
+
+        fn: main() ~ int {
+            var last = Node { value: 3, next: none };
+            var first = Node { value: 4, next: &last };
+            var maybe: ?&Node = none;
+            if maybe == none {
+                maybe = &first;
+            }
+            if maybe != none {
+                maybe.value += 10;
+            }
+            return sum(maybe);
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(17, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_optional_traversal() {
+    let code = String::from("
+        cont: Node {
+            value: int;
+            next: ?&Node;
+        }
+
+        fn: sum(head: ?&Node) ~ int {
+            var total = 0;
+            var cur = head;
+            while cur != none {
+                if cur != none {
+                    total += cur.value;
+                    cur = cur.next;
+                }
+            }
+            return total;
+        }
+
+        fn: main() ~ int {
+            var third = Node { value: 3, next: none };
+            var second = Node { value: 2, next: &third };
+            var first = Node { value: 1, next: &second };
+            var maybe: ?&Node = &first;
+            if maybe != none {
+                maybe.value += 10;
+                maybe = none;
+            }
+            if maybe == none {
+                return sum(&first) * 100;
+            }
+            return 0;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(1600, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_unchecked_optional() {
+    let code = String::from("
+        cont: Node {
+            value: int;
+        }
+
+        fn: main() ~ int {
+            var maybe: ?&Node = none;
+            return maybe.value;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::UncheckedOptional(_)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_operator_overloading() {
+    let code = String::from("
+        import: core::Add;
+        import: core::Mul;
+        import: core::Eq;
+        import: core::Ord;
+
+        cont: Vec2 {
+            x: int;
+            y: int;
+        }
+
+        impl: Add for Vec2 {
+            fn: add(&this, other: Vec2) ~ Vec2 {
+                return Vec2 { x: x + other.x, y: y + other.y };
+            }
+        }
+
+        impl: Mul for Vec2 {
+            fn: mul(&this, factor: int) ~ Vec2 {
+                return Vec2 { x: x * factor, y: y * factor };
+            }
+        }
+
+        impl: Eq for Vec2 {
+            fn: eq(&this, other: Vec2) ~ bool {
+                return x == other.x && y == other.y;
+            }
+        }
+
+        impl: Ord for Vec2 {
+            fn: cmp(&this, other: Vec2) ~ int {
+                return (x + y) - (other.x + other.y);
+            }
+        }
+
+        fn: main() ~ int {
+            var a = Vec2 { x: 1, y: 2 };
+            var b = Vec2 { x: 3, y: 4 };
+            var sum = (a + b) * 2 + a;
+            sum += b;
+            var flags = 0;
+            if a + b == Vec2 { x: 4, y: 6 } {
+                flags += 1;
+            }
+            if a != b {
+                flags += 2;
+            }
+            if a < b {
+                flags += 4;
+            }
+            if a >= b {
+                flags += 8;
+            }
+            return sum.x * 1000 + sum.y * 10 + flags;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(12 * 1000 + 18 * 10 + 7, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_operator_eq_not_bool() {
+    let code = String::from("
+        import: core::Eq;
+
+        cont: Id {
+            value: int;
+        }
+
+        impl: Eq for Id {
+            fn: eq(&this, other: Id) ~ int {
+                return value - other.value;
+            }
+        }
+
+        fn: main() ~ int {
+            var id = Id { value: 1 };
+            if id == id {
+                return 1;
+            }
+            return 0;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    match load_res {
+        Err(err) => match *err {
+            EngineError::CompileError(CompilerError::TypeMismatch(Type::Bool, Type::Int)) => {},
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a compile error")
+    }
+}
+
+#[test]
+fn test_engine_core_library() {
+    let code = String::from("
+        fn: halve(text: string) ~ core::int::Parsed {
+            var value = int::parse(text)?;
+            return core::int::Parsed::Ok(value / 2);
+        }
+
+        fn: main() ~ int {
+            var length = float::sqrt((3.0 * 3.0) + (4.0 * 4.0));
+            assert::equal_float(length, 5.0);
+            assert::equal_int(int::max(int::abs(-7), 2), 7);
+            assert::equal_string(string::to_upper(string::trim(\" oxs \")), \"OXS\");
+            assert::that(string::starts_with(\"core\", \"co\"), \"expected a prefix\");
+            var halved = 0;
+            match halve(int::to_string(84)) {
+                core::int::Parsed::Ok(value) => {
+                    halved = value;
+                },
+                core::int::Parsed::Err(msg) => {
+                    return -1;
+                }
+            }
+            return halved * 100 + string::find(\"hello\", \"llo\");
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(42 * 100 + 2, reg_val_res.unwrap());
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_core_assertion_failure() {
+    let code = String::from("
+        fn: main() ~ int {
+            try {
+                assert::equal_int(1 + 1, 3);
+            } catch err {
+                return err.len();
+            }
+            return 0;
+        }
+
+        fn: uncaught() ~ int {
+            assert::that(false, \"unreachable\");
+            return 1;
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!("assertion failed: 2 != 3".len() as i64, reg_val_res.unwrap());
+
+    let run_res = engine.run_fn("root::uncaught");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::Panic(msg) => assert_eq!("assertion failed: unreachable", msg),
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a panic")
+    }
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_core_string_repeat_limit() {
+    let code = String::from("
+        fn: main() ~ int {
+            var repeated = string::repeat(\"abc\", 3);
+            try {
+                string::repeat(\"abc\", 9223372036854775807);
+            } catch err {
+                return repeated.len();
+            }
+            return 0;
+        }
+
+        fn: uncaught() ~ int {
+            var repeated = string::repeat(\"abc\", 16777216);
+            return repeated.len();
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(9, reg_val_res.unwrap());
+
+    let run_res = engine.run_fn("root::uncaught");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::Panic(msg) => assert_eq!("repeated string exceeds 16777216 bytes", msg),
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a panic")
+    }
+    assert_eq!(0, engine.get_stack_size());
+}
+
+#[test]
+fn test_engine_core_arithmetic_mode() {
+    let code = String::from("
+        fn: main() ~ int {
+            return int::pow(3, 40);
+        }
+    ");
+
+    let mut engine = Engine::new(1024);
+    let load_res = engine.load_code(&code);
+    assert!(load_res.is_ok());
+
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(3i64.wrapping_pow(40), reg_val_res.unwrap());
+
+    engine.set_arithmetic_mode(ArithmeticMode::Saturating);
+    let run_res = engine.run_fn("root::main");
+    assert!(run_res.is_ok());
+    let reg_val_res = engine.get_register_value::<i64>(Register::R0);
+    assert_eq!(i64::MAX, reg_val_res.unwrap());
+
+    engine.set_arithmetic_mode(ArithmeticMode::Checked);
+    let run_res = engine.run_fn("root::main");
+    match run_res {
+        Err(err) => match *err {
+            EngineError::Panic(msg) => assert_eq!("integer overflow in pow", msg),
+            other => panic!("Unexpected error: {:?}", other)
+        },
+        Ok(_) => panic!("Expected a panic")
+    }
+    assert_eq!(0, engine.get_stack_size());
+}
@@ -30,6 +30,18 @@ fn test_lex_string_literal() {
     assert_eq!(lexer.slice(), "\"This is a string literal.\"");
 }
 
+#[test]
+fn test_lex_interpolated_string_literal() {
+    let mut lexer = Token::lexer("\"Hello {name}\" + \"plain\"");
+
+    assert_eq!(lexer.token, Token::InterpolatedStringLiteral);
+    assert_eq!(lexer.slice(), "\"Hello {name}\"");
+    lexer.advance();
+    assert_eq!(lexer.token, Token::Plus);
+    lexer.advance();
+    assert_eq!(lexer.token, Token::StringLiteral);
+}
+
 #[test]
 fn test_lex_while() {
     let mut lexer = Token::lexer("while nextT <= t2 { }");
@@ -100,4 +112,20 @@ fn test_lex_weird_mod_name() {
     lexer.advance();
     assert_eq!(lexer.token, Token::Text);
     lexer.advance();
-}
\ No newline at end of file
+}
+#[test]
+fn test_lex_bitwise_ops() {
+    let mut lexer = Token::lexer("a % b | c ^ d << e >> f %= &= |= ^= <<= >>= || &&");
+
+    let expected = [
+        Token::Text, Token::Percent, Token::Text, Token::Pipe, Token::Text,
+        Token::Caret, Token::Text, Token::ShiftLeft, Token::Text, Token::ShiftRight, Token::Text,
+        Token::ModAssign, Token::AndAssign, Token::OrAssign, Token::XorAssign,
+        Token::ShiftLeftAssign, Token::ShiftRightAssign, Token::Or, Token::DoubleAnd
+    ];
+    for token in expected.iter() {
+        assert_eq!(lexer.token, *token);
+        lexer.advance();
+    }
+    assert_eq!(lexer.token, Token::End);
+}
@@ -113,6 +113,129 @@ fn test_parse_container_decl() {
     assert!(decl_res.is_ok());
 }
 
+#[test]
+fn test_parse_generic_decl() {
+    let code = String::from("
+        cont: Pair<A, B: Ord + Hash> {
+            first: A;
+            second: B;
+        }
+    ");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let decl_res = parser.parse_container_decl(&mut lexer);
+    assert!(decl_res.is_ok());
+
+    if let Declaration::Container(cont_decl) = decl_res.unwrap() {
+        assert_eq!(cont_decl.type_params.len(), 2);
+        assert_eq!(cont_decl.type_params[0], (String::from("A"), Vec::new()));
+        assert_eq!(cont_decl.type_params[1], (String::from("B"), vec![Type::Other(String::from("Ord")), Type::Other(String::from("Hash"))]));
+    }
+
+    let code = String::from("fn: max<T>(a: T, b: T) ~ Pair<T, T>;");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let decl_res = parser.parse_fn_decl(&mut lexer);
+
+    assert!(decl_res.is_ok());
+
+    if let Declaration::Function(fn_decl) = decl_res.unwrap() {
+        assert_eq!(fn_decl.type_params.len(), 1);
+        assert_eq!(fn_decl.returns.to_string(), String::from("Pair<T, T>"));
+    }
+
+    let code = String::from("fn: conv<T: Into<int>, U: core::Ord>(value: T) ~ int;");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let decl_res = parser.parse_fn_decl(&mut lexer);
+
+    assert!(decl_res.is_ok());
+
+    if let Declaration::Function(fn_decl) = decl_res.unwrap() {
+        assert_eq!(fn_decl.type_params[0], (String::from("T"), vec![Type::Generic(String::from("Into"), vec![Type::Int])]));
+        assert_eq!(fn_decl.type_params[1], (String::from("U"), vec![Type::Other(String::from("core::Ord"))]));
+    }
+}
+
+#[test]
+fn test_parse_closure() {
+    let code = String::from("var f: fn(int, float) ~ int = fn(x: int, y: float) ~ int { return x; };");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let stmt_res = parser.parse_var_decl(&mut lexer);
+
+    assert!(stmt_res.is_ok());
+
+    if let Statement::VariableDecl(var_decl) = stmt_res.unwrap() {
+        assert_eq!(var_decl.var_type.to_string(), String::from("fn(int, float) ~ int"));
+        match *var_decl.assignment {
+            Expression::Closure(fn_decl) => {
+                assert_eq!(fn_decl.arguments.len(), 2);
+                assert_eq!(fn_decl.returns, Type::Int);
+                assert_eq!(fn_decl.code_block.unwrap().len(), 1);
+            },
+            other => panic!("Expected a closure, got {:?}", other)
+        }
+    }
+}
+
+#[test]
+fn test_parse_static_decl() {
+    let code = String::from("static counter: int = 0;");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let decl_res = parser.parse_static_decl(&mut lexer);
+
+    assert!(decl_res.is_ok());
+
+    match decl_res.unwrap() {
+        Declaration::StaticVar(var_decl) => {
+            assert_eq!(var_decl.name, String::from("counter"));
+            assert_eq!(var_decl.var_type, Type::Int);
+        },
+        other => panic!("Expected a static variable, got {:?}", other)
+    }
+
+    let code = String::from("const LIMIT = 10 * 2;");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let decl_res = parser.parse_static_decl(&mut lexer);
+
+    assert!(decl_res.is_ok());
+
+    match decl_res.unwrap() {
+        Declaration::Constant(var_decl) => {
+            assert_eq!(var_decl.name, String::from("LIMIT"));
+            assert_eq!(var_decl.var_type, Type::Auto);
+        },
+        other => panic!("Expected a constant, got {:?}", other)
+    }
+}
+
+#[test]
+fn test_parse_cast() {
+    let code = String::from("var x = a.b as float * 2.0;");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let stmt_res = parser.parse_var_decl(&mut lexer);
+
+    assert!(stmt_res.is_ok());
+
+    if let Statement::VariableDecl(var_decl) = stmt_res.unwrap() {
+        let member_expr = Expression::MemberAccess(
+            Box::new(Expression::Variable(String::from("a"))),
+            Box::new(Expression::Variable(String::from("b")))
+        );
+        let expected = Expression::Multiplication(
+            Box::new(Expression::Cast(Box::new(member_expr), Type::Float)),
+            Box::new(Expression::FloatLiteral(2.0))
+        );
+        assert_eq!(*var_decl.assignment, expected);
+    }
+}
+
 #[test]
 fn test_parse_empty_fn_decl() {
     let code = String::from("fn: main(arg: int) ~ int;");
@@ -273,6 +396,114 @@ fn test_parse_raw_var_expr() {
     //expr.print(0);
 }
 
+#[test]
+fn test_parse_bitwise_expr() {
+    let code = String::from("
+        a & b | c << 2 + 1 == d % 4;
+    ");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let expr_res = parser.parse_expr(&mut lexer, &[Token::Semicolon]);
+    assert!(expr_res.is_ok());
+
+    let var = |name: &str| Box::new(Expression::Variable(String::from(name)));
+    let expected = Expression::Equals(
+        Box::new(Expression::BitOr(
+            Box::new(Expression::BitAnd(var("a"), var("b"))),
+            Box::new(Expression::ShiftLeft(
+                var("c"),
+                Box::new(Expression::Addition(
+                    Box::new(Expression::IntLiteral(2)),
+                    Box::new(Expression::IntLiteral(1))
+                ))
+            ))
+        )),
+        Box::new(Expression::Modulo(var("d"), Box::new(Expression::IntLiteral(4))))
+    );
+    assert_eq!(expr_res.unwrap(), expected);
+}
+
+#[test]
+fn test_parse_unary_expr() {
+    let code = String::from("
+        -a * *p.x[1] as float - -2 == !b;
+    ");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let expr_res = parser.parse_expr(&mut lexer, &[Token::Semicolon]);
+    assert!(expr_res.is_ok());
+
+    let var = |name: &str| Box::new(Expression::Variable(String::from(name)));
+    let expected = Expression::Equals(
+        Box::new(Expression::Subtraction(
+            Box::new(Expression::Multiplication(
+                Box::new(Expression::Negate(var("a"))),
+                Box::new(Expression::Cast(
+                    Box::new(Expression::Deref(Box::new(Expression::Index(
+                        Box::new(Expression::MemberAccess(var("p"), var("x"))),
+                        Box::new(Expression::IntLiteral(1))
+                    )))),
+                    Type::Float
+                ))
+            )),
+            Box::new(Expression::IntLiteral(-2))
+        )),
+        Box::new(Expression::Not(var("b")))
+    );
+    assert_eq!(expr_res.unwrap(), expected);
+}
+
+#[test]
+fn test_parse_try_expr() {
+    let code = String::from("
+        parse(text)?.value + -cfg.load()?;
+    ");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let expr_res = parser.parse_expr(&mut lexer, &[Token::Semicolon]);
+    assert!(expr_res.is_ok());
+
+    let var = |name: &str| Box::new(Expression::Variable(String::from(name)));
+    let expected = Expression::Addition(
+        Box::new(Expression::MemberAccess(
+            Box::new(Expression::Try(Box::new(Expression::Call(String::from("parse"), vec![
+                Expression::Variable(String::from("text"))
+            ])))),
+            var("value")
+        )),
+        Box::new(Expression::Negate(Box::new(Expression::Try(Box::new(Expression::MemberAccess(
+            var("cfg"),
+            Box::new(Expression::Call(String::from("load"), Vec::new()))
+        ))))))
+    );
+    assert_eq!(expr_res.unwrap(), expected);
+}
+
+#[test]
+fn test_parse_expr_unexpected_token() {
+    let code = String::from("x = (1 + 2) 3;");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let expr_res = parser.parse_expr(&mut lexer, &[Token::Semicolon]);
+    match expr_res {
+        Err(ParseError { error_type: ParseErrorType::UnexpectedToken(Token::IntLiteral), token_pos }) => {
+            assert_eq!(12..13, token_pos);
+        },
+        other => panic!("Unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn test_parse_nested_generic_type() {
+    let code = String::from("Pair<int, Box<Box<float>>>");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let type_res = parser.parse_type(&mut lexer);
+    assert!(type_res.is_ok());
+    assert_eq!(type_res.unwrap().to_string(), String::from("Pair<int, Box<Box<float>>>"));
+    assert_eq!(lexer.token, Token::End);
+}
+
 #[test]
 fn test_parse_full_fn() {
     let code = String::from("
@@ -669,4 +900,397 @@ fn test_parse_cont_instance() {
             }
         }
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_for_stmt() {
+    let code = String::from("
+        for i in 0..10 {
+            x += i;
+        }
+        for j in 1..=n by 2 {
+            x += j;
+        }
+    ");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let stmt_list_res = parser.parse_statement_list(&mut lexer);
+    assert!(stmt_list_res.is_ok());
+
+    let stmt_list = stmt_list_res.unwrap();
+    assert_eq!(stmt_list.len(), 2);
+
+    if let Statement::For(for_stmt_args) = &stmt_list[0] {
+        assert_eq!(for_stmt_args.var_name, String::from("i"));
+        assert_eq!(for_stmt_args.range_start, Expression::IntLiteral(0));
+        assert_eq!(for_stmt_args.range_end, Expression::IntLiteral(10));
+        assert!(!for_stmt_args.inclusive);
+        assert!(for_stmt_args.step.is_none());
+        assert_eq!(for_stmt_args.stmt_list.len(), 1);
+    } else {
+        panic!("Expected a for statement");
+    }
+
+    if let Statement::For(for_stmt_args) = &stmt_list[1] {
+        assert_eq!(for_stmt_args.var_name, String::from("j"));
+        assert_eq!(for_stmt_args.range_end, Expression::Variable(String::from("n")));
+        assert!(for_stmt_args.inclusive);
+        assert_eq!(for_stmt_args.step, Some(Expression::IntLiteral(2)));
+    } else {
+        panic!("Expected a for statement");
+    }
+}
+
+#[test]
+fn test_parse_loop_and_block() {
+    let code = String::from("
+        loop {
+            break;
+        }
+        {
+            var x = 4;
+        }
+    ");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let stmt_list_res = parser.parse_statement_list(&mut lexer);
+    assert!(stmt_list_res.is_ok());
+
+    let stmt_list = stmt_list_res.unwrap();
+    assert_eq!(stmt_list.len(), 2);
+    assert_eq!(stmt_list[0], Statement::Loop(vec![Statement::Break]));
+    if let Statement::CodeBlock(block) = &stmt_list[1] {
+        assert_eq!(block.len(), 1);
+    } else {
+        panic!("Expected a code block");
+    }
+}
+
+#[test]
+fn test_parse_array_expr() {
+    let code = String::from("
+        values[i + 1] = [1, 2, values[0]];
+    ");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let expr_res = parser.parse_expr(&mut lexer, &[Token::Semicolon]);
+    assert!(expr_res.is_ok());
+
+    let values = Box::new(Expression::Variable(String::from("values")));
+    let index = Expression::Index(
+        values.clone(),
+        Box::new(Expression::Addition(
+            Box::new(Expression::Variable(String::from("i"))),
+            Box::new(Expression::IntLiteral(1))
+        ))
+    );
+    let array = Expression::ArrayLiteral(vec![
+        Expression::IntLiteral(1),
+        Expression::IntLiteral(2),
+        Expression::Index(values, Box::new(Expression::IntLiteral(0)))
+    ]);
+    assert_eq!(expr_res.unwrap(), Expression::Assign(Box::new(index), Box::new(array)));
+}
+
+#[test]
+fn test_parse_array_repeat_expr() {
+    let code = String::from("
+        [[0.5; 2]; 16];
+    ");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let expr_res = parser.parse_expr(&mut lexer, &[Token::Semicolon]);
+    assert!(expr_res.is_ok());
+
+    let row = Expression::ArrayRepeat(Box::new(Expression::FloatLiteral(0.5)), 2);
+    assert_eq!(expr_res.unwrap(), Expression::ArrayRepeat(Box::new(row), 16));
+}
+
+#[test]
+fn test_parse_tuple() {
+    let code = String::from("
+        var pair: (int, (float, bool)) = (1, (2.0, true));
+        var (a, b) = pair;
+        x = pair.1.0 + (a + 1) * 2;
+    ");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+    let stmt_list_res = parser.parse_statement_list(&mut lexer);
+    assert!(stmt_list_res.is_ok());
+
+    let stmt_list = stmt_list_res.unwrap();
+    assert_eq!(stmt_list.len(), 3);
+
+    if let Statement::VariableDecl(var_decl_args) = &stmt_list[0] {
+        let inner_type = Type::Tuple(vec![Type::Float, Type::Bool]);
+        assert_eq!(var_decl_args.var_type, Type::Tuple(vec![Type::Int, inner_type]));
+        let inner_tuple = Expression::Tuple(vec![
+            Expression::FloatLiteral(2.0),
+            Expression::BoolLiteral(true)
+        ]);
+        let tuple = Expression::Tuple(vec![Expression::IntLiteral(1), inner_tuple]);
+        assert_eq!(*var_decl_args.assignment, tuple);
+    } else {
+        panic!("Expected a variable declaration");
+    }
+
+    if let Statement::TupleDecl(tuple_decl_args) = &stmt_list[1] {
+        assert_eq!(tuple_decl_args.names, vec![String::from("a"), String::from("b")]);
+        assert_eq!(*tuple_decl_args.assignment, Expression::Variable(String::from("pair")));
+    } else {
+        panic!("Expected a tuple declaration");
+    }
+
+    if let Statement::Expression(Expression::Assign(_, rhs)) = &stmt_list[2] {
+        if let Expression::Addition(lhs, _) = &**rhs {
+            let pair = Box::new(Expression::Variable(String::from("pair")));
+            let access = Expression::TupleAccess(Box::new(Expression::TupleAccess(pair, 1)), 0);
+            assert_eq!(**lhs, access);
+        } else {
+            panic!("Expected an addition");
+        }
+    } else {
+        panic!("Expected an assignment");
+    }
+}
+
+#[test]
+fn test_parse_slice_expr() {
+    let code = String::from("
+        text[1..n] + text[..2] + text[i + 1..];
+    ");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let expr_res = parser.parse_expr(&mut lexer, &[Token::Semicolon]);
+    assert!(expr_res.is_ok());
+
+    let text = Box::new(Expression::Variable(String::from("text")));
+    let first = Expression::Slice(
+        text.clone(),
+        Some(Box::new(Expression::IntLiteral(1))),
+        Some(Box::new(Expression::Variable(String::from("n"))))
+    );
+    let second = Expression::Slice(text.clone(), None, Some(Box::new(Expression::IntLiteral(2))));
+    let third = Expression::Slice(
+        text,
+        Some(Box::new(Expression::Addition(
+            Box::new(Expression::Variable(String::from("i"))),
+            Box::new(Expression::IntLiteral(1))
+        ))),
+        None
+    );
+    let expected = Expression::Addition(
+        Box::new(Expression::Addition(Box::new(first), Box::new(second))),
+        Box::new(third)
+    );
+    assert_eq!(expr_res.unwrap(), expected);
+}
+
+#[test]
+fn test_parse_interpolated_string() {
+    let code = String::from("
+        \"Hello {name}, {{{count + 1}}} files\";
+    ");
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let expr_res = parser.parse_expr(&mut lexer, &[Token::Semicolon]);
+    assert!(expr_res.is_ok());
+
+    let expected = Expression::Interpolation(vec![
+        Expression::StringLiteral(String::from("\"Hello \"")),
+        Expression::Variable(String::from("name")),
+        Expression::StringLiteral(String::from("\", {\"")),
+        Expression::Addition(
+            Box::new(Expression::Variable(String::from("count"))),
+            Box::new(Expression::IntLiteral(1))
+        ),
+        Expression::StringLiteral(String::from("\"} files\""))
+    ]);
+    assert_eq!(expr_res.unwrap(), expected);
+
+    let code = String::from("\"Unclosed {name\";");
+    let mut lexer = Token::lexer(code.as_str());
+    let expr_res = parser.parse_expr(&mut lexer, &[Token::Semicolon]);
+    assert!(expr_res.is_err());
+}
+
+#[test]
+fn test_parse_enum_decl() {
+    let code = String::from("
+        enum: Shape {
+            Circle(float),
+            Rect(float, float),
+            Empty
+        }
+    ");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let decl_res = parser.parse_enum_decl(&mut lexer);
+    assert!(decl_res.is_ok());
+
+    let expected = Declaration::Enum(EnumDeclArgs {
+        name: String::from("Shape"),
+        variants: vec![
+            (String::from("Circle"), vec![Type::Float]),
+            (String::from("Rect"), vec![Type::Float, Type::Float]),
+            (String::from("Empty"), Vec::new())
+        ]
+    });
+    assert_eq!(decl_res.unwrap(), expected);
+}
+
+#[test]
+fn test_parse_match() {
+    let code = String::from("
+        match shape {
+            Shape::Rect(w, h) => {
+                return w;
+            },
+            Shape::Empty => {}
+            _ => {}
+        }
+    ");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let stmt_res = parser.parse_match(&mut lexer);
+    assert!(stmt_res.is_ok());
+
+    let expected = Statement::Match(MatchStatementArgs {
+        match_expr: Expression::Variable(String::from("shape")),
+        arms: vec![
+            (
+                MatchPattern::Variant(String::from("Shape::Rect"), vec![String::from("w"), String::from("h")]),
+                vec![Statement::Return(Some(Expression::Variable(String::from("w"))))]
+            ),
+            (MatchPattern::Variant(String::from("Shape::Empty"), Vec::new()), Vec::new()),
+            (MatchPattern::Wildcard, Vec::new())
+        ]
+    });
+    assert_eq!(stmt_res.unwrap(), expected);
+}
+
+#[test]
+fn test_parse_try() {
+    let code = String::from("
+        try {
+            panic(\"failed\");
+        } catch err {
+            return 1;
+        }
+    ");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let stmt_res = parser.parse_try(&mut lexer);
+    assert!(stmt_res.is_ok());
+
+    let expected = Statement::Try(TryStatementArgs {
+        try_block: vec![Statement::Panic(Box::new(Expression::StringLiteral(String::from("\"failed\""))))],
+        catch_var: String::from("err"),
+        catch_block: vec![Statement::Return(Some(Expression::IntLiteral(1)))]
+    });
+    assert_eq!(stmt_res.unwrap(), expected);
+}
+
+#[test]
+fn test_parse_if_var() {
+    let code = String::from("
+        if var node = find() {
+            return 1;
+        }
+    ");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let stmt_res = parser.parse_if(&mut lexer);
+    assert!(stmt_res.is_ok());
+
+    let expected = Statement::If(IfStatementArgs {
+        if_expr: Expression::Call(String::from("find"), Vec::new()),
+        binding: Some(String::from("node")),
+        if_block: vec![Statement::Return(Some(Expression::IntLiteral(1)))],
+        else_block: None,
+        else_if_list: None
+    });
+    assert_eq!(stmt_res.unwrap(), expected);
+}
+
+#[test]
+fn test_parse_optional_type() {
+    let code = String::from("
+        var node: ?&Node = none;
+    ");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let stmt_res = parser.parse_var_decl(&mut lexer);
+    assert!(stmt_res.is_ok());
+
+    if let Statement::VariableDecl(var_decl_args) = stmt_res.unwrap() {
+        let expected_type = Type::Optional(Box::new(Type::Reference(Box::new(Type::Other(String::from("Node"))))));
+        assert_eq!(var_decl_args.var_type, expected_type);
+        assert_eq!(*var_decl_args.assignment, Expression::NoneLiteral);
+    } else {
+        panic!("Expected a variable declaration");
+    }
+}
+
+#[test]
+fn test_parse_primitive_module_path() {
+    let code = String::from("float::sqrt(x)");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let expr_res = parser.parse_expr(&mut lexer, &[]);
+    assert!(expr_res.is_ok());
+
+    let expected = Expression::Call(String::from("float::sqrt"), vec![Expression::Variable(String::from("x"))]);
+    assert_eq!(expr_res.unwrap(), expected);
+}
+
+#[test]
+fn test_parse_type_call() {
+    let code = String::from("Stack<int>::new(1)");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let expr_res = parser.parse_expr(&mut lexer, &[]);
+    assert!(expr_res.is_ok());
+
+    let expected = Expression::TypeCall(
+        Type::Generic(String::from("Stack"), vec![Type::Int]),
+        String::from("new"),
+        vec![Expression::IntLiteral(1)]
+    );
+    assert_eq!(expr_res.unwrap(), expected);
+
+    // Comparisons still parse as such
+    let code = String::from("size < limit");
+
+    let mut lexer = Token::lexer(code.as_str());
+    let parser = Parser::new(code.clone());
+
+    let expr_res = parser.parse_expr(&mut lexer, &[]);
+    assert!(expr_res.is_ok());
+
+    let expected = Expression::LessThan(
+        Box::new(Expression::Variable(String::from("size"))),
+        Box::new(Expression::Variable(String::from("limit")))
+    );
+    assert_eq!(expr_res.unwrap(), expected);
+}